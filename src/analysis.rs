@@ -0,0 +1,431 @@
+//! Double-dummy analysis merging.
+//!
+//! Batch solvers typically write one analysis file per board. [`merge_dd_dir`]
+//! collects those files and attaches their results (`DoubleDummyTricks`,
+//! `OptimumScore`, `ParContract`) to a master set of boards.
+//!
+//! Two file kinds are understood:
+//! - `.pbn` fragments: one or more games carrying a `Deal` and analysis tags
+//! - `.json` files: an object (or array of objects) with the keys `board`,
+//!   `fingerprint`, `deal`, `double_dummy_tricks`, `optimum_score` and
+//!   `par_contract`, all optional
+//!
+//! Analyses are matched to boards by deal [`Fingerprint`] whenever one is
+//! available, because board numbers are frequently reused across sets.
+//! Board numbers are only used for analyses that don't identify their deal.
+
+use crate::error::{ParseError, Result};
+use crate::fingerprint::Fingerprint;
+use bridge_types::{Board, Deal};
+use std::path::Path;
+
+/// Analysis results for one board
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DdAnalysis {
+    /// Board number the analysis was produced for
+    pub board_number: Option<u32>,
+    /// Fingerprint of the analysed deal
+    pub fingerprint: Option<Fingerprint>,
+    /// Value for the DoubleDummyTricks tag
+    pub double_dummy_tricks: Option<String>,
+    /// Value for the OptimumScore tag
+    pub optimum_score: Option<String>,
+    /// Value for the ParContract tag
+    pub par_contract: Option<String>,
+}
+
+/// Outcome of a merge
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MergeReport {
+    /// Analyses attached to at least one board
+    pub merged: usize,
+    /// Analyses matched by fingerprint only to boards with a different number
+    pub number_conflicts: usize,
+    /// Analyses that matched no board (or several boards by number only)
+    pub unmatched: Vec<DdAnalysis>,
+}
+
+/// Attach analyses to boards, matching by fingerprint, then by board number.
+///
+/// An analysis with a fingerprint is attached to every board dealt with
+/// that deal, so a deal repeated in a set gets the results on each copy.
+pub fn merge_dd(boards: &mut [Board], analyses: Vec<DdAnalysis>) -> MergeReport {
+    let fingerprints: Vec<Option<Fingerprint>> =
+        boards.iter().map(|b| Fingerprint::of(&b.deal)).collect();
+    let mut report = MergeReport::default();
+
+    for analysis in analyses {
+        let targets: Vec<usize> = match analysis.fingerprint {
+            Some(fp) => fingerprints
+                .iter()
+                .enumerate()
+                .filter(|(_, f)| **f == Some(fp))
+                .map(|(i, _)| i)
+                .collect(),
+            None => {
+                let by_number: Vec<usize> = boards
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, b)| b.number.is_some() && b.number == analysis.board_number)
+                    .map(|(i, _)| i)
+                    .collect();
+                if by_number.len() == 1 {
+                    by_number
+                } else {
+                    Vec::new()
+                }
+            }
+        };
+
+        if targets.is_empty() {
+            report.unmatched.push(analysis);
+            continue;
+        }
+
+        if analysis.board_number.is_some()
+            && targets
+                .iter()
+                .all(|&i| boards[i].number != analysis.board_number)
+        {
+            report.number_conflicts += 1;
+        }
+        for index in targets {
+            apply_analysis(&mut boards[index], &analysis);
+        }
+        report.merged += 1;
+    }
+
+    report
+}
+
+/// Copy the analysis results that are present onto a board
+pub(crate) fn apply_analysis(board: &mut Board, analysis: &DdAnalysis) {
+    if analysis.double_dummy_tricks.is_some() {
+        board.double_dummy_tricks = analysis.double_dummy_tricks.clone();
+    }
+    if analysis.optimum_score.is_some() {
+        board.optimum_score = analysis.optimum_score.clone();
+    }
+    if analysis.par_contract.is_some() {
+        board.par_contract = analysis.par_contract.clone();
+    }
+}
+
+/// Read every `.pbn` and `.json` analysis file in `dir` (in file name order)
+/// and merge the results into `boards`.
+pub fn merge_dd_dir(boards: &mut [Board], dir: &Path) -> Result<MergeReport> {
+    let mut paths: Vec<_> = std::fs::read_dir(dir)?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<std::io::Result<_>>()?;
+    paths.sort();
+
+    let mut analyses = Vec::new();
+    for path in paths {
+        let ext = path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase());
+        match ext.as_deref() {
+            Some("pbn") => analyses.extend(read_pbn_analyses(&std::fs::read_to_string(&path)?)?),
+            Some("json") => analyses.extend(read_json_analyses(&std::fs::read_to_string(&path)?)?),
+            _ => {}
+        }
+    }
+
+    Ok(merge_dd(boards, analyses))
+}
+
+/// Extract analyses from PBN fragments
+pub fn read_pbn_analyses(content: &str) -> Result<Vec<DdAnalysis>> {
+    let boards = crate::pbn::read_pbn(content)?;
+    Ok(boards
+        .into_iter()
+        .map(|board| DdAnalysis {
+            board_number: board.number,
+            fingerprint: Fingerprint::of(&board.deal),
+            double_dummy_tricks: board.double_dummy_tricks,
+            optimum_score: board.optimum_score,
+            par_contract: board.par_contract,
+        })
+        .collect())
+}
+
+/// Extract analyses from a JSON object or array of objects; a `board`,
+/// `fingerprint` or `deal` value that cannot be read is an error
+pub fn read_json_analyses(content: &str) -> Result<Vec<DdAnalysis>> {
+    let objects = json::parse_objects(content).map_err(ParseError::Json)?;
+    objects
+        .into_iter()
+        .map(|fields| {
+            let mut analysis = DdAnalysis::default();
+            for (key, value) in fields {
+                match key.as_str() {
+                    "board" => {
+                        let number = value.parse().map_err(|_| {
+                            ParseError::Json(format!("invalid board number {:?}", value))
+                        })?;
+                        analysis.board_number = Some(number)
+                    }
+                    "fingerprint" => {
+                        analysis.fingerprint = Some(value.parse().map_err(ParseError::Json)?)
+                    }
+                    "deal" => {
+                        let fingerprint = Deal::from_pbn(&value)
+                            .as_ref()
+                            .and_then(Fingerprint::of)
+                            .ok_or_else(|| ParseError::Json(format!("invalid deal {:?}", value)))?;
+                        analysis.fingerprint = Some(fingerprint)
+                    }
+                    "double_dummy_tricks" => analysis.double_dummy_tricks = Some(value),
+                    "optimum_score" => analysis.optimum_score = Some(value),
+                    "par_contract" => analysis.par_contract = Some(value),
+                    _ => {}
+                }
+            }
+            Ok(analysis)
+        })
+        .collect()
+}
+
+/// Minimal reader for flat JSON objects with scalar values.
+mod json {
+    use std::iter::Peekable;
+    use std::str::Chars;
+
+    /// Parse a single object or an array of objects into key/value lists.
+    /// Scalar values are returned as text; `null` values are dropped.
+    pub(super) fn parse_objects(text: &str) -> Result<Vec<Vec<(String, String)>>, String> {
+        let mut chars = text.chars().peekable();
+        skip_ws(&mut chars);
+
+        let objects = match chars.peek() {
+            Some('[') => {
+                chars.next();
+                let mut objects = Vec::new();
+                loop {
+                    skip_ws(&mut chars);
+                    if chars.peek() == Some(&']') {
+                        chars.next();
+                        break;
+                    }
+                    objects.push(parse_object(&mut chars)?);
+                    skip_ws(&mut chars);
+                    match chars.next() {
+                        Some(',') => continue,
+                        Some(']') => break,
+                        other => return Err(format!("expected ',' or ']', got {:?}", other)),
+                    }
+                }
+                objects
+            }
+            Some('{') => vec![parse_object(&mut chars)?],
+            other => return Err(format!("expected '{{' or '[', got {:?}", other)),
+        };
+
+        skip_ws(&mut chars);
+        match chars.next() {
+            None => Ok(objects),
+            Some(c) => Err(format!("unexpected trailing character {:?}", c)),
+        }
+    }
+
+    fn parse_object(chars: &mut Peekable<Chars>) -> Result<Vec<(String, String)>, String> {
+        let mut fields = Vec::new();
+        if chars.next() != Some('{') {
+            return Err("expected '{'".to_string());
+        }
+
+        loop {
+            skip_ws(chars);
+            match chars.next() {
+                Some('}') if fields.is_empty() => return Ok(fields),
+                Some('"') => {}
+                other => return Err(format!("expected key, got {:?}", other)),
+            }
+            let key = parse_string(chars)?;
+
+            skip_ws(chars);
+            if chars.next() != Some(':') {
+                return Err(format!("expected ':' after key {:?}", key));
+            }
+            skip_ws(chars);
+
+            if let Some(value) = parse_scalar(chars)? {
+                fields.push((key, value));
+            }
+
+            skip_ws(chars);
+            match chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(fields),
+                other => return Err(format!("expected ',' or '}}', got {:?}", other)),
+            }
+        }
+    }
+
+    fn parse_scalar(chars: &mut Peekable<Chars>) -> Result<Option<String>, String> {
+        if chars.peek() == Some(&'"') {
+            chars.next();
+            return parse_string(chars).map(Some);
+        }
+
+        let mut literal = String::new();
+        while let Some(&c) = chars.peek() {
+            if c == ',' || c == '}' || c.is_whitespace() {
+                break;
+            }
+            literal.push(c);
+            chars.next();
+        }
+
+        match literal.as_str() {
+            "" => Err("missing value".to_string()),
+            "null" => Ok(None),
+            _ => Ok(Some(literal)),
+        }
+    }
+
+    /// Parse a string body; the opening quote has been consumed
+    fn parse_string(chars: &mut Peekable<Chars>) -> Result<String, String> {
+        let mut value = String::new();
+        loop {
+            match chars.next() {
+                Some('"') => return Ok(value),
+                Some('\\') => match chars.next() {
+                    Some('n') => value.push('\n'),
+                    Some('t') => value.push('\t'),
+                    Some('r') => value.push('\r'),
+                    Some('u') => {
+                        let hex: String = chars.by_ref().take(4).collect();
+                        let c = u32::from_str_radix(&hex, 16)
+                            .ok()
+                            .and_then(char::from_u32)
+                            .ok_or_else(|| format!("invalid escape \\u{}", hex))?;
+                        value.push(c);
+                    }
+                    Some(c) => value.push(c),
+                    None => return Err("unterminated string".to_string()),
+                },
+                Some(c) => value.push(c),
+                None => return Err("unterminated string".to_string()),
+            }
+        }
+    }
+
+    fn skip_ws(chars: &mut Peekable<Chars>) {
+        while chars.peek().is_some_and(|c| c.is_whitespace()) {
+            chars.next();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DEAL_1: &str = "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ";
+    const DEAL_2: &str = "E:Q7.AKT9.JT3.JT96 J653.QJ8.A.AQ732 K92.654.K954.K84 AT84.732.Q8762.5";
+
+    fn boards() -> Vec<Board> {
+        vec![
+            Board::new()
+                .with_number(1)
+                .with_deal(Deal::from_pbn(DEAL_1).unwrap()),
+            Board::new()
+                .with_number(2)
+                .with_deal(Deal::from_pbn(DEAL_2).unwrap()),
+        ]
+    }
+
+    #[test]
+    fn test_merge_by_fingerprint_over_number() {
+        let mut boards = boards();
+        // Analysis labelled board 1 but actually for the deal of board 2
+        let json = format!(
+            r#"[{{"board": 1, "deal": "{}", "double_dummy_tricks": "88667799555566775555", "optimum_score": null}}]"#,
+            DEAL_2
+        );
+        let analyses = read_json_analyses(&json).unwrap();
+        let report = merge_dd(&mut boards, analyses);
+
+        assert_eq!(report.merged, 1);
+        assert_eq!(report.number_conflicts, 1);
+        assert!(boards[0].double_dummy_tricks.is_none());
+        assert_eq!(
+            boards[1].double_dummy_tricks.as_deref(),
+            Some("88667799555566775555")
+        );
+    }
+
+    #[test]
+    fn test_merge_by_number_and_unmatched() {
+        let mut boards = boards();
+        let analyses = vec![
+            DdAnalysis {
+                board_number: Some(1),
+                par_contract: Some("NS 4S".to_string()),
+                ..DdAnalysis::default()
+            },
+            DdAnalysis {
+                board_number: Some(9),
+                par_contract: Some("EW 3N".to_string()),
+                ..DdAnalysis::default()
+            },
+        ];
+        let report = merge_dd(&mut boards, analyses);
+        assert_eq!(report.merged, 1);
+        assert_eq!(report.unmatched.len(), 1);
+        assert_eq!(boards[0].par_contract.as_deref(), Some("NS 4S"));
+    }
+
+    #[test]
+    fn test_merge_into_repeated_deal() {
+        let mut boards = boards();
+        boards.push(
+            Board::new()
+                .with_number(3)
+                .with_deal(Deal::from_pbn(DEAL_1).unwrap()),
+        );
+        let analyses = vec![DdAnalysis {
+            board_number: Some(3),
+            fingerprint: Fingerprint::of(&Deal::from_pbn(DEAL_1).unwrap()),
+            optimum_score: Some("NS 620".to_string()),
+            ..DdAnalysis::default()
+        }];
+        let report = merge_dd(&mut boards, analyses);
+
+        assert_eq!(report.merged, 1);
+        assert_eq!(report.number_conflicts, 0);
+        assert_eq!(boards[0].optimum_score.as_deref(), Some("NS 620"));
+        assert!(boards[1].optimum_score.is_none());
+        assert_eq!(boards[2].optimum_score.as_deref(), Some("NS 620"));
+    }
+
+    #[test]
+    fn test_read_pbn_analyses() {
+        let pbn = format!(
+            "[Board \"7\"]\n[Deal \"{}\"]\n[OptimumScore \"NS 620\"]\n",
+            DEAL_1
+        );
+        let analyses = read_pbn_analyses(&pbn).unwrap();
+        assert_eq!(analyses.len(), 1);
+        assert_eq!(analyses[0].board_number, Some(7));
+        assert!(analyses[0].fingerprint.is_some());
+        assert_eq!(analyses[0].optimum_score.as_deref(), Some("NS 620"));
+    }
+
+    #[test]
+    fn test_json_errors() {
+        assert!(read_json_analyses("{\"board\": }").is_err());
+        assert!(read_json_analyses("{\"fingerprint\": \"xyz\"}").is_err());
+        assert!(matches!(
+            read_json_analyses("{\"board\": \"one\"}"),
+            Err(ParseError::Json(_))
+        ));
+        assert!(matches!(
+            read_json_analyses("{\"deal\": \"N:AKQ.xyz\"}"),
+            Err(ParseError::Json(_))
+        ));
+        assert_eq!(read_json_analyses("{}").unwrap().len(), 1);
+    }
+}
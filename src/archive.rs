@@ -0,0 +1,140 @@
+//! Reading archives split across many files.
+//!
+//! Event archives are often stored as one file per session or round, in
+//! any mix of formats. [`ArchiveReader`] reads them in order as a single
+//! stream of boards, tagging each board with the file it came from.
+
+use crate::error::Result;
+use crate::files::read_path;
+use crate::format::Format;
+use bridge_types::Board;
+use std::path::{Path, PathBuf};
+
+/// Metadata for one file of an archive
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ArchiveFile {
+    /// Path of the file
+    pub path: PathBuf,
+    /// Format of the file, once it has been read and identified
+    pub format: Option<Format>,
+    /// Number of boards read from the file so far
+    pub boards: usize,
+}
+
+/// Iterates the boards of several files in order.
+///
+/// Yields `(file_index, board)`, where `file_index` indexes [`files`](Self::files).
+/// The format of each file is taken from its extension, or detected from its
+/// content. A file that can't be read or parsed yields one error, after
+/// which reading continues with the next file.
+pub struct ArchiveReader {
+    files: Vec<ArchiveFile>,
+    /// Index of the next file to open
+    next_file: usize,
+    /// Boards of the current file not yet returned
+    pending: std::vec::IntoIter<Board>,
+}
+
+impl ArchiveReader {
+    /// Create a reader over `paths`, read in the given order.
+    pub fn new<I, P>(paths: I) -> Self
+    where
+        I: IntoIterator<Item = P>,
+        P: AsRef<Path>,
+    {
+        let files = paths
+            .into_iter()
+            .map(|path| ArchiveFile {
+                path: path.as_ref().to_path_buf(),
+                format: None,
+                boards: 0,
+            })
+            .collect();
+        Self {
+            files,
+            next_file: 0,
+            pending: Vec::new().into_iter(),
+        }
+    }
+
+    /// The files of the archive, in reading order
+    pub fn files(&self) -> &[ArchiveFile] {
+        &self.files
+    }
+
+    /// Read and parse the file at `index`
+    fn load(&mut self, index: usize) -> Result<Vec<Board>> {
+        let file = &mut self.files[index];
+        let (format, boards) = read_path(&file.path)?;
+        file.format = Some(format);
+        Ok(boards)
+    }
+}
+
+impl Iterator for ArchiveReader {
+    type Item = Result<(usize, Board)>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(board) = self.pending.next() {
+                let index = self.next_file - 1;
+                self.files[index].boards += 1;
+                return Some(Ok((index, board)));
+            }
+
+            if self.next_file >= self.files.len() {
+                return None;
+            }
+            let index = self.next_file;
+            self.next_file += 1;
+
+            match self.load(index) {
+                Ok(boards) => self.pending = boards.into_iter(),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_archive_in_order() {
+        let dir = std::env::temp_dir().join(format!("archive-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let first = dir.join("session1.pbn");
+        std::fs::write(
+            &first,
+            "[Board \"1\"]\n[Deal \"N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ\"]\n\n\
+             [Board \"2\"]\n[Deal \"E:Q7.AKT9.JT3.JT96 J653.QJ8.A.AQ732 K92.654.K954.K84 AT84.732.Q8762.5\"]\n",
+        )
+        .unwrap();
+        let second = dir.join("session2.txt");
+        std::fs::write(
+            &second,
+            "n AKQJ.T98.765.432 e T98.765.432.AKQJ s 765.432.AKQJ.T98 w 432.AKQJ.T98.765\n",
+        )
+        .unwrap();
+        let missing = dir.join("missing.pbn");
+
+        let mut reader = ArchiveReader::new([&first, &missing, &second]);
+        let items: Vec<_> = reader.by_ref().collect();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(items.len(), 4);
+        let (file, board) = items[1].as_ref().unwrap();
+        assert_eq!((*file, board.number), (0, Some(2)));
+        assert!(items[2].is_err());
+        assert_eq!(items[3].as_ref().unwrap().0, 2);
+
+        let files = reader.files();
+        assert_eq!(files[0].format, Some(Format::Pbn));
+        assert_eq!(files[0].boards, 2);
+        assert_eq!(files[1].format, None);
+        assert_eq!(files[2].format, Some(Format::Oneline));
+        assert_eq!(files[2].boards, 1);
+    }
+}
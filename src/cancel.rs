@@ -0,0 +1,64 @@
+//! Cancellation and progress for long-running operations.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// A handle shared between a long-running operation and its caller.
+///
+/// The caller keeps a clone to cancel the operation from another thread
+/// and to poll how many records it has processed. Operations check the
+/// token between records, so cancelling stops them at the next record
+/// boundary.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken {
+    inner: Arc<Inner>,
+}
+
+#[derive(Debug, Default)]
+struct Inner {
+    cancelled: AtomicBool,
+    processed: AtomicUsize,
+}
+
+impl CancelToken {
+    /// Create a token that is not cancelled
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask the operation to stop
+    pub fn cancel(&self) {
+        self.inner.cancelled.store(true, Ordering::Relaxed);
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// Number of records the operation has processed so far
+    pub fn processed(&self) -> usize {
+        self.inner.processed.load(Ordering::Relaxed)
+    }
+
+    /// Count one processed record
+    pub(crate) fn record_processed(&self) {
+        self.inner.processed.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_clones_share_state() {
+        let token = CancelToken::new();
+        let worker = token.clone();
+        worker.record_processed();
+        assert_eq!(token.processed(), 1);
+        assert!(!worker.is_cancelled());
+        token.cancel();
+        assert!(worker.is_cancelled());
+    }
+}
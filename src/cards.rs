@@ -0,0 +1,87 @@
+//! Canonical card order.
+//!
+//! Encoders that record one value per card (fingerprints, card-by-card
+//! comparisons) walk the 52 cards in the same order:
+//! spades, hearts, diamonds, clubs, and within each suit from the ace down
+//! to the two. Sharing the order here keeps their output consistent.
+
+use bridge_types::{Card, Deal, Direction, Rank, Suit};
+
+/// Suits in canonical order
+pub const SUITS: [Suit; 4] = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+
+/// Ranks in canonical order (Ace first)
+pub const RANKS: [Rank; 13] = [
+    Rank::Ace,
+    Rank::King,
+    Rank::Queen,
+    Rank::Jack,
+    Rank::Ten,
+    Rank::Nine,
+    Rank::Eight,
+    Rank::Seven,
+    Rank::Six,
+    Rank::Five,
+    Rank::Four,
+    Rank::Three,
+    Rank::Two,
+];
+
+/// The character for `rank`, or `x` for a rank below `spots_below`
+pub(crate) fn rank_char(rank: Rank, spots_below: Option<Rank>) -> char {
+    if spots_below.is_some_and(|below| rank < below) {
+        'x'
+    } else {
+        rank.to_char()
+    }
+}
+
+/// The 52 cards in canonical order
+pub fn all_cards() -> impl Iterator<Item = Card> {
+    SUITS
+        .into_iter()
+        .flat_map(|suit| RANKS.into_iter().map(move |rank| Card::new(suit, rank)))
+}
+
+/// The seat holding `card`, if any; the first of North, East, South, West
+/// when more than one hand holds it
+pub fn holder(deal: &Deal, card: Card) -> Option<Direction> {
+    Direction::ALL
+        .into_iter()
+        .find(|&dir| deal.hand(dir).has_card(card))
+}
+
+/// Each of the 52 cards in canonical order with the seat holding it
+/// (`None` for cards no hand holds)
+pub fn card_seats(deal: &Deal) -> impl Iterator<Item = (Card, Option<Direction>)> + '_ {
+    all_cards().map(move |card| (card, holder(deal, card)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_card_seats() {
+        let deal =
+            Deal::from_pbn("N:AKQ976.KJ84.T32. J84.Q97.AK4.QJ87 T53.AT65..AT9654 2.32.QJ98765.K32")
+                .unwrap();
+        let seats: Vec<_> = card_seats(&deal).collect();
+        assert_eq!(seats.len(), 52);
+        assert_eq!(
+            seats[0],
+            (Card::new(Suit::Spades, Rank::Ace), Some(Direction::North))
+        );
+        assert_eq!(
+            seats[51],
+            (Card::new(Suit::Clubs, Rank::Two), Some(Direction::West))
+        );
+
+        let mut partial = Deal::new();
+        partial.set_hand(Direction::South, deal.hand(Direction::South).clone());
+        assert_eq!(
+            card_seats(&partial).filter(|(_, s)| s.is_some()).count(),
+            13
+        );
+    }
+}
@@ -0,0 +1,58 @@
+//! Per-record checksums for line-based output.
+//!
+//! A record line can end with ` #` and the eight hex digits of the CRC-32
+//! of the text before it, so readers can tell a corrupted or truncated
+//! record from a valid one.
+
+/// CRC-32 (IEEE) of `text`
+fn crc32(text: &str) -> u32 {
+    let mut crc = !0u32;
+    for &byte in text.as_bytes() {
+        crc ^= u32::from(byte);
+        for _ in 0..8 {
+            let mask = (crc & 1).wrapping_neg();
+            crc = (crc >> 1) ^ (0xEDB8_8320 & mask);
+        }
+    }
+    !crc
+}
+
+/// `record` followed by its checksum
+pub(crate) fn append_checksum(record: &str) -> String {
+    format!("{} #{:08x}", record, crc32(record))
+}
+
+/// Split a line into its record and checksum digits, if it ends with a
+/// checksum; the record is trimmed
+pub(crate) fn split_checksum(line: &str) -> (&str, Option<&str>) {
+    let line = line.trim_end();
+    match line.rsplit_once(" #") {
+        Some((record, digits))
+            if digits.len() == 8 && digits.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            (record.trim_end(), Some(digits))
+        }
+        _ => (line, None),
+    }
+}
+
+/// Whether `digits` are the checksum of `record`
+pub(crate) fn checksum_matches(record: &str, digits: &str) -> bool {
+    u32::from_str_radix(digits, 16).is_ok_and(|crc| crc == crc32(record))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_checksum_round_trip() {
+        assert_eq!(crc32("123456789"), 0xCBF4_3926);
+        let line = append_checksum("n AKQ.J6.KJ42.95");
+        let (record, digits) = split_checksum(&line);
+        assert_eq!(record, "n AKQ.J6.KJ42.95");
+        assert!(checksum_matches(record, digits.unwrap()));
+        assert!(!checksum_matches("n AKQ.J6.KJ42.9", digits.unwrap()));
+        assert_eq!(split_checksum("n AKQ #12"), ("n AKQ #12", None));
+    }
+}
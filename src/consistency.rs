@@ -0,0 +1,180 @@
+//! Cross-format consistency checks.
+//!
+//! Archives often carry the same board twice: as a PBN game and as a LIN
+//! play record (embedded in commentary or stored alongside). These checks
+//! verify that both describe the same board.
+
+use crate::cards::{card_seats, holder};
+use crate::lin::LinData;
+use crate::scoring::same_contract;
+use bridge_types::{Board, Card, Direction, Vulnerability};
+use std::fmt;
+
+/// A difference between a PBN board and its LIN record
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Discrepancy {
+    /// A card is held by different seats (or missing from one deal)
+    CardSeat {
+        card: Card,
+        pbn: Option<Direction>,
+        lin: Option<Direction>,
+    },
+    /// The dealers differ
+    Dealer { pbn: Direction, lin: Direction },
+    /// The vulnerabilities differ
+    Vulnerability {
+        pbn: Vulnerability,
+        lin: Vulnerability,
+    },
+    /// The PBN Contract tag doesn't match the contract reached in the LIN auction
+    Contract { pbn: String, lin: String },
+}
+
+impl fmt::Display for Discrepancy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Discrepancy::CardSeat { card, pbn, lin } => write!(
+                f,
+                "{}{} held by {} in PBN but {} in LIN",
+                card.suit.to_char(),
+                card.rank.to_char(),
+                seat_name(*pbn),
+                seat_name(*lin)
+            ),
+            Discrepancy::Dealer { pbn, lin } => write!(
+                f,
+                "dealer is {} in PBN but {} in LIN",
+                pbn.to_char(),
+                lin.to_char()
+            ),
+            Discrepancy::Vulnerability { pbn, lin } => write!(
+                f,
+                "vulnerability is {} in PBN but {} in LIN",
+                pbn.to_pbn(),
+                lin.to_pbn()
+            ),
+            Discrepancy::Contract { pbn, lin } => {
+                write!(f, "contract is {} in PBN but {} in LIN", pbn, lin)
+            }
+        }
+    }
+}
+
+fn seat_name(seat: Option<Direction>) -> String {
+    match seat {
+        Some(dir) => dir.to_char().to_string(),
+        None => "nobody".to_string(),
+    }
+}
+
+/// Compare a PBN board with a LIN record of the same board.
+///
+/// `pbn_contract` is the value of the game's `Contract` tag, if any; it is
+/// compared with the final contract of the LIN auction when both are known.
+pub fn check_board_against_lin(
+    board: &Board,
+    pbn_contract: Option<&str>,
+    lin: &LinData,
+) -> Vec<Discrepancy> {
+    let mut discrepancies = Vec::new();
+
+    for (card, pbn) in card_seats(&board.deal) {
+        let lin_holder = holder(&lin.deal, card);
+        if pbn != lin_holder {
+            discrepancies.push(Discrepancy::CardSeat {
+                card,
+                pbn,
+                lin: lin_holder,
+            });
+        }
+    }
+
+    if let Some(dealer) = board.dealer {
+        if dealer != lin.dealer {
+            discrepancies.push(Discrepancy::Dealer {
+                pbn: dealer,
+                lin: lin.dealer,
+            });
+        }
+    }
+
+    if board.vulnerable != lin.vulnerability {
+        discrepancies.push(Discrepancy::Vulnerability {
+            pbn: board.vulnerable,
+            lin: lin.vulnerability,
+        });
+    }
+
+    let pbn_contract = pbn_contract
+        .map(str::trim)
+        .filter(|c| !c.is_empty() && *c != "?");
+    if let (Some(pbn), Some(lin)) = (pbn_contract, lin.final_contract()) {
+        if !same_contract(pbn, &lin) {
+            discrepancies.push(Discrepancy::Contract {
+                pbn: pbn.to_string(),
+                lin,
+            });
+        }
+    }
+
+    discrepancies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lin::parse_lin;
+
+    const LIN: &str = "pn|S,W,N,E|md|3S87HAQT2D95CAQT43,SAQT95H874DAJ2CJ5,S62HKJ53DKQT43CK6,|sv|n|mb|1N|mb|p|mb|3N|mb|p|mb|p|mb|p|";
+
+    fn pbn_board() -> Board {
+        let lin = parse_lin(LIN).unwrap();
+        Board::new()
+            .with_dealer(Direction::North)
+            .with_vulnerability(Vulnerability::NorthSouth)
+            .with_deal(lin.deal.clone())
+    }
+
+    #[test]
+    fn test_consistent_board() {
+        let lin = parse_lin(LIN).unwrap();
+        let issues = check_board_against_lin(&pbn_board(), Some("3NT"), &lin);
+        assert!(issues.is_empty(), "{:?}", issues);
+        // Contracts are compared as contracts, not as text
+        assert!(check_board_against_lin(&pbn_board(), Some(" 3n"), &lin).is_empty());
+        assert_eq!(
+            check_board_against_lin(&pbn_board(), Some("3NTX"), &lin).len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_contract_and_dealer_mismatch() {
+        let lin = parse_lin(LIN).unwrap();
+        let board = pbn_board().with_dealer(Direction::East);
+        let issues = check_board_against_lin(&board, Some("4S"), &lin);
+        assert_eq!(issues.len(), 2);
+        assert!(issues.contains(&Discrepancy::Dealer {
+            pbn: Direction::East,
+            lin: Direction::North,
+        }));
+        assert!(issues.contains(&Discrepancy::Contract {
+            pbn: "4S".to_string(),
+            lin: "3NT".to_string(),
+        }));
+    }
+
+    #[test]
+    fn test_card_mismatch() {
+        let lin = parse_lin(LIN).unwrap();
+        let mut board = pbn_board();
+        let north = board.deal.hand(Direction::North).clone();
+        let east = board.deal.hand(Direction::East).clone();
+        board.deal.set_hand(Direction::North, east);
+        board.deal.set_hand(Direction::East, north);
+
+        let issues = check_board_against_lin(&board, None, &lin);
+        assert_eq!(issues.len(), 26);
+        assert!(issues[0].to_string().contains("in PBN but"));
+    }
+}
@@ -0,0 +1,282 @@
+//! Deal constraint verification.
+//!
+//! Themed teaching sets are dealt to a constraint profile (e.g. "North 12+
+//! HCP, 5+ spades"). Verifying an imported set against the profile confirms
+//! the dealing machine was given the right file.
+
+use bridge_types::{Board, Direction, Suit};
+use std::fmt;
+use std::str::FromStr;
+
+/// What a constraint measures
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Measure {
+    /// High card points (A=4, K=3, Q=2, J=1)
+    Hcp,
+    /// Number of cards in a suit
+    Length(Suit),
+}
+
+/// A range constraint on one seat's hand
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Constraint {
+    /// The constrained seat
+    pub seat: Direction,
+    /// What is measured
+    pub measure: Measure,
+    /// Minimum value (inclusive)
+    pub min: u32,
+    /// Maximum value (inclusive), if bounded
+    pub max: Option<u32>,
+}
+
+impl Constraint {
+    /// The measured value for a board
+    pub fn value(&self, board: &Board) -> u32 {
+        let hand = board.deal.hand(self.seat);
+        match self.measure {
+            Measure::Hcp => hand.hcp() as u32,
+            Measure::Length(suit) => hand.suit_length(suit) as u32,
+        }
+    }
+
+    /// Whether a board satisfies this constraint
+    pub fn is_satisfied_by(&self, board: &Board) -> bool {
+        let value = self.value(board);
+        value >= self.min && self.max.is_none_or(|max| value <= max)
+    }
+}
+
+impl fmt::Display for Constraint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", seat_name(self.seat))?;
+        match self.max {
+            None => write!(f, "{}+", self.min)?,
+            Some(max) if max == self.min => write!(f, "{}", max)?,
+            Some(max) => write!(f, "{}-{}", self.min, max)?,
+        }
+        match self.measure {
+            Measure::Hcp => write!(f, " HCP"),
+            Measure::Length(suit) => write!(f, " {}", suit_name(suit)),
+        }
+    }
+}
+
+/// A set of constraints that every board of a deal set must satisfy
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConstraintProfile {
+    /// The constraints, all of which must hold
+    pub constraints: Vec<Constraint>,
+}
+
+impl ConstraintProfile {
+    /// Create an empty profile (satisfied by every board)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a constraint
+    pub fn with(mut self, constraint: Constraint) -> Self {
+        self.constraints.push(constraint);
+        self
+    }
+
+    /// Check every board, returning the violations in board order
+    pub fn verify(&self, boards: &[Board]) -> Vec<Violation> {
+        let mut violations = Vec::new();
+        for (index, board) in boards.iter().enumerate() {
+            for constraint in &self.constraints {
+                if !constraint.is_satisfied_by(board) {
+                    violations.push(Violation {
+                        board_index: index,
+                        board_number: board.number,
+                        constraint: *constraint,
+                        actual: constraint.value(board),
+                    });
+                }
+            }
+        }
+        violations
+    }
+}
+
+/// Parse a profile such as `"North 12+ HCP, 5+ spades; South 0-7 HCP"`.
+///
+/// Clauses are separated by `,` or `;`. A clause may start with a seat name
+/// (or its initial); clauses without one apply to the previous clause's seat.
+/// Ranges are written `n`, `n+` or `n-m`.
+impl FromStr for ConstraintProfile {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut profile = ConstraintProfile::new();
+        let mut seat = None;
+
+        for clause in s.split([',', ';']) {
+            let words: Vec<&str> = clause.split_whitespace().collect();
+            let mut words = words.as_slice();
+            if words.is_empty() {
+                continue;
+            }
+
+            if let Some(dir) = parse_seat(words[0]) {
+                seat = Some(dir);
+                words = &words[1..];
+            }
+            let seat = seat.ok_or_else(|| format!("no seat given for \"{}\"", clause.trim()))?;
+
+            let [range, measure] = words else {
+                return Err(format!(
+                    "expected \"<range> <measure>\" in \"{}\"",
+                    clause.trim()
+                ));
+            };
+            let (min, max) = parse_range(range)?;
+            let measure = parse_measure(measure)?;
+
+            profile.constraints.push(Constraint {
+                seat,
+                measure,
+                min,
+                max,
+            });
+        }
+
+        Ok(profile)
+    }
+}
+
+/// A board that fails a constraint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// Position of the board in the checked set
+    pub board_index: usize,
+    /// The board's number, if it has one
+    pub board_number: Option<u32>,
+    /// The failed constraint
+    pub constraint: Constraint,
+    /// The measured value
+    pub actual: u32,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.board_number {
+            Some(num) => write!(f, "board {}", num)?,
+            None => write!(f, "board #{}", self.board_index + 1)?,
+        }
+        write!(f, ": expected {}, got {}", self.constraint, self.actual)
+    }
+}
+
+fn parse_seat(word: &str) -> Option<Direction> {
+    match word.to_ascii_lowercase().as_str() {
+        "north" | "n" => Some(Direction::North),
+        "east" | "e" => Some(Direction::East),
+        "south" | "s" => Some(Direction::South),
+        "west" | "w" => Some(Direction::West),
+        _ => None,
+    }
+}
+
+fn parse_range(word: &str) -> Result<(u32, Option<u32>), String> {
+    let number = |text: &str| {
+        text.parse::<u32>()
+            .map_err(|_| format!("invalid range: \"{}\"", word))
+    };
+
+    if let Some(min) = word.strip_suffix('+') {
+        Ok((number(min)?, None))
+    } else if let Some((min, max)) = word.split_once('-') {
+        let (min, max) = (number(min)?, number(max)?);
+        if min > max {
+            return Err(format!("invalid range: \"{}\"", word));
+        }
+        Ok((min, Some(max)))
+    } else {
+        let exact = number(word)?;
+        Ok((exact, Some(exact)))
+    }
+}
+
+fn parse_measure(word: &str) -> Result<Measure, String> {
+    match word.to_ascii_lowercase().as_str() {
+        "hcp" => Ok(Measure::Hcp),
+        "spades" | "spade" => Ok(Measure::Length(Suit::Spades)),
+        "hearts" | "heart" => Ok(Measure::Length(Suit::Hearts)),
+        "diamonds" | "diamond" => Ok(Measure::Length(Suit::Diamonds)),
+        "clubs" | "club" => Ok(Measure::Length(Suit::Clubs)),
+        _ => Err(format!("unknown measure: \"{}\"", word)),
+    }
+}
+
+fn seat_name(seat: Direction) -> &'static str {
+    match seat {
+        Direction::North => "North",
+        Direction::East => "East",
+        Direction::South => "South",
+        Direction::West => "West",
+    }
+}
+
+fn suit_name(suit: Suit) -> &'static str {
+    match suit {
+        Suit::Spades => "spades",
+        Suit::Hearts => "hearts",
+        Suit::Diamonds => "diamonds",
+        Suit::Clubs => "clubs",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bridge_types::Deal;
+
+    fn board(number: u32, pbn: &str) -> Board {
+        Board::new()
+            .with_number(number)
+            .with_deal(Deal::from_pbn(pbn).unwrap())
+    }
+
+    #[test]
+    fn test_parse_profile() {
+        let profile: ConstraintProfile = "North 12+ HCP, 5+ spades; S 0-7 hcp".parse().unwrap();
+        assert_eq!(profile.constraints.len(), 3);
+        assert_eq!(profile.constraints[1].seat, Direction::North);
+        assert_eq!(
+            profile.constraints[1].measure,
+            Measure::Length(Suit::Spades)
+        );
+        assert_eq!(profile.constraints[2].max, Some(7));
+        assert_eq!(profile.constraints[0].to_string(), "North 12+ HCP");
+
+        assert!("12+ HCP".parse::<ConstraintProfile>().is_err());
+        assert!("North 12+ trumps".parse::<ConstraintProfile>().is_err());
+    }
+
+    #[test]
+    fn test_verify() {
+        // North: AQJ7.K.Q75.AT942 (17 HCP, 4 spades)
+        let boards = vec![
+            board(
+                1,
+                "N:AQJ7.K.Q75.AT942 K843.T542.J6.863 962.AJ7.KT82.J75 T5.Q9863.A943.KQ",
+            ),
+            board(
+                2,
+                "N:AQJ75.K.Q75.AT94 K843.T542.J6.863 962.AJ7.KT82.J75 T.Q9863.A943.KQ2",
+            ),
+        ];
+        let profile: ConstraintProfile = "North 12+ HCP, 5+ spades".parse().unwrap();
+
+        let violations = profile.verify(&boards);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].board_number, Some(1));
+        assert_eq!(violations[0].actual, 4);
+        assert_eq!(
+            violations[0].to_string(),
+            "board 1: expected North 5+ spades, got 4"
+        );
+    }
+}
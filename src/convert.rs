@@ -0,0 +1,923 @@
+//! Bulk conversion between file formats.
+//!
+//! [`bulk`] streams a file through a read → transform → write pipeline with
+//! a bounded number of records in flight, so memory use stays constant no
+//! matter how large the input is. Records can be converted on worker
+//! threads; output order always matches input order.
+//!
+//! PBN input is read game by game with a [`PbnReader`], so tag values
+//! inherited with `#` and `##` and the `% PBN` and `% EXPORT` header
+//! directives apply across the whole file, as when it is read at once.
+//! Input is decoded as set by [`ConvertOptions::encoding`], and output is
+//! written with the line endings of [`ConvertOptions::layout`].
+//!
+//! [`bulk_to_sink`] and [`convert_to_sink`] send the boards to a
+//! [`DealSink`] instead, as one session: the sink stores either all of
+//! them or, when reading fails or the conversion is cancelled, none.
+//!
+//! # Example
+//!
+//! ```
+//! use bridge_encodings::convert::{self, ConvertOptions};
+//! use bridge_encodings::Format;
+//! use std::io::Cursor;
+//!
+//! let input = "n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72\n";
+//! let mut output = Vec::new();
+//! let opts = ConvertOptions::new(Format::Pbn);
+//! let report = convert::convert(Cursor::new(input), &mut output, &opts).unwrap();
+//! assert_eq!(report.boards_written, 1);
+//! ```
+
+use crate::cancel::CancelToken;
+use crate::diagnostics::{Diagnostics, Outcome};
+use crate::encoding::TextEncoding;
+use crate::error::Result;
+use crate::format::Format;
+use crate::layout::{LayoutWriter, TextLayout, WriteOptions};
+use crate::pbn::{Game, PbnReader, PbnWriteOptions};
+use crate::sink::DealSink;
+use crate::transform::{Chain as TransformChain, Transform};
+use bridge_types::{Board, Deal};
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Chain, Cursor, Read, Write};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+/// Options for a bulk conversion
+#[derive(Debug, Clone)]
+pub struct ConvertOptions {
+    /// Input format; detected from the file extension or content when `None`
+    pub input: Option<Format>,
+    /// Output format
+    pub output: Format,
+    /// How the input is decoded
+    pub encoding: TextEncoding,
+    /// Line endings and final newline of the output
+    pub layout: TextLayout,
+    /// How PBN output is written: the header, its version and `% EXPORT`
+    /// line, and the tags of each game; line endings come from `layout`
+    pub pbn: PbnWriteOptions,
+    /// Transforms applied to each board before it is written; boards the
+    /// chain drops are left out of the output. Records are transformed on
+    /// the worker threads, so a stateful step such as
+    /// [`Renumber`](crate::transform::Renumber) only sees the boards in
+    /// input order when `threads` is 1
+    pub transform: Option<Arc<TransformChain>>,
+    /// Number of worker threads (0 = one per CPU, 1 = convert on the calling thread)
+    pub threads: usize,
+    /// Maximum number of records held in memory between reading and writing
+    pub queue_capacity: usize,
+    /// Token to stop the conversion early and follow its progress; each
+    /// written record counts as processed
+    pub cancel: Option<CancelToken>,
+}
+
+impl ConvertOptions {
+    /// Create options for converting to `output` with default settings
+    pub fn new(output: Format) -> Self {
+        Self {
+            output,
+            ..Self::default()
+        }
+    }
+}
+
+impl Default for ConvertOptions {
+    fn default() -> Self {
+        Self {
+            input: None,
+            output: Format::Pbn,
+            encoding: TextEncoding::Auto,
+            layout: TextLayout::default(),
+            pbn: PbnWriteOptions::default(),
+            transform: None,
+            threads: 0,
+            queue_capacity: 1024,
+            cancel: None,
+        }
+    }
+}
+
+/// Summary of a completed conversion
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BatchReport {
+    /// Number of input records read (PBN games, LIN lines, oneline deals, ...);
+    /// PBN header directives and commentary outside games are not records
+    pub records_read: usize,
+    /// Number of boards written to the output
+    pub boards_written: usize,
+    /// Number of input records that contained no parseable board
+    pub skipped: usize,
+    /// Number of boards dropped by [`ConvertOptions::transform`]
+    pub dropped: usize,
+    /// Whether the conversion was cancelled before the end of the input
+    pub cancelled: bool,
+}
+
+impl BatchReport {
+    /// Summary of the conversion: partial when records were skipped or
+    /// the conversion was cancelled, failed when no record produced a board
+    pub fn outcome(&self) -> Outcome {
+        let outcome = Outcome::classify(
+            self.records_read.saturating_sub(self.skipped),
+            self.skipped,
+            &Diagnostics::new(),
+        );
+        if self.cancelled {
+            outcome.max(Outcome::Partial)
+        } else {
+            outcome
+        }
+    }
+}
+
+/// Convert the file at `path_in` into `path_out`.
+///
+/// The input format is taken from `opts.input`, else from the file
+/// extension, else detected from the content.
+pub fn bulk(path_in: &Path, path_out: &Path, opts: &ConvertOptions) -> Result<BatchReport> {
+    let reader = BufReader::new(File::open(path_in)?);
+    let writer = BufWriter::new(File::create(path_out)?);
+
+    let mut opts = opts.clone();
+    if opts.input.is_none() {
+        opts.input = Format::from_path(path_in);
+    }
+
+    convert(reader, writer, &opts)
+}
+
+/// Convert records from `reader` into `writer`.
+///
+/// This is the stream-level form of [`bulk`]. When `opts.cancel` is
+/// cancelled, reading stops, the records already read are written, and the
+/// report is marked as cancelled.
+pub fn convert<R, W>(reader: R, mut writer: W, opts: &ConvertOptions) -> Result<BatchReport>
+where
+    R: BufRead + Send,
+    W: Write,
+{
+    let mut records = open_records(reader, opts)?;
+    let mut report = BatchReport::default();
+    let mut out = LayoutWriter::new(&mut writer, WriteOptions::from(opts.layout));
+
+    if opts.output == Format::Pbn {
+        let mut header = String::new();
+        crate::pbn::write_header(&mut header, &opts.pbn);
+        out.write(&header)?;
+    }
+
+    if worker_count(opts.threads) <= 1 {
+        loop {
+            if is_cancelled(opts) {
+                report.cancelled = true;
+                break;
+            }
+            let Some(record) = records.next_record()? else {
+                break;
+            };
+            let converted = convert_record(&record, opts);
+            report.records_read += 1;
+            write_converted(&mut out, &converted, opts.output, &mut report)?;
+            if let Some(cancel) = &opts.cancel {
+                cancel.record_processed();
+            }
+        }
+    } else {
+        convert_threaded(records, &mut out, opts, &mut report)?;
+    }
+
+    out.finish()?;
+    writer.flush()?;
+    Ok(report)
+}
+
+/// Read the boards of the file at `path_in` into `sink` as one session.
+///
+/// The input format is found as by [`bulk`]; see [`convert_to_sink`].
+pub fn bulk_to_sink<S>(path_in: &Path, sink: &mut S, opts: &ConvertOptions) -> Result<BatchReport>
+where
+    S: DealSink + ?Sized,
+{
+    let reader = BufReader::new(File::open(path_in)?);
+
+    let mut opts = opts.clone();
+    if opts.input.is_none() {
+        opts.input = Format::from_path(path_in);
+    }
+
+    convert_to_sink(reader, sink, &opts)
+}
+
+/// Read the boards of `reader` into `sink` as one session.
+///
+/// The sink decides how boards are stored, so `opts.output`, `opts.layout`,
+/// `opts.pbn` and `opts.threads` are not used. The session is committed only when
+/// the whole input has been read; if reading fails it is rolled back and
+/// the error returned, and if `opts.cancel` is cancelled it is rolled back
+/// and the report, marked as cancelled, counts no boards written.
+pub fn convert_to_sink<R, S>(reader: R, sink: &mut S, opts: &ConvertOptions) -> Result<BatchReport>
+where
+    R: BufRead,
+    S: DealSink + ?Sized,
+{
+    let mut records = open_records(reader, opts)?;
+    let mut report = BatchReport::default();
+
+    sink.begin()?;
+    let result =
+        sink_records(&mut records, sink, opts, &mut report).and_then(|()| match report.cancelled {
+            true => sink.rollback(),
+            false => sink.commit(),
+        });
+    if let Err(e) = result {
+        // The original error matters more than a failed rollback
+        let _ = sink.rollback();
+        return Err(e);
+    }
+    if report.cancelled {
+        report.boards_written = 0;
+    }
+    Ok(report)
+}
+
+/// Write the boards of each record to `sink`, until the end of the input
+/// or a cancellation
+fn sink_records<R, S>(
+    records: &mut Records<R>,
+    sink: &mut S,
+    opts: &ConvertOptions,
+    report: &mut BatchReport,
+) -> Result<()>
+where
+    R: BufRead,
+    S: DealSink + ?Sized,
+{
+    loop {
+        if is_cancelled(opts) {
+            report.cancelled = true;
+            return Ok(());
+        }
+        let Some(record) = records.next_record()? else {
+            return Ok(());
+        };
+        report.records_read += 1;
+        let boards = match record {
+            Record::Game(game) => vec![game.board],
+            Record::Text { format, text } => parse_record(format, &text),
+        };
+        if boards.is_empty() {
+            report.skipped += 1;
+        }
+        let parsed = boards.len();
+        let boards: Vec<Board> = match &opts.transform {
+            Some(chain) => chain.run(boards).collect(),
+            None => boards,
+        };
+        report.dropped += parsed - boards.len();
+        for board in &boards {
+            sink.write(board)?;
+            report.boards_written += 1;
+        }
+        if let Some(cancel) = &opts.cancel {
+            cancel.record_processed();
+        }
+    }
+}
+
+/// Whether the caller asked to stop
+fn is_cancelled(opts: &ConvertOptions) -> bool {
+    opts.cancel.as_ref().is_some_and(CancelToken::is_cancelled)
+}
+
+/// Resolve the requested thread count (0 = available parallelism)
+fn worker_count(threads: usize) -> usize {
+    if threads == 0 {
+        thread::available_parallelism()
+            .map(|n| n.get())
+            .unwrap_or(1)
+    } else {
+        threads
+    }
+}
+
+/// Run the pipeline with a reader thread, worker threads and an in-order writer.
+///
+/// A fixed pool of permits bounds the number of records between the reader
+/// and the writer: the reader takes a permit per record, the writer returns
+/// it once the record has been written.
+fn convert_threaded<R, W>(
+    mut records: Records<R>,
+    writer: &mut LayoutWriter<'_, W>,
+    opts: &ConvertOptions,
+    report: &mut BatchReport,
+) -> Result<()>
+where
+    R: BufRead + Send,
+    W: Write,
+{
+    let capacity = opts.queue_capacity.max(1);
+
+    let (job_tx, job_rx) = mpsc::sync_channel::<(usize, Record)>(capacity);
+    let (done_tx, done_rx) = mpsc::sync_channel::<(usize, Converted)>(capacity);
+    let (permit_tx, permit_rx) = mpsc::sync_channel::<()>(capacity);
+    for _ in 0..capacity {
+        // Cannot block: the channel has room for exactly `capacity` permits
+        let _ = permit_tx.send(());
+    }
+    let job_rx = Mutex::new(job_rx);
+
+    thread::scope(|scope| {
+        for _ in 0..worker_count(opts.threads) {
+            let job_rx = &job_rx;
+            let done_tx = done_tx.clone();
+            scope.spawn(move || loop {
+                let job = match job_rx.lock() {
+                    Ok(rx) => rx.recv(),
+                    Err(_) => return,
+                };
+                let Ok((seq, record)) = job else { return };
+                if done_tx.send((seq, convert_record(&record, opts))).is_err() {
+                    return;
+                }
+            });
+        }
+        drop(done_tx);
+
+        let reader = scope.spawn(move || -> Result<(usize, bool)> {
+            let mut seq = 0;
+            loop {
+                if is_cancelled(opts) {
+                    return Ok((seq, true));
+                }
+                let Some(record) = records.next_record()? else {
+                    return Ok((seq, false));
+                };
+                if permit_rx.recv().is_err() || job_tx.send((seq, record)).is_err() {
+                    // Writer has stopped
+                    return Ok((seq, false));
+                }
+                seq += 1;
+            }
+        });
+
+        let written = write_in_order(done_rx, permit_tx, writer, opts, report);
+
+        let read = match reader.join() {
+            Ok(result) => result,
+            Err(payload) => std::panic::resume_unwind(payload),
+        };
+        (report.records_read, report.cancelled) = read?;
+        written
+    })
+}
+
+/// Write converted records in sequence order, releasing a permit per record.
+fn write_in_order<W: Write>(
+    done_rx: Receiver<(usize, Converted)>,
+    permit_tx: SyncSender<()>,
+    writer: &mut LayoutWriter<'_, W>,
+    opts: &ConvertOptions,
+    report: &mut BatchReport,
+) -> Result<()> {
+    let mut pending = BTreeMap::new();
+    let mut next = 0;
+
+    for (seq, converted) in done_rx {
+        pending.insert(seq, converted);
+        while let Some(converted) = pending.remove(&next) {
+            write_converted(writer, &converted, opts.output, report)?;
+            if let Some(cancel) = &opts.cancel {
+                cancel.record_processed();
+            }
+            next += 1;
+            let _ = permit_tx.send(());
+        }
+    }
+
+    Ok(())
+}
+
+/// One input record
+enum Record {
+    /// A PBN game, read with the inherited tags and directives of the
+    /// games before it
+    Game(Box<Game>),
+    /// The raw text of a record in another format
+    Text { format: Format, text: String },
+}
+
+/// The formatted output of one record
+struct Converted {
+    /// Formatted boards, one entry per board
+    boards: Vec<ConvertedBoard>,
+    /// Number of boards dropped by the transform
+    dropped: usize,
+}
+
+/// One board of a converted record
+enum ConvertedBoard {
+    /// The formatted board
+    Text(String),
+    /// A deal without a board number for printall output, formatted when
+    /// written so it is numbered by its place in the output
+    Unnumbered(Deal),
+}
+
+/// Parse a record and format its boards in the output format.
+///
+/// PBN to PBN conversion writes the whole [`Game`] so tags without a board
+/// field survive; the transform then replaces only the board of the game.
+fn convert_record(record: &Record, opts: &ConvertOptions) -> Converted {
+    let transform = |board: Board| match &opts.transform {
+        Some(chain) => chain.apply(board),
+        None => Some(board),
+    };
+    match record {
+        Record::Game(game) => {
+            let Some(board) = transform(game.board.clone()) else {
+                return Converted {
+                    boards: Vec::new(),
+                    dropped: 1,
+                };
+            };
+            let text = if opts.output == Format::Pbn {
+                let mut game = Game::clone(game);
+                game.board = board;
+                ConvertedBoard::Text(crate::pbn::game_to_pbn_with(&game, &opts.pbn))
+            } else {
+                format_board(&board, opts)
+            };
+            Converted {
+                boards: vec![text],
+                dropped: 0,
+            }
+        }
+        Record::Text { format, text } => {
+            let parsed = parse_record(*format, text);
+            let count = parsed.len();
+            let boards: Vec<ConvertedBoard> = parsed
+                .into_iter()
+                .filter_map(transform)
+                .map(|board| format_board(&board, opts))
+                .collect();
+            Converted {
+                dropped: count - boards.len(),
+                boards,
+            }
+        }
+    }
+}
+
+/// Parse the text of a record into boards; malformed records yield no boards
+fn parse_record(format: Format, text: &str) -> Vec<Board> {
+    match format {
+        // Read as games by the PbnReader
+        Format::Pbn => Vec::new(),
+        Format::Lin => crate::lin::parse_lin(text)
+            .map(|data| vec![data.to_board()])
+            .unwrap_or_default(),
+        Format::Oneline => crate::oneline::parse_oneline(text)
+            .map(|deal| vec![Board::new().with_deal(deal)])
+            .unwrap_or_default(),
+        Format::Printall => crate::printall::parse_printall_string(text)
+            .map(|deals| {
+                deals
+                    .into_iter()
+                    .map(|deal| Board::new().with_deal(deal))
+                    .collect()
+            })
+            .unwrap_or_default(),
+    }
+}
+
+/// Format a single board
+fn format_board(board: &Board, opts: &ConvertOptions) -> ConvertedBoard {
+    match opts.output {
+        Format::Pbn => ConvertedBoard::Text(crate::pbn::board_to_pbn_with(board, &opts.pbn)),
+        Format::Oneline => ConvertedBoard::Text(crate::oneline::format_oneline(&board.deal)),
+        Format::Printall => match board.number {
+            Some(number) => ConvertedBoard::Text(crate::printall::format_printall(
+                &board.deal,
+                number as usize,
+            )),
+            None => ConvertedBoard::Unnumbered(board.deal.clone()),
+        },
+        Format::Lin => ConvertedBoard::Text(crate::lin::board_to_lin(board) + "\n"),
+    }
+}
+
+/// Write a converted record and update the report
+fn write_converted<W: Write>(
+    writer: &mut LayoutWriter<'_, W>,
+    converted: &Converted,
+    output: Format,
+    report: &mut BatchReport,
+) -> Result<()> {
+    report.dropped += converted.dropped;
+    if converted.boards.is_empty() {
+        if converted.dropped == 0 {
+            report.skipped += 1;
+        }
+        return Ok(());
+    }
+
+    for board in &converted.boards {
+        // PBN games are separated by a blank line
+        if output == Format::Pbn && report.boards_written > 0 {
+            writer.write("\n")?;
+        }
+        match board {
+            ConvertedBoard::Text(text) => writer.write(text)?,
+            ConvertedBoard::Unnumbered(deal) => {
+                let number = report.boards_written + 1;
+                writer.write(&crate::printall::format_printall(deal, number))?
+            }
+        }
+        report.boards_written += 1;
+    }
+
+    Ok(())
+}
+
+/// The input with the line the format was detected from put back in front
+type Input<R> = Chain<Cursor<Vec<u8>>, R>;
+
+/// Find the input format and open its records. When the format is
+/// detected, the lines before the first recognizable one are skipped.
+fn open_records<R: BufRead>(mut reader: R, opts: &ConvertOptions) -> Result<Records<Input<R>>> {
+    let mut first = Vec::new();
+    let format = match opts.input {
+        Some(format) => format,
+        None => loop {
+            first.clear();
+            if reader.read_until(b'\n', &mut first)? == 0 {
+                return Ok(Records::Empty);
+            }
+            if let Some(format) = Format::detect(&opts.encoding.decode(&first)?) {
+                break format;
+            }
+        },
+    };
+    let reader = Cursor::new(first).chain(reader);
+    Ok(match format {
+        Format::Pbn => Records::Pbn(Box::new(
+            PbnReader::new(reader).with_encoding(opts.encoding),
+        )),
+        format => Records::Text(RecordSplitter {
+            reader,
+            format,
+            encoding: opts.encoding,
+            line_buf: Vec::new(),
+        }),
+    })
+}
+
+/// The records of the input
+enum Records<R: BufRead> {
+    /// PBN games, read by one reader so state carries from game to game
+    Pbn(Box<PbnReader<R>>),
+    /// Records of the other formats
+    Text(RecordSplitter<R>),
+    /// Input in which no format was recognized
+    Empty,
+}
+
+impl<R: BufRead> Records<R> {
+    /// Read the next record, or `None` at end of input
+    fn next_record(&mut self) -> Result<Option<Record>> {
+        match self {
+            Records::Pbn(reader) => Ok(reader
+                .next_game()
+                .transpose()?
+                .map(|game| Record::Game(Box::new(game)))),
+            Records::Text(splitter) => splitter.next_record(),
+            Records::Empty => Ok(None),
+        }
+    }
+}
+
+/// Splits input into records: blank-line separated blocks for printall,
+/// single lines for LIN and oneline.
+struct RecordSplitter<R: BufRead> {
+    reader: R,
+    format: Format,
+    encoding: TextEncoding,
+    line_buf: Vec<u8>,
+}
+
+impl<R: BufRead> RecordSplitter<R> {
+    /// Read the next record, or `None` at end of input
+    fn next_record(&mut self) -> Result<Option<Record>> {
+        let format = self.format;
+        let mut text = String::new();
+
+        loop {
+            self.line_buf.clear();
+            if self.reader.read_until(b'\n', &mut self.line_buf)? == 0 {
+                break;
+            }
+            let decoded = self.encoding.decode(&self.line_buf)?;
+            let line = decoded.trim();
+
+            match format {
+                Format::Lin | Format::Oneline | Format::Pbn => {
+                    if !line.is_empty() {
+                        return Ok(Some(Record::Text {
+                            format,
+                            text: line.to_string(),
+                        }));
+                    }
+                }
+                Format::Printall => {
+                    if line.is_empty() {
+                        if !text.is_empty() {
+                            return Ok(Some(Record::Text { format, text }));
+                        }
+                        continue;
+                    }
+                    text.push_str(line);
+                    text.push('\n');
+                }
+            }
+        }
+
+        Ok((!text.is_empty()).then_some(Record::Text { format, text }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const ONELINE: &str = "\
+n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72
+n A754.7642.KJ2.A9 e QT.AK95.87.K8652 s K93.J83.QT6543.T w J862.QT.A9.QJ743
+Generated 100 hands
+";
+
+    fn run(input: &str, opts: &ConvertOptions) -> (String, BatchReport) {
+        let mut output = Vec::new();
+        let report = convert(Cursor::new(input), &mut output, opts).unwrap();
+        (String::from_utf8(output).unwrap(), report)
+    }
+
+    #[test]
+    fn test_oneline_to_pbn() {
+        let (output, report) = run(ONELINE, &ConvertOptions::new(Format::Pbn));
+        assert_eq!(report.records_read, 3);
+        assert_eq!(report.boards_written, 2);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(report.outcome(), Outcome::Partial);
+
+        let boards = crate::pbn::read_pbn(&output).unwrap();
+        assert_eq!(boards.len(), 2);
+    }
+
+    #[test]
+    fn test_cancel() {
+        let cancel = CancelToken::new();
+        for threads in [1, 4] {
+            let opts = ConvertOptions {
+                threads,
+                cancel: Some(cancel.clone()),
+                ..ConvertOptions::new(Format::Pbn)
+            };
+            let (_, report) = run(ONELINE, &opts);
+            assert!(!report.cancelled);
+            assert_eq!(report.boards_written, 2);
+        }
+        assert_eq!(cancel.processed(), 6);
+
+        cancel.cancel();
+        let opts = ConvertOptions {
+            cancel: Some(cancel),
+            ..ConvertOptions::new(Format::Pbn)
+        };
+        let (_, report) = run(ONELINE, &opts);
+        assert!(report.cancelled);
+        assert_eq!(report.records_read, 0);
+        assert_eq!(report.outcome(), Outcome::Partial);
+    }
+
+    #[test]
+    fn test_pbn_to_oneline() {
+        let pbn = r#"% PBN 2.1
+
+[Board "1"]
+[Dealer "N"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+{A commentary
+
+with a blank line}
+
+[Board "2"]
+[Dealer "E"]
+[Deal "E:Q7.AKT9.JT3.JT96 J653.QJ8.A.AQ732 K92.654.K954.K84 AT84.732.Q8762.5"]
+"#;
+        let (output, report) = run(pbn, &ConvertOptions::new(Format::Oneline));
+        assert_eq!(report.boards_written, 2);
+        assert_eq!(output.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_pbn_to_pbn_keeps_unknown_tags() {
+        let pbn = "[Generator \"Dealer 4.2\"]\n[Board \"1\"]\n[BCFlags \"1f\"]\n\
+                   [Deal \"N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ\"]\n";
+        let (output, report) = run(pbn, &ConvertOptions::new(Format::Pbn));
+        assert_eq!(report.boards_written, 1);
+        assert!(output.contains("[BCFlags \"1f\"]"));
+        assert!(output.contains("[Generator \"Dealer 4.2\"]"));
+    }
+
+    #[test]
+    fn test_threaded_output_matches_input_order() {
+        let mut input = String::new();
+        let mut pbn = String::new();
+        for i in 1..=500 {
+            pbn.push_str(&format!(
+                "[Board \"{}\"]\n[Deal \"N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ\"]\n\n",
+                i
+            ));
+        }
+        input.push_str(&pbn);
+
+        let single = ConvertOptions {
+            threads: 1,
+            ..ConvertOptions::new(Format::Pbn)
+        };
+        let threaded = ConvertOptions {
+            threads: 4,
+            queue_capacity: 8,
+            ..ConvertOptions::new(Format::Pbn)
+        };
+
+        let (expected, _) = run(&input, &single);
+        let (output, report) = run(&input, &threaded);
+        assert_eq!(report.records_read, 500);
+        assert_eq!(report.boards_written, 500);
+        assert_eq!(output, expected);
+
+        let boards = crate::pbn::read_pbn(&output).unwrap();
+        let numbers: Vec<_> = boards.iter().filter_map(|b| b.number).collect();
+        assert_eq!(numbers, (1..=500).collect::<Vec<u32>>());
+    }
+
+    #[test]
+    fn test_transform_drops_boards_in_order() {
+        use crate::transform::{Anonymize, Filter};
+
+        let mut pbn = String::new();
+        for i in 1..=100 {
+            pbn.push_str(&format!(
+                "[Event \"Club\"]\n[Board \"{}\"]\n[Deal \"N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ\"]\n\n",
+                i
+            ));
+        }
+        let chain = TransformChain::new()
+            .with(Filter(|board: &Board| {
+                board.number.is_some_and(|n| n % 3 == 0)
+            }))
+            .with(Anonymize);
+        let opts = ConvertOptions {
+            threads: 4,
+            queue_capacity: 8,
+            transform: Some(Arc::new(chain)),
+            ..ConvertOptions::new(Format::Pbn)
+        };
+
+        let (output, report) = run(&pbn, &opts);
+        assert_eq!(report.records_read, 100);
+        assert_eq!(report.boards_written, 33);
+        assert_eq!(report.dropped, 67);
+        assert_eq!(report.skipped, 0);
+        assert!(!output.contains("Club"));
+        let boards = crate::pbn::read_pbn(&output).unwrap();
+        let numbers: Vec<_> = boards.iter().filter_map(|b| b.number).collect();
+        assert_eq!(numbers, (1..=33).map(|n| n * 3).collect::<Vec<u32>>());
+
+        let (output, report) = run(ONELINE, &ConvertOptions { threads: 1, ..opts });
+        assert_eq!(report.dropped, 2);
+        assert_eq!(report.skipped, 1);
+        assert_eq!(output, "% PBN 2.1\n% EXPORT\n\n");
+    }
+
+    #[test]
+    fn test_pbn_file_state_carries_across_games() {
+        let pbn = "% PBN 1.0\n% EXPORT\n\n\
+                   [Event \"##Club\"]\n[Site \"Hall\"]\n[Board \"1\"]\n[Vulnerable \"n-s\"]\n\n\
+                   [Site \"#\"]\n[Board \"2\"]\n[Vulnerable \"both\"]\n";
+        for threads in [1, 4] {
+            let opts = ConvertOptions {
+                threads,
+                ..ConvertOptions::new(Format::Pbn)
+            };
+            let (output, report) = run(pbn, &opts);
+            assert_eq!(report.records_read, 2);
+            assert_eq!(report.skipped, 0);
+            assert_eq!(report.outcome(), Outcome::Clean);
+
+            let boards = crate::pbn::read_pbn(&output).unwrap();
+            assert_eq!(boards[1].event.as_deref(), Some("Club"));
+            assert_eq!(boards[1].site.as_deref(), Some("Hall"));
+            assert_eq!(boards[1].vulnerable, bridge_types::Vulnerability::Both);
+        }
+    }
+
+    #[test]
+    fn test_windows_1252_input() {
+        let input: &[u8] = b"[Board \"1\"]\n[North \"J\xF6rg\"]\n";
+        let mut output = Vec::new();
+        let report = convert(input, &mut output, &ConvertOptions::new(Format::Pbn)).unwrap();
+        assert_eq!(report.boards_written, 1);
+        assert!(String::from_utf8(output)
+            .unwrap()
+            .contains("[North \"J\u{F6}rg\"]"));
+    }
+
+    #[test]
+    fn test_printall_numbered_by_output_position() {
+        let input = ONELINE.replace("\nn A754", "\nnot a deal\nn A754");
+        let (output, report) = run(&input, &ConvertOptions::new(Format::Printall));
+        assert_eq!(report.boards_written, 2);
+        assert!(output.contains("   2.\n"));
+        assert!(!output.contains("   3.\n"));
+    }
+
+    #[test]
+    fn test_crlf_layout() {
+        let opts = ConvertOptions {
+            layout: TextLayout {
+                line_ending: crate::LineEnding::CrLf,
+                trailing_newline: true,
+            },
+            ..ConvertOptions::new(Format::Pbn)
+        };
+        let (output, report) = run(ONELINE, &opts);
+        assert_eq!(report.boards_written, 2);
+        assert!(output.starts_with("% PBN 2.1\r\n% EXPORT\r\n\r\n"));
+        assert!(output.contains("\"]\r\n\r\n[Event"));
+        assert!(output.ends_with("\"]\r\n"));
+        assert!(!output.replace("\r\n", "").contains(['\r', '\n']));
+    }
+
+    #[test]
+    fn test_pbn_write_options() {
+        let opts = ConvertOptions {
+            pbn: PbnWriteOptions::default()
+                .with_export(false)
+                .with_empty_tags(false),
+            ..ConvertOptions::new(Format::Pbn)
+        };
+        let (output, _) = run(ONELINE, &opts);
+        assert!(output.starts_with("% PBN 2.1\n\n["));
+        assert!(!output.contains("% EXPORT"));
+        assert!(!output.contains("[West \"\"]"));
+
+        let opts = ConvertOptions {
+            pbn: PbnWriteOptions::default().with_header(false),
+            ..ConvertOptions::new(Format::Pbn)
+        };
+        let (output, _) = run(ONELINE, &opts);
+        assert!(output.starts_with("[Event \"\"]"));
+    }
+
+    #[test]
+    fn test_convert_to_sink() {
+        use crate::sink::MemorySink;
+
+        let mut sink = MemorySink::new();
+        let opts = ConvertOptions::new(Format::Pbn);
+        let report = convert_to_sink(Cursor::new(ONELINE), &mut sink, &opts).unwrap();
+        assert_eq!(report.boards_written, sink.boards().len());
+        assert!(!sink.boards().is_empty());
+
+        let cancel = CancelToken::new();
+        cancel.cancel();
+        let opts = ConvertOptions {
+            cancel: Some(cancel),
+            ..ConvertOptions::new(Format::Pbn)
+        };
+        let mut sink = MemorySink::new();
+        let report = convert_to_sink(Cursor::new(ONELINE), &mut sink, &opts).unwrap();
+        assert!(report.cancelled);
+        assert_eq!(report.boards_written, 0);
+        assert!(sink.boards().is_empty());
+    }
+
+    #[test]
+    fn test_oneline_to_lin() {
+        let (output, report) = run(ONELINE, &ConvertOptions::new(Format::Lin));
+        assert!(report.boards_written > 0);
+        assert_eq!(output.lines().count(), report.boards_written);
+        for line in output.lines() {
+            let lin = crate::lin::parse_lin(line).unwrap();
+            assert_eq!(lin.deal.hand(bridge_types::Direction::North).len(), 13);
+        }
+    }
+}
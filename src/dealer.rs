@@ -0,0 +1,169 @@
+//! Dealer scripts.
+//!
+//! Scripts for Hans van Staveren's `dealer` program can fix cards with a
+//! `predeal` statement:
+//!
+//! ```text
+//! predeal north SAQ542, HKJ87, D32, CAK
+//!         south S9, H32
+//! condition hcp(north) >= 15
+//! ```
+//!
+//! [`read_predeal`] extracts those cards into a partial [`Deal`], so
+//! generated completions can be combined with the scripted predeal.
+
+use crate::error::{ParseError, Result, SourceLocation};
+use crate::seat::SeatParser;
+use bridge_types::{Card, Deal, Direction, Hand, Rank, Suit};
+use std::path::Path;
+
+/// Read the `predeal` statements of a dealer script into a partial deal.
+///
+/// Seats without predealt cards have empty hands. A card assigned twice is
+/// an error.
+pub fn read_predeal(script: &str) -> Result<Deal> {
+    let mut hands: Vec<(Direction, Vec<Card>)> = Vec::new();
+    let mut in_predeal = false;
+    let mut seat: Option<Direction> = None;
+
+    for (index, line) in script.lines().enumerate() {
+        let line = line.split('#').next().unwrap_or("");
+        let mut tokens = line
+            .split(|c: char| c.is_whitespace() || c == ',')
+            .filter(|t| !t.is_empty())
+            .peekable();
+        let Some(&first) = tokens.peek() else {
+            continue;
+        };
+
+        if first.eq_ignore_ascii_case("predeal") {
+            tokens.next();
+            in_predeal = true;
+            seat = None;
+        } else if !in_predeal || parse_seat(first).is_none() {
+            // Any other statement ends the predeal
+            in_predeal = false;
+            continue;
+        }
+
+        for token in tokens {
+            if let Some(dir) = parse_seat(token) {
+                seat = Some(dir);
+                continue;
+            }
+            let Some(dir) = seat else {
+                return Err(dealer_error(
+                    index,
+                    line,
+                    token,
+                    format!("cards before a seat: {}", token),
+                ));
+            };
+            let cards = parse_holding(token).ok_or_else(|| {
+                dealer_error(index, line, token, format!("invalid holding: {}", token))
+            })?;
+            for card in cards {
+                if hands.iter().any(|(_, held)| held.contains(&card)) {
+                    return Err(dealer_error(
+                        index,
+                        line,
+                        token,
+                        format!(
+                            "{}{} predealt twice",
+                            card.suit.to_char(),
+                            card.rank.to_char()
+                        ),
+                    ));
+                }
+                match hands.iter_mut().find(|(d, _)| *d == dir) {
+                    Some((_, held)) => held.push(card),
+                    None => hands.push((dir, vec![card])),
+                }
+            }
+        }
+    }
+
+    let mut deal = Deal::new();
+    for (dir, cards) in hands {
+        if cards.len() > 13 {
+            return Err(ParseError::Dealer(format!(
+                "{} cards predealt to {}",
+                cards.len(),
+                dir.to_char()
+            )));
+        }
+        deal.set_hand(dir, Hand::from_cards(cards));
+    }
+    Ok(deal)
+}
+
+/// Read the `predeal` statements of a dealer script file
+pub fn read_predeal_file(path: impl AsRef<Path>) -> Result<Deal> {
+    read_predeal(&std::fs::read_to_string(path)?)
+}
+
+/// An error at `token` on the line with the given index
+fn dealer_error(index: usize, line: &str, token: &str, message: String) -> ParseError {
+    ParseError::Syntax {
+        format: "Dealer script",
+        location: SourceLocation::find(index + 1, line, token),
+        message,
+    }
+}
+
+/// Dealer scripts name seats with English words only
+fn parse_seat(token: &str) -> Option<Direction> {
+    SeatParser::new()
+        .with_letters(false)
+        .with_localized(false)
+        .parse(token)
+}
+
+/// Parse a holding like `SAQ542` or `H10`
+fn parse_holding(token: &str) -> Option<Vec<Card>> {
+    let mut chars = token.chars();
+    let suit = Suit::from_char(chars.next()?.to_ascii_uppercase())?;
+    let ranks = chars.as_str().to_ascii_uppercase().replace("10", "T");
+    if ranks.is_empty() {
+        return None;
+    }
+    ranks
+        .chars()
+        .map(|c| Rank::from_char(c).map(|rank| Card::new(suit, rank)))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_predeal() {
+        let script = "generate 10000\n\
+            predeal north SAQ542, HKJ87, D32, CAK # opener\n\
+            \x20       south S9, H103\n\
+            condition hcp(north) >= 15\n\
+            predeal east C2\n";
+        let deal = read_predeal(script).unwrap();
+        assert_eq!(deal.hand(Direction::North).len(), 13);
+        assert_eq!(deal.hand(Direction::South).len(), 3);
+        assert!(deal
+            .hand(Direction::South)
+            .has_card(Card::new(Suit::Hearts, Rank::Ten)));
+        assert_eq!(deal.hand(Direction::East).len(), 1);
+        assert!(deal.hand(Direction::West).is_empty());
+    }
+
+    #[test]
+    fn test_invalid_predeal() {
+        assert!(read_predeal("predeal north SA\npredeal south SA").is_err());
+        assert!(read_predeal("predeal SA").is_err());
+        assert!(read_predeal("predeal north SX").is_err());
+
+        let err = read_predeal("condition true\npredeal north SAK, HQZ").unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!(location.line, 2);
+        assert_eq!((location.start_column, location.end_column), (20, 22));
+        assert_eq!(location.text, "HQZ");
+    }
+}
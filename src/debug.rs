@@ -0,0 +1,309 @@
+//! Parse tracing for debugging input files.
+//!
+//! [`explain_record`] runs a single record through the readers themselves
+//! ([`PbnReader`] for PBN, the LIN parser for LIN) with tracing turned on
+//! and reports each decision they make, to help track down why a file
+//! parses differently than expected.
+//!
+//! # Example
+//!
+//! ```
+//! use bridge_encodings::debug::explain_record;
+//!
+//! let report = explain_record("[Board \"1\"]\n[Dealer \"X\"]\n[Generator \"dealer\"]\n");
+//! println!("{}", report);
+//! assert_eq!(report.entries.len(), 4);
+//! ```
+
+use crate::diagnostics::Diagnostics;
+use crate::format::Format;
+use crate::pbn::PbnReader;
+use std::fmt;
+
+/// What the parser did at one step
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceAction {
+    /// A format detector matched (or none did)
+    Detected,
+    /// Data was interpreted and stored
+    Applied,
+    /// Data was recognized but deliberately not interpreted
+    Ignored,
+    /// Data was interpreted but had an invalid value
+    Rejected,
+    /// Input was skipped without being interpreted (comments, directives, ...)
+    Skipped,
+}
+
+/// One parsing decision
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TraceEntry {
+    /// 1-based line number within the record, if the step relates to a line
+    pub line: Option<usize>,
+    /// What was done
+    pub action: TraceAction,
+    /// Human-readable description
+    pub detail: String,
+}
+
+/// The trace of parsing a single record
+#[derive(Debug, Clone, Default)]
+pub struct ExplainReport {
+    /// Detected format, if any detector matched
+    pub format: Option<Format>,
+    /// Parsing decisions in the order they were made
+    pub entries: Vec<TraceEntry>,
+}
+
+impl ExplainReport {
+    fn push(&mut self, line: Option<usize>, action: TraceAction, detail: String) {
+        self.entries.push(TraceEntry {
+            line,
+            action,
+            detail,
+        });
+    }
+}
+
+/// Parse decisions recorded by a reader; recording is off unless the
+/// trace was created with [`Trace::enabled`]
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Trace {
+    entries: Option<Vec<TraceEntry>>,
+}
+
+impl Trace {
+    /// A trace that records
+    pub(crate) fn enabled() -> Self {
+        Self {
+            entries: Some(Vec::new()),
+        }
+    }
+
+    /// Record a decision; `detail` is only built when recording
+    pub(crate) fn record(
+        &mut self,
+        line: Option<usize>,
+        action: TraceAction,
+        detail: impl FnOnce() -> String,
+    ) {
+        if let Some(entries) = &mut self.entries {
+            entries.push(TraceEntry {
+                line,
+                action,
+                detail: detail(),
+            });
+        }
+    }
+
+    /// Number of decisions recorded
+    pub(crate) fn count(&self) -> usize {
+        self.entries.as_ref().map_or(0, Vec::len)
+    }
+
+    /// Put the decisions from `start` on in line order, those without a
+    /// line last
+    pub(crate) fn sort_from(&mut self, start: usize) {
+        if let Some(entries) = &mut self.entries {
+            entries[start..].sort_by_key(|entry| entry.line.unwrap_or(usize::MAX));
+        }
+    }
+
+    /// The decisions recorded
+    pub(crate) fn into_entries(self) -> Vec<TraceEntry> {
+        self.entries.unwrap_or_default()
+    }
+}
+
+impl fmt::Display for TraceAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            TraceAction::Detected => "detected",
+            TraceAction::Applied => "applied",
+            TraceAction::Ignored => "ignored",
+            TraceAction::Rejected => "rejected",
+            TraceAction::Skipped => "skipped",
+        };
+        f.write_str(name)
+    }
+}
+
+impl fmt::Display for ExplainReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for entry in &self.entries {
+            match entry.line {
+                Some(line) => {
+                    writeln!(f, "line {:>4}: {:<8} {}", line, entry.action, entry.detail)?
+                }
+                None => writeln!(f, "           {:<8} {}", entry.action, entry.detail)?,
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Trace how a single record (one PBN game, LIN line, oneline deal or
+/// printall block) is detected and parsed.
+pub fn explain_record(record: &str) -> ExplainReport {
+    let mut report = ExplainReport {
+        format: Format::detect(record),
+        entries: Vec::new(),
+    };
+
+    let format = match report.format {
+        Some(format) => format,
+        None => {
+            report.push(
+                None,
+                TraceAction::Detected,
+                "no format detector matched the first non-blank line".to_string(),
+            );
+            return report;
+        }
+    };
+    report.push(None, TraceAction::Detected, format!("{:?} format", format));
+
+    match format {
+        Format::Pbn => explain_pbn(record, &mut report),
+        Format::Lin => explain_lin(record, &mut report),
+        Format::Oneline => explain_oneline(record, &mut report),
+        Format::Printall => explain_printall(record, &mut report),
+    }
+
+    report
+}
+
+/// Read the record with a tracing [`PbnReader`]
+fn explain_pbn(record: &str, report: &mut ExplainReport) {
+    let mut reader = PbnReader::new(record.as_bytes()).with_trace(Trace::enabled());
+    let mut errors = Vec::new();
+    while let Some(game) = reader.next_game() {
+        if let Err(e) = game {
+            errors.push(e.to_string());
+        }
+    }
+    report.entries.extend(reader.into_trace().into_entries());
+    for error in errors {
+        report.push(None, TraceAction::Rejected, error);
+    }
+}
+
+/// Parse the record with the LIN parser, tracing each command
+fn explain_lin(record: &str, report: &mut ExplainReport) {
+    let mut trace = Trace::enabled();
+    let parsed = crate::lin::parse_lin_traced(record, &mut Diagnostics::new(), &mut trace);
+    report.entries.extend(trace.into_entries());
+    if let Err(e) = parsed {
+        report.push(None, TraceAction::Rejected, e.to_string());
+    }
+}
+
+fn explain_oneline(record: &str, report: &mut ExplainReport) {
+    for (idx, line) in record.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match crate::oneline::parse_oneline(line) {
+            Ok(_) => report.push(
+                Some(idx + 1),
+                TraceAction::Applied,
+                "oneline deal".to_string(),
+            ),
+            Err(e) => report.push(Some(idx + 1), TraceAction::Skipped, e.to_string()),
+        }
+    }
+}
+
+fn explain_printall(record: &str, report: &mut ExplainReport) {
+    let lines: Vec<&str> = record.lines().collect();
+    match crate::printall::parse_printall(&lines) {
+        Ok((_, consumed)) => report.push(
+            Some(1),
+            TraceAction::Applied,
+            format!("printall deal ({} lines)", consumed),
+        ),
+        Err(e) => report.push(Some(1), TraceAction::Rejected, e.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explain_pbn_record() {
+        let record = r#"% PBN 2.1
+[Board "1"]
+[Dealer "X"]
+[Generator "dealer"]
+{A comment}
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+"#;
+        let report = explain_record(record);
+        assert_eq!(report.format, Some(Format::Pbn));
+
+        let actions: Vec<_> = report.entries.iter().map(|e| e.action).collect();
+        assert_eq!(
+            actions,
+            vec![
+                TraceAction::Detected,
+                TraceAction::Applied,
+                TraceAction::Applied,
+                TraceAction::Rejected,
+                TraceAction::Ignored,
+                TraceAction::Applied,
+                TraceAction::Applied,
+            ]
+        );
+        let lines: Vec<_> = report.entries.iter().map(|e| e.line).collect();
+        assert_eq!(
+            lines,
+            [None, Some(1), Some(2), Some(3), Some(4), Some(5), Some(6)]
+        );
+        assert!(report.to_string().contains("invalid Dealer"));
+    }
+
+    #[test]
+    fn test_explain_pbn_follows_reader() {
+        let record = "[Event \"##Club\"]\n[Board \"1\"]\n[Auction \"N\"]\n1S 9Q\n\n[Board \"2\"]\n";
+        let report = explain_record(record);
+        let text = report.to_string();
+        assert!(text.contains("Event = \"Club\" (for this and following games)"));
+        assert!(text.contains("invalid call"));
+        assert!(text.contains("Event = \"Club\" (inherited)"));
+        let rejected = report
+            .entries
+            .iter()
+            .find(|e| e.action == TraceAction::Rejected)
+            .unwrap();
+        assert_eq!(rejected.line, Some(3));
+    }
+
+    #[test]
+    fn test_explain_lin_record() {
+        let report = explain_record("pn|S,W,N,E|md|1SAKHJD876C5432,,,|sv|q|\npc|S2|zz|1|");
+        assert_eq!(report.format, Some(Format::Lin));
+        let actions: Vec<_> = report.entries.iter().map(|e| (e.line, e.action)).collect();
+        assert_eq!(
+            actions,
+            [
+                (None, TraceAction::Detected),
+                (Some(1), TraceAction::Applied),
+                (Some(1), TraceAction::Applied),
+                (Some(1), TraceAction::Rejected),
+                (Some(2), TraceAction::Applied),
+                (Some(2), TraceAction::Ignored),
+                (Some(2), TraceAction::Ignored),
+            ]
+        );
+        assert!(report.entries[3].detail.contains("invalid sv"));
+    }
+
+    #[test]
+    fn test_explain_undetected() {
+        let report = explain_record("Generated 100 hands");
+        assert_eq!(report.format, None);
+        assert_eq!(report.entries.len(), 1);
+    }
+}
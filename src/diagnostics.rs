@@ -0,0 +1,185 @@
+//! Non-fatal parse warnings.
+//!
+//! Lenient parsing recovers from many problems (defaulting a bad value,
+//! deriving a missing hand). Those recoveries are reported as warnings in a
+//! [`Diagnostics`] sink, separate from the hard errors in
+//! [`ParseError`](crate::ParseError).
+
+use std::fmt;
+
+/// The kind of recovery the parser made
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum WarningKind {
+    /// An invalid value was replaced with a default
+    DefaultedValue,
+    /// An invalid value was ignored
+    IgnoredValue,
+    /// A missing hand was derived from the other three
+    DerivedHand,
+    /// A line that looked like data could not be parsed and was skipped
+    SkippedLine,
+    /// A deal with hands of other than 13 cards was kept as found
+    IrregularDeal,
+}
+
+/// A non-fatal problem found while parsing
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    /// Input line (1-based), when known
+    pub line: Option<usize>,
+    /// What the parser did
+    pub kind: WarningKind,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line, self.message),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+/// Collects warnings during parsing
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diagnostics {
+    warnings: Vec<Warning>,
+}
+
+impl Diagnostics {
+    /// Create an empty sink
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Record a warning
+    pub fn warn(&mut self, line: Option<usize>, kind: WarningKind, message: impl Into<String>) {
+        self.warnings.push(Warning {
+            line,
+            kind,
+            message: message.into(),
+        });
+    }
+
+    /// The warnings recorded so far
+    pub fn warnings(&self) -> &[Warning] {
+        &self.warnings
+    }
+
+    /// Whether no warnings were recorded
+    pub fn is_empty(&self) -> bool {
+        self.warnings.is_empty()
+    }
+
+    /// Number of warnings recorded
+    pub fn len(&self) -> usize {
+        self.warnings.len()
+    }
+
+    /// Remove all warnings
+    pub fn clear(&mut self) {
+        self.warnings.clear();
+    }
+}
+
+/// Summary of a parse or conversion, for mapping to a process exit status.
+///
+/// Variants are ordered from best to worst, so outcomes of several steps
+/// combine with `max`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Outcome {
+    /// Everything parsed without warnings
+    Clean,
+    /// Everything parsed, with recoveries reported as warnings
+    Warnings,
+    /// Some records were skipped
+    Partial,
+    /// Nothing usable was produced
+    Failed,
+}
+
+impl Outcome {
+    /// Classify a run that parsed `parsed` records, skipped `skipped`
+    /// records and recorded `diagnostics`
+    pub fn classify(parsed: usize, skipped: usize, diagnostics: &Diagnostics) -> Self {
+        if parsed == 0 && skipped > 0 {
+            Outcome::Failed
+        } else if skipped > 0 {
+            Outcome::Partial
+        } else if !diagnostics.is_empty() {
+            Outcome::Warnings
+        } else {
+            Outcome::Clean
+        }
+    }
+
+    /// Classify a single parse
+    pub fn from_result<T, E>(
+        result: &std::result::Result<T, E>,
+        diagnostics: &Diagnostics,
+    ) -> Self {
+        match result {
+            Ok(_) => Self::classify(1, 0, diagnostics),
+            Err(_) => Outcome::Failed,
+        }
+    }
+
+    /// Whether anything usable was produced
+    pub fn is_success(self) -> bool {
+        self != Outcome::Failed
+    }
+
+    /// Exit status for command line tools: 0 clean, 1 failed, 2 partial,
+    /// 3 warnings
+    pub fn exit_code(self) -> i32 {
+        match self {
+            Outcome::Clean => 0,
+            Outcome::Failed => 1,
+            Outcome::Partial => 2,
+            Outcome::Warnings => 3,
+        }
+    }
+}
+
+impl fmt::Display for Outcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Outcome::Clean => "success",
+            Outcome::Warnings => "success with warnings",
+            Outcome::Partial => "partial success",
+            Outcome::Failed => "failure",
+        })
+    }
+}
+
+impl IntoIterator for Diagnostics {
+    type Item = Warning;
+    type IntoIter = std::vec::IntoIter<Warning>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.warnings.into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcome() {
+        let mut diagnostics = Diagnostics::new();
+        assert_eq!(Outcome::classify(3, 0, &diagnostics), Outcome::Clean);
+        assert_eq!(Outcome::classify(0, 2, &diagnostics), Outcome::Failed);
+        diagnostics.warn(Some(4), WarningKind::IgnoredValue, "invalid Dealer");
+        assert_eq!(Outcome::classify(3, 0, &diagnostics), Outcome::Warnings);
+        assert_eq!(Outcome::classify(3, 1, &diagnostics), Outcome::Partial);
+
+        let failed: std::result::Result<(), ()> = Err(());
+        assert_eq!(Outcome::from_result(&failed, &diagnostics), Outcome::Failed);
+        assert_eq!(Outcome::Warnings.max(Outcome::Partial), Outcome::Partial);
+        assert_eq!(Outcome::Partial.exit_code(), 2);
+        assert!(!Outcome::Failed.is_success());
+    }
+}
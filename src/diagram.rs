@@ -0,0 +1,424 @@
+//! Hand diagrams for forum posts.
+//!
+//! [`game_summary`] renders a complete game: the hand diagram with suit
+//! symbols, the auction table, the opening lead and the result. The pieces
+//! are also available on their own, and [`render_bbcode`] and
+//! [`render_markdown`] produce the diagram for forums that format posts.
+//!
+//! Every renderer takes the seats to hide, as in
+//! [`PbnWriteOptions::hidden_hands`](crate::pbn::PbnWriteOptions::hidden_hands),
+//! so teaching material can be exported the same way in every format; a
+//! hidden seat is named without its cards. The `_with` variants take the
+//! shared [`WriteOptions`]: suit symbols, spot cards, partial deals (hands
+//! without cards shown like hidden ones) and layout.
+//!
+//! ```text
+//! Board 1       North
+//! Dealer N      ♠ K843
+//! Vul None      ♥ T542
+//!               ♦ J6
+//!               ♣ 863
+//! ```
+
+use crate::cards::rank_char;
+use crate::layout::{SuitSymbols, WriteOptions};
+use crate::pbn::{Auction, Call, Game};
+use crate::scoring::PlayedResult;
+use crate::seat::SeatOrder;
+use bridge_types::{Board, Direction, Rank, Strain, Suit};
+use std::cmp::Reverse;
+
+/// Width of the left column and of each hand in the middle row
+const WIDTH: usize = 14;
+
+/// Width of an auction table column
+const CALL_WIDTH: usize = 7;
+
+/// Auction table columns
+const AUCTION_SEATS: SeatOrder = SeatOrder::WNES;
+
+/// Render the hand diagram of a board, leaving the `hidden` seats blank
+pub fn render_diagram(board: &Board, hidden: &[Direction]) -> String {
+    render_diagram_with(board, hidden, &WriteOptions::default())
+}
+
+/// Render the hand diagram of a board with the given suit symbols, spot
+/// cards and layout
+pub fn render_diagram_with(board: &Board, hidden: &[Direction], options: &WriteOptions) -> String {
+    let absent = options.absent_seats(&board.deal, hidden);
+    options.finish(diagram_text(board, &absent, options))
+}
+
+fn diagram_text(board: &Board, hidden: &[Direction], options: &WriteOptions) -> String {
+    let mut info = header(board);
+    info.resize(5, String::new());
+    let north = hand_lines(board, Direction::North, hidden, options);
+    let west = hand_lines(board, Direction::West, hidden, options);
+    let east = hand_lines(board, Direction::East, hidden, options);
+    let south = hand_lines(board, Direction::South, hidden, options);
+
+    let mut lines = Vec::new();
+    for i in 0..5 {
+        lines.push(pad(&info[i], WIDTH) + &north[i]);
+    }
+    for i in 0..5 {
+        lines.push(pad(&west[i], 2 * WIDTH) + &east[i]);
+    }
+    for line in &south {
+        lines.push(pad("", WIDTH) + line);
+    }
+    finish(lines)
+}
+
+/// Render an auction as a table with columns West, North, East, South
+pub fn render_auction(auction: &Auction) -> String {
+    auction_text(auction, SuitSymbols::default())
+}
+
+fn auction_text(auction: &Auction, symbols: SuitSymbols) -> String {
+    let mut lines = vec![AUCTION_SEATS
+        .into_iter()
+        .map(|seat| pad(seat_name(seat), CALL_WIDTH))
+        .collect::<String>()];
+
+    let start = AUCTION_SEATS.position(auction.first);
+    let mut row = pad("", start * CALL_WIDTH);
+    for (i, call) in auction.calls.iter().enumerate() {
+        let mut text = format_call(call.call, symbols);
+        if call.alert {
+            text.push('!');
+        }
+        row.push_str(&pad(&text, CALL_WIDTH));
+        if (start + i) % 4 == 3 {
+            lines.push(std::mem::take(&mut row));
+        }
+    }
+    if !row.trim().is_empty() {
+        lines.push(row);
+    }
+    finish(lines)
+}
+
+/// Render a game for pasting into a forum: diagram, auction, opening lead
+/// and result, as far as the game records them
+pub fn game_summary(game: &Game) -> String {
+    game_summary_with(game, &WriteOptions::default())
+}
+
+/// Render a game summary with the given suit symbols and layout
+pub fn game_summary_with(game: &Game, options: &WriteOptions) -> String {
+    let symbols = options.symbols;
+    let absent = options.absent_seats(&game.board.deal, &[]);
+    let mut out = diagram_text(&game.board, &absent, options);
+
+    if let Some(auction) = &game.auction {
+        out.push('\n');
+        out.push_str(&auction_text(auction, symbols));
+    }
+
+    let lead = game
+        .play
+        .as_ref()
+        .and_then(|play| Some((play.leader, play.tricks.first()?[0]?)));
+    let result = PlayedResult::from_game(game);
+    if lead.is_some() || result.is_some() {
+        out.push('\n');
+    }
+    if let Some((leader, card)) = lead {
+        out.push_str(&format!(
+            "Lead: {}{} ({})\n",
+            symbols.suit(card.suit),
+            card.rank.to_char(),
+            seat_name(leader)
+        ));
+    }
+    if let Some(result) = result {
+        out.push_str(&format!("Result: {}\n", result));
+    }
+    options.finish(out)
+}
+
+/// Render a board as BBCode: a bold header and one line per hand, with
+/// red suit symbols for hearts and diamonds; the `hidden` seats are named
+/// without their cards
+pub fn render_bbcode(board: &Board, hidden: &[Direction]) -> String {
+    render_bbcode_with(board, hidden, &WriteOptions::default())
+}
+
+/// Render a board as BBCode with the given suit symbols, spot cards,
+/// partial deal handling and layout
+pub fn render_bbcode_with(board: &Board, hidden: &[Direction], options: &WriteOptions) -> String {
+    let absent = options.absent_seats(&board.deal, hidden);
+    let mut out = format!("[b]{}[/b]\n", header(board).join(", "));
+    for seat in SEATS {
+        if absent.contains(&seat) {
+            out.push_str(&format!("[b]{}[/b]:\n", seat_name(seat)));
+            continue;
+        }
+        let suits: Vec<String> = Suit::ALL
+            .iter()
+            .map(|&suit| {
+                let symbol = match suit {
+                    Suit::Hearts | Suit::Diamonds => {
+                        format!("[color=red]{}[/color]", options.symbols.suit(suit))
+                    }
+                    Suit::Spades | Suit::Clubs => options.symbols.suit(suit).to_string(),
+                };
+                format!(
+                    "{} {}",
+                    symbol,
+                    suit_cards(board, seat, suit, options.spots_below)
+                )
+            })
+            .collect();
+        out.push_str(&format!(
+            "[b]{}[/b]: {}\n",
+            seat_name(seat),
+            suits.join(" ")
+        ));
+    }
+    options.finish(out)
+}
+
+/// Render a board as a Markdown table in diagram layout; the `hidden`
+/// seats are named without their cards
+pub fn render_markdown(board: &Board, hidden: &[Direction]) -> String {
+    render_markdown_with(board, hidden, &WriteOptions::default())
+}
+
+/// Render a board as a Markdown table with the given suit symbols, spot
+/// cards, partial deal handling and layout
+pub fn render_markdown_with(board: &Board, hidden: &[Direction], options: &WriteOptions) -> String {
+    let absent = options.absent_seats(&board.deal, hidden);
+    let cell = |seat: Direction| {
+        let mut lines = vec![format!("**{}**", seat_name(seat))];
+        if !absent.contains(&seat) {
+            lines.extend(Suit::ALL.iter().map(|&suit| {
+                format!(
+                    "{} {}",
+                    options.symbols.suit(suit),
+                    suit_cards(board, seat, suit, options.spots_below)
+                )
+            }));
+        }
+        lines.join("<br>")
+    };
+
+    let rows = [
+        [
+            header(board).join("<br>"),
+            cell(Direction::North),
+            String::new(),
+        ],
+        [cell(Direction::West), String::new(), cell(Direction::East)],
+        [String::new(), cell(Direction::South), String::new()],
+    ];
+    let mut out = String::from("| | | |\n|:--|:--|:--|\n");
+    for row in rows {
+        out.push_str(&format!("| {} | {} | {} |\n", row[0], row[1], row[2]));
+    }
+    options.finish(out)
+}
+
+/// Seats in diagram reading order
+const SEATS: [Direction; 4] = [
+    Direction::North,
+    Direction::West,
+    Direction::East,
+    Direction::South,
+];
+
+/// Board number, dealer and vulnerability, as far as known
+fn header(board: &Board) -> Vec<String> {
+    let mut header = Vec::new();
+    if let Some(number) = board.number {
+        header.push(format!("Board {}", number));
+    }
+    if let Some(dealer) = board.dealer {
+        header.push(format!("Dealer {}", dealer.to_char()));
+    }
+    header.push(format!("Vul {}", board.vulnerable.to_pbn()));
+    header
+}
+
+/// Seat name followed by the four suits, or blank suit lines if hidden
+fn hand_lines(
+    board: &Board,
+    seat: Direction,
+    hidden: &[Direction],
+    options: &WriteOptions,
+) -> Vec<String> {
+    let mut lines = vec![seat_name(seat).to_string()];
+    for suit in Suit::ALL {
+        if hidden.contains(&seat) {
+            lines.push(String::new());
+        } else {
+            lines.push(format!(
+                "{} {}",
+                options.symbols.suit(suit),
+                suit_cards(board, seat, suit, options.spots_below)
+            ));
+        }
+    }
+    lines
+}
+
+/// Ranks held in a suit, highest first, or `-` for a void; ranks below
+/// `spots_below` are written as `x`
+fn suit_cards(board: &Board, seat: Direction, suit: Suit, spots_below: Option<Rank>) -> String {
+    let mut ranks: Vec<Rank> = board
+        .deal
+        .hand(seat)
+        .cards_in_suit(suit)
+        .iter()
+        .map(|card| card.rank)
+        .collect();
+    if ranks.is_empty() {
+        return "-".to_string();
+    }
+    ranks.sort_by_key(|&rank| Reverse(rank));
+    ranks
+        .iter()
+        .map(|&rank| rank_char(rank, spots_below))
+        .collect()
+}
+
+fn format_call(call: Call, symbols: SuitSymbols) -> String {
+    match call {
+        Call::Bid(level, strain) => {
+            let strain = match strain {
+                Strain::Clubs => symbols.suit(Suit::Clubs).to_string(),
+                Strain::Diamonds => symbols.suit(Suit::Diamonds).to_string(),
+                Strain::Hearts => symbols.suit(Suit::Hearts).to_string(),
+                Strain::Spades => symbols.suit(Suit::Spades).to_string(),
+                Strain::NoTrump => "NT".to_string(),
+            };
+            format!("{}{}", level, strain)
+        }
+        other => other.to_string(),
+    }
+}
+
+fn seat_name(seat: Direction) -> &'static str {
+    match seat {
+        Direction::North => "North",
+        Direction::East => "East",
+        Direction::South => "South",
+        Direction::West => "West",
+    }
+}
+
+/// Pad to `width` characters (not bytes, so suit symbols count as one)
+fn pad(text: &str, width: usize) -> String {
+    let len = text.chars().count();
+    format!("{}{}", text, " ".repeat(width.saturating_sub(len)))
+}
+
+/// Join lines, dropping trailing spaces
+fn finish(lines: Vec<String>) -> String {
+    lines
+        .iter()
+        .map(|line| line.trim_end().to_string() + "\n")
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pbn::read_pbn_games;
+
+    const PBN: &str = r#"[Board "1"]
+[Dealer "N"]
+[Vulnerable "None"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+[Declarer "E"]
+[Contract "3NT"]
+[Result "10"]
+[Auction "N"]
+Pass 1C! Pass 1H
+Pass 3NT AP
+[Play "S"]
+S9 - - -
+"#;
+
+    #[test]
+    fn test_game_summary() {
+        let games = read_pbn_games(PBN).unwrap();
+        let summary = game_summary(&games[0]);
+        let lines: Vec<&str> = summary.lines().collect();
+
+        assert_eq!(lines[0], "Board 1       North");
+        assert_eq!(lines[1], "Dealer N      ♠ K843");
+        assert_eq!(lines[6], "♠ T5                        ♠ AQJ7");
+        assert_eq!(lines[10], "              South");
+        assert!(summary.contains("\nWest   North  East   South\n"));
+        assert!(summary.contains("\n       Pass   1♣!    Pass\n1♥     Pass   3NT    Pass\n"));
+        assert!(summary.contains("Lead: ♠9 (South)\n"));
+        assert!(summary.ends_with("Result: 3NT E +1\n"));
+    }
+
+    #[test]
+    fn test_hidden_seats() {
+        let games = read_pbn_games(PBN).unwrap();
+        let diagram = render_diagram(&games[0].board, &[Direction::East, Direction::West]);
+        assert!(!diagram.contains("AQJ7"));
+        assert!(diagram.contains("K843"));
+        assert!(diagram.contains("\nWest                        East\n"));
+    }
+
+    #[test]
+    fn test_bbcode_and_markdown() {
+        let games = read_pbn_games(PBN).unwrap();
+        let board = &games[0].board;
+
+        let bbcode = render_bbcode(board, &[Direction::West]);
+        assert!(bbcode.starts_with("[b]Board 1, Dealer N, Vul None[/b]\n"));
+        assert!(bbcode.contains("[b]North[/b]: ♠ K843 [color=red]♥[/color] T542"));
+        assert!(bbcode.contains("\n[b]West[/b]:\n"));
+        assert!(!bbcode.contains("Q9863"));
+
+        let markdown = render_markdown(board, &[Direction::West]);
+        let rows: Vec<&str> = markdown.lines().collect();
+        assert_eq!(rows.len(), 5);
+        assert!(rows[2].starts_with("| Board 1<br>Dealer N<br>Vul None | **North**<br>♠ K843<br>"));
+        assert!(rows[3].starts_with("| **West** |  | **East**<br>♠ AQJ7"));
+    }
+
+    #[test]
+    fn test_bbcode_and_markdown_options() {
+        let games = read_pbn_games(PBN).unwrap();
+        let full = &games[0].board;
+        let mut board = full.clone();
+        board.deal = bridge_types::Deal::new();
+        board
+            .deal
+            .set_hand(Direction::North, full.deal.hand(Direction::North).clone());
+        board
+            .deal
+            .set_hand(Direction::South, full.deal.hand(Direction::South).clone());
+        let options = WriteOptions::new()
+            .with_symbols(SuitSymbols::Letters)
+            .with_spots_below(Some(Rank::Ten))
+            .with_partial_deals(true);
+
+        let bbcode = render_bbcode_with(&board, &[], &options);
+        assert!(bbcode.contains("[b]North[/b]: S Kxxx [color=red]H[/color] Txxx"));
+        assert!(bbcode.contains("\n[b]East[/b]:\n"));
+        assert!(!bbcode.contains(" -"));
+
+        let markdown = render_markdown_with(&board, &[], &options);
+        let rows: Vec<&str> = markdown.lines().collect();
+        assert!(rows[2].contains("**North**<br>S Kxxx<br>H Txxx"));
+        assert!(rows[3].starts_with("| **West** |  | **East** |"));
+    }
+
+    #[test]
+    fn test_letter_symbols() {
+        let games = read_pbn_games(PBN).unwrap();
+        let options = WriteOptions::new().with_symbols(SuitSymbols::Letters);
+        let summary = game_summary_with(&games[0], &options);
+        assert!(summary.contains("\nDealer N      S K843\n"));
+        assert!(summary.contains(" 1C! "));
+        assert!(summary.contains("Lead: S9 (South)\n"));
+    }
+}
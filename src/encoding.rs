@@ -0,0 +1,242 @@
+//! Character encodings of input files.
+//!
+//! Files from older programs are often in ISO-8859-1 or Windows-1252
+//! rather than UTF-8, most visibly in player names. [`TextEncoding`]
+//! decodes file content to a string; the default, [`TextEncoding::Auto`],
+//! reads valid UTF-8 as such and anything else as Windows-1252.
+//!
+//! For consumers that accept only ASCII, [`to_ascii`] transliterates
+//! text, reporting what it changed. When
+//! [`WriteOptions::ascii_only`](crate::WriteOptions::ascii_only) is set,
+//! the PBN writer applies it to each tag value and commentary block before
+//! escaping them, so a curly quote is written as an escaped `\"`, and
+//! [`write_pbn_games_with_report`](crate::pbn::write_pbn_games_with_report)
+//! returns the report; the other writers, which don't quote their text,
+//! apply it to their whole output.
+
+use crate::error::Result;
+
+/// How to decode the bytes of a file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TextEncoding {
+    /// UTF-8 when the content is valid UTF-8, Windows-1252 otherwise
+    #[default]
+    Auto,
+    /// UTF-8; invalid content is an error
+    Utf8,
+    /// UTF-8 with invalid sequences replaced by U+FFFD
+    Utf8Lossy,
+    /// ISO-8859-1 (every byte is the code point of the same value)
+    Latin1,
+    /// Windows-1252 (ISO-8859-1 with printable characters in 0x80-0x9F)
+    Windows1252,
+}
+
+impl TextEncoding {
+    /// Decode `bytes`, dropping a leading UTF-8 byte order mark
+    pub fn decode(self, bytes: &[u8]) -> Result<String> {
+        let utf8 = bytes.strip_prefix(b"\xEF\xBB\xBF").unwrap_or(bytes);
+        Ok(match self {
+            TextEncoding::Auto => match std::str::from_utf8(utf8) {
+                Ok(text) => text.to_string(),
+                Err(_) => decode_windows_1252(bytes),
+            },
+            TextEncoding::Utf8 => std::str::from_utf8(utf8)
+                .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?
+                .to_string(),
+            TextEncoding::Utf8Lossy => String::from_utf8_lossy(utf8).into_owned(),
+            TextEncoding::Latin1 => bytes.iter().map(|&b| char::from(b)).collect(),
+            TextEncoding::Windows1252 => decode_windows_1252(bytes),
+        })
+    }
+}
+
+/// Characters for the bytes 0x80-0x9F; the five unassigned bytes keep
+/// their ISO-8859-1 control code
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{81}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{8D}', '\u{017D}', '\u{8F}',
+    '\u{90}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{9D}', '\u{017E}', '\u{0178}',
+];
+
+fn decode_windows_1252(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .map(|&b| match b {
+            0x80..=0x9F => WINDOWS_1252_HIGH[usize::from(b - 0x80)],
+            _ => char::from(b),
+        })
+        .collect()
+}
+
+/// A non-ASCII character replaced by [`to_ascii`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AsciiChange {
+    /// The character replaced
+    pub original: char,
+    /// Its replacement; empty if it was removed
+    pub replacement: &'static str,
+    /// How many times it occurred
+    pub count: usize,
+}
+
+/// What [`to_ascii`] changed, one entry per distinct character in order
+/// of first occurrence
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AsciiReport {
+    /// The characters replaced or removed
+    pub changes: Vec<AsciiChange>,
+}
+
+impl AsciiReport {
+    /// Whether the text was already ASCII
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// Add the changes of `other`, as if its text followed this one
+    pub(crate) fn merge(&mut self, other: AsciiReport) {
+        for change in other.changes {
+            match self
+                .changes
+                .iter_mut()
+                .find(|c| c.original == change.original)
+            {
+                Some(c) => c.count += change.count,
+                None => self.changes.push(change),
+            }
+        }
+    }
+
+    /// The characters removed because they have no transliteration
+    pub fn removed(&self) -> impl Iterator<Item = char> + '_ {
+        self.changes
+            .iter()
+            .filter(|change| change.replacement.is_empty())
+            .map(|change| change.original)
+    }
+}
+
+/// `text` with accented letters, typographic punctuation and suit symbols
+/// transliterated to ASCII (`Jörg` to `Jorg`, `♠` to `S`) and any other
+/// non-ASCII character removed
+pub fn to_ascii(text: &str) -> (String, AsciiReport) {
+    let mut report = AsciiReport::default();
+    if text.is_ascii() {
+        return (text.to_string(), report);
+    }
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii() {
+            out.push(c);
+            continue;
+        }
+        let replacement = transliterate(c);
+        out.push_str(replacement);
+        match report
+            .changes
+            .iter_mut()
+            .find(|change| change.original == c)
+        {
+            Some(change) => change.count += 1,
+            None => report.changes.push(AsciiChange {
+                original: c,
+                replacement,
+                count: 1,
+            }),
+        }
+    }
+    (out, report)
+}
+
+/// The ASCII form of a non-ASCII character; empty if it has none
+fn transliterate(c: char) -> &'static str {
+    match c {
+        'À'..='Å' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'à'..='å' | 'ā' | 'ă' | 'ą' => "a",
+        'Æ' => "AE",
+        'æ' => "ae",
+        'Ç' | 'Ć' | 'Č' => "C",
+        'ç' | 'ć' | 'č' => "c",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'ð' | 'ď' | 'đ' => "d",
+        'È'..='Ë' | 'Ē' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'è'..='ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'Ğ' => "G",
+        'ğ' => "g",
+        'Ì'..='Ï' | 'Ī' | 'Į' | 'İ' => "I",
+        'ì'..='ï' | 'ī' | 'į' | 'ı' => "i",
+        'Ł' | 'Ľ' => "L",
+        'ł' | 'ľ' => "l",
+        'Ñ' | 'Ń' | 'Ň' => "N",
+        'ñ' | 'ń' | 'ň' => "n",
+        'Ò'..='Ö' | 'Ø' | 'Ő' => "O",
+        'ò'..='ö' | 'ø' | 'ő' => "o",
+        'Œ' => "OE",
+        'œ' => "oe",
+        'Ř' => "R",
+        'ř' => "r",
+        'Ś' | 'Ş' | 'Š' => "S",
+        'ś' | 'ş' | 'š' => "s",
+        'ß' => "ss",
+        'Ť' | 'Ţ' => "T",
+        'ť' | 'ţ' => "t",
+        'Þ' => "TH",
+        'þ' => "th",
+        'Ù'..='Ü' | 'Ū' | 'Ů' | 'Ű' => "U",
+        'ù'..='ü' | 'ū' | 'ů' | 'ű' => "u",
+        'Ý' | 'Ÿ' => "Y",
+        'ý' | 'ÿ' => "y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        'ź' | 'ż' | 'ž' => "z",
+        '♠' | '♤' => "S",
+        '♥' | '♡' => "H",
+        '♦' | '♢' => "D",
+        '♣' | '♧' => "C",
+        '‘' | '’' | '‚' | '′' => "'",
+        '“' | '”' | '„' | '″' | '«' | '»' => "\"",
+        '‐' | '‑' | '–' | '—' | '−' => "-",
+        '…' => "...",
+        '\u{A0}' | '\u{2002}'..='\u{200A}' => " ",
+        '½' => "1/2",
+        '×' => "x",
+        _ => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        let latin1 = b"[North \"J\xF6rg M\xFCller\"]";
+        assert_eq!(
+            TextEncoding::Auto.decode(latin1).unwrap(),
+            "[North \"Jörg Müller\"]"
+        );
+        assert_eq!(
+            TextEncoding::Auto
+                .decode("\u{FEFF}Jörg".as_bytes())
+                .unwrap(),
+            "Jörg"
+        );
+        assert!(TextEncoding::Utf8.decode(latin1).is_err());
+        assert_eq!(
+            TextEncoding::Windows1252.decode(b"\x93x\x94").unwrap(),
+            "\u{201C}x\u{201D}"
+        );
+        assert_eq!(TextEncoding::Latin1.decode(b"\x93").unwrap(), "\u{93}");
+    }
+
+    #[test]
+    fn test_to_ascii() {
+        let (text, report) = to_ascii("Jörg Müller – 2♠ “Ü” 漢");
+        assert_eq!(text, "Jorg Muller - 2S \"U\" ");
+        assert_eq!(report.changes[0].original, 'ö');
+        assert_eq!(report.changes[1].replacement, "u");
+        assert!(report.removed().eq(['漢']));
+        assert_eq!(to_ascii("plain").1, AsciiReport::default());
+    }
+}
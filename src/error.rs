@@ -1,5 +1,6 @@
 //! Error types for bridge file format parsing.
 
+use std::fmt;
 use thiserror::Error;
 
 /// Errors that can occur when parsing bridge file formats
@@ -8,15 +9,153 @@ pub enum ParseError {
     #[error("PBN parse error: {0}")]
     Pbn(String),
 
+    #[error("PBN parse error in game at {span}: {message}")]
+    PbnGame {
+        span: GameSpan,
+        message: String,
+        /// Where in the game the problem is, when it is in one place
+        location: Option<SourceLocation>,
+    },
+
+    #[error("{format} parse error at {location}: {message}")]
+    Syntax {
+        /// The format or kind of input (`Oneline`, `Printall`, ...)
+        format: &'static str,
+        location: SourceLocation,
+        message: String,
+    },
+
+    #[error("Invalid {tag} tag value: {message}")]
+    TagValue {
+        /// The tag whose value was rejected
+        tag: String,
+        message: String,
+    },
+
     #[error("LIN parse error: {0}")]
     Lin(String),
 
     #[error("Oneline parse error: {0}")]
     Oneline(String),
 
+    #[error("Dealer script error: {0}")]
+    Dealer(String),
+
+    #[error("JSON parse error: {0}")]
+    Json(String),
+
+    #[error("Unrecognized file format: {0}")]
+    UnknownFormat(String),
+
+    #[error("Limit exceeded: {0}")]
+    LimitExceeded(String),
+
+    #[error("Operation cancelled")]
+    Cancelled,
+
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
 }
 
+impl ParseError {
+    /// Where in the input the error is, if known
+    pub fn location(&self) -> Option<&SourceLocation> {
+        match self {
+            ParseError::PbnGame { location, .. } => location.as_ref(),
+            ParseError::Syntax { location, .. } => Some(location),
+            _ => None,
+        }
+    }
+
+    /// Move the location down by `lines` lines, for errors from parsers
+    /// given part of a larger input
+    pub(crate) fn offset_lines(mut self, lines: usize) -> Self {
+        if let ParseError::Syntax { location, .. } = &mut self {
+            location.line += lines;
+        }
+        self
+    }
+}
+
+/// A place in the input: the offending text and where it is on its line
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// Line number (1-based)
+    pub line: usize,
+    /// First column of the text (1-based, in characters)
+    pub start_column: usize,
+    /// Last column of the text (inclusive)
+    pub end_column: usize,
+    /// The offending text
+    pub text: String,
+}
+
+impl SourceLocation {
+    /// The location of `text`, which starts at byte `offset` of `line_text`
+    pub(crate) fn new(line: usize, line_text: &str, offset: usize, text: &str) -> Self {
+        let start_column = line_text
+            .get(..offset)
+            .map_or(0, |before| before.chars().count())
+            + 1;
+        Self {
+            line,
+            start_column,
+            end_column: start_column + text.chars().count().max(1) - 1,
+            text: text.to_string(),
+        }
+    }
+
+    /// The location of the first occurrence of `text` in `line_text`, or of
+    /// the whole line when it doesn't occur
+    pub(crate) fn find(line: usize, line_text: &str, text: &str) -> Self {
+        match line_text.find(text).filter(|_| !text.is_empty()) {
+            Some(offset) => Self::new(line, line_text, offset, text),
+            None => Self::new(line, line_text, 0, line_text),
+        }
+    }
+}
+
+impl fmt::Display for SourceLocation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, columns {}-{} (`{}`)",
+            self.line, self.start_column, self.end_column, self.text
+        )
+    }
+}
+
+/// A game that was skipped because it could not be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BoardError {
+    /// Lines of the skipped game
+    pub span: GameSpan,
+    /// Description of the problem
+    pub message: String,
+    /// Where in the game the problem is, when it is in one place
+    pub location: Option<SourceLocation>,
+}
+
+impl fmt::Display for BoardError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "game at {}: {}", self.span, self.message)
+    }
+}
+
+/// The range of input lines (1-based, inclusive) occupied by one PBN game
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GameSpan {
+    /// First line of the game
+    pub start_line: usize,
+    /// Last line of the game
+    pub end_line: usize,
+}
+
+impl fmt::Display for GameSpan {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "lines {}-{}", self.start_line, self.end_line)
+    }
+}
+
 /// Result type for bridge parsing operations
 pub type Result<T> = std::result::Result<T, ParseError>;
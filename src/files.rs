@@ -0,0 +1,101 @@
+//! Reading and writing whole files in any supported format.
+
+use crate::encoding::TextEncoding;
+use crate::error::{ParseError, Result};
+use crate::format::Format;
+use crate::layout::WriteOptions;
+use bridge_types::{Board, Deal};
+use std::path::Path;
+
+/// Read every board from a file.
+///
+/// The format is taken from the file extension, or detected from the
+/// content when the extension doesn't identify one.
+pub fn read_deals(path: impl AsRef<Path>) -> Result<Vec<Board>> {
+    read_path(path.as_ref()).map(|(_, boards)| boards)
+}
+
+/// Write boards to a file in the given format, replacing its content.
+///
+/// Oneline and printall keep only the deals.
+pub fn write_deals(path: impl AsRef<Path>, boards: &[Board], format: Format) -> Result<()> {
+    std::fs::write(path, format_boards(boards, format))?;
+    Ok(())
+}
+
+/// The content [`write_deals`] writes
+pub(crate) fn format_boards(boards: &[Board], format: Format) -> String {
+    let deals = || boards.iter().map(|b| b.deal.clone()).collect::<Vec<Deal>>();
+    match format {
+        Format::Pbn => crate::pbn::write_pbn(boards),
+        Format::Lin => crate::lin::write_lin(boards),
+        Format::Oneline => crate::oneline::write_oneline(&deals(), &WriteOptions::new()),
+        Format::Printall => crate::printall::write_printall(&deals(), &WriteOptions::new()),
+    }
+}
+
+/// Read a file and identify its format
+pub(crate) fn read_path(path: &Path) -> Result<(Format, Vec<Board>)> {
+    let content = TextEncoding::Auto.decode(&std::fs::read(path)?)?;
+    let format = Format::from_path(path)
+        .or_else(|| Format::detect(&content))
+        .ok_or_else(|| ParseError::UnknownFormat(path.display().to_string()))?;
+    Ok((format, read_boards(&content, format)?))
+}
+
+/// Parse the whole content of a file in the given format
+fn read_boards(content: &str, format: Format) -> Result<Vec<Board>> {
+    match format {
+        Format::Pbn => crate::pbn::read_pbn(content),
+        Format::Lin => Ok(crate::lin::parse_lin_file(content)?
+            .iter()
+            .map(|data| data.to_board())
+            .collect()),
+        Format::Oneline => content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                crate::oneline::parse_oneline(line)
+                    .map(|deal| Board::new().with_deal(deal))
+                    .map_err(|e| e.offset_lines(i))
+            })
+            .collect(),
+        Format::Printall => Ok(crate::printall::parse_printall_string(content)?
+            .into_iter()
+            .map(|deal| Board::new().with_deal(deal))
+            .collect()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bridge_types::Direction;
+
+    #[test]
+    fn test_write_and_read_deals() {
+        let dir = std::env::temp_dir().join(format!("files-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let deal =
+            Deal::from_pbn("N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ")
+                .unwrap();
+        let boards = [Board::new().with_number(4).with_deal(deal.clone())];
+
+        let pbn = dir.join("out.pbn");
+        let printall = dir.join("out.txt");
+        write_deals(&pbn, &boards, Format::Pbn).unwrap();
+        write_deals(&printall, &boards, Format::Printall).unwrap();
+        let from_pbn = read_deals(&pbn).unwrap();
+        let from_printall = read_deals(&printall).unwrap();
+        std::fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(from_pbn[0].number, Some(4));
+        for board in [&from_pbn[0], &from_printall[0]] {
+            assert_eq!(
+                board.deal.to_pbn(Direction::North),
+                deal.to_pbn(Direction::North)
+            );
+        }
+    }
+}
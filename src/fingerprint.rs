@@ -0,0 +1,102 @@
+//! Deal fingerprints.
+//!
+//! A fingerprint records which seat holds each of the 52 cards, in
+//! [canonical order](crate::cards), packed into 104 bits. Two deals have
+//! the same fingerprint exactly when every card is in the same hand, so
+//! fingerprints identify deals independently of board numbers.
+
+use crate::cards::card_seats;
+use crate::seat::SeatOrder;
+use bridge_types::Deal;
+use std::fmt;
+use std::str::FromStr;
+
+/// Compact identity of a complete deal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint(u128);
+
+impl Fingerprint {
+    /// Fingerprint a deal. Returns `None` unless every card is held by exactly one seat.
+    pub fn of(deal: &Deal) -> Option<Fingerprint> {
+        // With every card held, 52 cards in all means none is held twice
+        let held: usize = SeatOrder::NESW
+            .into_iter()
+            .map(|dir| deal.hand(dir).len())
+            .sum();
+        if held != 52 {
+            return None;
+        }
+
+        let mut bits: u128 = 0;
+        for (_, holder) in card_seats(deal) {
+            // A seat is encoded as its index in N, E, S, W order
+            let seat = SeatOrder::NESW
+                .into_iter()
+                .position(|dir| Some(dir) == holder)?;
+            bits = (bits << 2) | seat as u128;
+        }
+
+        Some(Fingerprint(bits))
+    }
+
+    /// The raw 104-bit value
+    pub fn as_u128(self) -> u128 {
+        self.0
+    }
+}
+
+impl fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:026x}", self.0)
+    }
+}
+
+impl FromStr for Fingerprint {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        if s.len() != 26 {
+            return Err(format!("fingerprint must be 26 hex digits, got {:?}", s));
+        }
+        u128::from_str_radix(s, 16)
+            .map(Fingerprint)
+            .map_err(|_| format!("invalid fingerprint: {:?}", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fingerprint_round_trip() {
+        let deal =
+            Deal::from_pbn("N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ")
+                .unwrap();
+        let fp = Fingerprint::of(&deal).unwrap();
+        let text = fp.to_string();
+        assert_eq!(text.len(), 26);
+        assert_eq!(text.parse::<Fingerprint>().unwrap(), fp);
+    }
+
+    #[test]
+    fn test_same_deal_any_orientation() {
+        let a =
+            Deal::from_pbn("N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ")
+                .unwrap();
+        let b =
+            Deal::from_pbn("S:962.AJ7.KT82.J75 T5.Q9863.A943.KQ K843.T542.J6.863 AQJ7.K.Q75.AT942")
+                .unwrap();
+        let c =
+            Deal::from_pbn("N:AQJ7.K.Q75.AT942 K843.T542.J6.863 962.AJ7.KT82.J75 T5.Q9863.A943.KQ")
+                .unwrap();
+        assert_eq!(Fingerprint::of(&a), Fingerprint::of(&b));
+        assert_ne!(Fingerprint::of(&a), Fingerprint::of(&c));
+    }
+
+    #[test]
+    fn test_incomplete_deal() {
+        assert_eq!(Fingerprint::of(&Deal::new()), None);
+    }
+}
@@ -0,0 +1,48 @@
+//! Sample inputs for tests and examples.
+//!
+//! Small, realistic files in each supported format, embedded in the crate
+//! so integration tests and doctests downstream can use them without
+//! keeping their own copies. The files describe the same two deals.
+//!
+//! ```
+//! use bridge_encodings::{fixtures, pbn};
+//!
+//! let games = pbn::read_pbn_games(fixtures::SAMPLE_PBN).unwrap();
+//! assert_eq!(games.len(), 2);
+//! ```
+
+/// A PBN file with two games: a played board with auction, note and
+/// partial play, and a hand record with double dummy tricks
+pub const SAMPLE_PBN: &str = include_str!("sample.pbn");
+
+/// A BBO vugraph LIN file: a `vg` header line and two `qx` records with
+/// player names, auctions, an annotated alert, card play and claims
+pub const SAMPLE_LIN_VUGRAPH: &str = include_str!("sample_vugraph.lin");
+
+/// Two deals in dealer.exe oneline format
+pub const SAMPLE_ONELINE: &str = include_str!("sample.oneline");
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Fingerprint;
+
+    #[test]
+    fn test_fixtures_agree() {
+        let pbn = crate::pbn::read_pbn(SAMPLE_PBN).unwrap();
+        // The vg header line reads as a record without a deal
+        let lin = &crate::lin::parse_lin_file(SAMPLE_LIN_VUGRAPH).unwrap()[1..];
+        let oneline: Vec<_> = SAMPLE_ONELINE
+            .lines()
+            .map(|line| crate::oneline::parse_oneline(line).unwrap())
+            .collect();
+
+        assert_eq!((pbn.len(), lin.len(), oneline.len()), (2, 2, 2));
+        for i in 0..2 {
+            let fingerprint = Fingerprint::of(&pbn[i].deal).unwrap();
+            assert_eq!(Fingerprint::of(&lin[i].deal), Some(fingerprint));
+            assert_eq!(Fingerprint::of(&oneline[i]), Some(fingerprint));
+        }
+        assert_eq!(lin[0].played_result().map(|r| r.tricks), Some(9));
+    }
+}
@@ -0,0 +1,92 @@
+//! File format identification.
+
+use std::path::Path;
+
+/// A bridge file format supported by this crate.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Format {
+    /// Portable Bridge Notation
+    Pbn,
+    /// BBO LIN records, one per line
+    Lin,
+    /// dealer.exe oneline format, one deal per line
+    Oneline,
+    /// dealer.exe printall (newspaper-style) format
+    Printall,
+}
+
+impl Format {
+    /// Guess the format from a file extension (`.pbn`, `.lin`).
+    ///
+    /// Returns `None` for extensions that don't identify a single format.
+    pub fn from_path(path: &Path) -> Option<Format> {
+        let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+        match ext.as_str() {
+            "pbn" => Some(Format::Pbn),
+            "lin" => Some(Format::Lin),
+            _ => None,
+        }
+    }
+
+    /// Guess the format from the first non-blank line of `content`.
+    pub fn detect(content: &str) -> Option<Format> {
+        let line = content.lines().map(str::trim).find(|l| !l.is_empty())?;
+
+        if line.starts_with('[')
+            || line.starts_with('%')
+            || line.starts_with('{')
+            || line.starts_with(';')
+        {
+            return Some(Format::Pbn);
+        }
+        if is_lin_line(line) {
+            return Some(Format::Lin);
+        }
+        if crate::oneline::parse_oneline(line).is_ok() {
+            return Some(Format::Oneline);
+        }
+        if crate::reader::is_board_number_line(line) {
+            return Some(Format::Printall);
+        }
+        None
+    }
+}
+
+/// Check if a line starts with a LIN command token (e.g. "pn|", "md|", "qx|")
+fn is_lin_line(line: &str) -> bool {
+    match line.split_once('|') {
+        Some((command, _)) => command.len() == 2 && command.chars().all(|c| c.is_ascii_lowercase()),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_path() {
+        assert_eq!(Format::from_path(Path::new("a/b.pbn")), Some(Format::Pbn));
+        assert_eq!(Format::from_path(Path::new("x.LIN")), Some(Format::Lin));
+        assert_eq!(Format::from_path(Path::new("deals.txt")), None);
+        assert_eq!(Format::from_path(Path::new("deals")), None);
+    }
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(Format::detect("\n% PBN 2.1\n"), Some(Format::Pbn));
+        assert_eq!(Format::detect("[Board \"1\"]"), Some(Format::Pbn));
+        assert_eq!(
+            Format::detect("pn|South,West,North,East|md|3SAKHJD876C5432,,,|"),
+            Some(Format::Lin)
+        );
+        assert_eq!(
+            Format::detect(
+                "n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72"
+            ),
+            Some(Format::Oneline)
+        );
+        assert_eq!(Format::detect("   1.\n"), Some(Format::Printall));
+        assert_eq!(Format::detect("Generated 100 hands"), None);
+    }
+}
@@ -0,0 +1,434 @@
+//! BridgeWinners hand-post notation.
+//!
+//! Forum posts give a hand as four suit holdings, spades first, with `x`
+//! for a small card whose rank doesn't matter:
+//! ```text
+//! AKxx Kx Qxxx xxx
+//! ♠AKxx ♥Kx ♦Qxxx ♣xxx
+//! ```
+//! and an auction as calls separated by dashes or spaces, with opponents'
+//! calls often in parentheses and a `?` for the call being asked about:
+//! ```text
+//! 1♥ (1♠) 2♣ (P) ?
+//! ```
+//!
+//! [`PartialHand`] keeps the `x` cards as counts per suit, since they
+//! stand for any card below the ranks given; [`PartialDeal::complete`]
+//! turns them into concrete low cards for a whole deal.
+
+use crate::cards::{RANKS, SUITS};
+use crate::error::{ParseError, Result, SourceLocation};
+use crate::pbn::Call;
+use crate::seat::{parse_seat, SeatOrder};
+use bridge_types::{Board, Card, Deal, Direction, Hand, Rank, Suit};
+use std::fmt;
+
+/// A hand whose small cards may be given only as `x`
+#[derive(Debug, Clone)]
+pub struct PartialHand {
+    /// The cards given by rank
+    pub known: Hand,
+    /// Number of `x` cards in each suit, spades first
+    pub spots: [u8; 4],
+}
+
+impl PartialHand {
+    /// A hand with no cards
+    pub fn new() -> Self {
+        Self {
+            known: Hand::new(),
+            spots: [0; 4],
+        }
+    }
+
+    /// Number of cards in `suit`, known or not
+    pub fn suit_length(&self, suit: Suit) -> usize {
+        self.known.suit_length(suit) + self.spots(suit)
+    }
+
+    /// Number of `x` cards in `suit`
+    pub fn spots(&self, suit: Suit) -> usize {
+        SUITS
+            .iter()
+            .position(|&s| s == suit)
+            .map_or(0, |i| self.spots[i] as usize)
+    }
+
+    /// Number of cards, known or not
+    pub fn len(&self) -> usize {
+        self.known.len() + self.spots.iter().map(|&n| n as usize).sum::<usize>()
+    }
+
+    /// Whether the hand has no cards
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// High card points; `x` cards count as small cards
+    pub fn hcp(&self) -> u8 {
+        self.known.hcp()
+    }
+
+    /// The hand with each `x` replaced by the lowest card of its suit not
+    /// already held
+    pub fn with_lowest_spots(&self) -> Hand {
+        let mut hand = self.known.clone();
+        for (suit, &count) in SUITS.iter().zip(&self.spots) {
+            let free = RANKS
+                .iter()
+                .rev()
+                .map(|&rank| Card::new(*suit, rank))
+                .filter(|&card| !self.known.has_card(card));
+            for card in free.take(count as usize) {
+                hand.add_card(card);
+            }
+        }
+        hand
+    }
+
+    /// A board with this hand at `seat` and the other hands empty; `x`
+    /// cards are filled in as by [`with_lowest_spots`](Self::with_lowest_spots)
+    pub fn to_board(&self, seat: Direction) -> Board {
+        let mut deal = Deal::new();
+        deal.set_hand(seat, self.with_lowest_spots());
+        Board::new().with_deal(deal)
+    }
+}
+
+impl Default for PartialHand {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl fmt::Display for PartialHand {
+    /// Post notation: `AKxx Kx Qxxx xxx`, with `-` for a void
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, suit) in SUITS.iter().enumerate() {
+            if i > 0 {
+                f.write_str(" ")?;
+            }
+            let mut holding: String = RANKS
+                .iter()
+                .filter(|&&rank| self.known.has_card(Card::new(*suit, rank)))
+                .map(|rank| rank.to_char())
+                .collect();
+            holding.extend(std::iter::repeat_n('x', self.spots[i] as usize));
+            if holding.is_empty() {
+                holding.push('-');
+            }
+            f.write_str(&holding)?;
+        }
+        Ok(())
+    }
+}
+
+/// A deal whose hands may have `x` cards; hands not given have no cards
+#[derive(Debug, Clone, Default)]
+pub struct PartialDeal {
+    /// Hands in North, East, South, West order
+    pub hands: [PartialHand; 4],
+}
+
+impl PartialDeal {
+    /// A deal with no cards
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The hand at `seat`
+    pub fn hand(&self, seat: Direction) -> &PartialHand {
+        &self.hands[SeatOrder::NESW.position(seat)]
+    }
+
+    /// Set the hand at `seat`
+    pub fn set_hand(&mut self, seat: Direction, hand: PartialHand) {
+        self.hands[SeatOrder::NESW.position(seat)] = hand;
+    }
+
+    /// Parse a Deal tag value whose hands may contain `x`, like
+    /// `S:AKxx.Kx.Qxxx.xxx - - -`; a hand given as `-` has no cards
+    pub fn from_pbn(value: &str) -> std::result::Result<Self, String> {
+        let invalid = |message: &str| format!("invalid Deal: \"{}\": {}", value, message);
+        let (first, hands) = value
+            .trim()
+            .split_once(':')
+            .ok_or_else(|| invalid("no first seat"))?;
+        let first = parse_seat(first).ok_or_else(|| invalid("invalid first seat"))?;
+        let hands: Vec<&str> = hands.split_whitespace().collect();
+        if hands.len() != 4 {
+            return Err(invalid("expected 4 hands"));
+        }
+        let mut deal = Self::new();
+        for (text, seat) in hands.into_iter().zip(SeatOrder::clockwise_from(first)) {
+            if text != "-" {
+                let hand = parse_holdings(text).map_err(|(_, _, message)| invalid(&message))?;
+                deal.set_hand(seat, hand);
+            }
+        }
+        Ok(deal)
+    }
+
+    /// The deal with each `x` replaced by the lowest card of its suit that
+    /// no hand holds, given out in North, East, South, West order.
+    ///
+    /// Fails if a card is in more than one hand or a suit has more than
+    /// 13 cards.
+    pub fn complete(&self) -> std::result::Result<Deal, String> {
+        let mut hands: Vec<Hand> = self.hands.iter().map(|h| h.known.clone()).collect();
+        for (i, &suit) in SUITS.iter().enumerate() {
+            let mut held: Vec<Card> = Vec::new();
+            for card in hands.iter().flat_map(|hand| hand.cards_in_suit(suit)) {
+                if held.contains(&card) {
+                    return Err(format!(
+                        "{}{} is in more than one hand",
+                        suit.to_char(),
+                        card.rank.to_char()
+                    ));
+                }
+                held.push(card);
+            }
+            let mut free = RANKS
+                .iter()
+                .rev()
+                .map(|&rank| Card::new(suit, rank))
+                .filter(|card| !held.contains(card));
+            for (hand, partial) in hands.iter_mut().zip(&self.hands) {
+                for _ in 0..partial.spots[i] {
+                    let card = free
+                        .next()
+                        .ok_or_else(|| format!("more than 13 {:?}", suit))?;
+                    hand.add_card(card);
+                }
+            }
+        }
+        let mut deal = Deal::new();
+        for (seat, hand) in SeatOrder::NESW.into_iter().zip(hands) {
+            deal.set_hand(seat, hand);
+        }
+        Ok(deal)
+    }
+}
+
+/// An auction from a post
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PostAuction {
+    /// The calls, in order
+    pub calls: Vec<Call>,
+    /// Whether the auction ends with `?`, asking for the next call
+    pub question: bool,
+}
+
+/// Parse a hand in post notation.
+///
+/// Suits are separated by spaces or dots, spades first, and may be
+/// prefixed by a suit symbol or letter followed by `:`; `-` or `void`
+/// (or nothing, between dots) is an empty suit and `10` is accepted for
+/// the ten.
+pub fn parse_partial_hand(input: &str) -> Result<PartialHand> {
+    parse_holdings(input).map_err(|(offset, text, message)| ParseError::Syntax {
+        format: "BridgeWinners",
+        location: SourceLocation::new(1, input, offset, text),
+        message,
+    })
+}
+
+/// Parse a hand as [`parse_partial_hand`] does.
+///
+/// Errors give the byte offset and text of the offending part of `input`
+pub(crate) fn parse_holdings(
+    input: &str,
+) -> std::result::Result<PartialHand, (usize, &str, String)> {
+    let dotted = input.contains('.');
+    let groups: Vec<(usize, &str)> = input
+        .split(|c: char| if dotted { c == '.' } else { c.is_whitespace() })
+        .scan(0, |offset, group| {
+            let start = *offset;
+            *offset += group.len() + 1;
+            let trimmed = group.trim_start();
+            Some((start + group.len() - trimmed.len(), trimmed.trim_end()))
+        })
+        .filter(|(_, group)| dotted || !group.is_empty())
+        .collect();
+    if groups.len() != 4 {
+        return Err((
+            0,
+            input.trim_end(),
+            format!("expected 4 suits, got {}", groups.len()),
+        ));
+    }
+
+    let mut hand = PartialHand::new();
+    for (i, (&suit, &(offset, group))) in SUITS.iter().zip(&groups).enumerate() {
+        let holding = strip_suit_prefix(group, suit);
+        let offset = offset + group.len() - holding.len();
+        if matches!(holding, "-" | "—" | "void" | "Void") {
+            continue;
+        }
+        let mut chars = holding.char_indices().peekable();
+        while let Some((at, c)) = chars.next() {
+            let rank = match c {
+                'x' | 'X' => {
+                    hand.spots[i] += 1;
+                    continue;
+                }
+                '1' if chars.next_if(|&(_, c)| c == '0').is_some() => Some(Rank::Ten),
+                _ => Rank::from_char(c.to_ascii_uppercase()),
+            };
+            let text = &input[offset + at..offset + at + c.len_utf8()];
+            let card = rank
+                .map(|rank| Card::new(suit, rank))
+                .ok_or_else(|| (offset + at, text, format!("invalid rank: {}", c)))?;
+            if hand.known.has_card(card) {
+                return Err((offset + at, text, format!("duplicate card: {}", c)));
+            }
+            hand.known.add_card(card);
+        }
+        if hand.suit_length(suit) > 13 {
+            let text = &input[offset..offset + holding.len()];
+            return Err((offset, text, format!("more than 13 {:?}", suit)));
+        }
+    }
+    if hand.len() > 13 {
+        return Err((
+            0,
+            input.trim_end(),
+            format!("{} cards in one hand", hand.len()),
+        ));
+    }
+    Ok(hand)
+}
+
+/// Parse an auction in post notation.
+///
+/// Calls are separated by spaces, dashes, commas or slashes; parentheses
+/// around opponents' calls are ignored, suit symbols stand for their
+/// letters, and `Dbl`, `Rdbl` and `AP` (all pass) are accepted.
+pub fn parse_post_auction(input: &str) -> Result<PostAuction> {
+    let mut auction = PostAuction::default();
+    let mut offset = 0;
+    for token in input.split(|c: char| c.is_whitespace() || matches!(c, '-' | ',' | '/')) {
+        let start = offset;
+        offset += token.len() + 1;
+        let text = token.trim_matches(|c| c == '(' || c == ')');
+        if text.is_empty() {
+            continue;
+        }
+        let at = start + token.find(text).unwrap_or(0);
+        let error = |message: String| ParseError::Syntax {
+            format: "BridgeWinners",
+            location: SourceLocation::new(1, input, at, text),
+            message,
+        };
+        if auction.question {
+            return Err(error(format!("call after `?`: {}", text)));
+        }
+        if text == "?" {
+            auction.question = true;
+            continue;
+        }
+        let letters: String = text
+            .chars()
+            .map(|c| match c {
+                '♠' => 'S',
+                '♥' => 'H',
+                '♦' => 'D',
+                '♣' => 'C',
+                _ => c,
+            })
+            .collect();
+        match letters.to_ascii_uppercase().as_str() {
+            "AP" | "ALLPASS" => auction.calls.extend([Call::Pass; 3]),
+            "DBL" => auction.calls.push(Call::Double),
+            "RDBL" => auction.calls.push(Call::Redouble),
+            call => auction
+                .calls
+                .push(Call::parse(call).ok_or_else(|| error(format!("invalid call: {}", text)))?),
+        }
+    }
+    Ok(auction)
+}
+
+/// `group` without a leading symbol, letter and `:`, or letter alone, naming `suit`
+fn strip_suit_prefix(group: &str, suit: Suit) -> &str {
+    let symbol = match suit {
+        Suit::Spades => '♠',
+        Suit::Hearts => '♥',
+        Suit::Diamonds => '♦',
+        Suit::Clubs => '♣',
+    };
+    if let Some(rest) = group.strip_prefix(symbol) {
+        return rest.trim_start_matches(':');
+    }
+    group
+        .strip_prefix(suit.to_char())
+        .and_then(|rest| rest.strip_prefix(':'))
+        .unwrap_or(group)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_partial_hand() {
+        let hand = parse_partial_hand("AKxx Kx Qxxx xxx").unwrap();
+        assert_eq!(hand.len(), 13);
+        assert_eq!(hand.hcp(), 12);
+        assert_eq!(hand.spots, [2, 1, 3, 3]);
+        assert_eq!(hand.to_string(), "AKxx Kx Qxxx xxx");
+        let filled = hand.with_lowest_spots();
+        assert!(filled.has_card(Card::new(Suit::Spades, Rank::Three)));
+        assert_eq!(filled.len(), 13);
+
+        let hand = parse_partial_hand("♠AQ10x ♥- ♦KJxxx ♣xxxx").unwrap();
+        assert_eq!(hand.to_string(), "AQTx - KJxxx xxxx");
+        assert_eq!(hand.suit_length(Suit::Hearts), 0);
+
+        let error = parse_partial_hand("AKxx Kx QZxx xxx").unwrap_err();
+        assert_eq!(error.location().map(|l| l.start_column), Some(10));
+        assert!(parse_partial_hand("AKxx Kx Qxxxx xxx").is_err());
+    }
+
+    #[test]
+    fn test_complete_partial_deal() {
+        let partial = PartialDeal::from_pbn("N:AKxx.Kx.Qxxx.xxx - xxx.AQJxx..Kxxxx -").unwrap();
+        let deal = partial.complete().unwrap();
+        let north = deal.hand(Direction::North);
+        let south = deal.hand(Direction::South);
+        assert_eq!((north.len(), south.len()), (13, 13));
+        // North's small spades are given out first, then South's
+        assert!(north.has_card(Card::new(Suit::Spades, Rank::Three)));
+        assert!(south.has_card(Card::new(Suit::Spades, Rank::Six)));
+        assert_eq!(south.suit_length(Suit::Diamonds), 0);
+
+        let mut crowded = PartialDeal::new();
+        crowded.set_hand(
+            Direction::North,
+            parse_partial_hand("xxxxxxx x x x").unwrap(),
+        );
+        crowded.set_hand(
+            Direction::East,
+            parse_partial_hand("xxxxxxx x x x").unwrap(),
+        );
+        assert!(crowded.complete().is_err());
+    }
+
+    #[test]
+    fn test_parse_post_auction() {
+        let auction = parse_post_auction("1♥ (1♠) 2♣ - (Dbl) ?").unwrap();
+        assert_eq!(
+            auction.calls,
+            [
+                Call::Bid(1, bridge_types::Strain::Hearts),
+                Call::Bid(1, bridge_types::Strain::Spades),
+                Call::Bid(2, bridge_types::Strain::Clubs),
+                Call::Double,
+            ]
+        );
+        assert!(auction.question);
+        assert_eq!(parse_post_auction("1NT 3NT AP").unwrap().calls.len(), 5);
+        let error = parse_post_auction("1H 9C").unwrap_err();
+        assert_eq!(error.location().map(|l| l.start_column), Some(4));
+    }
+}
@@ -0,0 +1,314 @@
+//! Output settings shared by all writers.
+//!
+//! [`WriteOptions`] holds what every writer needs to know: the line ending
+//! and trailing newline policy ([`TextLayout`]) and how suits are shown in
+//! human-readable output. Format-specific options, like
+//! [`PbnWriteOptions`](crate::pbn::PbnWriteOptions), embed one, so an
+//! application configures it once.
+
+use crate::encoding::to_ascii;
+use bridge_types::{Deal, Direction, Rank, Suit};
+use std::borrow::Cow;
+
+/// Line terminator used in written output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum LineEnding {
+    /// `\n`
+    #[default]
+    Lf,
+    /// `\r\n`, as expected by some Windows programs
+    CrLf,
+}
+
+impl LineEnding {
+    /// The terminator string
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// How writers terminate lines and the end of their output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct TextLayout {
+    /// Line terminator
+    pub line_ending: LineEnding,
+    /// End the output with a line terminator
+    pub trailing_newline: bool,
+}
+
+impl Default for TextLayout {
+    fn default() -> Self {
+        Self {
+            line_ending: LineEnding::Lf,
+            trailing_newline: true,
+        }
+    }
+}
+
+impl TextLayout {
+    /// Apply the layout to `\n`-terminated text produced by a writer.
+    ///
+    /// Only the final line terminator is subject to `trailing_newline`;
+    /// blank lines before it are kept. `\r\n` line ends already in the
+    /// text, e.g. from commentary read from a Windows file, are treated as
+    /// `\n`, so they aren't doubled.
+    pub fn apply(&self, text: String) -> String {
+        if text.is_empty() {
+            return text;
+        }
+        let text = normalize_line_ends(&text);
+        let body = text.strip_suffix('\n').unwrap_or(&text);
+
+        let mut out = match self.line_ending {
+            LineEnding::Lf => body.to_string(),
+            LineEnding::CrLf => body.replace('\n', "\r\n"),
+        };
+        if self.trailing_newline {
+            out.push_str(self.line_ending.as_str());
+        }
+        out
+    }
+}
+
+/// `text` with `\r\n` line ends replaced by `\n`
+fn normalize_line_ends(text: &str) -> Cow<'_, str> {
+    if text.contains("\r\n") {
+        Cow::Owned(text.replace("\r\n", "\n"))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Writes `\n`-terminated chunks of text with a [`WriteOptions`], giving
+/// the same output as [`WriteOptions::finish`] on their concatenation
+pub(crate) struct LayoutWriter<'a, W: std::io::Write> {
+    writer: &'a mut W,
+    options: WriteOptions,
+    /// A line terminator held back until more text or the end arrives
+    pending: bool,
+}
+
+impl<'a, W: std::io::Write> LayoutWriter<'a, W> {
+    pub(crate) fn new(writer: &'a mut W, options: WriteOptions) -> Self {
+        Self {
+            writer,
+            options,
+            pending: false,
+        }
+    }
+
+    /// Write a chunk of text ending with `\n`
+    pub(crate) fn write(&mut self, text: &str) -> std::io::Result<()> {
+        if text.is_empty() {
+            return Ok(());
+        }
+        let terminator = self.options.layout.line_ending.as_str();
+        if self.pending {
+            self.writer.write_all(terminator.as_bytes())?;
+        }
+        let ascii = self.options.ascii_only.then(|| to_ascii(text).0);
+        let text = normalize_line_ends(ascii.as_deref().unwrap_or(text));
+        let body = text.strip_suffix('\n').unwrap_or(&text);
+        match self.options.layout.line_ending {
+            LineEnding::Lf => self.writer.write_all(body.as_bytes())?,
+            LineEnding::CrLf => self
+                .writer
+                .write_all(body.replace('\n', "\r\n").as_bytes())?,
+        }
+        self.pending = true;
+        Ok(())
+    }
+
+    /// Write the final line terminator, if the layout asks for one
+    pub(crate) fn finish(self) -> std::io::Result<()> {
+        let layout = self.options.layout;
+        if self.pending && layout.trailing_newline {
+            self.writer
+                .write_all(layout.line_ending.as_str().as_bytes())?;
+        }
+        Ok(())
+    }
+}
+
+/// How suits are shown in human-readable output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SuitSymbols {
+    /// `♠ ♥ ♦ ♣`
+    #[default]
+    Unicode,
+    /// `S H D C`, for plain ASCII output
+    Letters,
+}
+
+impl SuitSymbols {
+    /// The symbol for `suit`
+    pub fn suit(self, suit: Suit) -> char {
+        match (self, suit) {
+            (SuitSymbols::Unicode, Suit::Spades) => '♠',
+            (SuitSymbols::Unicode, Suit::Hearts) => '♥',
+            (SuitSymbols::Unicode, Suit::Diamonds) => '♦',
+            (SuitSymbols::Unicode, Suit::Clubs) => '♣',
+            (SuitSymbols::Letters, suit) => suit.to_char(),
+        }
+    }
+}
+
+/// Settings common to all writers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct WriteOptions {
+    /// Line endings and final newline
+    pub layout: TextLayout,
+    /// Suit symbols in human-readable output (diagrams, auctions)
+    pub symbols: SuitSymbols,
+    /// Allow deals with fewer than four hands (minibridge and teaching
+    /// layouts): hands without cards are written as absent, not as voids
+    pub partial_deals: bool,
+    /// Write cards below this rank as `x` in human-readable output
+    /// (diagrams, oneline), for teaching material
+    pub spots_below: Option<Rank>,
+    /// End each record of line-based output (oneline) with a checksum
+    pub checksums: bool,
+    /// Write only ASCII: names and annotations are transliterated as by
+    /// [`to_ascii`](crate::to_ascii), for consumers that reject
+    /// any byte above 0x7F
+    pub ascii_only: bool,
+}
+
+impl WriteOptions {
+    /// Default options: `\n` line endings, a final newline, Unicode suits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the line terminator
+    pub fn with_line_ending(mut self, line_ending: LineEnding) -> Self {
+        self.layout.line_ending = line_ending;
+        self
+    }
+
+    /// Set whether the output ends with a line terminator
+    pub fn with_trailing_newline(mut self, trailing_newline: bool) -> Self {
+        self.layout.trailing_newline = trailing_newline;
+        self
+    }
+
+    /// Set the suit symbols
+    pub fn with_symbols(mut self, symbols: SuitSymbols) -> Self {
+        self.symbols = symbols;
+        self
+    }
+
+    /// Set whether deals may have fewer than four hands
+    pub fn with_partial_deals(mut self, partial_deals: bool) -> Self {
+        self.partial_deals = partial_deals;
+        self
+    }
+
+    /// Set the rank below which cards are written as `x`
+    pub fn with_spots_below(mut self, spots_below: Option<Rank>) -> Self {
+        self.spots_below = spots_below;
+        self
+    }
+
+    /// Set whether records end with a checksum
+    pub fn with_checksums(mut self, checksums: bool) -> Self {
+        self.checksums = checksums;
+        self
+    }
+
+    /// The seats to write as absent: `hidden` plus, for partial deals, the
+    /// seats without cards
+    pub(crate) fn absent_seats(&self, deal: &Deal, hidden: &[Direction]) -> Vec<Direction> {
+        let mut absent = hidden.to_vec();
+        if self.partial_deals {
+            absent.extend(
+                Direction::ALL
+                    .into_iter()
+                    .filter(|&dir| deal.hand(dir).is_empty() && !hidden.contains(&dir)),
+            );
+        }
+        absent
+    }
+
+    /// Set whether output is restricted to ASCII
+    pub fn with_ascii_only(mut self, ascii_only: bool) -> Self {
+        self.ascii_only = ascii_only;
+        self
+    }
+
+    /// Apply the layout to `\n`-terminated text produced by a writer, and
+    /// transliterate it in ASCII-only mode
+    pub fn finish(&self, text: String) -> String {
+        let text = match self.ascii_only {
+            true => to_ascii(&text).0,
+            false => text,
+        };
+        self.layout.apply(text)
+    }
+}
+
+impl From<TextLayout> for WriteOptions {
+    fn from(layout: TextLayout) -> Self {
+        Self {
+            layout,
+            ..Self::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let text = "a\n\nb\n".to_string();
+        assert_eq!(TextLayout::default().apply(text.clone()), "a\n\nb\n");
+
+        let crlf = TextLayout {
+            line_ending: LineEnding::CrLf,
+            trailing_newline: true,
+        };
+        assert_eq!(crlf.apply(text.clone()), "a\r\n\r\nb\r\n");
+
+        let bare = TextLayout {
+            line_ending: LineEnding::CrLf,
+            trailing_newline: false,
+        };
+        assert_eq!(bare.apply(text), "a\r\n\r\nb");
+        assert_eq!(bare.apply(String::new()), "");
+        assert_eq!(bare.apply("x\n\n".to_string()), "x\r\n");
+    }
+
+    #[test]
+    fn test_apply_to_crlf_text() {
+        let text = "a\r\nb\nc\r\n".to_string();
+        let crlf = TextLayout {
+            line_ending: LineEnding::CrLf,
+            trailing_newline: true,
+        };
+        assert_eq!(crlf.apply(text.clone()), "a\r\nb\r\nc\r\n");
+        assert_eq!(TextLayout::default().apply(text.clone()), "a\nb\nc\n");
+
+        let mut out = Vec::new();
+        let mut writer = LayoutWriter::new(&mut out, WriteOptions::from(crlf));
+        writer.write(&text).unwrap();
+        writer.write("d\n").unwrap();
+        writer.finish().unwrap();
+        assert_eq!(out, b"a\r\nb\r\nc\r\nd\r\n");
+    }
+
+    #[test]
+    fn test_write_options_builder() {
+        let options = WriteOptions::new()
+            .with_line_ending(LineEnding::CrLf)
+            .with_trailing_newline(false)
+            .with_symbols(SuitSymbols::Letters);
+        assert_eq!(options.layout.apply("a\nb\n".to_string()), "a\r\nb");
+        assert_eq!(options.symbols.suit(Suit::Hearts), 'H');
+        assert_eq!(WriteOptions::new().symbols.suit(Suit::Hearts), '♥');
+    }
+}
@@ -21,15 +21,58 @@
 //! assert_eq!(boards.len(), 1);
 //! ```
 
+pub mod analysis;
+pub mod archive;
+mod cancel;
+pub mod cards;
+mod checksum;
+pub mod consistency;
+pub mod constraints;
+pub mod convert;
+pub mod dealer;
+pub mod debug;
+mod diagnostics;
+pub mod diagram;
+mod encoding;
 mod error;
+mod files;
+mod fingerprint;
+pub mod fixtures;
+mod format;
+pub mod handpost;
+mod layout;
+mod limits;
 pub mod lin;
 pub mod oneline;
 pub mod pbn;
+pub mod prelude;
 pub mod printall;
 mod reader;
+pub mod sanity;
+pub mod scoring;
+pub mod search;
+mod seat;
+pub mod sink;
+mod size;
+pub mod stats;
+pub mod synthetic;
+pub mod tail;
+pub mod transform;
 
-pub use error::{ParseError, Result};
+pub use cancel::CancelToken;
+pub use diagnostics::{Diagnostics, Outcome, Warning, WarningKind};
+pub use encoding::{to_ascii, AsciiChange, AsciiReport, TextEncoding};
+pub use error::{BoardError, GameSpan, ParseError, Result, SourceLocation};
+pub use files::{read_deals, write_deals};
+pub use fingerprint::Fingerprint;
+pub use format::Format;
+pub use layout::{LineEnding, SuitSymbols, TextLayout, WriteOptions};
+pub use limits::Limits;
 pub use reader::DealReader;
+pub use seat::{
+    next_seat, opponents, parse_seat, partner, rotate_seat, seat_offset, SeatOrder, SeatParser,
+};
+pub use size::estimate_size;
 
 // Re-export bridge-types for convenience
 pub use bridge_types::{
@@ -0,0 +1,57 @@
+//! Resource limits for file-level parsers.
+
+use crate::error::{ParseError, Result};
+
+/// Upper bounds on the work done by a file-level parse.
+///
+/// Services parsing untrusted uploads can use these to bound the work per
+/// request without pre-scanning the input. Unset limits are not enforced.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Limits {
+    /// Maximum number of boards to read
+    pub max_boards: Option<usize>,
+    /// Maximum input size in bytes
+    pub max_bytes: Option<usize>,
+}
+
+impl Limits {
+    /// No limits
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the maximum number of boards
+    pub fn with_max_boards(mut self, max: usize) -> Self {
+        self.max_boards = Some(max);
+        self
+    }
+
+    /// Set the maximum input size in bytes
+    pub fn with_max_bytes(mut self, max: usize) -> Self {
+        self.max_bytes = Some(max);
+        self
+    }
+
+    /// Fail if `content` is larger than `max_bytes`
+    pub(crate) fn check_bytes(&self, content: &str) -> Result<()> {
+        match self.max_bytes {
+            Some(max) if content.len() > max => Err(ParseError::LimitExceeded(format!(
+                "input is {} bytes, limit is {}",
+                content.len(),
+                max
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Fail if `count` boards exceed `max_boards`
+    pub(crate) fn check_boards(&self, count: usize) -> Result<()> {
+        match self.max_boards {
+            Some(max) if count > max => Err(ParseError::LimitExceeded(format!(
+                "more than {} boards",
+                max
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
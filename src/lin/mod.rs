@@ -3,8 +3,26 @@
 //! LIN is a pipe-delimited format used by Bridge Base Online to encode
 //! complete hand records including deal, auction, and cardplay in URLs.
 
-use crate::error::Result;
-use bridge_types::{Card, Deal, Direction, Hand, Rank, Suit, Vulnerability};
+mod session;
+mod writer;
+
+pub use session::{
+    parse_lin_session, parse_lin_session_with_diagnostics, stitch_sessions, LinSession,
+};
+pub use writer::{
+    board_to_lin, board_to_lin_with, format_md, lin_call, md_dealer_digit, md_slot, write_lin,
+    write_lin_with, write_lin_with_options, LinWriteOptions, SvSource, MD_SEATS,
+};
+
+use crate::cards::{all_cards, SUITS};
+use crate::debug::{Trace, TraceAction};
+use crate::diagnostics::{Diagnostics, Warning, WarningKind};
+use crate::error::{ParseError, Result};
+use crate::limits::Limits;
+use crate::pbn::{Auction, Game, PbnTag, Play, TagPair};
+use crate::scoring::format_contract;
+use crate::seat::{next_seat, standard_dealer};
+use bridge_types::{Board, Card, Contract, Deal, Direction, Hand, Rank, Suit, Vulnerability};
 
 /// A bid with optional alert and annotation
 #[derive(Debug, Clone)]
@@ -17,6 +35,25 @@ pub struct BidWithAnnotation {
     pub annotation: Option<String>,
 }
 
+/// The room of a vugraph table, from the letter of `qx` (`o12`, `c12`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum LinRoom {
+    /// `o`: the open room
+    Open,
+    /// `c`: the closed room
+    Closed,
+}
+
+impl LinRoom {
+    /// The room as written in the PBN Room tag
+    pub fn as_str(self) -> &'static str {
+        match self {
+            LinRoom::Open => "Open",
+            LinRoom::Closed => "Closed",
+        }
+    }
+}
+
 /// Parsed LIN data from a BBO hand record
 #[derive(Debug, Clone)]
 pub struct LinData {
@@ -36,6 +73,10 @@ pub struct LinData {
     pub play: Vec<Card>,
     /// Claim (number of tricks), if hand was claimed
     pub claim: Option<u8>,
+    /// Room of a vugraph record, from `qx`
+    pub room: Option<LinRoom>,
+    /// Board number from `qx`
+    pub qx_board: Option<u32>,
 }
 
 impl LinData {
@@ -60,12 +101,161 @@ impl LinData {
 
         tricks.join("|")
     }
+
+    /// Final contract of the auction in PBN notation (e.g. "4HX", "3NT"),
+    /// "Pass" for a passed-out board, or `None` if the auction is incomplete
+    pub fn final_contract(&self) -> Option<String> {
+        let auction = self.pbn_auction().filter(Auction::is_complete)?;
+        Some(match auction.contract() {
+            Some((contract, _)) => format_contract(&contract),
+            None => "Pass".to_string(),
+        })
+    }
+
+    /// The auction as PBN calls, with alerts; `None` if there are no bids
+    /// or a bid isn't a valid call
+    pub fn pbn_auction(&self) -> Option<Auction> {
+        if self.auction.is_empty() {
+            return None;
+        }
+        let calls: Vec<String> = self
+            .auction
+            .iter()
+            .map(|b| {
+                if b.alert {
+                    format!("{}!", b.bid)
+                } else {
+                    b.bid.clone()
+                }
+            })
+            .collect();
+        Auction::parse(self.dealer, &calls.join(" ")).ok()
+    }
+
+    /// The final contract, doubled or redoubled as in the auction, and its
+    /// declarer; `None` if the auction is incomplete or passed out
+    pub fn contract(&self) -> Option<(Contract, Direction)> {
+        self.pbn_auction().filter(Auction::is_complete)?.contract()
+    }
+
+    /// Board number taken from the board header (e.g. "Board 12" -> 12),
+    /// or else from `qx`
+    pub fn board_number(&self) -> Option<u32> {
+        self.board_header
+            .as_deref()
+            .and_then(trailing_number)
+            .or(self.qx_board)
+    }
+
+    /// Convert to a `Board` carrying the deal, dealer, vulnerability and board number
+    pub fn to_board(&self) -> Board {
+        let mut board = Board::new()
+            .with_dealer(self.dealer)
+            .with_vulnerability(self.vulnerability)
+            .with_deal(self.deal.clone());
+        board.number = self.board_number();
+        board
+    }
+
+    /// Convert to a PBN [`Game`] carrying the board, the player names, the
+    /// auction, the contract and the card play; bid annotations become
+    /// Note tags referenced from their calls
+    pub fn to_game(&self) -> Game {
+        let mut game = Game::new(self.to_board());
+        for (seat, name) in MD_SEATS.iter().zip(&self.player_names) {
+            game.set_player(*seat, name);
+        }
+        game.auction = self.pbn_auction();
+        let mut notes = Vec::new();
+        if let Some(auction) = &mut game.auction {
+            if auction.calls.len() == self.auction.len() {
+                for (call, bid) in auction.calls.iter_mut().zip(&self.auction) {
+                    if let Some(text) = bid.annotation.as_deref().filter(|t| !t.is_empty()) {
+                        notes.push(format!("{}:{}", notes.len() + 1, pbn_suit_escapes(text)));
+                        call.note = Some(notes.len() as u32);
+                    }
+                }
+            }
+        }
+        let mut push = |tag, value: String| game.tags.push(TagPair { tag, value });
+        if let Some(room) = self.room {
+            push(PbnTag::Room, room.as_str().to_string());
+        }
+        if game.auction.is_some() {
+            push(PbnTag::Auction, self.dealer.to_char().to_string());
+            // Notes follow the section they explain
+            for note in notes {
+                push(PbnTag::Note, note);
+            }
+        }
+        match self.contract() {
+            Some((contract, declarer)) => {
+                push(PbnTag::Declarer, declarer.to_char().to_string());
+                game.contract = Some(contract);
+                let leader = next_seat(declarer);
+                if !self.play.is_empty() {
+                    push(PbnTag::Play, leader.to_char().to_string());
+                    game.play = Some(Play::from_played(leader, contract.strain, &self.play));
+                }
+            }
+            None if self.final_contract().as_deref() == Some("Pass") => {
+                push(PbnTag::Contract, "Pass".to_string());
+            }
+            None => {}
+        }
+        game
+    }
+}
+
+/// Replace BBO suit symbols (`!S`, `!h`, ...) with PBN suit escapes (`\S`)
+fn pbn_suit_escapes(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        let suit = chars
+            .peek()
+            .map(|s| s.to_ascii_uppercase())
+            .filter(|s| c == '!' && matches!(s, 'S' | 'H' | 'D' | 'C'));
+        match suit {
+            Some(suit) => {
+                chars.next();
+                out.push('\\');
+                out.push(suit);
+            }
+            None => out.push(c),
+        }
+    }
+    out
 }
 
 /// Parse a LIN string into LinData
 pub fn parse_lin(lin_str: &str) -> Result<LinData> {
+    parse_lin_with_diagnostics(lin_str, &mut Diagnostics::new())
+}
+
+/// Parse a LIN string into LinData, recording recovered problems (invalid
+/// values, a derived fourth hand) in `diagnostics`.
+///
+/// An md whose dealer digit is missing or not 1-4 (some scraped LIN starts
+/// with `0` or a seat letter) keeps its hands; the dealer is then taken
+/// from the board number in `ah` or `qx` by the standard rotation, or
+/// else defaults to North, with a warning either way.
+pub fn parse_lin_with_diagnostics(lin_str: &str, diagnostics: &mut Diagnostics) -> Result<LinData> {
+    parse_lin_traced(lin_str, diagnostics, &mut Trace::default())
+}
+
+/// [`parse_lin_with_diagnostics`], recording in `trace` what is done with
+/// each command
+pub(crate) fn parse_lin_traced(
+    lin_str: &str,
+    diagnostics: &mut Diagnostics,
+    trace: &mut Trace,
+) -> Result<LinData> {
     let mut player_names = [String::new(), String::new(), String::new(), String::new()];
     let mut dealer = Direction::North;
+    let mut dealer_missing = false;
+    let mut qx_number = None;
+    let mut room = None;
     let mut deal = Deal::new();
     let mut vulnerability = Vulnerability::None;
     let mut board_header = None;
@@ -74,10 +264,24 @@ pub fn parse_lin(lin_str: &str) -> Result<LinData> {
     let mut claim = None;
 
     let tokens: Vec<&str> = lin_str.split('|').collect();
+    // The line (1-based) each token starts on
+    let lines: Vec<usize> = tokens
+        .iter()
+        .scan(1, |line, token| {
+            let leading = &token[..token.len() - token.trim_start().len()];
+            let start = *line + leading.matches('\n').count();
+            *line += token.matches('\n').count();
+            Some(start)
+        })
+        .collect();
     let mut i = 0;
 
     while i < tokens.len() {
         let token = tokens[i].trim();
+        let line = lines[i];
+        let value = tokens.get(i + 1).copied();
+        let warnings = diagnostics.len();
+        let mut interpreted = true;
 
         match token {
             "pn" => {
@@ -92,16 +296,37 @@ pub fn parse_lin(lin_str: &str) -> Result<LinData> {
             "md" => {
                 if i + 1 < tokens.len() {
                     let deal_str = tokens[i + 1];
-                    if let Some((d, hands)) = parse_md(deal_str) {
-                        dealer = d;
-                        deal = hands;
+                    match parse_md(deal_str) {
+                        Ok((d, hands)) => {
+                            match d {
+                                Some(d) => dealer = d,
+                                None => dealer_missing = true,
+                            }
+                            deal = hands;
+                            let fourth = deal_str.split(',').nth(3).unwrap_or("");
+                            if fourth.trim().is_empty() && !deal.hand(MD_SEATS[3]).is_empty() {
+                                diagnostics.warn(
+                                    None,
+                                    WarningKind::DerivedHand,
+                                    "md gives three hands; East derived from the remaining cards",
+                                );
+                            }
+                        }
+                        Err(message) => diagnostics.warn(None, WarningKind::IgnoredValue, message),
                     }
                     i += 1;
                 }
             }
             "sv" => {
                 if i + 1 < tokens.len() {
-                    vulnerability = parse_sv(tokens[i + 1]);
+                    vulnerability = parse_sv(tokens[i + 1]).unwrap_or_else(|| {
+                        diagnostics.warn(
+                            None,
+                            WarningKind::DefaultedValue,
+                            format!("invalid sv \"{}\"; using None", tokens[i + 1]),
+                        );
+                        Vulnerability::None
+                    });
                     i += 1;
                 }
             }
@@ -111,6 +336,13 @@ pub fn parse_lin(lin_str: &str) -> Result<LinData> {
                     i += 1;
                 }
             }
+            "qx" => {
+                if let Some(value) = tokens.get(i + 1) {
+                    qx_number = trailing_number(value);
+                    room = parse_room(value);
+                    i += 1;
+                }
+            }
             "mb" => {
                 if i + 1 < tokens.len() {
                     let bid_str = tokens[i + 1];
@@ -139,8 +371,13 @@ pub fn parse_lin(lin_str: &str) -> Result<LinData> {
             }
             "pc" => {
                 if i + 1 < tokens.len() {
-                    if let Some(card) = parse_card(tokens[i + 1]) {
-                        play.push(card);
+                    match parse_card(tokens[i + 1]) {
+                        Some(card) => play.push(card),
+                        None => diagnostics.warn(
+                            None,
+                            WarningKind::IgnoredValue,
+                            format!("invalid card: \"{}\"", tokens[i + 1]),
+                        ),
                     }
                     i += 1;
                 }
@@ -148,15 +385,42 @@ pub fn parse_lin(lin_str: &str) -> Result<LinData> {
             "mc" => {
                 if i + 1 < tokens.len() {
                     claim = tokens[i + 1].parse().ok();
+                    if claim.is_none() {
+                        diagnostics.warn(
+                            None,
+                            WarningKind::IgnoredValue,
+                            format!("invalid claim: \"{}\"", tokens[i + 1]),
+                        );
+                    }
                     i += 1;
                 }
             }
-            _ => {}
+            _ => interpreted = false,
         }
 
+        if !token.is_empty() {
+            let raised = &diagnostics.warnings()[warnings..];
+            trace_command(trace, line, token, value, interpreted, raised);
+        }
         i += 1;
     }
 
+    if dealer_missing {
+        let number = board_header
+            .as_deref()
+            .and_then(trailing_number)
+            .or(qx_number);
+        let message = match number {
+            Some(number) => {
+                dealer = standard_dealer(number);
+                format!("md has no dealer digit; dealer taken from board {}", number)
+            }
+            None => "md has no dealer digit; using North".to_string(),
+        };
+        trace.record(None, TraceAction::Applied, || message.clone());
+        diagnostics.warn(None, WarningKind::DefaultedValue, message);
+    }
+
     Ok(LinData {
         player_names,
         dealer,
@@ -166,40 +430,106 @@ pub fn parse_lin(lin_str: &str) -> Result<LinData> {
         auction,
         play,
         claim,
+        room,
+        qx_board: qx_number,
     })
 }
 
-/// Parse the md (make deal) field
-/// Format: dealer_digit + hands (3 hands, 4th is implied)
-fn parse_md(md_str: &str) -> Option<(Direction, Deal)> {
-    if md_str.is_empty() {
-        return None;
+/// The room of a `qx` value (`o12`, `c12`); `None` for other tables
+fn parse_room(qx: &str) -> Option<LinRoom> {
+    match qx.trim().chars().next()? {
+        'o' | 'O' => Some(LinRoom::Open),
+        'c' | 'C' => Some(LinRoom::Closed),
+        _ => None,
     }
+}
 
+/// Record what the parser did with a command, judged by the warnings it
+/// `raised`: an invalid value was ignored or replaced by a default
+fn trace_command(
+    trace: &mut Trace,
+    line: usize,
+    command: &str,
+    value: Option<&str>,
+    interpreted: bool,
+    raised: &[Warning],
+) {
+    let line = Some(line);
+    let rejected = raised.iter().find(|warning| {
+        matches!(
+            warning.kind,
+            WarningKind::IgnoredValue | WarningKind::DefaultedValue
+        )
+    });
+    match (interpreted, value, rejected) {
+        (false, _, _) => trace.record(line, TraceAction::Ignored, || {
+            format!("command {} is not interpreted", command)
+        }),
+        (true, None, _) => trace.record(line, TraceAction::Skipped, || {
+            format!("command {} has no value", command)
+        }),
+        (true, Some(_), Some(warning)) => {
+            trace.record(line, TraceAction::Rejected, || warning.message.clone())
+        }
+        (true, Some(value), None) => trace.record(line, TraceAction::Applied, || {
+            let notes: Vec<&str> = raised.iter().map(|w| w.message.as_str()).collect();
+            match notes.is_empty() {
+                true => format!("{}|{}|", command, value),
+                false => format!("{}|{}| ({})", command, value, notes.join("; ")),
+            }
+        }),
+    }
+}
+
+/// The number at the end of `text` (e.g. "Board 12" -> 12, "o12" -> 12)
+fn trailing_number(text: &str) -> Option<u32> {
+    let digits: String = text
+        .chars()
+        .rev()
+        .skip_while(|c| !c.is_ascii_digit())
+        .take_while(|c| c.is_ascii_digit())
+        .collect();
+    digits.chars().rev().collect::<String>().parse().ok()
+}
+
+/// Parse the md (make deal) field
+/// Format: dealer_digit + hands (3 hands, 4th is implied)
+///
+/// The dealer is `None` when the leading digit is another digit or a seat
+/// letter, or is missing altogether.
+fn parse_md(md_str: &str) -> std::result::Result<(Option<Direction>, Deal), String> {
+    let invalid = || format!("invalid md: \"{}\"", md_str);
     // First character is dealer: 1=S, 2=W, 3=N, 4=E (BBO convention)
-    let dealer_char = md_str.chars().next()?;
-    let dealer = match dealer_char {
-        '1' => Direction::South,
-        '2' => Direction::West,
-        '3' => Direction::North,
-        '4' => Direction::East,
-        _ => return None,
+    let mut chars = md_str.chars();
+    let first = chars.next().ok_or_else(invalid)?;
+    let (dealer, hands_str) = match first {
+        '1' => (Some(Direction::South), chars.as_str()),
+        '2' => (Some(Direction::West), chars.as_str()),
+        '3' => (Some(Direction::North), chars.as_str()),
+        '4' => (Some(Direction::East), chars.as_str()),
+        c if c.is_ascii_digit() => (None, chars.as_str()),
+        // A seat letter is followed by a suit letter or the first comma;
+        // otherwise the S starts the spades of the first hand
+        'N' | 'E' | 'S' | 'W' | 'n' | 'e' | 's' | 'w'
+            if chars
+                .clone()
+                .next()
+                .is_some_and(|c| matches!(c.to_ascii_uppercase(), 'S' | 'H' | 'D' | 'C' | ',')) =>
+        {
+            (None, chars.as_str())
+        }
+        c if matches!(c.to_ascii_uppercase(), 'S' | 'H' | 'D' | 'C') || c == ',' => (None, md_str),
+        _ => return Err(invalid()),
     };
 
-    let hands_str = &md_str[1..];
     let hand_strs: Vec<&str> = hands_str.split(',').collect();
 
     if hand_strs.len() < 3 {
-        return None;
+        return Err(invalid());
     }
 
     let mut deal = Deal::new();
-    let directions = [
-        Direction::South,
-        Direction::West,
-        Direction::North,
-        Direction::East,
-    ];
+    let directions = MD_SEATS;
 
     for (i, hand_str) in hand_strs.iter().enumerate().take(3) {
         if let Some(hand) = parse_lin_hand(hand_str) {
@@ -207,12 +537,19 @@ fn parse_md(md_str: &str) -> Option<(Direction, Deal)> {
         }
     }
 
-    // Calculate the 4th hand from the remaining cards
-    if let Some(fourth_hand) = calculate_fourth_hand(&deal, directions[3]) {
+    // The 4th hand is derived only from three complete hands; otherwise
+    // it is left empty rather than given the rest of the pack
+    let invalid_because = |reason| format!("invalid md: \"{}\": {}", md_str, reason);
+    known_cards(&deal, directions[3]).map_err(invalid_because)?;
+    if directions[..3]
+        .iter()
+        .all(|&seat| deal.hand(seat).len() == 13)
+    {
+        let fourth_hand = missing_hand(&deal, directions[3]).map_err(invalid_because)?;
         deal.set_hand(directions[3], fourth_hand);
     }
 
-    Some((dealer, deal))
+    Ok((dealer, deal))
 }
 
 /// Parse a single hand in LIN format
@@ -240,39 +577,61 @@ fn parse_lin_hand(hand_str: &str) -> Option<Hand> {
     Some(hand)
 }
 
-/// Calculate the fourth hand from the three known hands
-fn calculate_fourth_hand(deal: &Deal, fourth_dir: Direction) -> Option<Hand> {
-    let mut fourth = Hand::new();
+/// Derive the hand at `missing` from the cards not held by the other
+/// three hands, as LIN records that give only three hands require.
+///
+/// It is an error unless the known hands hold 13 cards each, none of them
+/// twice: only then is the fourth hand the rest of the pack.
+pub fn derive_missing_hand(deal: &Deal, missing: Direction) -> Result<Hand> {
+    missing_hand(deal, missing).map_err(ParseError::Lin)
+}
 
-    for suit in Suit::ALL {
-        for rank in Rank::ALL {
-            let card = Card::new(suit, rank);
-            let mut found = false;
+fn missing_hand(deal: &Deal, missing: Direction) -> std::result::Result<Hand, String> {
+    let known = known_cards(deal, missing)?;
+    let others = Direction::ALL.into_iter().filter(|&seat| seat != missing);
+    let sizes: Vec<usize> = others.map(|seat| deal.hand(seat).len()).collect();
+    if sizes.iter().any(|&size| size != 13) {
+        let sizes: Vec<String> = sizes.iter().map(usize::to_string).collect();
+        return Err(format!(
+            "the other hands hold {} cards, not 13 each",
+            sizes.join(", ")
+        ));
+    }
 
-            for dir in Direction::ALL {
-                if dir != fourth_dir && deal.hand(dir).has_card(card) {
-                    found = true;
-                    break;
-                }
-            }
+    Ok(Hand::from_cards(
+        all_cards().filter(|card| !known.contains(card)).collect(),
+    ))
+}
 
-            if !found {
-                fourth.add_card(card);
+/// The cards of the hands other than `missing`; an error if a card is
+/// dealt twice
+fn known_cards(deal: &Deal, missing: Direction) -> std::result::Result<Vec<Card>, String> {
+    let mut known: Vec<Card> = Vec::new();
+    let mut duplicates: Vec<String> = Vec::new();
+    for seat in Direction::ALL.into_iter().filter(|&seat| seat != missing) {
+        for suit in SUITS {
+            for card in deal.hand(seat).cards_in_suit(suit) {
+                if known.contains(&card) {
+                    duplicates.push(format!("{}{}", card.suit.to_char(), card.rank.to_char()));
+                }
+                known.push(card);
             }
         }
     }
-
-    Some(fourth)
+    if !duplicates.is_empty() {
+        return Err(format!("cards dealt twice: {}", duplicates.join(", ")));
+    }
+    Ok(known)
 }
 
-/// Parse vulnerability from sv field
-fn parse_sv(sv: &str) -> Vulnerability {
+/// Parse vulnerability from sv field; `None` for unknown values
+fn parse_sv(sv: &str) -> Option<Vulnerability> {
     match sv.to_lowercase().as_str() {
-        "o" | "0" | "-" => Vulnerability::None,
-        "n" | "ns" => Vulnerability::NorthSouth,
-        "e" | "ew" => Vulnerability::EastWest,
-        "b" | "both" | "all" => Vulnerability::Both,
-        _ => Vulnerability::None,
+        "o" | "0" | "-" => Some(Vulnerability::None),
+        "n" | "ns" => Some(Vulnerability::NorthSouth),
+        "e" | "ew" => Some(Vulnerability::EastWest),
+        "b" | "both" | "all" => Some(Vulnerability::Both),
+        _ => None,
     }
 }
 
@@ -290,6 +649,14 @@ fn parse_card(card_str: &str) -> Option<Card> {
 
 /// Parse multiple boards from a LIN file (tournament format)
 pub fn parse_lin_file(content: &str) -> Result<Vec<LinData>> {
+    parse_lin_file_with_limits(content, &Limits::new())
+}
+
+/// Parse multiple boards from a LIN file, failing with
+/// [`ParseError::LimitExceeded`](crate::ParseError::LimitExceeded) if the
+/// content or the number of boards is over the limits
+pub fn parse_lin_file_with_limits(content: &str, limits: &Limits) -> Result<Vec<LinData>> {
+    limits.check_bytes(content)?;
     let mut boards = Vec::new();
 
     for line in content.lines() {
@@ -304,11 +671,63 @@ pub fn parse_lin_file(content: &str) -> Result<Vec<LinData>> {
                 // Skip malformed lines
             }
         }
+        limits.check_boards(boards.len())?;
     }
 
     Ok(boards)
 }
 
+/// Find LIN records embedded in free text such as PBN commentary.
+///
+/// Recognizes BBO handviewer links (`...handviewer.html?lin=...`, usually
+/// percent-encoded) and bare LIN strings. Only records containing an `md`
+/// (deal) command are returned, percent-decoded and ready for [`parse_lin`].
+pub fn extract_lin(text: &str) -> Vec<String> {
+    let mut found = Vec::new();
+
+    for word in text.split_whitespace() {
+        let word =
+            word.trim_matches(|c| matches!(c, '"' | '\'' | '(' | ')' | '<' | '>' | '{' | '}'));
+
+        let candidate = match word.find("lin=") {
+            Some(pos) => {
+                let value = &word[pos + 4..];
+                value.split('&').next().unwrap_or(value)
+            }
+            None => word,
+        };
+
+        let decoded = percent_decode(candidate);
+        if decoded.starts_with("md|") || decoded.contains("|md|") {
+            found.push(decoded);
+        }
+    }
+
+    found
+}
+
+/// Decode `%XX` escapes; invalid escapes are kept as-is
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        if bytes[i] == b'%' && i + 2 < bytes.len() {
+            let hex = std::str::from_utf8(&bytes[i + 1..i + 3]).ok();
+            if let Some(byte) = hex.and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        decoded.push(bytes[i]);
+        i += 1;
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -330,10 +749,10 @@ mod tests {
 
     #[test]
     fn test_parse_sv() {
-        assert_eq!(parse_sv("o"), Vulnerability::None);
-        assert_eq!(parse_sv("b"), Vulnerability::Both);
-        assert_eq!(parse_sv("n"), Vulnerability::NorthSouth);
-        assert_eq!(parse_sv("e"), Vulnerability::EastWest);
+        assert_eq!(parse_sv("o"), Some(Vulnerability::None));
+        assert_eq!(parse_sv("b"), Some(Vulnerability::Both));
+        assert_eq!(parse_sv("n"), Some(Vulnerability::NorthSouth));
+        assert_eq!(parse_sv("e"), Some(Vulnerability::EastWest));
     }
 
     #[test]
@@ -358,6 +777,11 @@ mod tests {
         assert_eq!(data.auction.len(), 2);
         assert_eq!(data.auction[0].bid, "1C");
         assert_eq!(data.play.len(), 4);
+
+        let game = data.to_game();
+        assert_eq!(game.player(Direction::West), Some("West"));
+        let pbn = crate::pbn::game_to_pbn_with(&game, &Default::default());
+        assert!(pbn.contains("[East \"East\"]"));
     }
 
     #[test]
@@ -383,4 +807,174 @@ mod tests {
         assert!(data.auction[2].alert);
         assert_eq!(data.auction[2].annotation, Some("5 hearts".to_string()));
     }
+
+    #[test]
+    fn test_play_to_pbn() {
+        let lin = "md|1SAKHJD876C5432,,,|sv|o|mb|1S|mb|p|mb|p|mb|p|\
+                   pc|HK|pc|H2|pc|HA|pc|S2|pc|DA|pc|D2|pc|D3|";
+        let game = parse_lin(lin).unwrap().to_game();
+        let pbn = crate::pbn::game_to_pbn_with(&game, &Default::default());
+        assert!(pbn.contains("[Play \"W\"]\nHK H2 HA S2\nD2 D3 - DA\n*\n"));
+    }
+
+    #[test]
+    fn test_annotations_to_notes() {
+        use crate::pbn::{AlertMark, NoteStyle, NoteSuits, PbnWriteOptions};
+
+        let lin = "md|1SAKHJD876C5432,,,|sv|o|mb|1C!|an|could+be+short|mb|p|mb|1H!|an|4+!s|";
+        let game = parse_lin(lin).unwrap().to_game();
+        let pbn = crate::pbn::game_to_pbn_with(&game, &PbnWriteOptions::default());
+        assert!(pbn.contains("1C! =1= Pass 1H! =2=\n[Note \"1:could be short\"]"));
+        assert!(pbn.contains("[Note \"2:4 \\S\"]"));
+
+        let options = PbnWriteOptions::default().with_note_suits(NoteSuits::Unicode);
+        assert!(crate::pbn::game_to_pbn_with(&game, &options).contains("[Note \"2:4 ♠\"]"));
+
+        let options = PbnWriteOptions::default()
+            .with_alert_mark(AlertMark::Asterisk)
+            .with_note_style(NoteStyle::Inline);
+        let pbn = crate::pbn::game_to_pbn_with(&game, &options);
+        assert!(pbn.contains("1C* Pass 1H*\n"));
+        assert!(!pbn.contains("[Note"));
+    }
+
+    #[test]
+    fn test_to_board() {
+        let lin =
+            "pn|S,W,N,E|md|4SAKHJD876C5432,S2HQT9DKQ5CKQJT9,SQJT9HA32DAJ2CA8,|sv|e|ah|Board+12|";
+        let board = parse_lin(lin).unwrap().to_board();
+        assert_eq!(board.number, Some(12));
+        assert_eq!(board.dealer, Some(Direction::East));
+        assert_eq!(board.vulnerable, Vulnerability::EastWest);
+    }
+
+    #[test]
+    fn test_md_without_dealer_digit() {
+        let hands = "S87HAQT2D95CAQT43,SAQT95H874DAJ2CJ5,S62HKJ53DKQT43CK6,";
+        for md in [
+            format!("0{}", hands),
+            format!("W{}", hands),
+            hands.to_string(),
+        ] {
+            let mut diagnostics = Diagnostics::new();
+            let lin = format!("md|{}|ah|Board+3|", md);
+            let data = parse_lin_with_diagnostics(&lin, &mut diagnostics).unwrap();
+            assert_eq!(data.dealer, Direction::South);
+            assert_eq!(data.deal.hand(Direction::South).len(), 13);
+            assert!(diagnostics
+                .warnings()
+                .iter()
+                .any(|w| w.kind == WarningKind::DefaultedValue));
+        }
+
+        let lin = format!("qx|c6|md|0{}|", hands);
+        assert_eq!(parse_lin(&lin).unwrap().dealer, Direction::East);
+        let lin = format!("md|0{}|", hands);
+        assert_eq!(parse_lin(&lin).unwrap().dealer, Direction::North);
+        let lin = format!("md|x{}|", hands);
+        assert_eq!(
+            parse_lin(&lin).unwrap().deal.hand(Direction::South).len(),
+            0
+        );
+    }
+
+    #[test]
+    fn test_final_contract() {
+        let lin = "md|1SAKHJD876C5432,,,|mb|1H|mb|p|mb|4H|mb|d|mb|p|mb|p|mb|p|";
+        assert_eq!(
+            parse_lin(lin).unwrap().final_contract(),
+            Some("4HX".to_string())
+        );
+
+        let lin = "md|1SAKHJD876C5432,,,|mb|1N|mb|p|mb|3N|mb|p|mb|p|mb|p|";
+        assert_eq!(
+            parse_lin(lin).unwrap().final_contract(),
+            Some("3NT".to_string())
+        );
+
+        let lin = "md|1SAKHJD876C5432,,,|mb|p|mb|p|mb|p|mb|p|";
+        assert_eq!(
+            parse_lin(lin).unwrap().final_contract(),
+            Some("Pass".to_string())
+        );
+
+        let lin = "md|1SAKHJD876C5432,,,|mb|1C|mb|p|";
+        assert_eq!(parse_lin(lin).unwrap().final_contract(), None);
+    }
+
+    #[test]
+    fn test_extract_lin() {
+        let text = "See http://www.bridgebase.com/tools/handviewer.html?lin=pn%7CS,W,N,E%7Cmd%7C3SAKHJD876C5432,,,%7Csv%7Co%7C&b=1 \
+                    or pn|A,B,C,D|md|1SAKHJD876C5432,,,|mb|1C| for details.";
+        let found = extract_lin(text);
+        assert_eq!(found.len(), 2);
+        assert_eq!(found[0], "pn|S,W,N,E|md|3SAKHJD876C5432,,,|sv|o|");
+        assert_eq!(parse_lin(&found[1]).unwrap().auction.len(), 1);
+        assert!(extract_lin("no links here | md").is_empty());
+    }
+
+    #[test]
+    fn test_parse_lin_file_with_limits() {
+        let line = "md|3S87HAQT2D95CAQT43,SAQT95H874DAJ2CJ5,S62HKJ53DKQT43CK6,|sv|n|\n";
+        let content = line.repeat(3);
+        assert_eq!(parse_lin_file(&content).unwrap().len(), 3);
+        assert!(parse_lin_file_with_limits(&content, &Limits::new().with_max_boards(2)).is_err());
+    }
+
+    #[test]
+    fn test_derive_missing_hand() {
+        let deal =
+            Deal::from_pbn("N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 -").unwrap();
+        let west = derive_missing_hand(&deal, Direction::West).unwrap();
+        assert_eq!(west.len(), 13);
+        assert!(west.has_card(Card::new(Suit::Spades, Rank::Ten)));
+
+        let mut deal = deal;
+        deal.set_hand(
+            Direction::South,
+            Hand::from_cards(vec![Card::new(Suit::Spades, Rank::King)]),
+        );
+        let err = derive_missing_hand(&deal, Direction::West).unwrap_err();
+        assert!(err.to_string().contains("SK"));
+
+        // Fewer than 39 known cards don't determine the fourth hand
+        let mut short =
+            Deal::from_pbn("N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 -").unwrap();
+        short.set_hand(
+            Direction::South,
+            Hand::from_cards(vec![Card::new(Suit::Spades, Rank::Nine)]),
+        );
+        let err = derive_missing_hand(&short, Direction::West).unwrap_err();
+        assert!(err.to_string().contains("not 13 each"));
+
+        let mut diagnostics = Diagnostics::new();
+        let data = parse_lin_with_diagnostics("md|1SAKHJD876C5432,,,|", &mut diagnostics).unwrap();
+        assert_eq!(data.deal.hand(Direction::South).len(), 10);
+        assert!(data.deal.hand(Direction::East).is_empty());
+        assert!(diagnostics.is_empty());
+
+        let mut diagnostics = Diagnostics::new();
+        let lin = "md|3SK843HT542DJ6C863,SK843HT542DJ6C863,S962HAJ7DKT82CJ75,|";
+        parse_lin_with_diagnostics(lin, &mut diagnostics).unwrap();
+        assert!(diagnostics.warnings()[0]
+            .message
+            .contains("cards dealt twice"));
+    }
+
+    #[test]
+    fn test_parse_lin_diagnostics() {
+        let mut diagnostics = Diagnostics::new();
+        let lin = "md|3S87HAQT2D95CAQT43,SAQT95H874DAJ2CJ5,S62HKJ53DKQT43CK6,|sv|x|pc|Z9|";
+        parse_lin_with_diagnostics(lin, &mut diagnostics).unwrap();
+
+        let kinds: Vec<_> = diagnostics.warnings().iter().map(|w| w.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                WarningKind::DerivedHand,
+                WarningKind::DefaultedValue,
+                WarningKind::IgnoredValue,
+            ]
+        );
+    }
 }
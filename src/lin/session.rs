@@ -0,0 +1,355 @@
+//! BBO vugraph sessions.
+//!
+//! A vugraph LIN file holds one segment of a match: a `vg` header naming
+//! the event, segment, boards and teams, an `rs` list of the results at
+//! both tables, `pn` with the eight players, and a `qx` record (`o1`,
+//! `c1`, `o2`, ...) for each board played in each room. Tournaments often
+//! split a match over several files, one per segment; [`stitch_sessions`]
+//! joins them back into one timeline.
+
+use super::{parse_lin_with_diagnostics, LinData, LinRoom};
+use crate::diagnostics::{Diagnostics, WarningKind};
+use crate::error::{ParseError, Result};
+
+/// One vugraph segment, or several stitched together
+#[derive(Debug, Clone, Default)]
+pub struct LinSession {
+    /// Event name, from `vg`
+    pub event: String,
+    /// Segment name (e.g. "Round 1"), from `vg`; the segments joined by
+    /// ", " once stitched
+    pub segment: String,
+    /// Scoring letter from `vg`: `I` IMPs, `P` matchpoints, `B` board-a-match
+    pub scoring: String,
+    /// First board of the segment
+    pub first_board: u32,
+    /// Last board of the segment
+    pub last_board: u32,
+    /// The two teams, from `vg`
+    pub teams: [String; 2],
+    /// Scores the teams carry into the segment, from `vg`
+    pub carry_over: [f64; 2],
+    /// Results from `rs`, open then closed room for each board from
+    /// `first_board` on; empty for boards not played
+    pub results: Vec<String>,
+    /// The records, one per board and room, each with its players
+    pub tables: Vec<LinData>,
+}
+
+impl LinSession {
+    /// The result of `board` in `room`, as given by `rs` (e.g. "3NW=")
+    pub fn result(&self, board: u32, room: LinRoom) -> Option<&str> {
+        let index = board.checked_sub(self.first_board)? as usize * 2 + room_index(room);
+        self.results
+            .get(index)
+            .map(String::as_str)
+            .filter(|result| !result.is_empty())
+    }
+
+    /// The record of `board` played in `room`
+    pub fn table(&self, board: u32, room: LinRoom) -> Option<&LinData> {
+        self.tables
+            .iter()
+            .find(|table| table.board_number() == Some(board) && table.room == Some(room))
+    }
+}
+
+/// Position of a room's result within a board's pair of `rs` entries
+fn room_index(room: LinRoom) -> usize {
+    match room {
+        LinRoom::Open => 0,
+        LinRoom::Closed => 1,
+    }
+}
+
+/// Parse a vugraph LIN file into a session
+pub fn parse_lin_session(content: &str) -> Result<LinSession> {
+    parse_lin_session_with_diagnostics(content, &mut Diagnostics::new())
+}
+
+/// Parse a vugraph LIN file into a session, recording recovered problems
+/// in `diagnostics`.
+///
+/// Each `qx` starts a record, which is parsed as by
+/// [`parse_lin_with_diagnostics`]. A record without its own `pn` gets the
+/// players of its room from the session's `pn`: the first four names for
+/// the open room, the last four for the closed room. Without `vg` the
+/// board range is taken from the records.
+pub fn parse_lin_session_with_diagnostics(
+    content: &str,
+    diagnostics: &mut Diagnostics,
+) -> Result<LinSession> {
+    let mut session = LinSession::default();
+    let mut players: Vec<String> = Vec::new();
+    let mut has_vg = false;
+    // Each record's commands, and whether it names its players
+    let mut records: Vec<(String, bool)> = Vec::new();
+
+    let tokens: Vec<&str> = content.split('|').collect();
+    for pair in tokens.chunks(2) {
+        let command = pair[0].trim();
+        let value = pair.get(1).copied().unwrap_or("");
+        match command {
+            "" => {}
+            "vg" => {
+                parse_vg(&mut session, value, diagnostics);
+                has_vg = true;
+            }
+            "rs" => session.results = value.split(',').map(|r| r.trim().to_string()).collect(),
+            "pn" if records.is_empty() => {
+                players = value.split(',').map(str::to_string).collect();
+            }
+            _ => {
+                if command == "qx" {
+                    records.push((String::new(), false));
+                }
+                match records.last_mut() {
+                    Some((record, named)) => {
+                        *named |= command == "pn";
+                        record.push_str(&format!("{}|{}|", command, value));
+                    }
+                    None => diagnostics.warn(
+                        None,
+                        WarningKind::IgnoredValue,
+                        format!("{}|{}| before the first qx", command, value),
+                    ),
+                }
+            }
+        }
+    }
+
+    for (record, named) in records {
+        let mut data = parse_lin_with_diagnostics(&record, diagnostics)?;
+        if !named {
+            let names = match data.room {
+                Some(LinRoom::Closed) if players.len() > 4 => &players[4..],
+                _ => &players[..players.len().min(4)],
+            };
+            for (slot, name) in data.player_names.iter_mut().zip(names) {
+                slot.clone_from(name);
+            }
+        }
+        session.tables.push(data);
+    }
+
+    if !has_vg {
+        let numbers = session.tables.iter().filter_map(LinData::board_number);
+        session.first_board = numbers.clone().min().unwrap_or(0);
+        session.last_board = numbers.max().unwrap_or(0);
+    }
+    Ok(session)
+}
+
+/// Read the `vg` header: event, segment, scoring, first and last board,
+/// then each team with its carry-over score
+fn parse_vg(session: &mut LinSession, value: &str, diagnostics: &mut Diagnostics) {
+    let fields: Vec<&str> = value.split(',').map(str::trim).collect();
+    let field = |i: usize| fields.get(i).copied().unwrap_or("");
+    session.event = field(0).to_string();
+    session.segment = field(1).to_string();
+    session.scoring = field(2).to_string();
+    session.teams = [field(5).to_string(), field(7).to_string()];
+
+    session.first_board = vg_number(field(3), "first board", diagnostics).unwrap_or(1);
+    session.last_board =
+        vg_number(field(4), "last board", diagnostics).unwrap_or(session.first_board);
+    for (team, i) in [(0, 6), (1, 8)] {
+        // A missing carry-over is 0, not a problem
+        if !field(i).is_empty() {
+            session.carry_over[team] = vg_number(field(i), "score", diagnostics).unwrap_or(0.0);
+        }
+    }
+}
+
+/// A number field of `vg`, with a warning if it isn't one
+fn vg_number<T: std::str::FromStr>(
+    text: &str,
+    what: &str,
+    diagnostics: &mut Diagnostics,
+) -> Option<T> {
+    let number = text.parse().ok();
+    if number.is_none() {
+        diagnostics.warn(
+            None,
+            WarningKind::IgnoredValue,
+            format!("invalid {} in vg: \"{}\"", what, text),
+        );
+    }
+    number
+}
+
+/// Join the segments of one match into a single session.
+///
+/// Segments are put in board order, whatever order they come in. The
+/// event, scoring and carry-over scores are those of the first segment;
+/// the boards span all segments, with their `rs` results aligned by board
+/// and room. Each record keeps the players named for it, so changes of
+/// lineup between segments are kept. A board played in the same room in
+/// two segments is taken from the earlier one.
+///
+/// It is an error to stitch no sessions, or segments between different
+/// teams.
+pub fn stitch_sessions(mut sessions: Vec<LinSession>) -> Result<LinSession> {
+    sessions.sort_by_key(|session| session.first_board);
+    let mut sessions = sessions.into_iter();
+    let mut stitched = sessions
+        .next()
+        .ok_or_else(|| ParseError::Lin("no sessions to stitch".to_string()))?;
+
+    for session in sessions {
+        if !same_teams(&stitched.teams, &session.teams) {
+            return Err(ParseError::Lin(format!(
+                "segment \"{}\" is between {} and {}, not {} and {}",
+                session.segment,
+                session.teams[0],
+                session.teams[1],
+                stitched.teams[0],
+                stitched.teams[1]
+            )));
+        }
+        if stitched.teams.iter().all(String::is_empty) {
+            stitched.teams = session.teams.clone();
+        }
+        if stitched.event.is_empty() {
+            stitched.event = session.event.clone();
+        }
+        if !session.segment.is_empty() {
+            if !stitched.segment.is_empty() {
+                stitched.segment.push_str(", ");
+            }
+            stitched.segment.push_str(&session.segment);
+        }
+
+        let first_board = stitched.first_board.min(session.first_board);
+        let last_board = stitched.last_board.max(session.last_board);
+        let mut results = Vec::new();
+        for board in first_board..=last_board {
+            for room in [LinRoom::Open, LinRoom::Closed] {
+                let result = stitched
+                    .result(board, room)
+                    .or_else(|| session.result(board, room));
+                results.push(result.unwrap_or_default().to_string());
+            }
+        }
+        while results.last().is_some_and(String::is_empty) {
+            results.pop();
+        }
+        stitched.results = results;
+        stitched.first_board = first_board;
+        stitched.last_board = last_board;
+
+        for table in session.tables {
+            let repeated = match (table.board_number(), table.room) {
+                (Some(board), Some(room)) => stitched.table(board, room).is_some(),
+                _ => false,
+            };
+            if !repeated {
+                stitched.tables.push(table);
+            }
+        }
+    }
+
+    stitched
+        .tables
+        .sort_by_key(|table| (table.board_number(), table.room));
+    Ok(stitched)
+}
+
+/// Whether two segments are between the same teams; a segment without
+/// team names matches any
+fn same_teams(a: &[String; 2], b: &[String; 2]) -> bool {
+    let unnamed = |teams: &[String; 2]| teams.iter().all(String::is_empty);
+    unnamed(a)
+        || unnamed(b)
+        || a.iter()
+            .zip(b)
+            .all(|(a, b)| a.trim().eq_ignore_ascii_case(b.trim()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fixtures::SAMPLE_LIN_VUGRAPH;
+    use bridge_types::Direction;
+
+    const HANDS: &str = "S962HAJ7DKT82CJ75,ST5HQ9863DA943CKQ,SK843HT542DJ6C863,";
+
+    /// A segment of boards `first..=last` with both rooms of each board
+    fn segment(name: &str, first: u32, last: u32, players: &str) -> String {
+        let mut lin = format!(
+            "vg|Club Teams,{},I,{},{},Lions,0,Tigers,0|",
+            name, first, last
+        );
+        let results: Vec<String> = (first..=last)
+            .flat_map(|board| [format!("3NN{}", board), format!("4SS{}", board)])
+            .collect();
+        lin.push_str(&format!("rs|{}|pn|{}|\n", results.join(","), players));
+        for board in first..=last {
+            for room in ['o', 'c'] {
+                lin.push_str(&format!("qx|{}{}|md|3{}|sv|o|mb|p|\n", room, board, HANDS));
+            }
+        }
+        lin
+    }
+
+    #[test]
+    fn test_parse_session() {
+        let session = parse_lin_session(SAMPLE_LIN_VUGRAPH).unwrap();
+        assert_eq!(session.event, "Club Teams");
+        assert_eq!(session.segment, "Round 1");
+        assert_eq!((session.first_board, session.last_board), (1, 2));
+        assert_eq!(session.teams, ["Lions".to_string(), "Tigers".to_string()]);
+        assert_eq!(session.tables.len(), 2);
+        let table = session.table(2, LinRoom::Open).unwrap();
+        assert_eq!(table.player_names[0], "Shah");
+        assert_eq!(table.qx_board, Some(2));
+
+        let lin = segment("Round 1", 1, 2, "A,B,C,D,E,F,G,H");
+        let session = parse_lin_session(&lin).unwrap();
+        assert_eq!(session.tables.len(), 4);
+        assert_eq!(session.result(2, LinRoom::Closed), Some("4SS2"));
+        assert_eq!(session.result(3, LinRoom::Open), None);
+        let closed = session.table(1, LinRoom::Closed).unwrap();
+        assert_eq!(closed.player_names[0], "E");
+        assert_eq!(closed.to_game().player(Direction::East), Some("H"));
+        assert_eq!(
+            closed.to_game().tag(&crate::pbn::PbnTag::Room),
+            Some("Closed")
+        );
+    }
+
+    #[test]
+    fn test_stitch_sessions() {
+        let first = parse_lin_session(&segment("Round 1", 1, 2, "A,B,C,D,E,F,G,H")).unwrap();
+        let second = parse_lin_session(&segment("Round 2", 3, 4, "A,B,C,D,E,F,G,X")).unwrap();
+        let stitched = stitch_sessions(vec![second.clone(), first.clone()]).unwrap();
+
+        assert_eq!(stitched.segment, "Round 1, Round 2");
+        assert_eq!((stitched.first_board, stitched.last_board), (1, 4));
+        assert_eq!(stitched.tables.len(), 8);
+        let order: Vec<_> = stitched
+            .tables
+            .iter()
+            .map(|table| (table.board_number(), table.room))
+            .collect();
+        assert_eq!(order[2], (Some(2), Some(LinRoom::Open)));
+        assert_eq!(order[7], (Some(4), Some(LinRoom::Closed)));
+        assert_eq!(stitched.result(1, LinRoom::Open), Some("3NN1"));
+        assert_eq!(stitched.result(4, LinRoom::Closed), Some("4SS4"));
+        let substitute = stitched.table(3, LinRoom::Closed).unwrap();
+        assert_eq!(substitute.player_names[3], "X");
+        assert_eq!(
+            stitched.table(1, LinRoom::Closed).unwrap().player_names[3],
+            "H"
+        );
+
+        // A board repeated in a later file is taken once
+        let stitched = stitch_sessions(vec![first.clone(), first.clone()]).unwrap();
+        assert_eq!(stitched.tables.len(), 4);
+
+        let mut other = second;
+        other.teams[1] = "Bears".to_string();
+        assert!(stitch_sessions(vec![first, other]).is_err());
+        assert!(stitch_sessions(Vec::new()).is_err());
+    }
+}
@@ -0,0 +1,298 @@
+//! LIN writer.
+
+use super::LinData;
+use crate::cards::SUITS;
+use crate::layout::WriteOptions;
+use crate::pbn::Call;
+use crate::seat::{standard_dealer, standard_vulnerability, SeatOrder};
+use bridge_types::{Board, Deal, Direction, Strain, Vulnerability};
+
+/// Seats in the order their hands appear in the `md` command.
+///
+/// The order is fixed: hands always start with South, whoever dealt. The
+/// dealer is encoded separately by the leading digit.
+pub const MD_SEATS: [Direction; 4] = SeatOrder::SWNE.seats();
+
+/// Position of a seat's hand in the `md` command (South = 0 ... East = 3)
+pub fn md_slot(seat: Direction) -> usize {
+    SeatOrder::SWNE.position(seat)
+}
+
+/// The `md` dealer digit for a seat (1 = South, 2 = West, 3 = North, 4 = East)
+pub fn md_dealer_digit(dealer: Direction) -> char {
+    match dealer {
+        Direction::South => '1',
+        Direction::West => '2',
+        Direction::North => '3',
+        Direction::East => '4',
+    }
+}
+
+/// Format the value of an `md` command: the dealer digit followed by the
+/// four hands in [`MD_SEATS`] order.
+///
+/// This takes the whole deal rather than a list of hands so the hand order
+/// can't be gotten wrong by the caller.
+pub fn format_md(deal: &Deal, dealer: Direction) -> String {
+    let hands: Vec<String> = MD_SEATS
+        .iter()
+        .map(|&seat| format_lin_hand(deal, seat))
+        .collect();
+    format!("{}{}", md_dealer_digit(dealer), hands.join(","))
+}
+
+/// Format one hand as suit letters followed by ranks (e.g. "SAK3HQJ2D654CT98")
+fn format_lin_hand(deal: &Deal, seat: Direction) -> String {
+    let hand = deal.hand(seat);
+    let mut out = String::new();
+    for suit in SUITS {
+        out.push(suit.to_char());
+        let mut ranks: Vec<_> = hand.cards_in_suit(suit).iter().map(|c| c.rank).collect();
+        ranks.sort_by_key(|&rank| std::cmp::Reverse(rank));
+        out.extend(ranks.iter().map(|r| r.to_char()));
+    }
+    out
+}
+
+/// The `sv` value for a vulnerability
+fn format_sv(vulnerability: Vulnerability) -> &'static str {
+    match vulnerability {
+        Vulnerability::None => "o",
+        Vulnerability::NorthSouth => "n",
+        Vulnerability::EastWest => "e",
+        Vulnerability::Both => "b",
+    }
+}
+
+/// Where the LIN writer takes the `sv` (vulnerability) value from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum SvSource {
+    /// The board's vulnerability
+    #[default]
+    Explicit,
+    /// The standard 16-board cycle for the board number; boards without a
+    /// number keep their own vulnerability
+    FromBoardNumber,
+    /// No `sv` command; BBO then shows the board as not vulnerable
+    Omit,
+}
+
+/// Options controlling LIN output
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LinWriteOptions {
+    /// Where the `sv` value comes from
+    pub vulnerability: SvSource,
+    /// Settings shared with the other writers
+    pub common: WriteOptions,
+}
+
+impl LinWriteOptions {
+    /// Set the settings shared with the other writers
+    pub fn with_common(mut self, common: WriteOptions) -> Self {
+        self.common = common;
+        self
+    }
+
+    /// Set where the `sv` value comes from
+    pub fn with_vulnerability(mut self, vulnerability: SvSource) -> Self {
+        self.vulnerability = vulnerability;
+        self
+    }
+}
+
+/// Convert a single board to a LIN record (without a line ending).
+///
+/// The dealer defaults to the standard dealer for the board number, or
+/// North if the board has neither.
+pub fn board_to_lin(board: &Board) -> String {
+    board_to_lin_with(board, &LinWriteOptions::default())
+}
+
+/// Convert a single board to a LIN record with the given options
+pub fn board_to_lin_with(board: &Board, options: &LinWriteOptions) -> String {
+    let dealer = board
+        .dealer
+        .unwrap_or_else(|| board.number.map_or(Direction::North, standard_dealer));
+
+    let mut lin = format!("md|{}|", format_md(&board.deal, dealer));
+    let vulnerability = match (options.vulnerability, board.number) {
+        (SvSource::Omit, _) => None,
+        (SvSource::FromBoardNumber, Some(n)) => Some(standard_vulnerability(n)),
+        _ => Some(board.vulnerable),
+    };
+    if let Some(vulnerability) = vulnerability {
+        lin.push_str(&format!("sv|{}|", format_sv(vulnerability)));
+    }
+    if let Some(num) = board.number {
+        lin.push_str(&format!("ah|Board {}|", num));
+    }
+    lin
+}
+
+/// A call in LIN notation (`p`, `d`, `r`, `1N`, `4S`)
+pub fn lin_call(call: Call) -> String {
+    match call {
+        Call::Pass => "p".to_string(),
+        Call::Double => "d".to_string(),
+        Call::Redouble => "r".to_string(),
+        Call::Bid(level, Strain::NoTrump) => format!("{}N", level),
+        Call::Bid(level, strain) => Call::Bid(level, strain).to_string(),
+    }
+}
+
+impl LinData {
+    /// Write a LIN record (without a line ending) with the players, deal,
+    /// vulnerability, board header, auction with alerts and annotations,
+    /// play and claim
+    pub fn to_lin(&self) -> String {
+        let mut lin = String::new();
+        if self.player_names.iter().any(|name| !name.is_empty()) {
+            lin.push_str(&format!("pn|{}|", self.player_names.join(",")));
+        }
+        lin.push_str(&format!(
+            "md|{}|sv|{}|",
+            format_md(&self.deal, self.dealer),
+            format_sv(self.vulnerability)
+        ));
+        if let Some(header) = &self.board_header {
+            lin.push_str(&format!("ah|{}|", header));
+        }
+        for bid in &self.auction {
+            let call = Call::parse(&bid.bid).map_or_else(|| bid.bid.clone(), lin_call);
+            let alert = if bid.alert { "!" } else { "" };
+            lin.push_str(&format!("mb|{}{}|", call, alert));
+            if let Some(annotation) = &bid.annotation {
+                lin.push_str(&format!("an|{}|", annotation));
+            }
+        }
+        for card in &self.play {
+            lin.push_str(&format!(
+                "pc|{}{}|",
+                card.suit.to_char(),
+                card.rank.to_char()
+            ));
+        }
+        if let Some(claim) = self.claim {
+            lin.push_str(&format!("mc|{}|", claim));
+        }
+        lin
+    }
+}
+
+/// Write boards as LIN records, one per line
+pub fn write_lin(boards: &[Board]) -> String {
+    write_lin_with(boards, &WriteOptions::default())
+}
+
+/// Write boards as LIN records, one per line, with the given options
+pub fn write_lin_with(boards: &[Board], options: &WriteOptions) -> String {
+    write_lin_with_options(boards, &LinWriteOptions::default().with_common(*options))
+}
+
+/// Write boards as LIN records, one per line, with the given LIN options
+pub fn write_lin_with_options(boards: &[Board], options: &LinWriteOptions) -> String {
+    let text = boards
+        .iter()
+        .map(|board| board_to_lin_with(board, options) + "\n")
+        .collect();
+    options.common.finish(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::lin::parse_lin;
+
+    const PBN_DEAL: &str = "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ";
+
+    #[test]
+    fn test_md_starts_with_south_for_every_dealer() {
+        let deal = Deal::from_pbn(PBN_DEAL).unwrap();
+        for dealer in Direction::ALL {
+            let md = format_md(&deal, dealer);
+            assert_eq!(md.chars().next(), Some(md_dealer_digit(dealer)));
+            assert!(md[1..].starts_with("S962HAJ7DKT82CJ75,ST5HQ9863DA943CKQ,"));
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let deal = Deal::from_pbn(PBN_DEAL).unwrap();
+        let board = Board::new()
+            .with_number(6)
+            .with_dealer(Direction::East)
+            .with_vulnerability(Vulnerability::EastWest)
+            .with_deal(deal);
+
+        let lin = board_to_lin(&board);
+        let parsed = parse_lin(&lin).unwrap();
+        assert_eq!(parsed.dealer, Direction::East);
+        assert_eq!(parsed.vulnerability, Vulnerability::EastWest);
+        assert_eq!(parsed.board_number(), Some(6));
+        assert_eq!(parsed.deal.to_pbn(Direction::North), PBN_DEAL);
+    }
+
+    #[test]
+    fn test_sv_source() {
+        let board = Board::new()
+            .with_number(6)
+            .with_deal(Deal::from_pbn(PBN_DEAL).unwrap());
+        let sv = |source| {
+            board_to_lin_with(
+                &board,
+                &LinWriteOptions::default().with_vulnerability(source),
+            )
+        };
+        assert!(sv(SvSource::Explicit).contains("|sv|o|"));
+        assert!(sv(SvSource::FromBoardNumber).contains("|sv|e|"));
+        assert!(!sv(SvSource::Omit).contains("sv|"));
+    }
+
+    #[test]
+    fn test_doubled_contracts_round_trip() {
+        use bridge_types::{Contract, Doubled};
+
+        let md = "md|3S87HAQT2D95CAQT43,SAQT95H874DAJ2CJ5,S62HKJ53DKQT43CK6,|sv|b|";
+        let cases = [
+            (
+                "mb|1S|mb|2H|mb|d|mb|p|mb|p|mb|p|",
+                Contract::new(2, Strain::Hearts, Doubled::Doubled),
+                Direction::East,
+            ),
+            (
+                "mb|1H|mb|p|mb|4H|mb|d|mb|r|mb|p|mb|p|mb|p|",
+                Contract::new(4, Strain::Hearts, Doubled::Redoubled),
+                Direction::North,
+            ),
+            (
+                "mb|1N!|an|15-17|mb|X|mb|XX|mb|p|mb|p|mb|p|",
+                Contract::new(1, Strain::NoTrump, Doubled::Redoubled),
+                Direction::North,
+            ),
+        ];
+        for (auction, contract, declarer) in cases {
+            let data = parse_lin(&format!("{}{}", md, auction)).unwrap();
+            assert_eq!(data.contract(), Some((contract, declarer)));
+
+            let written = data.to_lin();
+            let reread = parse_lin(&written).unwrap();
+            assert_eq!(reread.contract(), Some((contract, declarer)));
+            assert!(written.contains("|mb|p|mb|p|mb|p|"));
+
+            let game = data.to_game();
+            assert_eq!(game.contract, Some(contract));
+            assert_eq!(game.declarer().map(|d| d.seat), Some(declarer));
+        }
+        let written = parse_lin(&format!("{}{}", md, cases[2].0))
+            .unwrap()
+            .to_lin();
+        assert!(written.contains("mb|1N!|an|15-17|mb|d|mb|r|"));
+    }
+
+    #[test]
+    fn test_md_slot_matches_md_seats() {
+        for (slot, &seat) in MD_SEATS.iter().enumerate() {
+            assert_eq!(md_slot(seat), slot);
+        }
+    }
+}
@@ -7,30 +7,61 @@
 //!
 //! Each hand is a position character followed by cards in S.H.D.C format.
 
-use crate::error::{ParseError, Result};
+use crate::cards::rank_char;
+use crate::checksum::{append_checksum, checksum_matches, split_checksum};
+use crate::error::{ParseError, Result, SourceLocation};
+use crate::handpost::{parse_holdings, PartialDeal};
+use crate::layout::WriteOptions;
+use crate::seat::{parse_seat, SeatOrder};
 use bridge_types::{Card, Deal, Direction, Hand, Rank, Suit};
 
 /// Parse a deal in dealer.exe oneline format
 ///
 /// Format: "n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72"
+///
+/// A trailing checksum, as written with
+/// [`WriteOptions::checksums`], is verified. Errors are
+/// [`ParseError::Syntax`] located on line 1 at the offending part of the
+/// input.
 pub fn parse_oneline(input: &str) -> Result<Deal> {
-    let parts: Vec<&str> = input.split_whitespace().collect();
+    let error = |offset: usize, text: &str, message: String| ParseError::Syntax {
+        format: "Oneline",
+        location: SourceLocation::new(1, input, offset, text),
+        message,
+    };
+    let (record, checksum) = split_checksum(input);
+    if let Some(digits) = checksum.filter(|digits| !checksum_matches(record, digits)) {
+        let offset = input.trim_end().len() - digits.len();
+        return Err(error(offset, digits, "Checksum mismatch".to_string()));
+    }
+    let parts = tokens(record);
 
     if parts.len() != 8 {
-        return Err(ParseError::Oneline(format!(
-            "Expected 8 parts (4 positions + 4 hands), got {}",
-            parts.len()
-        )));
+        return Err(error(
+            0,
+            input.trim_end(),
+            format!(
+                "Expected 8 parts (4 positions + 4 hands), got {}",
+                parts.len()
+            ),
+        ));
     }
 
     let mut deal = Deal::new();
 
     for i in 0..4 {
-        let pos_str = parts[i * 2];
-        let hand_str = parts[i * 2 + 1];
-
-        let direction = parse_direction_char(pos_str)?;
-        let hand = parse_hand(hand_str)?;
+        let (pos_offset, pos_str) = parts[i * 2];
+        let (hand_offset, hand_str) = parts[i * 2 + 1];
+
+        let direction = parse_direction_char(pos_str).ok_or_else(|| {
+            error(
+                pos_offset,
+                pos_str,
+                format!("Invalid direction: {}", pos_str),
+            )
+        })?;
+        let hand = parse_hand(hand_str)
+            .map_err(|(offset, text, message)| error(hand_offset + offset, text, message))?;
 
         deal.set_hand(direction, hand);
     }
@@ -38,42 +69,109 @@ pub fn parse_oneline(input: &str) -> Result<Deal> {
     Ok(deal)
 }
 
+/// Parse a deal in oneline format whose hands may contain `x` for small
+/// cards, as in `n AKxx.Kx.Qxxx.xxx e - s - w -`; a hand given as `-`
+/// has no cards
+pub fn parse_oneline_partial(input: &str) -> Result<PartialDeal> {
+    let parts = tokens(input);
+    let error = |offset: usize, text: &str, message: String| ParseError::Syntax {
+        format: "Oneline",
+        location: SourceLocation::new(1, input, offset, text),
+        message,
+    };
+    if parts.len() != 8 {
+        return Err(error(
+            0,
+            input.trim_end(),
+            format!(
+                "Expected 8 parts (4 positions + 4 hands), got {}",
+                parts.len()
+            ),
+        ));
+    }
+
+    let mut deal = PartialDeal::new();
+    for pair in parts.chunks(2) {
+        let [(pos_offset, pos_str), (hand_offset, hand_str)] = [pair[0], pair[1]];
+        let direction = parse_direction_char(pos_str).ok_or_else(|| {
+            error(
+                pos_offset,
+                pos_str,
+                format!("Invalid direction: {}", pos_str),
+            )
+        })?;
+        if hand_str != "-" {
+            let hand = parse_holdings(hand_str)
+                .map_err(|(offset, text, message)| error(hand_offset + offset, text, message))?;
+            deal.set_hand(direction, hand);
+        }
+    }
+    Ok(deal)
+}
+
+/// The whitespace-separated tokens of `input` with their byte offsets
+fn tokens(input: &str) -> Vec<(usize, &str)> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in input.char_indices() {
+        match (c.is_whitespace(), start) {
+            (true, Some(s)) => {
+                tokens.push((s, &input[s..i]));
+                start = None;
+            }
+            (false, None) => start = Some(i),
+            _ => {}
+        }
+    }
+    if let Some(s) = start {
+        tokens.push((s, &input[s..]));
+    }
+    tokens
+}
+
 /// Format a deal in oneline format
 ///
 /// Output: "n CARDS e CARDS s CARDS w CARDS\n"
 pub fn format_oneline(deal: &Deal) -> String {
+    format_oneline_with(deal, None)
+}
+
+/// Format a deal in oneline format, writing ranks below `spots_below` as `x`
+fn format_oneline_with(deal: &Deal, spots_below: Option<Rank>) -> String {
     let mut result = String::new();
 
-    for &dir in &[
-        Direction::North,
-        Direction::East,
-        Direction::South,
-        Direction::West,
-    ] {
+    for dir in SeatOrder::NESW {
         if !result.is_empty() {
             result.push(' ');
         }
         result.push(direction_char(dir));
         result.push(' ');
-        result.push_str(&format_hand(deal.hand(dir)));
+        result.push_str(&format_hand(deal.hand(dir), spots_below));
     }
 
     result.push('\n');
     result
 }
 
+/// Write deals in oneline format, one per line, with the given options
+pub fn write_oneline(deals: &[Deal], options: &WriteOptions) -> String {
+    options.finish(
+        deals
+            .iter()
+            .map(|deal| {
+                let line = format_oneline_with(deal, options.spots_below);
+                match options.checksums {
+                    true => append_checksum(line.trim_end()) + "\n",
+                    false => line,
+                }
+            })
+            .collect(),
+    )
+}
+
 /// Parse a single character direction (n, e, s, w)
-fn parse_direction_char(s: &str) -> Result<Direction> {
-    match s.to_lowercase().as_str() {
-        "n" => Ok(Direction::North),
-        "e" => Ok(Direction::East),
-        "s" => Ok(Direction::South),
-        "w" => Ok(Direction::West),
-        _ => Err(ParseError::Oneline(format!(
-            "Invalid direction character: {}",
-            s
-        ))),
-    }
+fn parse_direction_char(s: &str) -> Option<Direction> {
+    parse_seat(s)
 }
 
 /// Get lowercase direction character
@@ -87,37 +185,47 @@ fn direction_char(dir: Direction) -> char {
 }
 
 /// Parse a hand in format: Spades.Hearts.Diamonds.Clubs
-fn parse_hand(s: &str) -> Result<Hand> {
+///
+/// Errors give the byte offset and text of the offending part of `s`
+fn parse_hand(s: &str) -> std::result::Result<Hand, (usize, &str, String)> {
     let suits_str: Vec<&str> = s.split('.').collect();
     if suits_str.len() != 4 {
-        return Err(ParseError::Oneline(format!(
-            "Expected 4 suits separated by dots, got {}",
-            suits_str.len()
-        )));
+        return Err((
+            0,
+            s,
+            format!(
+                "Expected 4 suits separated by dots, got {}",
+                suits_str.len()
+            ),
+        ));
     }
 
     let mut hand = Hand::new();
     let suits = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
+    let mut offset = 0;
 
     for (suit_idx, &suit_str) in suits_str.iter().enumerate() {
         let suit = suits[suit_idx];
 
-        // Empty string means void suit
-        if suit_str.is_empty() {
-            continue;
-        }
-
-        for c in suit_str.chars() {
-            let rank = parse_rank(c)?;
+        for (i, c) in suit_str.char_indices() {
+            let rank = parse_rank(c).ok_or_else(|| {
+                let at = offset + i;
+                (
+                    at,
+                    &s[at..at + c.len_utf8()],
+                    format!("Invalid rank character: {}", c),
+                )
+            })?;
             hand.add_card(Card::new(suit, rank));
         }
+        offset += suit_str.len() + 1;
     }
 
     Ok(hand)
 }
 
 /// Format a hand in Spades.Hearts.Diamonds.Clubs format
-fn format_hand(hand: &Hand) -> String {
+fn format_hand(hand: &Hand, spots_below: Option<Rank>) -> String {
     let suits = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
     let mut result = Vec::new();
 
@@ -128,7 +236,10 @@ fn format_hand(hand: &Hand) -> String {
         } else {
             // Sort by rank descending (Ace first)
             cards.sort_by(|a, b| b.rank.cmp(&a.rank));
-            let suit_str: String = cards.iter().map(|c| c.rank.to_char()).collect();
+            let suit_str: String = cards
+                .iter()
+                .map(|c| rank_char(c.rank, spots_below))
+                .collect();
             result.push(suit_str);
         }
     }
@@ -137,8 +248,8 @@ fn format_hand(hand: &Hand) -> String {
 }
 
 /// Parse a rank character
-fn parse_rank(c: char) -> Result<Rank> {
-    Rank::from_char(c).ok_or_else(|| ParseError::Oneline(format!("Invalid rank character: {}", c)))
+fn parse_rank(c: char) -> Option<Rank> {
+    Rank::from_char(c)
 }
 
 #[cfg(test)]
@@ -159,6 +270,39 @@ mod tests {
         assert_eq!(north.suit_length(Suit::Clubs), 2);
     }
 
+    #[test]
+    fn test_spot_cards() {
+        let partial = parse_oneline_partial("n AKxx.Kx.Qxxx.xxx e - s - w -").unwrap();
+        assert_eq!(partial.hand(Direction::North).spots, [2, 1, 3, 3]);
+        assert!(partial.hand(Direction::East).is_empty());
+        let error = parse_oneline_partial("n AKxx.Kx.Qxxy.xxx e - s - w -").unwrap_err();
+        assert_eq!(error.location().map(|l| l.start_column), Some(14));
+
+        let deal = partial.complete().unwrap();
+        let options = WriteOptions::new().with_spots_below(Some(Rank::Ten));
+        assert_eq!(
+            write_oneline(&[deal], &options),
+            "n AKxx.Kx.Qxxx.xxx e ... s ... w ...\n"
+        );
+    }
+
+    #[test]
+    fn test_checksums() {
+        let input = "n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72";
+        let deal = parse_oneline(input).unwrap();
+        let options = WriteOptions::new().with_checksums(true);
+        let line = write_oneline(&[deal], &options);
+        assert!(line.starts_with(input) && line.len() == input.len() + 11);
+        assert!(parse_oneline(&line).is_ok());
+
+        let corrupted = line.replacen("AKQT3", "AKQT4", 1);
+        let error = parse_oneline(&corrupted).unwrap_err();
+        assert_eq!(
+            error.location().map(|l| l.start_column),
+            Some(input.len() + 3)
+        );
+    }
+
     #[test]
     fn test_format_oneline() {
         let input = "n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72";
@@ -201,4 +345,36 @@ mod tests {
             assert_eq!(deal.hand(dir).len(), reparsed.hand(dir).len());
         }
     }
+
+    #[test]
+    fn test_write_oneline_layout() {
+        let deal = parse_oneline(
+            "n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72",
+        )
+        .unwrap();
+        let options = WriteOptions::new()
+            .with_line_ending(crate::LineEnding::CrLf)
+            .with_trailing_newline(false);
+        let text = write_oneline(&[deal.clone(), deal], &options);
+        assert_eq!(text.matches("\r\n").count(), 1);
+        assert!(text.ends_with("QJ72"));
+    }
+
+    #[test]
+    fn test_error_location() {
+        let err = parse_oneline(
+            "n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QZ95.T.AK863 w 98.873.9653.QJ72",
+        )
+        .unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!((location.start_column, location.end_column), (46, 46));
+        assert_eq!(location.text, "Z");
+
+        let err = parse_oneline(
+            "x AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72",
+        )
+        .unwrap_err();
+        assert_eq!(err.location().unwrap().text, "x");
+        assert!(parse_oneline("n AKQ").unwrap_err().location().is_some());
+    }
 }
@@ -0,0 +1,459 @@
+//! PBN auction sections.
+
+use crate::seat::rotate_seat;
+use bridge_types::{Contract, Direction, Doubled, Strain};
+use std::fmt;
+
+/// A single call
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Call {
+    Pass,
+    Double,
+    Redouble,
+    Bid(u8, Strain),
+}
+
+impl Call {
+    /// Parse a call in PBN (`Pass`, `X`, `XX`, `1NT`) or LIN (`p`, `d`,
+    /// `r`, `1N`) notation, ignoring case
+    pub fn parse(s: &str) -> Option<Call> {
+        let upper = s.trim().to_ascii_uppercase();
+        match upper.as_str() {
+            "PASS" | "P" => return Some(Call::Pass),
+            "X" | "D" => return Some(Call::Double),
+            "XX" | "R" => return Some(Call::Redouble),
+            _ => {}
+        }
+        let mut chars = upper.chars();
+        let level = chars.next()?.to_digit(10).filter(|l| (1..=7).contains(l))? as u8;
+        let strain = match chars.as_str() {
+            "C" => Strain::Clubs,
+            "D" => Strain::Diamonds,
+            "H" => Strain::Hearts,
+            "S" => Strain::Spades,
+            "N" | "NT" => Strain::NoTrump,
+            _ => return None,
+        };
+        Some(Call::Bid(level, strain))
+    }
+}
+
+impl fmt::Display for Call {
+    /// PBN notation
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Call::Pass => f.write_str("Pass"),
+            Call::Double => f.write_str("X"),
+            Call::Redouble => f.write_str("XX"),
+            Call::Bid(level, strain) => {
+                let strain = match strain {
+                    Strain::Clubs => "C",
+                    Strain::Diamonds => "D",
+                    Strain::Hearts => "H",
+                    Strain::Spades => "S",
+                    Strain::NoTrump => "NT",
+                };
+                write!(f, "{}{}", level, strain)
+            }
+        }
+    }
+}
+
+/// A call with its annotations
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuctionCall {
+    pub call: Call,
+    /// Whether the call was marked with a `!` suffix
+    pub alert: bool,
+    /// Number of the note referenced with `=n=`, if any
+    pub note: Option<u32>,
+    /// Numeric annotation glyphs (`$n`), in file order. Suffixes other
+    /// than `!` are stored as their glyph (`?` is `$2`, `!!` is `$3`, ...).
+    pub nags: Vec<u8>,
+}
+
+impl AuctionCall {
+    /// Apply a suffix: `!` marks an alert, the others add their NAG
+    fn annotate(&mut self, suffix: &str) -> Result<(), String> {
+        match suffix {
+            "" => {}
+            "!" => self.alert = true,
+            _ => self
+                .nags
+                .push(suffix_nag(suffix).ok_or_else(|| format!("invalid suffix: {}", suffix))?),
+        }
+        Ok(())
+    }
+}
+
+/// How alerted calls are marked in a written auction
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AlertMark {
+    /// `1C!`
+    #[default]
+    Exclamation,
+    /// `1C*`, as written by some scoring programs
+    Asterisk,
+}
+
+impl AlertMark {
+    fn as_str(self) -> &'static str {
+        match self {
+            AlertMark::Exclamation => "!",
+            AlertMark::Asterisk => "*",
+        }
+    }
+}
+
+/// How calls with a note are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NoteStyle {
+    /// A `=n=` reference to the Note tag (BridgeComposer)
+    #[default]
+    References,
+    /// The alert mark only, without the note, for consumers that don't
+    /// accept note references
+    Inline,
+}
+
+/// Whether `token` is a suffix annotation (`!`, `?`, `!!`, `??`, `!?`, `?!`)
+pub(crate) fn is_suffix(token: &str) -> bool {
+    (1..=2).contains(&token.len()) && token.chars().all(|c| c == '!' || c == '?')
+}
+
+/// The call NAG ($1-$6) of a suffix; cards use the same order from $7
+pub(crate) fn suffix_nag(suffix: &str) -> Option<u8> {
+    let nag = match suffix {
+        "!" => 1,
+        "?" => 2,
+        "!!" => 3,
+        "??" => 4,
+        "!?" => 5,
+        "?!" => 6,
+        _ => return None,
+    };
+    Some(nag)
+}
+
+/// Parse a `$n` token; `Ok(None)` for other tokens
+pub(crate) fn parse_nag(token: &str) -> Result<Option<u8>, String> {
+    match token.strip_prefix('$') {
+        Some(value) => value
+            .parse()
+            .map(Some)
+            .map_err(|_| format!("invalid NAG: {}", token)),
+        None => Ok(None),
+    }
+}
+
+/// A parsed auction section
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Auction {
+    /// Player making the first call (the Auction tag value)
+    pub first: Direction,
+    /// Calls in order; a trailing `AP` is expanded into passes
+    pub calls: Vec<AuctionCall>,
+}
+
+impl Auction {
+    /// Parse the tokens of an auction section starting with `first`.
+    ///
+    /// Suffixes and NAGs (`$n`) annotate the call before them; parsing
+    /// stops at a `*` terminator. A `*` directly after a call marks an
+    /// alert, like `!`.
+    pub fn parse(first: Direction, section: &str) -> Result<Self, String> {
+        let mut calls: Vec<AuctionCall> = Vec::new();
+        for token in section.split_whitespace() {
+            if token == "*" {
+                break;
+            }
+            if let Some(nag) = parse_nag(token)? {
+                if let Some(last) = calls.last_mut() {
+                    last.nags.push(nag);
+                }
+                continue;
+            }
+            if is_suffix(token) {
+                if let Some(last) = calls.last_mut() {
+                    last.annotate(token)?;
+                }
+                continue;
+            }
+            if let Some(number) = token.strip_prefix('=').and_then(|t| t.strip_suffix('=')) {
+                let number = number
+                    .parse()
+                    .map_err(|_| format!("invalid note reference: {}", token))?;
+                if let Some(last) = calls.last_mut() {
+                    last.note = Some(number);
+                }
+                continue;
+            }
+            if token.eq_ignore_ascii_case("AP") {
+                // Passes already made count towards the three that end
+                // the auction, or the four of a passed out board
+                let trailing = calls
+                    .iter()
+                    .rev()
+                    .take_while(|c| c.call == Call::Pass)
+                    .count();
+                let passes = if trailing < calls.len() {
+                    3usize.saturating_sub(trailing)
+                } else {
+                    4usize.saturating_sub(trailing)
+                };
+                calls.extend((0..passes).map(|_| AuctionCall {
+                    call: Call::Pass,
+                    alert: false,
+                    note: None,
+                    nags: Vec::new(),
+                }));
+                break;
+            }
+
+            let (token, asterisk) = match token.strip_suffix('*') {
+                Some(call) => (call, true),
+                None => (token, false),
+            };
+            let text = token.trim_end_matches(['?', '!']);
+            let call = Call::parse(text).ok_or_else(|| format!("invalid call: {}", token))?;
+            let mut call = AuctionCall {
+                call,
+                alert: asterisk,
+                note: None,
+                nags: Vec::new(),
+            };
+            call.annotate(&token[text.len()..])?;
+            calls.push(call);
+        }
+        Ok(Auction { first, calls })
+    }
+
+    /// The section text, four calls per line
+    pub fn to_pbn(&self) -> String {
+        self.to_pbn_with(AlertMark::default(), NoteStyle::default())
+    }
+
+    /// The section text with alerts marked by `alert`; with
+    /// [`NoteStyle::Inline`], calls with a note are marked as alerted
+    /// instead of referencing it
+    pub fn to_pbn_with(&self, alert: AlertMark, notes: NoteStyle) -> String {
+        let mut out = String::new();
+        for (i, call) in self.calls.iter().enumerate() {
+            out.push_str(&call.call.to_string());
+            let note = call.note.filter(|_| notes == NoteStyle::References);
+            if call.alert || (call.note.is_some() && note.is_none()) {
+                out.push_str(alert.as_str());
+            }
+            if let Some(note) = note {
+                out.push_str(&format!(" ={}=", note));
+            }
+            let mut nags = call.nags.clone();
+            nags.sort_unstable();
+            for nag in nags {
+                out.push_str(&format!(" ${}", nag));
+            }
+            out.push(if i % 4 == 3 { '\n' } else { ' ' });
+        }
+        if !out.is_empty() && !out.ends_with('\n') {
+            out.pop();
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Whether the auction is over: three passes after a call, or four
+    /// passes on a passed out board
+    pub fn is_complete(&self) -> bool {
+        self.calls.len() >= 4
+            && self.calls[self.calls.len() - 3..]
+                .iter()
+                .all(|c| c.call == Call::Pass)
+    }
+
+    /// Player making the call at `index`
+    pub fn caller(&self, index: usize) -> Direction {
+        rotate_seat(self.first, index)
+    }
+
+    /// The final contract and declarer, or `None` if passed out or the
+    /// auction has no bids
+    pub fn contract(&self) -> Option<(Contract, Direction)> {
+        let (last_bid, level, strain) = self.last_bid()?;
+        let doubled = self.calls[last_bid..]
+            .iter()
+            .fold(Doubled::None, |doubled, c| match c.call {
+                Call::Double => Doubled::Doubled,
+                Call::Redouble => Doubled::Redoubled,
+                _ => doubled,
+            });
+        Some((Contract::new(level, strain, doubled), self.declarer()?))
+    }
+
+    /// The declarer: the first player of the side that made the last bid
+    /// to bid its strain, at any level. A transfer (1NT-2H-2S) makes the
+    /// player who completed it declarer; bids of the strain by the
+    /// opponents, doubles and redoubles don't count. `None` if the auction
+    /// has no bids.
+    pub fn declarer(&self) -> Option<Direction> {
+        let (last_bid, _, strain) = self.last_bid()?;
+        let declaring_side = last_bid % 2;
+        let declarer_index = (0..=last_bid).find(|&i| {
+            i % 2 == declaring_side && matches!(self.calls[i].call, Call::Bid(_, s) if s == strain)
+        })?;
+        Some(self.caller(declarer_index))
+    }
+
+    /// Index, level and strain of the last bid
+    fn last_bid(&self) -> Option<(usize, u8, Strain)> {
+        self.calls
+            .iter()
+            .enumerate()
+            .rev()
+            .find_map(|(i, c)| match c.call {
+                Call::Bid(level, strain) => Some((i, level, strain)),
+                _ => None,
+            })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_auction() {
+        let auction =
+            Auction::parse(Direction::East, "1C! =1= 1H X 3H\n4S $1 Pass Pass X\nAP").unwrap();
+        assert_eq!(auction.calls.len(), 11);
+        assert!(auction.calls[0].alert);
+        assert_eq!(auction.calls[0].note, Some(1));
+        assert_eq!(auction.calls[4].call, Call::Bid(4, Strain::Spades));
+        assert_eq!(auction.caller(4), Direction::East);
+
+        let (contract, declarer) = auction.contract().unwrap();
+        assert_eq!(contract, Contract::new(4, Strain::Spades, Doubled::Doubled));
+        assert_eq!(declarer, Direction::East);
+    }
+
+    #[test]
+    fn test_to_pbn_round_trip() {
+        let auction = Auction::parse(Direction::West, "1D! =1= X 1S =2= Pass 2S AP").unwrap();
+        let text = auction.to_pbn();
+        assert_eq!(text, "1D! =1= X 1S =2= Pass\n2S Pass Pass Pass\n");
+        assert_eq!(Auction::parse(Direction::West, &text).unwrap(), auction);
+    }
+
+    #[test]
+    fn test_annotations() {
+        let auction = Auction::parse(Direction::North, "1S !! =1= $25 2H?! X! $13 Pass").unwrap();
+        assert_eq!(auction.calls[0].nags, [3, 25]);
+        assert_eq!(auction.calls[0].note, Some(1));
+        assert_eq!(auction.calls[1].nags, [6]);
+        assert!(auction.calls[2].alert);
+        assert_eq!(auction.calls[2].nags, [13]);
+        assert_eq!(auction.to_pbn(), "1S =1= $3 $25 2H $6 X! $13 Pass\n");
+        assert!(Auction::parse(Direction::North, "1S $300").is_err());
+    }
+
+    #[test]
+    fn test_alert_styles() {
+        let mut auction = Auction::parse(Direction::North, "1C* 1H 2C =1= Pass").unwrap();
+        assert!(auction.calls[0].alert);
+        assert_eq!(auction.calls.len(), 4);
+        auction.calls[1].alert = true;
+        assert_eq!(
+            auction.to_pbn_with(AlertMark::Asterisk, NoteStyle::References),
+            "1C* 1H* 2C =1= Pass\n"
+        );
+        assert_eq!(
+            auction.to_pbn_with(AlertMark::Exclamation, NoteStyle::Inline),
+            "1C! 1H! 2C! Pass\n"
+        );
+    }
+
+    #[test]
+    fn test_declarer() {
+        let declarer = |first, calls| Auction::parse(first, calls).unwrap().declarer();
+        let n = Direction::North;
+        // Opener's suit raised by partner
+        assert_eq!(declarer(n, "1H Pass 4H AP"), Some(Direction::North));
+        // Transfer: responder bids hearts to show spades, opener declares
+        assert_eq!(
+            declarer(n, "1NT Pass 2H Pass 2S AP"),
+            Some(Direction::North)
+        );
+        // Texas transfer completed at game level
+        assert_eq!(
+            declarer(n, "1NT Pass 4D Pass 4H AP"),
+            Some(Direction::North)
+        );
+        // Partner names the strain first, even at a lower level
+        assert_eq!(declarer(n, "1C Pass 1S Pass 4S AP"), Some(Direction::South));
+        // Opponents' bid of the same strain doesn't count
+        assert_eq!(declarer(n, "1S 2S 3S Pass 4S AP"), Some(Direction::North));
+        assert_eq!(
+            declarer(n, "Pass 1D 1H Pass 2H Pass Pass 3H AP"),
+            Some(Direction::West)
+        );
+        // Doubles and redoubles after the last bid don't change declarer
+        assert_eq!(declarer(n, "1NT X XX AP"), Some(Direction::North));
+        assert_eq!(
+            declarer(n, "1S Pass 2S X Pass Pass XX AP"),
+            Some(Direction::North)
+        );
+        // Dealer other than North
+        assert_eq!(
+            declarer(Direction::West, "Pass 1NT Pass 3NT AP"),
+            Some(Direction::North)
+        );
+        // Sacrifice by the other side in a new strain
+        assert_eq!(declarer(n, "1H 1S 4H 4S X AP"), Some(Direction::East));
+        // No bids
+        assert_eq!(declarer(n, "AP"), None);
+        assert_eq!(Auction::parse(n, "").unwrap().declarer(), None);
+    }
+
+    #[test]
+    fn test_passed_out() {
+        let auction = Auction::parse(Direction::North, "AP").unwrap();
+        assert_eq!(auction.calls.len(), 4);
+        assert!(auction.contract().is_none());
+
+        let auction = Auction::parse(Direction::North, "Pass Pass AP").unwrap();
+        assert_eq!(auction.calls.len(), 4);
+    }
+
+    #[test]
+    fn test_is_complete() {
+        let complete = |section| {
+            Auction::parse(Direction::North, section)
+                .unwrap()
+                .is_complete()
+        };
+        assert!(complete("1H Pass Pass Pass"));
+        assert!(complete("Pass Pass Pass Pass"));
+        assert!(complete("1H AP"));
+        assert!(!complete("Pass Pass Pass"));
+        assert!(!complete("1H Pass Pass"));
+        assert!(!complete("1H Pass Pass X"));
+    }
+
+    #[test]
+    fn test_all_pass_after_passes() {
+        let calls = |section| Auction::parse(Direction::North, section).unwrap().calls;
+        assert_eq!(calls("1H Pass AP").len(), 5);
+        assert_eq!(calls("1H Pass Pass AP").len(), 5);
+        assert_eq!(calls("1H Pass Pass Pass AP").len(), 4);
+        assert_eq!(calls("1H X AP").len(), 5);
+        assert!(calls("1H Pass AP")[1..]
+            .iter()
+            .all(|call| call.call == Call::Pass));
+    }
+
+    #[test]
+    fn test_invalid_call() {
+        assert!(Auction::parse(Direction::North, "1C 8H").is_err());
+        assert_eq!(Call::parse("1n"), Some(Call::Bid(1, Strain::NoTrump)));
+        assert_eq!(Call::Bid(3, Strain::NoTrump).to_string(), "3NT");
+    }
+}
@@ -0,0 +1,126 @@
+//! BridgeComposer extension tags.
+//!
+//! BridgeComposer writes a few tags of its own: `BCFlags`, a hexadecimal
+//! set of display options, and `Hidden`, the seats whose hands are hidden
+//! in its diagrams. [`ComposerTags`] reads and writes them; the game keeps
+//! them as [`PbnTag::Unknown`] pairs, so files round-trip unchanged unless
+//! they are set.
+
+use super::reader::TagPair;
+use super::tag::PbnTag;
+use bridge_types::Direction;
+
+/// Name of the display options tag
+const BC_FLAGS: &str = "BCFlags";
+/// Name of the hidden hands tag
+const HIDDEN: &str = "Hidden";
+
+/// The BridgeComposer extension tags of a game
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ComposerTags {
+    /// The display options bit set (`BCFlags`, written in hexadecimal)
+    pub flags: Option<u32>,
+    /// Seats whose hands BridgeComposer hides (`Hidden`, e.g. `EW`)
+    pub hidden: Vec<Direction>,
+}
+
+impl ComposerTags {
+    /// The names of the tags covered by `ComposerTags`
+    pub const TAG_NAMES: [&'static str; 2] = [BC_FLAGS, HIDDEN];
+
+    /// Read the extension tags from a game's tag pairs; the first
+    /// occurrence of each tag is used and invalid values are ignored
+    pub fn from_tags(tags: &[TagPair]) -> Self {
+        let value = |name: &str| {
+            tags.iter()
+                .find(|pair| pair.tag.name() == name)
+                .map(|pair| pair.value.trim())
+        };
+        Self {
+            flags: value(BC_FLAGS).and_then(|v| parse_flags(v).ok()),
+            hidden: value(HIDDEN)
+                .and_then(|v| parse_hidden(v).ok())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The tag pairs for the fields that are set
+    pub fn to_tags(&self) -> Vec<TagPair> {
+        let mut tags = Vec::new();
+        if let Some(flags) = self.flags {
+            tags.push(TagPair {
+                tag: PbnTag::Unknown(BC_FLAGS.to_string()),
+                value: format!("{:x}", flags),
+            });
+        }
+        if !self.hidden.is_empty() {
+            tags.push(TagPair {
+                tag: PbnTag::Unknown(HIDDEN.to_string()),
+                value: self.hidden.iter().map(|seat| seat.to_char()).collect(),
+            });
+        }
+        tags
+    }
+
+    /// Whether bit `bit` (from 0) of the display options is set
+    pub fn flag(&self, bit: u32) -> bool {
+        self.flags
+            .is_some_and(|flags| flags.checked_shr(bit).is_some_and(|f| f & 1 == 1))
+    }
+
+    /// Whether the tag pair is one covered by `ComposerTags`
+    pub(crate) fn covers(pair: &TagPair) -> bool {
+        Self::TAG_NAMES.contains(&pair.tag.name())
+    }
+}
+
+/// Parse a BCFlags value: hexadecimal, with or without a `0x` prefix
+fn parse_flags(value: &str) -> Result<u32, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .unwrap_or(value);
+    u32::from_str_radix(digits, 16).map_err(|_| format!("invalid BCFlags value: {}", value))
+}
+
+/// Parse a Hidden value: seat letters in any order
+fn parse_hidden(value: &str) -> Result<Vec<Direction>, String> {
+    let mut seats = Vec::new();
+    for c in value.chars().filter(|c| !c.is_whitespace()) {
+        let seat = Direction::from_char(c.to_ascii_uppercase())
+            .ok_or_else(|| format!("invalid seat in Hidden value: {}", value))?;
+        if !seats.contains(&seat) {
+            seats.push(seat);
+        }
+    }
+    Ok(seats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_composer_tags() {
+        let pbn =
+            "[Event \"Club\"]\n[Board \"1\"]\n[BCFlags \"1F\"]\n[Hidden \"ew\"]\n[BCOther \"x\"]\n";
+        let mut game = crate::pbn::read_pbn_games(pbn).unwrap().remove(0);
+        let tags = game.composer_tags();
+        assert_eq!(tags.flags, Some(0x1f));
+        assert!(tags.flag(4) && !tags.flag(5));
+        assert_eq!(tags.hidden, [Direction::East, Direction::West]);
+
+        let written = crate::pbn::game_to_pbn_with(&game, &Default::default());
+        assert!(written.contains("[BCFlags \"1F\"]\n"));
+        assert!(written.contains("[BCOther \"x\"]\n"));
+
+        game.set_composer_tags(&ComposerTags {
+            flags: Some(0x2a),
+            hidden: Vec::new(),
+        });
+        let written = crate::pbn::game_to_pbn_with(&game, &Default::default());
+        assert!(written.contains("[BCFlags \"2a\"]\n"));
+        assert!(!written.contains("[Hidden"));
+        assert!(written.contains("[BCOther \"x\"]\n"));
+    }
+}
@@ -0,0 +1,258 @@
+//! Double dummy trick tables.
+
+use crate::seat::{parse_seat, rotate_seat, SeatOrder};
+use bridge_types::{Direction, Strain};
+
+/// Declarer order of the DoubleDummyTricks value
+const DD_DECLARERS: [Direction; 4] = [
+    Direction::North,
+    Direction::South,
+    Direction::East,
+    Direction::West,
+];
+
+/// Strain order within each declarer of the DoubleDummyTricks value
+const DD_STRAINS: [Strain; 5] = [
+    Strain::NoTrump,
+    Strain::Spades,
+    Strain::Hearts,
+    Strain::Diamonds,
+    Strain::Clubs,
+];
+
+/// Tricks available to each declarer in each strain, double dummy
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct DoubleDummyTable {
+    /// Indexed like `DD_DECLARERS` and `DD_STRAINS`
+    tricks: [[Option<u8>; 5]; 4],
+}
+
+impl DoubleDummyTable {
+    /// The OptimumResultTable tag value for the rows written by
+    /// [`to_optimum_result_table`](Self::to_optimum_result_table)
+    pub const OPTIMUM_RESULT_TABLE_HEADER: &'static str = "Declarer;Denomination\\2R;Result\\2R";
+
+    /// An empty table
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tricks for `declarer` playing in `strain`, if known
+    pub fn get(&self, declarer: Direction, strain: Strain) -> Option<u8> {
+        self.tricks[declarer_index(declarer)][strain_index(strain)]
+    }
+
+    /// Set the tricks for `declarer` playing in `strain`
+    pub fn set(&mut self, declarer: Direction, strain: Strain, tricks: u8) {
+        self.tricks[declarer_index(declarer)][strain_index(strain)] = Some(tricks);
+    }
+
+    /// The table with each declarer's tricks moved `seats` seats clockwise,
+    /// for a deal rotated the same way
+    pub fn rotated(&self, seats: usize) -> Self {
+        let mut table = Self::new();
+        for declarer in DD_DECLARERS {
+            for strain in DD_STRAINS {
+                if let Some(tricks) = self.get(declarer, strain) {
+                    table.set(rotate_seat(declarer, seats), strain, tricks);
+                }
+            }
+        }
+        table
+    }
+
+    /// Whether all 20 entries are known
+    pub fn is_complete(&self) -> bool {
+        self.tricks.iter().flatten().all(Option::is_some)
+    }
+
+    /// Decode a DoubleDummyTricks value: 20 hex digits, five per declarer
+    /// in the order N, S, E, W, each group ordered NT, S, H, D, C
+    pub fn parse_dd_tricks(value: &str) -> Result<Self, String> {
+        let digits: Vec<char> = value.trim().chars().collect();
+        if digits.len() != 20 {
+            return Err(format!(
+                "DoubleDummyTricks has {} digits, not 20: \"{}\"",
+                digits.len(),
+                value
+            ));
+        }
+        let mut table = Self::new();
+        for (d, &declarer) in DD_DECLARERS.iter().enumerate() {
+            for (s, &strain) in DD_STRAINS.iter().enumerate() {
+                let digit = digits[d * 5 + s];
+                let tricks = digit.to_digit(16).filter(|&t| t <= 13).ok_or_else(|| {
+                    format!(
+                        "invalid DoubleDummyTricks digit '{}' for {} in {}",
+                        digit,
+                        declarer.to_char(),
+                        strain_name(strain)
+                    )
+                })?;
+                table.set(declarer, strain, tricks as u8);
+            }
+        }
+        Ok(table)
+    }
+
+    /// Decode a DoubleDummyTricks value, or `None` if it is invalid (see
+    /// [`parse_dd_tricks`](Self::parse_dd_tricks))
+    pub fn from_dd_tricks(value: &str) -> Option<Self> {
+        Self::parse_dd_tricks(value).ok()
+    }
+
+    /// Encode as a DoubleDummyTricks value, if the table is complete
+    pub fn to_dd_tricks(&self) -> Option<String> {
+        DD_DECLARERS
+            .iter()
+            .flat_map(|&declarer| DD_STRAINS.iter().map(move |&strain| (declarer, strain)))
+            .map(|(declarer, strain)| {
+                let tricks = self.get(declarer, strain)?;
+                char::from_digit(tricks as u32, 16)
+            })
+            .collect()
+    }
+
+    /// Parse an OptimumResultTable section.
+    ///
+    /// `header` is the tag value naming the columns (e.g.
+    /// `Declarer;Denomination\2R;Result\2R`); each row of `section` gives a
+    /// declarer, a strain (`NT`, `S`, `H`, `D`, `C`) and the tricks.
+    pub fn parse_optimum_result_table(header: &str, section: &str) -> Result<Self, String> {
+        let columns: Vec<&str> = header
+            .split(';')
+            .map(|column| column.split('\\').next().unwrap_or("").trim())
+            .collect();
+        let column = |name: &str| {
+            columns
+                .iter()
+                .position(|c| c.eq_ignore_ascii_case(name))
+                .ok_or_else(|| format!("OptimumResultTable has no {} column", name))
+        };
+        let (declarer_col, strain_col, result_col) = (
+            column("Declarer")?,
+            column("Denomination")?,
+            column("Result")?,
+        );
+
+        let mut table = Self::new();
+        for row in section.lines().filter(|row| !row.trim().is_empty()) {
+            let fields: Vec<&str> = row.split_whitespace().collect();
+            let field = |i: usize| {
+                fields
+                    .get(i)
+                    .copied()
+                    .ok_or_else(|| format!("short OptimumResultTable row: {}", row))
+            };
+            let declarer = parse_seat(field(declarer_col)?)
+                .ok_or_else(|| format!("invalid declarer in row: {}", row))?;
+            let strain = match field(strain_col)?.to_ascii_uppercase().as_str() {
+                "NT" | "N" => Strain::NoTrump,
+                "S" => Strain::Spades,
+                "H" => Strain::Hearts,
+                "D" => Strain::Diamonds,
+                "C" => Strain::Clubs,
+                _ => return Err(format!("invalid denomination in row: {}", row)),
+            };
+            let tricks = field(result_col)?
+                .parse::<u8>()
+                .ok()
+                .filter(|&t| t <= 13)
+                .ok_or_else(|| format!("invalid result in row: {}", row))?;
+            table.set(declarer, strain, tricks);
+        }
+        Ok(table)
+    }
+
+    /// The rows of an OptimumResultTable section with the columns
+    /// `Declarer;Denomination\2R;Result\2R`, for the known entries
+    pub fn to_optimum_result_table(&self) -> String {
+        let mut out = String::new();
+        for declarer in SeatOrder::NESW {
+            for strain in DD_STRAINS {
+                if let Some(tricks) = self.get(declarer, strain) {
+                    out.push_str(&format!(
+                        "{} {} {}\n",
+                        declarer.to_char(),
+                        strain_name(strain),
+                        tricks
+                    ));
+                }
+            }
+        }
+        out
+    }
+}
+
+/// The strain as written in an OptimumResultTable (`NT`, `S`, ...)
+fn strain_name(strain: Strain) -> &'static str {
+    match strain {
+        Strain::NoTrump => "NT",
+        Strain::Spades => "S",
+        Strain::Hearts => "H",
+        Strain::Diamonds => "D",
+        Strain::Clubs => "C",
+    }
+}
+
+fn declarer_index(declarer: Direction) -> usize {
+    match declarer {
+        Direction::North => 0,
+        Direction::South => 1,
+        Direction::East => 2,
+        Direction::West => 3,
+    }
+}
+
+fn strain_index(strain: Strain) -> usize {
+    match strain {
+        Strain::NoTrump => 0,
+        Strain::Spades => 1,
+        Strain::Hearts => 2,
+        Strain::Diamonds => 3,
+        Strain::Clubs => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dd_tricks_round_trip() {
+        let table = DoubleDummyTable::from_dd_tricks("88667799555566775555").unwrap();
+        assert_eq!(table.get(Direction::North, Strain::NoTrump), Some(8));
+        assert_eq!(table.get(Direction::South, Strain::Hearts), Some(9));
+        assert_eq!(table.get(Direction::West, Strain::Clubs), Some(5));
+        assert_eq!(
+            table.to_dd_tricks().as_deref(),
+            Some("88667799555566775555")
+        );
+        assert!(DoubleDummyTable::from_dd_tricks("8866").is_none());
+        assert_eq!(
+            DoubleDummyTable::parse_dd_tricks("8866779955556677555e").unwrap_err(),
+            "invalid DoubleDummyTricks digit 'e' for W in C"
+        );
+    }
+
+    #[test]
+    fn test_optimum_result_table() {
+        let table = DoubleDummyTable::parse_optimum_result_table(
+            "Declarer;Denomination\\2R;Result\\2R",
+            "N NT 9\nN S 10\nE H 4\n",
+        )
+        .unwrap();
+        assert_eq!(table.get(Direction::North, Strain::Spades), Some(10));
+        assert_eq!(table.get(Direction::East, Strain::Hearts), Some(4));
+        assert_eq!(table.get(Direction::East, Strain::Clubs), None);
+        assert!(!table.is_complete());
+        assert_eq!(table.to_optimum_result_table(), "N NT 9\nN S 10\nE H 4\n");
+
+        assert!(DoubleDummyTable::parse_optimum_result_table("Declarer;Result", "N 9").is_err());
+        assert!(DoubleDummyTable::parse_optimum_result_table(
+            "Declarer;Denomination;Result",
+            "N X 9"
+        )
+        .is_err());
+    }
+}
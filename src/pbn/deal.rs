@@ -0,0 +1,105 @@
+//! Deal tag values with hands that aren't given.
+//!
+//! A Deal tag may leave out hands, writing `-` in their place, as bidding
+//! problems that show only two hands do:
+//! `[Deal "W:KQT2.AT.J6542.85 - A8654.KQ5.T.QJT6 -"]`.
+//! A [`Deal`] can't tell such a hand from one with no cards, so
+//! [`DealTag`] keeps each hand as an `Option`.
+
+use crate::cards::SUITS;
+use crate::seat::{parse_seat, SeatOrder};
+use bridge_types::{Card, Deal, Direction, Hand, Rank};
+
+/// The hands of a Deal tag; a hand given as `-` is `None`
+#[derive(Debug, Clone)]
+pub struct DealTag {
+    /// The seat of the first hand in the tag value
+    pub first: Direction,
+    /// Hands in North, East, South, West order
+    pub hands: [Option<Hand>; 4],
+}
+
+impl DealTag {
+    /// Parse a Deal tag value like `N:AKQ.T98.J65.432 - - -`
+    pub fn from_pbn(value: &str) -> Result<Self, String> {
+        let invalid = || format!("invalid Deal: \"{}\"", value);
+        let (first, hands) = value.trim().split_once(':').ok_or_else(invalid)?;
+        let first = parse_seat(first).ok_or_else(invalid)?;
+        let hands: Vec<&str> = hands.split_whitespace().collect();
+        if hands.len() != 4 {
+            return Err(invalid());
+        }
+
+        let mut tag = DealTag {
+            first,
+            hands: Default::default(),
+        };
+        for (text, seat) in hands.into_iter().zip(SeatOrder::clockwise_from(first)) {
+            if text != "-" {
+                tag.hands[SeatOrder::NESW.position(seat)] =
+                    Some(parse_hand(text).ok_or_else(invalid)?);
+            }
+        }
+        Ok(tag)
+    }
+
+    /// The hand at `seat`; `None` when the tag doesn't give it
+    pub fn hand(&self, seat: Direction) -> Option<&Hand> {
+        self.hands[SeatOrder::NESW.position(seat)].as_ref()
+    }
+
+    /// The seats whose hands are given
+    pub fn given_seats(&self) -> Vec<Direction> {
+        Direction::ALL
+            .into_iter()
+            .filter(|&seat| self.hand(seat).is_some())
+            .collect()
+    }
+
+    /// The deal, with an empty hand for each hand not given
+    pub fn to_deal(&self) -> Deal {
+        let mut deal = Deal::new();
+        for seat in Direction::ALL {
+            if let Some(hand) = self.hand(seat) {
+                deal.set_hand(seat, hand.clone());
+            }
+        }
+        deal
+    }
+}
+
+/// Parse one hand: four dot-separated suits of ranks, spades first
+fn parse_hand(text: &str) -> Option<Hand> {
+    let suits: Vec<&str> = text.split('.').collect();
+    if suits.len() != 4 {
+        return None;
+    }
+    let mut hand = Hand::new();
+    for (ranks, suit) in suits.into_iter().zip(SUITS) {
+        for c in ranks.chars() {
+            hand.add_card(Card::new(suit, Rank::from_char(c)?));
+        }
+    }
+    Some(hand)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deal_tag() {
+        let tag = DealTag::from_pbn("W:KQT2.AT.J6542.85 - A8654.KQ5.T.QJT6 -").unwrap();
+        assert_eq!(tag.first, Direction::West);
+        assert_eq!(tag.given_seats(), [Direction::East, Direction::West]);
+        assert!(tag.hand(Direction::North).is_none());
+        assert_eq!(tag.hand(Direction::East).map(Hand::len), Some(13));
+        assert_eq!(
+            tag.to_deal().to_pbn(Direction::West),
+            "W:KQT2.AT.J6542.85 ... A8654.KQ5.T.QJT6 ..."
+        );
+
+        assert!(DealTag::from_pbn("N:AKQ - -").is_err());
+        assert!(DealTag::from_pbn("N:AKQ.T98.J65.43X - - -").is_err());
+    }
+}
@@ -0,0 +1,71 @@
+//! LIN records embedded in PBN commentary.
+
+use super::stream::PbnReader;
+use crate::error::Result;
+use crate::lin::{extract_lin, parse_lin, LinData};
+
+/// A LIN record found in the commentary of a PBN game
+#[derive(Debug, Clone)]
+pub struct EmbeddedLin {
+    /// Index of the board within the boards returned by [`read_pbn`](super::read_pbn)
+    pub board_index: usize,
+    /// The decoded LIN string
+    pub source: String,
+    /// The parsed LIN record
+    pub data: LinData,
+}
+
+/// Scan the commentary of every game for embedded LIN records or BBO
+/// handviewer links and parse them.
+///
+/// Each result records the index of the board it belongs to, so the LIN
+/// play record can be matched up with the boards from `read_pbn`.
+pub fn scan_embedded_lin(content: &str) -> Result<Vec<EmbeddedLin>> {
+    let mut reader = PbnReader::new(content.as_bytes());
+    let mut found = Vec::new();
+    let mut board_index = 0;
+
+    while let Some(game) = reader.next_game() {
+        let game = game?;
+
+        for block in &game.commentary {
+            for source in extract_lin(&block.text) {
+                if let Ok(data) = parse_lin(&source) {
+                    found.push(EmbeddedLin {
+                        board_index,
+                        source,
+                        data,
+                    });
+                }
+            }
+        }
+
+        board_index += 1;
+    }
+
+    Ok(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scan_embedded_lin() {
+        let pbn = r#"[Board "1"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+{No links here.}
+
+[Board "2"]
+[Deal "E:Q7.AKT9.JT3.JT96 J653.QJ8.A.AQ732 K92.654.K954.K84 AT84.732.Q8762.5"]
+{Played at table 3:
+https://www.bridgebase.com/tools/handviewer.html?lin=pn%7CS,W,N,E%7Cmd%7C4SAKHJD876C5432,,,%7Cmb%7C1C%7Cmb%7Cp%7C
+}
+"#;
+        let found = scan_embedded_lin(pbn).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].board_index, 1);
+        assert_eq!(found[0].data.auction.len(), 2);
+        assert_eq!(found[0].data.player_names[0], "S");
+    }
+}
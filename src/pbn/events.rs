@@ -0,0 +1,214 @@
+//! Event-based PBN parsing.
+//!
+//! [`PbnEvents`] splits PBN input into its lexical parts without building
+//! games, for callers with their own model of the data (extension tags,
+//! sections of their own) who would otherwise have to fork
+//! [`PbnReader`](super::PbnReader).
+
+use super::reader::{is_unterminated_deal, parse_tag_pair, TagPair};
+use super::tag::PbnTag;
+use crate::error::{ParseError, Result, SourceLocation};
+use std::io::BufRead;
+
+/// One part of PBN input, with the line (1-based) it starts on
+#[derive(Debug, Clone)]
+pub enum PbnEvent {
+    /// A tag pair; a Deal tag wrapped onto the next line is joined
+    TagPair { line: usize, pair: TagPair },
+    /// A line of section data (auction, play, table rows, ...), trimmed
+    SectionLine { line: usize, text: String },
+    /// A `;` comment or a `{ }` commentary block, without its delimiters
+    Comment { line: usize, text: String },
+    /// A `%` line such as `% PBN 2.1` or `% EXPORT`, without the `%`
+    Directive { line: usize, text: String },
+    /// The end of a game: at a blank line, at an Event tag starting the
+    /// next game (reported before it), or after the last line of input
+    GameBoundary { line: usize },
+}
+
+/// Pull parser yielding the [`PbnEvent`]s of PBN input in order.
+///
+/// A malformed tag pair is an error item; iteration continues with the
+/// next line.
+pub struct PbnEvents<R: BufRead> {
+    reader: R,
+    line_buf: String,
+    line_number: usize,
+    /// Whether a tag or section line has been seen since the last boundary
+    in_game: bool,
+    /// Whether the current game has an Event tag
+    has_event: bool,
+    /// Event read while reporting the boundary before it
+    pending: Option<PbnEvent>,
+    done: bool,
+}
+
+impl<R: BufRead> PbnEvents<R> {
+    /// Create a parser over `reader`
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            line_buf: String::new(),
+            line_number: 0,
+            in_game: false,
+            has_event: false,
+            pending: None,
+            done: false,
+        }
+    }
+
+    /// Read the next line, trimmed; `None` at EOF
+    fn read_line(&mut self) -> Result<Option<String>> {
+        self.line_buf.clear();
+        if self.reader.read_line(&mut self.line_buf)? == 0 {
+            return Ok(None);
+        }
+        self.line_number += 1;
+        Ok(Some(self.line_buf.trim().to_string()))
+    }
+
+    /// The end of the current game, if one is open
+    fn boundary(&mut self, line: usize) -> Option<PbnEvent> {
+        if !self.in_game {
+            return None;
+        }
+        self.in_game = false;
+        self.has_event = false;
+        Some(PbnEvent::GameBoundary { line })
+    }
+
+    fn next_event(&mut self) -> Result<Option<PbnEvent>> {
+        loop {
+            let Some(line) = self.read_line()? else {
+                self.done = true;
+                return Ok(self.boundary(self.line_number + 1));
+            };
+            let start = self.line_number;
+
+            if line.is_empty() {
+                match self.boundary(start) {
+                    Some(event) => return Ok(Some(event)),
+                    None => continue,
+                }
+            }
+            if let Some(text) = line.strip_prefix(';') {
+                return Ok(Some(PbnEvent::Comment {
+                    line: start,
+                    text: text.trim().to_string(),
+                }));
+            }
+            if let Some(text) = line.strip_prefix('%') {
+                return Ok(Some(PbnEvent::Directive {
+                    line: start,
+                    text: text.trim().to_string(),
+                }));
+            }
+            if let Some(text) = line.strip_prefix('{') {
+                let mut text = text.to_string();
+                while !text.contains('}') {
+                    let Some(next) = self.read_line()? else {
+                        break;
+                    };
+                    text.push('\n');
+                    text.push_str(&next);
+                }
+                if let Some(end) = text.find('}') {
+                    text.truncate(end);
+                }
+                return Ok(Some(PbnEvent::Comment { line: start, text }));
+            }
+
+            if line.starts_with('[') {
+                let mut line = line;
+                if is_unterminated_deal(&line) {
+                    if let Some(next) = self.read_line()? {
+                        line.push(' ');
+                        line.push_str(&next);
+                    }
+                }
+                let pair = parse_tag_pair(&line).ok_or_else(|| ParseError::Syntax {
+                    format: "PBN",
+                    location: SourceLocation::find(start, &line, &line),
+                    message: format!("malformed tag pair: {}", line),
+                })?;
+                let is_event = pair.tag == PbnTag::Event;
+                let event = PbnEvent::TagPair { line: start, pair };
+                if is_event {
+                    if self.has_event {
+                        self.pending = Some(event);
+                        return Ok(self.boundary(start));
+                    }
+                    self.has_event = true;
+                }
+                self.in_game = true;
+                return Ok(Some(event));
+            }
+
+            self.in_game = true;
+            return Ok(Some(PbnEvent::SectionLine {
+                line: start,
+                text: line,
+            }));
+        }
+    }
+}
+
+impl<R: BufRead> Iterator for PbnEvents<R> {
+    type Item = Result<PbnEvent>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(event) = self.pending.take() {
+            self.in_game = true;
+            self.has_event = true;
+            return Some(Ok(event));
+        }
+        if self.done {
+            return None;
+        }
+        match self.next_event() {
+            Ok(event) => event.map(Ok),
+            Err(ParseError::Io(e)) => {
+                self.done = true;
+                Some(Err(ParseError::Io(e)))
+            }
+            Err(e) => Some(Err(e)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_events() {
+        let pbn = "% PBN 2.1\n[Event \"Club\"]\n; note\n[Deal \"N:AKQJ.T98.765.432\n- - -\"]\n\
+                   [Auction \"N\"]\n1S Pass\n{two\nlines}\n[Event \"Club\"]\n[Bad\n\n";
+        let events: Vec<String> = PbnEvents::new(pbn.as_bytes())
+            .map(|event| match event {
+                Ok(PbnEvent::TagPair { line, pair }) => format!("{} {}", line, pair.name()),
+                Ok(PbnEvent::SectionLine { line, text }) => format!("{} [{}]", line, text),
+                Ok(PbnEvent::Comment { line, text }) => format!("{} {{{}}}", line, text),
+                Ok(PbnEvent::Directive { line, text }) => format!("{} %{}", line, text),
+                Ok(PbnEvent::GameBoundary { line }) => format!("{} --", line),
+                Err(e) => format!("{} error", e.location().map_or(0, |l| l.line)),
+            })
+            .collect();
+        assert_eq!(
+            events,
+            [
+                "1 %PBN 2.1",
+                "2 Event",
+                "3 {note}",
+                "4 Deal",
+                "6 Auction",
+                "7 [1S Pass]",
+                "8 {two\nlines}",
+                "10 --",
+                "10 Event",
+                "11 error",
+                "12 --",
+            ]
+        );
+    }
+}
@@ -0,0 +1,387 @@
+//! A PBN game with all of its tags.
+
+use super::auction::Auction;
+use super::composer::ComposerTags;
+use super::dd_table::DoubleDummyTable;
+use super::deal::DealTag;
+use super::info::{GameInfo, Scoring};
+use super::play::Play;
+use super::reader::TagPair;
+use super::table::PbnTable;
+use super::tag::PbnTag;
+use crate::error::GameSpan;
+use crate::scoring::{
+    parse_contract, DeclarerTag, OptimumScoreTag, ParContractTag, ResultTag, ScoreTag,
+};
+use bridge_types::{Board, Contract, Direction};
+
+/// One game of a PBN file.
+///
+/// The board holds the data this crate interprets; `tags` keeps every tag
+/// pair of the game in file order, including those without a board field
+/// (player names, contract, result, ...).
+#[derive(Debug, Clone)]
+pub struct Game {
+    /// The board built from the game's tags
+    pub board: Board,
+    /// All tag pairs, in file order
+    pub tags: Vec<TagPair>,
+    /// The contract from the Contract tag; `None` when the tag is missing,
+    /// empty or `Pass`. The writer puts it in the Contract tag.
+    pub contract: Option<Contract>,
+    /// The auction section, if the game has one
+    pub auction: Option<Auction>,
+    /// The play section, if the game has one
+    pub play: Option<Play>,
+    /// The OptimumResultTable section, if the game has one
+    pub optimum_result_table: Option<DoubleDummyTable>,
+    /// The ScoreTable section, if the game has one
+    pub score_table: Option<PbnTable>,
+    /// The TotalScoreTable section (event rankings), if the game has one
+    pub total_score_table: Option<PbnTable>,
+    /// The ActionTable section, if the game has one
+    pub action_table: Option<PbnTable>,
+    /// The InstantScoreTable section, if the game has one
+    pub instant_score_table: Option<PbnTable>,
+    /// The commentary blocks, in file order
+    pub commentary: Vec<Commentary>,
+    /// Lines of the input occupied by the game
+    pub span: GameSpan,
+}
+
+/// The section of a game whose calls or cards reference a note
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum NoteSection {
+    /// Notes referenced from calls of the auction
+    Auction,
+    /// Notes referenced from cards of the play
+    Play,
+}
+
+/// A `{...}` commentary block and where it appears in its game
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Commentary {
+    /// Text of the block, without braces
+    pub text: String,
+    /// The tag pair or section the block follows; `None` before the first tag
+    pub tag: Option<PbnTag>,
+    /// Inside the Auction or Play section: the number of calls, or of
+    /// tricks, before the block
+    pub position: Option<usize>,
+}
+
+impl Game {
+    /// A game holding only `board`, with no other tags or sections
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            tags: Vec::new(),
+            contract: None,
+            auction: None,
+            play: None,
+            optimum_result_table: None,
+            score_table: None,
+            total_score_table: None,
+            action_table: None,
+            instant_score_table: None,
+            commentary: Vec::new(),
+            span: GameSpan {
+                start_line: 0,
+                end_line: 0,
+            },
+        }
+    }
+
+    /// The value of the first occurrence of a tag
+    pub fn tag(&self, tag: &PbnTag) -> Option<&str> {
+        self.tags
+            .iter()
+            .find(|pair| pair.tag == *tag)
+            .map(|pair| pair.value.as_str())
+    }
+
+    /// Whether the Contract tag says the board was passed out
+    pub fn is_passed_out(&self) -> bool {
+        self.tag(&PbnTag::Contract).is_some_and(|value| {
+            matches!(parse_contract(value), Ok(None)) && !value.trim().is_empty()
+        })
+    }
+
+    /// The Scoring tag; `None` when absent or empty
+    pub fn scoring(&self) -> Option<Scoring> {
+        Scoring::from_pbn(self.tag(&PbnTag::Scoring)?)
+    }
+
+    /// The Deal tag, with `None` for hands given as `-`; `None` when
+    /// absent or invalid
+    pub fn deal_tag(&self) -> Option<DealTag> {
+        DealTag::from_pbn(self.tag(&PbnTag::Deal)?).ok()
+    }
+
+    /// The Declarer tag; `None` when absent, empty or invalid
+    pub fn declarer(&self) -> Option<DeclarerTag> {
+        DeclarerTag::from_pbn(self.tag(&PbnTag::Declarer)?).ok()?
+    }
+
+    /// The Result tag; `None` when absent, empty or invalid
+    pub fn result(&self) -> Option<ResultTag> {
+        ResultTag::from_pbn(self.tag(&PbnTag::Result)?).ok()?
+    }
+
+    /// The Score tag; `None` when absent, empty or invalid
+    pub fn score(&self) -> Option<ScoreTag> {
+        ScoreTag::from_pbn(self.tag(&PbnTag::Score)?).ok()?
+    }
+
+    /// The OptimumScore tag; `None` when absent, empty or invalid
+    pub fn optimum_score(&self) -> Option<OptimumScoreTag> {
+        OptimumScoreTag::from_pbn(self.board.optimum_score.as_deref()?).ok()?
+    }
+
+    /// The ParContract tag; `None` when absent, empty, `Pass` or invalid
+    pub fn par_contract(&self) -> Option<ParContractTag> {
+        ParContractTag::from_pbn(self.board.par_contract.as_deref()?).ok()?
+    }
+
+    /// Name of the player at `seat`, from the West, North, East or South tag
+    pub fn player(&self, seat: Direction) -> Option<&str> {
+        self.tag(&player_tag(seat)).filter(|name| !name.is_empty())
+    }
+
+    /// Set the name of the player at `seat`; an empty name removes the tag
+    pub fn set_player(&mut self, seat: Direction, name: &str) {
+        let tag = player_tag(seat);
+        self.tags.retain(|pair| pair.tag != tag);
+        if !name.is_empty() {
+            self.tags.push(TagPair {
+                tag,
+                value: name.to_string(),
+            });
+        }
+    }
+
+    /// The supplemental tags identifying the game within a match or event
+    pub fn info(&self) -> GameInfo {
+        GameInfo::from_tags(&self.tags)
+    }
+
+    /// Replace the supplemental tags covered by [`GameInfo`] with `info`;
+    /// tags for unset fields are removed
+    pub fn set_info(&mut self, info: &GameInfo) {
+        self.tags.retain(|pair| !GameInfo::TAGS.contains(&pair.tag));
+        self.tags.extend(info.to_tags());
+    }
+
+    /// The BridgeComposer extension tags (`BCFlags`, `Hidden`)
+    pub fn composer_tags(&self) -> ComposerTags {
+        ComposerTags::from_tags(&self.tags)
+    }
+
+    /// Replace the BridgeComposer extension tags with `tags`; tags for
+    /// unset fields are removed, other extension tags are kept
+    pub fn set_composer_tags(&mut self, tags: &ComposerTags) {
+        self.tags.retain(|pair| !ComposerTags::covers(pair));
+        self.tags.extend(tags.to_tags());
+    }
+
+    /// Tags this crate doesn't know (e.g. `BCFlags`), in file order; the
+    /// game writers emit them unchanged
+    pub fn extra_tags(&self) -> Vec<&TagPair> {
+        self.tags
+            .iter()
+            .filter(|pair| matches!(pair.tag, PbnTag::Unknown(_)))
+            .collect()
+    }
+
+    /// The table section following `tag`, for the tags with generic tables
+    /// (ScoreTable, TotalScoreTable, ActionTable and InstantScoreTable)
+    pub fn table(&self, tag: &PbnTag) -> Option<&PbnTable> {
+        match tag {
+            PbnTag::ScoreTable => self.score_table.as_ref(),
+            PbnTag::TotalScoreTable => self.total_score_table.as_ref(),
+            PbnTag::ActionTable => self.action_table.as_ref(),
+            PbnTag::InstantScoreTable => self.instant_score_table.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// The double dummy table, from the OptimumResultTable section or else
+    /// the DoubleDummyTricks tag
+    pub fn double_dummy_table(&self) -> Option<DoubleDummyTable> {
+        self.optimum_result_table.or_else(|| {
+            DoubleDummyTable::from_dd_tricks(self.board.double_dummy_tricks.as_deref()?)
+        })
+    }
+
+    /// Set the double dummy table, written as the OptimumResultTable
+    /// section and, when complete, the DoubleDummyTricks tag
+    pub fn set_double_dummy_table(&mut self, table: DoubleDummyTable) {
+        self.optimum_result_table = Some(table);
+        if let Some(tricks) = table.to_dd_tricks() {
+            self.board.double_dummy_tricks = Some(tricks);
+        }
+    }
+
+    /// Notes from the `[Note "n:text"]` tags as (number, text), in file order
+    pub fn notes(&self) -> Vec<(u32, &str)> {
+        self.tags
+            .iter()
+            .filter(|pair| pair.tag == PbnTag::Note)
+            .filter_map(|pair| note_number(&pair.value))
+            .collect()
+    }
+
+    /// The text of the first note numbered `number`, in either section.
+    ///
+    /// The auction and the play usually number their notes separately,
+    /// both from 1; [`auction_note`](Self::auction_note) and
+    /// [`play_note`](Self::play_note) tell them apart.
+    pub fn note(&self, number: u32) -> Option<&str> {
+        self.notes()
+            .into_iter()
+            .find(|&(n, _)| n == number)
+            .map(|(_, text)| text)
+    }
+
+    /// Notes as (section, number, text), in file order. A note belongs to
+    /// the Auction or Play section it follows; notes before both belong
+    /// to the auction.
+    pub fn section_notes(&self) -> Vec<(NoteSection, u32, &str)> {
+        let mut section = NoteSection::Auction;
+        let mut notes = Vec::new();
+        for pair in &self.tags {
+            match pair.tag {
+                PbnTag::Auction => section = NoteSection::Auction,
+                PbnTag::Play => section = NoteSection::Play,
+                PbnTag::Note => {
+                    if let Some((number, text)) = note_number(&pair.value) {
+                        notes.push((section, number, text));
+                    }
+                }
+                _ => {}
+            }
+        }
+        notes
+    }
+
+    /// The text of note `number` of the auction, as referenced by `=n=`
+    /// from a call
+    pub fn auction_note(&self, number: u32) -> Option<&str> {
+        self.section_note(NoteSection::Auction, number)
+    }
+
+    /// The text of note `number` of the play, as referenced by `=n=` from
+    /// a card
+    pub fn play_note(&self, number: u32) -> Option<&str> {
+        self.section_note(NoteSection::Play, number)
+    }
+
+    /// Note `number` of `section`, or else the first note with that number
+    /// wherever it is, for games whose notes aren't placed after their
+    /// sections
+    fn section_note(&self, section: NoteSection, number: u32) -> Option<&str> {
+        self.section_notes()
+            .into_iter()
+            .find(|&(s, n, _)| s == section && n == number)
+            .map(|(_, _, text)| text)
+            .or_else(|| self.note(number))
+    }
+
+    /// The note text explaining each call of the auction, one entry per
+    /// call; a note referenced by several calls explains each of them
+    pub fn call_notes(&self) -> Vec<Option<&str>> {
+        let Some(auction) = &self.auction else {
+            return Vec::new();
+        };
+        auction
+            .calls
+            .iter()
+            .map(|call| call.note.and_then(|n| self.auction_note(n)))
+            .collect()
+    }
+
+    /// Add a Note tag with the next free number, returning the number for
+    /// a call's or card's `note`
+    pub fn add_note(&mut self, text: &str) -> u32 {
+        let number = self.notes().iter().map(|&(n, _)| n).max().unwrap_or(0) + 1;
+        self.tags.push(TagPair {
+            tag: PbnTag::Note,
+            value: format!("{}:{}", number, text),
+        });
+        number
+    }
+
+    /// Merge notes of the same section with identical text into one note
+    /// referenced from each call or card that used them. Notes keep their
+    /// numbers; the auction and the play may both number theirs from 1.
+    pub fn merge_duplicate_notes(&mut self) {
+        // The number each note is merged into, in file order
+        let mut merged: Vec<(NoteSection, u32, &str, u32)> = Vec::new();
+        for (section, number, text) in self.section_notes() {
+            let text = text.trim();
+            let into = merged
+                .iter()
+                .find(|&&(s, _, t, _)| s == section && t == text)
+                .map_or(number, |&(_, n, _, _)| n);
+            merged.push((section, number, text, into));
+        }
+
+        // Keep the first note of each number and text; references to a
+        // number go to where its first note was merged into
+        let mut keep = Vec::with_capacity(merged.len());
+        let mut renumber: Vec<(NoteSection, u32, u32)> = Vec::new();
+        for (i, &(section, number, text, into)) in merged.iter().enumerate() {
+            let first = !merged[..i]
+                .iter()
+                .any(|&(s, n, t, _)| s == section && n == number && t == text);
+            keep.push(first && into == number);
+            if !renumber
+                .iter()
+                .any(|&(s, n, _)| s == section && n == number)
+            {
+                renumber.push((section, number, into));
+            }
+        }
+        let map = |section: NoteSection, old: u32| {
+            renumber
+                .iter()
+                .find(|&&(s, o, _)| s == section && o == old)
+                .or_else(|| renumber.iter().find(|&&(_, o, _)| o == old))
+                .map_or(old, |&(_, _, new)| new)
+        };
+
+        let mut keep = keep.into_iter();
+        self.tags.retain(|pair| {
+            pair.tag != PbnTag::Note
+                || note_number(&pair.value).is_none()
+                || keep.next().unwrap_or(true)
+        });
+
+        if let Some(auction) = &mut self.auction {
+            for call in &mut auction.calls {
+                call.note = call.note.map(|n| map(NoteSection::Auction, n));
+            }
+        }
+        if let Some(play) = &mut self.play {
+            for note in &mut play.notes {
+                note.note = map(NoteSection::Play, note.note);
+            }
+            play.notes.dedup();
+        }
+    }
+}
+
+/// The number and text of a Note tag value (`n:text`)
+pub(crate) fn note_number(value: &str) -> Option<(u32, &str)> {
+    let (number, text) = value.split_once(':')?;
+    Some((number.trim().parse().ok()?, text))
+}
+
+fn player_tag(seat: Direction) -> PbnTag {
+    match seat {
+        Direction::North => PbnTag::North,
+        Direction::East => PbnTag::East,
+        Direction::South => PbnTag::South,
+        Direction::West => PbnTag::West,
+    }
+}
@@ -0,0 +1,208 @@
+//! Grouping games played on the same deal.
+//!
+//! Files from team events hold each deal once per room. [`group_games`]
+//! collects those games into one [`DealRecord`] per deal. A
+//! [`BoardInstance`] is one table's part of a game (players, auction,
+//! play and result) without the deal, for building records from sources
+//! such as vugraph sessions that give the deal once.
+
+use super::auction::Auction;
+use super::game::Game;
+use super::play::Play;
+use super::reader::TagPair;
+use super::tag::PbnTag;
+use crate::scoring::{format_contract, PlayedResult};
+use crate::seat::SeatOrder;
+use bridge_types::{Board, Direction};
+
+/// One game played on a deal
+#[derive(Debug, Clone)]
+pub struct GameResult {
+    /// The Room tag (e.g. `Open`, `Closed`)
+    pub room: Option<String>,
+    /// The result from the Contract, Declarer and Result tags
+    pub result: Option<PlayedResult>,
+    /// The game itself
+    pub game: Game,
+}
+
+impl GameResult {
+    /// Summarize a game
+    pub fn new(game: Game) -> Self {
+        Self {
+            room: game
+                .tag(&PbnTag::Room)
+                .filter(|room| !room.is_empty())
+                .map(str::to_string),
+            result: PlayedResult::from_game(&game),
+            game,
+        }
+    }
+}
+
+/// One table's play of a deal: everything about a game except the board
+#[derive(Debug, Clone, Default)]
+pub struct BoardInstance {
+    /// The Room tag (e.g. `Open`, `Closed`)
+    pub room: Option<String>,
+    /// Player names, North, East, South, West
+    pub players: [Option<String>; 4],
+    /// The auction, if recorded
+    pub auction: Option<Auction>,
+    /// The card play, if recorded
+    pub play: Option<Play>,
+    /// The contract, declarer and tricks taken
+    pub result: Option<PlayedResult>,
+}
+
+impl BoardInstance {
+    /// The table-specific parts of a game
+    pub fn from_game(game: &Game) -> Self {
+        let result = GameResult::new(game.clone());
+        Self {
+            room: result.room,
+            players: SeatOrder::NESW
+                .seats()
+                .map(|seat| game.player(seat).map(str::to_string)),
+            auction: game.auction.clone(),
+            play: game.play.clone(),
+            result: result.result,
+        }
+    }
+
+    /// A game of this instance on `board`
+    pub fn to_game(&self, board: &Board) -> Game {
+        let mut game = Game::new(board.clone());
+        let mut push = |tag, value: String| game.tags.push(TagPair { tag, value });
+        if let Some(room) = &self.room {
+            push(PbnTag::Room, room.clone());
+        }
+        if let Some(result) = &self.result {
+            push(PbnTag::Declarer, result.declarer.to_char().to_string());
+            push(PbnTag::Contract, format_contract(&result.contract));
+            push(PbnTag::Result, result.tricks.to_string());
+        }
+        if let Some(play) = &self.play {
+            push(PbnTag::Play, play.leader.to_char().to_string());
+        }
+        for (seat, name) in SeatOrder::NESW.into_iter().zip(&self.players) {
+            if let Some(name) = name {
+                game.set_player(seat, name);
+            }
+        }
+        game.contract = self.result.map(|result| result.contract);
+        game.auction = self.auction.clone();
+        game.play = self.play.clone();
+        game
+    }
+}
+
+/// A deal with every game played on it
+#[derive(Debug, Clone)]
+pub struct DealRecord {
+    /// The board of the first game
+    pub board: Board,
+    /// The games, in file order
+    pub results: Vec<GameResult>,
+}
+
+impl DealRecord {
+    /// A deal not yet played
+    pub fn new(board: Board) -> Self {
+        Self {
+            board,
+            results: Vec::new(),
+        }
+    }
+
+    /// Add a table's play of the deal
+    pub fn add_instance(&mut self, instance: &BoardInstance) {
+        self.results
+            .push(GameResult::new(instance.to_game(&self.board)));
+    }
+
+    /// The table-specific parts of each game, in order
+    pub fn instances(&self) -> Vec<BoardInstance> {
+        self.results
+            .iter()
+            .map(|result| BoardInstance::from_game(&result.game))
+            .collect()
+    }
+}
+
+/// Group games by board number and deal, keeping the order in which each
+/// deal first appears
+pub fn group_games(games: Vec<Game>) -> Vec<DealRecord> {
+    let mut records: Vec<DealRecord> = Vec::new();
+    for game in games {
+        let existing = records.iter_mut().find(|record| {
+            record.board.number == game.board.number
+                && record.board.deal.to_pbn(Direction::North)
+                    == game.board.deal.to_pbn(Direction::North)
+        });
+        match existing {
+            Some(record) => record.results.push(GameResult::new(game)),
+            None => records.push(DealRecord {
+                board: game.board.clone(),
+                results: vec![GameResult::new(game)],
+            }),
+        }
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pbn::read_pbn_games;
+
+    #[test]
+    fn test_group_games() {
+        let content = r#"
+[Board "1"]
+[Room "Open"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+[Contract "4S"]
+[Declarer "E"]
+[Result "10"]
+
+[Board "2"]
+[Deal "N:AKQ.AKQ.AKQ.AKQJ T98.T98.T98.T987 765.765.765.654 J432.J432.J432.32"]
+
+[Board "1"]
+[Room "Closed"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+[Contract "Pass"]
+"#;
+        let records = group_games(read_pbn_games(content).unwrap());
+        assert_eq!(records.len(), 2);
+        assert_eq!(records[0].results.len(), 2);
+        assert_eq!(records[0].results[0].room.as_deref(), Some("Open"));
+        assert_eq!(records[0].results[0].result.map(|r| r.tricks), Some(10));
+        assert_eq!(records[0].results[1].room.as_deref(), Some("Closed"));
+        assert_eq!(records[0].results[1].result, None);
+        assert_eq!(records[1].results.len(), 1);
+    }
+
+    #[test]
+    fn test_board_instances() {
+        let games = read_pbn_games(crate::fixtures::SAMPLE_PBN).unwrap();
+        let instance = BoardInstance::from_game(&games[0]);
+        assert_eq!(instance.players[0].as_deref(), Some("Nguyen"));
+        assert_eq!(instance.result.map(|r| r.tricks), Some(9));
+
+        let mut record = DealRecord::new(games[0].board.clone());
+        record.add_instance(&instance);
+        record.add_instance(&BoardInstance {
+            room: Some("Closed".to_string()),
+            ..BoardInstance::default()
+        });
+        let instances = record.instances();
+        assert_eq!(instances.len(), 2);
+        assert_eq!(instances[0].result, instance.result);
+        assert_eq!(instances[0].auction, instance.auction);
+        assert_eq!(instances[0].play, instance.play);
+        assert_eq!(instances[1].room.as_deref(), Some("Closed"));
+        assert_eq!(record.results[1].game.board.number, Some(1));
+    }
+}
@@ -0,0 +1,244 @@
+//! Game index for PBN files.
+
+use super::stream::{CarriedState, PbnReader};
+use crate::cancel::CancelToken;
+use crate::error::{GameSpan, ParseError, Result};
+use crate::fingerprint::Fingerprint;
+use bridge_types::{Board, Deal};
+
+/// Location of one game in a PBN file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IndexEntry {
+    /// Byte offset of the game's first line
+    pub offset: usize,
+    /// Lines occupied by the game
+    pub span: GameSpan,
+    /// The game's board number, if any
+    pub board_number: Option<u32>,
+}
+
+/// An index of the games in a PBN file.
+///
+/// Built once per file, it allows jumping to a game without re-reading the
+/// games before it. With a fingerprint index, deals can also be looked up
+/// in O(log n).
+///
+/// The index keeps, for each game, what the reader carried into it from
+/// the games before: the `% PBN` and `% EXPORT` directives and the values
+/// inherited with `#` and `##`. A game read through the index is read as
+/// it is when the whole file is.
+#[derive(Debug, Clone, Default)]
+pub struct PbnIndex {
+    entries: Vec<IndexEntry>,
+    /// For each entry, where reading resumes after the previous game (byte
+    /// offset and number of lines before it) and the reader state there
+    resume: Vec<(usize, usize, CarriedState)>,
+    /// (fingerprint, entry index), sorted
+    fingerprints: Option<Vec<(Fingerprint, usize)>>,
+}
+
+impl PbnIndex {
+    /// Index the games of `content`
+    pub fn build(content: &str) -> Result<Self> {
+        Self::build_inner(content, false, None)
+    }
+
+    /// Index the games of `content`, including a fingerprint index for
+    /// [`find_deal`](Self::find_deal)
+    pub fn build_with_fingerprints(content: &str) -> Result<Self> {
+        Self::build_inner(content, true, None)
+    }
+
+    /// Index the games of `content`, counting each indexed game on `cancel`
+    /// and stopping with [`ParseError::Cancelled`] when it is cancelled
+    pub fn build_with_cancel(
+        content: &str,
+        with_fingerprints: bool,
+        cancel: &CancelToken,
+    ) -> Result<Self> {
+        Self::build_inner(content, with_fingerprints, Some(cancel))
+    }
+
+    fn build_inner(
+        content: &str,
+        with_fingerprints: bool,
+        cancel: Option<&CancelToken>,
+    ) -> Result<Self> {
+        let line_starts: Vec<usize> = std::iter::once(0)
+            .chain(content.match_indices('\n').map(|(i, _)| i + 1))
+            .collect();
+
+        let mut index = PbnIndex::default();
+        let mut fingerprints = Vec::new();
+        let mut reader = PbnReader::new(content.as_bytes());
+        let mut resume = (0, 0, reader.carried_state());
+        while let Some(game) = reader.next_game() {
+            if cancel.is_some_and(CancelToken::is_cancelled) {
+                return Err(ParseError::Cancelled);
+            }
+            let game = game?;
+            let entry = index.entries.len();
+            index.entries.push(IndexEntry {
+                offset: line_starts[game.span.start_line - 1],
+                span: game.span,
+                board_number: game.board.number,
+            });
+            let end_line = game.span.end_line;
+            let next = (
+                line_starts.get(end_line).copied().unwrap_or(content.len()),
+                end_line,
+                reader.carried_state(),
+            );
+            index.resume.push(std::mem::replace(&mut resume, next));
+            if with_fingerprints {
+                if let Some(fp) = Fingerprint::of(&game.board.deal) {
+                    fingerprints.push((fp, entry));
+                }
+            }
+            if let Some(cancel) = cancel {
+                cancel.record_processed();
+            }
+        }
+
+        if with_fingerprints {
+            fingerprints.sort();
+            index.fingerprints = Some(fingerprints);
+        }
+        Ok(index)
+    }
+
+    /// The indexed games, in file order
+    pub fn entries(&self) -> &[IndexEntry] {
+        &self.entries
+    }
+
+    /// Number of indexed games
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Whether the file has no games
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Whether the index can answer [`find_deal`](Self::find_deal)
+    pub fn has_fingerprints(&self) -> bool {
+        self.fingerprints.is_some()
+    }
+
+    /// The first game in the file with the same deal, by binary search of
+    /// the fingerprint index. Returns `None` if the deal isn't in the file,
+    /// is incomplete, or the index was built without fingerprints.
+    pub fn find_deal(&self, deal: &Deal) -> Option<&IndexEntry> {
+        let fingerprints = self.fingerprints.as_ref()?;
+        let fp = Fingerprint::of(deal)?;
+        let pos = fingerprints.partition_point(|&(f, _)| f < fp);
+        match fingerprints.get(pos) {
+            Some(&(f, entry)) if f == fp => self.entries.get(entry),
+            _ => None,
+        }
+    }
+
+    /// Read the board of an indexed game from the indexed `content`, with
+    /// the directives and inherited values that apply to it
+    pub fn board(&self, content: &str, entry: &IndexEntry) -> Result<Option<Board>> {
+        let position = self
+            .entries
+            .binary_search_by_key(&entry.offset, |e| e.offset)
+            .ok();
+        let (offset, lines_before, state) = match position.and_then(|i| self.resume.get(i)) {
+            Some((offset, lines_before, state)) => (*offset, *lines_before, state.clone()),
+            None => (
+                entry.offset,
+                entry.span.start_line - 1,
+                CarriedState::default(),
+            ),
+        };
+
+        let text = content.get(offset..).unwrap_or("");
+        let end = text
+            .split_inclusive('\n')
+            .take(entry.span.end_line.saturating_sub(lines_before))
+            .map(str::len)
+            .sum::<usize>();
+        let mut reader =
+            PbnReader::new(text[..end].as_bytes()).with_carried_state(state, lines_before);
+        Ok(reader.next_game().transpose()?.map(|game| game.board))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PBN: &str = r#"% PBN 2.1
+
+[Board "1"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+
+[Board "2"]
+[Deal "E:Q7.AKT9.JT3.JT96 J653.QJ8.A.AQ732 K92.654.K954.K84 AT84.732.Q8762.5"]
+
+[Board "3"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+"#;
+
+    #[test]
+    fn test_index_offsets() {
+        let index = PbnIndex::build(PBN).unwrap();
+        assert_eq!(index.len(), 3);
+        assert!(!index.has_fingerprints());
+
+        let entry = index.entries()[1];
+        assert!(PBN[entry.offset..].starts_with("[Board \"2\"]"));
+        let board = index.board(PBN, &entry).unwrap().unwrap();
+        assert_eq!(board.number, Some(2));
+    }
+
+    #[test]
+    fn test_board_keeps_inherited_values() {
+        let pbn = "% PBN 1.0\n\n\
+                   [Event \"##Club\"]\n[Site \"Hall\"]\n[Board \"1\"]\n[Vulnerable \"n-s\"]\n\n\
+                   % EXPORT\n\
+                   [Site \"#\"]\n[Board \"2\"]\n[Vulnerable \"e-w\"]\n";
+        let index = PbnIndex::build(pbn).unwrap();
+        let expected = crate::pbn::read_pbn(pbn).unwrap();
+        assert_eq!(index.len(), 2);
+
+        let board = index.board(pbn, &index.entries()[1]).unwrap().unwrap();
+        assert_eq!(board.number, Some(2));
+        assert_eq!(board.event.as_deref(), Some("Club"));
+        assert_eq!(board.site.as_deref(), Some("Hall"));
+        // Legacy vulnerability names are read as in the whole file
+        assert_eq!(board.vulnerable, expected[1].vulnerable);
+        assert_eq!(board.vulnerable, bridge_types::Vulnerability::EastWest);
+    }
+
+    #[test]
+    fn test_find_deal() {
+        let index = PbnIndex::build_with_fingerprints(PBN).unwrap();
+        let deal =
+            Deal::from_pbn("S:962.AJ7.KT82.J75 T5.Q9863.A943.KQ K843.T542.J6.863 AQJ7.K.Q75.AT942")
+                .unwrap();
+        assert_eq!(index.find_deal(&deal).unwrap().board_number, Some(1));
+
+        let other =
+            Deal::from_pbn("N:AKQJ.T98.765.432 T98.765.432.AKQJ 765.432.AKQJ.T98 432.AKQJ.T98.765")
+                .unwrap();
+        assert!(index.find_deal(&other).is_none());
+    }
+
+    #[test]
+    fn test_build_with_cancel() {
+        let cancel = CancelToken::new();
+        let index = PbnIndex::build_with_cancel(PBN, false, &cancel).unwrap();
+        assert_eq!(cancel.processed(), index.len());
+
+        cancel.cancel();
+        assert!(matches!(
+            PbnIndex::build_with_cancel(PBN, false, &cancel),
+            Err(ParseError::Cancelled)
+        ));
+    }
+}
@@ -0,0 +1,315 @@
+//! Typed values of the Scoring tag and of the supplemental tags identifying
+//! a game within a match or event.
+
+use super::reader::TagPair;
+use super::tag::PbnTag;
+use std::fmt;
+
+/// The playing mode of a game (Mode tag)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PlayingMode {
+    /// Electronic mail (`EM`)
+    Email,
+    /// Internet Bridge Server (`IBS`)
+    Ibs,
+    /// OK Bridge (`OKB`)
+    Okb,
+    /// Normal table (`TABLE`)
+    Table,
+    /// General telecommunication (`TC`)
+    Telecom,
+    /// Any other mode, as written
+    Other(String),
+}
+
+impl PlayingMode {
+    /// Parse a Mode tag value
+    pub fn from_pbn(value: &str) -> Self {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "EM" => PlayingMode::Email,
+            "IBS" => PlayingMode::Ibs,
+            "OKB" => PlayingMode::Okb,
+            "TABLE" => PlayingMode::Table,
+            "TC" => PlayingMode::Telecom,
+            _ => PlayingMode::Other(value.trim().to_string()),
+        }
+    }
+
+    /// The Mode tag value
+    pub fn to_pbn(&self) -> &str {
+        match self {
+            PlayingMode::Email => "EM",
+            PlayingMode::Ibs => "IBS",
+            PlayingMode::Okb => "OKB",
+            PlayingMode::Table => "TABLE",
+            PlayingMode::Telecom => "TC",
+            PlayingMode::Other(mode) => mode,
+        }
+    }
+}
+
+/// A basic scoring system named in the Scoring tag
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum ScoringMethod {
+    /// MatchPoints (`MP` or `MatchPoints`)
+    MatchPoints,
+    /// IMPs (`IMP`)
+    Imp,
+    /// Board-a-match (`BAM`)
+    BoardAMatch,
+    /// Cavendish (`Cavendish`)
+    Cavendish,
+    /// Chicago (`Chicago`)
+    Chicago,
+    /// Rubber bridge (`Rubber`)
+    Rubber,
+    /// Scores from the InstantScoreTable (`Instant`)
+    Instant,
+    /// Any other system, as written
+    Other(String),
+}
+
+impl ScoringMethod {
+    /// Parse a basic scoring system name
+    pub fn from_pbn(value: &str) -> Self {
+        match value.trim().to_ascii_uppercase().as_str() {
+            "MP" | "MATCHPOINTS" => ScoringMethod::MatchPoints,
+            "IMP" => ScoringMethod::Imp,
+            "BAM" => ScoringMethod::BoardAMatch,
+            "CAVENDISH" => ScoringMethod::Cavendish,
+            "CHICAGO" => ScoringMethod::Chicago,
+            "RUBBER" => ScoringMethod::Rubber,
+            "INSTANT" => ScoringMethod::Instant,
+            _ => ScoringMethod::Other(value.trim().to_string()),
+        }
+    }
+
+    /// The system name as written in the Scoring tag
+    pub fn to_pbn(&self) -> &str {
+        match self {
+            ScoringMethod::MatchPoints => "MP",
+            ScoringMethod::Imp => "IMP",
+            ScoringMethod::BoardAMatch => "BAM",
+            ScoringMethod::Cavendish => "Cavendish",
+            ScoringMethod::Chicago => "Chicago",
+            ScoringMethod::Rubber => "Rubber",
+            ScoringMethod::Instant => "Instant",
+            ScoringMethod::Other(method) => method,
+        }
+    }
+}
+
+/// A Scoring tag value: a basic system followed by modifiers, separated by
+/// semicolons (e.g. `IMP;Butler`)
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Scoring {
+    /// The basic scoring system
+    pub method: ScoringMethod,
+    /// Modifiers such as `Butler` or `OldMP`, as written
+    pub modifiers: Vec<String>,
+}
+
+impl Scoring {
+    /// Parse a Scoring tag value; `None` if it is empty
+    pub fn from_pbn(value: &str) -> Option<Self> {
+        let mut fields = value.split(';').map(str::trim).filter(|f| !f.is_empty());
+        let method = ScoringMethod::from_pbn(fields.next()?);
+        Some(Self {
+            method,
+            modifiers: fields.map(str::to_string).collect(),
+        })
+    }
+
+    /// The Scoring tag value
+    pub fn to_pbn(&self) -> String {
+        std::iter::once(self.method.to_pbn())
+            .chain(self.modifiers.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+}
+
+/// A time of day from the Time and UTCTime tags (`HH:MM:SS`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct TimeOfDay {
+    /// Hour, 0-23
+    pub hour: u8,
+    /// Minute, 0-59
+    pub minute: u8,
+    /// Second, 0-59
+    pub second: u8,
+}
+
+impl TimeOfDay {
+    /// Parse `HH:MM:SS`
+    pub fn from_pbn(value: &str) -> Option<Self> {
+        let mut fields = value.trim().split(':').map(|f| f.parse::<u8>().ok());
+        let time = Self {
+            hour: fields.next()??,
+            minute: fields.next()??,
+            second: fields.next()??,
+        };
+        let valid =
+            fields.next().is_none() && time.hour < 24 && time.minute < 60 && time.second < 60;
+        valid.then_some(time)
+    }
+}
+
+impl fmt::Display for TimeOfDay {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:02}:{:02}:{:02}", self.hour, self.minute, self.second)
+    }
+}
+
+/// Supplemental tags identifying a game within a match or event.
+///
+/// Empty tag values and unparseable times read as `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GameInfo {
+    /// Annotator of the game
+    pub annotator: Option<String>,
+    /// Playing mode
+    pub mode: Option<PlayingMode>,
+    /// Why the game ended (e.g. `normal`, `time forfeit`)
+    pub termination: Option<String>,
+    /// Local starting time
+    pub time: Option<TimeOfDay>,
+    /// UTC starting date, formatted like the Date tag
+    pub utc_date: Option<String>,
+    /// UTC starting time
+    pub utc_time: Option<TimeOfDay>,
+    /// Playing round (e.g. `2`, `3.1`)
+    pub round: Option<String>,
+    /// Stage of a multistage event (e.g. `Semifinal`)
+    pub stage: Option<String>,
+    /// Playing section (e.g. `Open`)
+    pub section: Option<String>,
+    /// Table, normally a number
+    pub table: Option<String>,
+    /// Home team
+    pub home_team: Option<String>,
+    /// Visiting team
+    pub visit_team: Option<String>,
+}
+
+impl GameInfo {
+    /// The tags covered by `GameInfo`
+    pub const TAGS: [PbnTag; 12] = [
+        PbnTag::Annotator,
+        PbnTag::Mode,
+        PbnTag::Termination,
+        PbnTag::Time,
+        PbnTag::UTCDate,
+        PbnTag::UTCTime,
+        PbnTag::Round,
+        PbnTag::Stage,
+        PbnTag::Section,
+        PbnTag::Table,
+        PbnTag::HomeTeam,
+        PbnTag::VisitTeam,
+    ];
+
+    /// Read the supplemental tags from a game's tag pairs; the first
+    /// occurrence of each tag is used
+    pub fn from_tags(tags: &[TagPair]) -> Self {
+        let value = |tag: PbnTag| {
+            tags.iter()
+                .find(|pair| pair.tag == tag)
+                .map(|pair| pair.value.trim())
+                .filter(|value| !value.is_empty())
+        };
+        let text = |tag| value(tag).map(str::to_string);
+        Self {
+            annotator: text(PbnTag::Annotator),
+            mode: value(PbnTag::Mode).map(PlayingMode::from_pbn),
+            termination: text(PbnTag::Termination),
+            time: value(PbnTag::Time).and_then(TimeOfDay::from_pbn),
+            utc_date: text(PbnTag::UTCDate),
+            utc_time: value(PbnTag::UTCTime).and_then(TimeOfDay::from_pbn),
+            round: text(PbnTag::Round),
+            stage: text(PbnTag::Stage),
+            section: text(PbnTag::Section),
+            table: text(PbnTag::Table),
+            home_team: text(PbnTag::HomeTeam),
+            visit_team: text(PbnTag::VisitTeam),
+        }
+    }
+
+    /// The tag pairs for the fields that are set, in [`TAGS`](Self::TAGS) order
+    pub fn to_tags(&self) -> Vec<TagPair> {
+        let values = [
+            self.annotator.clone(),
+            self.mode.as_ref().map(|mode| mode.to_pbn().to_string()),
+            self.termination.clone(),
+            self.time.map(|time| time.to_string()),
+            self.utc_date.clone(),
+            self.utc_time.map(|time| time.to_string()),
+            self.round.clone(),
+            self.stage.clone(),
+            self.section.clone(),
+            self.table.clone(),
+            self.home_team.clone(),
+            self.visit_team.clone(),
+        ];
+        Self::TAGS
+            .into_iter()
+            .zip(values)
+            .filter_map(|(tag, value)| Some(TagPair { tag, value: value? }))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_scoring() {
+        let scoring = Scoring::from_pbn("MatchPoints; MP2").unwrap();
+        assert_eq!(scoring.method, ScoringMethod::MatchPoints);
+        assert_eq!(scoring.modifiers, ["MP2"]);
+        assert_eq!(scoring.to_pbn(), "MP;MP2");
+
+        let scoring = Scoring::from_pbn("Patton").unwrap();
+        assert_eq!(scoring.method, ScoringMethod::Other("Patton".to_string()));
+        assert_eq!(Scoring::from_pbn(""), None);
+    }
+
+    #[test]
+    fn test_game_info_round_trip() {
+        let tags: Vec<TagPair> = [
+            (PbnTag::Mode, "table"),
+            (PbnTag::Time, "14:05:00"),
+            (PbnTag::UTCTime, "25:00:00"),
+            (PbnTag::Round, "3.1"),
+            (PbnTag::Table, "7"),
+            (PbnTag::HomeTeam, "Lions"),
+            (PbnTag::VisitTeam, ""),
+        ]
+        .into_iter()
+        .map(|(tag, value)| TagPair {
+            tag,
+            value: value.to_string(),
+        })
+        .collect();
+
+        let info = GameInfo::from_tags(&tags);
+        assert_eq!(info.mode, Some(PlayingMode::Table));
+        assert_eq!(
+            info.time,
+            Some(TimeOfDay {
+                hour: 14,
+                minute: 5,
+                second: 0
+            })
+        );
+        assert_eq!(info.utc_time, None);
+        assert_eq!(info.home_team.as_deref(), Some("Lions"));
+        assert_eq!(info.visit_team, None);
+
+        let written = info.to_tags();
+        assert_eq!(written.len(), 5);
+        assert_eq!(written[0].value, "TABLE");
+        assert_eq!(GameInfo::from_tags(&written), info);
+    }
+}
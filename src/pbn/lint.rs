@@ -0,0 +1,287 @@
+//! Conformance checks for PBN content.
+//!
+//! [`lint`] reads every game leniently and reports what a strict or
+//! conformance reader would reject, plus values that are valid on their
+//! own but inconsistent with each other, without stopping at the first
+//! problem.
+
+use super::stream::{is_valid_tag_name, PbnReader};
+use super::tag::PbnTag;
+use super::writer::MAX_LINE_LENGTH;
+use super::Game;
+use crate::cards::SUITS;
+use crate::diagnostics::WarningKind;
+use crate::scoring::{DeclarerTag, OptimumScoreTag, ParContractTag, ResultTag, ScoreTag};
+use crate::seat::{standard_dealer, standard_vulnerability};
+use bridge_types::{Card, Rank};
+use std::fmt;
+
+/// The kind of problem found by [`lint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LintKind {
+    /// A line that should be a tag pair is malformed
+    MalformedTag,
+    /// A tag name breaks the export format rules
+    InvalidTagName,
+    /// A known tag has an invalid value
+    InvalidValue,
+    /// A mandatory tag is missing
+    MissingTag,
+    /// A card appears more than once in the Deal tag
+    DuplicateCard,
+    /// The dealer isn't the standard one for the board number
+    DealerMismatch,
+    /// The vulnerability isn't the standard one for the board number
+    VulnerabilityMismatch,
+    /// A line is longer than [`MAX_LINE_LENGTH`] characters
+    LineTooLong,
+}
+
+/// A problem found by [`lint`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintIssue {
+    /// Input line (1-based): the offending tag's line, or the first line of
+    /// the game for problems with the game as a whole
+    pub line: usize,
+    /// What kind of problem it is
+    pub kind: LintKind,
+    /// Description of the problem
+    pub message: String,
+}
+
+impl fmt::Display for LintIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Check PBN content against the standard, reporting every problem found
+/// in file order
+pub fn lint(content: &str) -> Vec<LintIssue> {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut issues = Vec::new();
+    let mut reader = PbnReader::new(content.as_bytes());
+    while let Some(game) = reader.next_game() {
+        for warning in reader.diagnostics().warnings() {
+            issues.push(LintIssue {
+                line: warning.line.unwrap_or(0),
+                kind: match warning.kind {
+                    WarningKind::SkippedLine => LintKind::MalformedTag,
+                    _ => LintKind::InvalidValue,
+                },
+                message: warning.message.clone(),
+            });
+        }
+        // The lenient reader only fails on I/O errors
+        let Ok(game) = game else {
+            break;
+        };
+        lint_game(&game, &lines, &mut issues);
+    }
+    issues
+}
+
+/// Validation of a tag value the reader doesn't parse
+type ValueCheck = fn(&str) -> Result<(), String>;
+
+fn lint_game(game: &Game, lines: &[&str], issues: &mut Vec<LintIssue>) {
+    let start = game.span.start_line;
+    let tag_line = |tag: &PbnTag| {
+        let prefix = format!("[{} ", tag.name());
+        (start..=game.span.end_line)
+            .find(|&n| {
+                lines
+                    .get(n - 1)
+                    .is_some_and(|l| l.trim_start().starts_with(&prefix))
+            })
+            .unwrap_or(start)
+    };
+    let mut push = |line, kind, message: String| {
+        issues.push(LintIssue {
+            line,
+            kind,
+            message,
+        })
+    };
+
+    for n in start..=game.span.end_line {
+        let length = lines.get(n - 1).map_or(0, |l| l.chars().count());
+        if length > MAX_LINE_LENGTH {
+            push(
+                n,
+                LintKind::LineTooLong,
+                format!(
+                    "line is {} characters long, over {}",
+                    length, MAX_LINE_LENGTH
+                ),
+            );
+        }
+    }
+
+    for pair in &game.tags {
+        if !is_valid_tag_name(pair.tag.name()) {
+            push(
+                tag_line(&pair.tag),
+                LintKind::InvalidTagName,
+                format!("invalid tag name: {}", pair.tag.name()),
+            );
+        }
+    }
+
+    // Values the reader keeps as text without checking them
+    let checks: [(PbnTag, ValueCheck); 5] = [
+        (PbnTag::Declarer, |v| DeclarerTag::from_pbn(v).map(drop)),
+        (PbnTag::Result, |v| ResultTag::from_pbn(v).map(drop)),
+        (PbnTag::Score, |v| ScoreTag::from_pbn(v).map(drop)),
+        (PbnTag::OptimumScore, |v| {
+            OptimumScoreTag::from_pbn(v).map(drop)
+        }),
+        (PbnTag::ParContract, |v| {
+            ParContractTag::from_pbn(v).map(drop)
+        }),
+    ];
+    for (tag, check) in checks {
+        if let Some(Err(message)) = game.tag(&tag).map(check) {
+            push(tag_line(&tag), LintKind::InvalidValue, message);
+        }
+    }
+
+    let missing: Vec<&str> = PbnTag::MANDATORY
+        .iter()
+        .filter(|tag| game.tag(tag).is_none())
+        .map(PbnTag::name)
+        .collect();
+    if !missing.is_empty() {
+        push(
+            start,
+            LintKind::MissingTag,
+            format!("missing mandatory tags: {}", missing.join(", ")),
+        );
+    }
+
+    if let Some(value) = game.tag(&PbnTag::Deal) {
+        let duplicates = duplicate_cards(value);
+        if !duplicates.is_empty() {
+            let cards: Vec<String> = duplicates
+                .iter()
+                .map(|card| format!("{}{}", card.suit.to_char(), card.rank.to_char()))
+                .collect();
+            push(
+                tag_line(&PbnTag::Deal),
+                LintKind::DuplicateCard,
+                format!("cards dealt twice: {}", cards.join(", ")),
+            );
+        }
+    }
+
+    let board = &game.board;
+    let Some(number) = board.number else {
+        return;
+    };
+    if let Some(dealer) = board.dealer {
+        let expected = standard_dealer(number);
+        if dealer != expected {
+            push(
+                tag_line(&PbnTag::Dealer),
+                LintKind::DealerMismatch,
+                format!(
+                    "board {} is dealt by {}, not {}",
+                    number,
+                    expected.to_char(),
+                    dealer.to_char()
+                ),
+            );
+        }
+    }
+    if game.tag(&PbnTag::Vulnerable).is_some() {
+        let expected = standard_vulnerability(number);
+        if board.vulnerable != expected {
+            push(
+                tag_line(&PbnTag::Vulnerable),
+                LintKind::VulnerabilityMismatch,
+                format!(
+                    "board {} has vulnerability {}, not {}",
+                    number,
+                    expected.to_pbn(),
+                    board.vulnerable.to_pbn()
+                ),
+            );
+        }
+    }
+}
+
+/// Cards given more than once in a Deal tag value
+fn duplicate_cards(value: &str) -> Vec<Card> {
+    let hands = value.split_once(':').map_or(value, |(_, hands)| hands);
+    let mut seen = Vec::new();
+    let mut duplicates = Vec::new();
+    for hand in hands.split_whitespace() {
+        for (ranks, suit) in hand.split('.').zip(SUITS) {
+            for rank in ranks.chars().filter_map(Rank::from_char) {
+                let card = Card::new(suit, rank);
+                if seen.contains(&card) {
+                    if !duplicates.contains(&card) {
+                        duplicates.push(card);
+                    }
+                } else {
+                    seen.push(card);
+                }
+            }
+        }
+    }
+    duplicates
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lint() {
+        let pbn = r#"[Event "Club"]
+[Board "2"]
+[Dealer "N"]
+[Vulnerable "Both"]
+[Deal "N:AK843.T542.J6.86 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+[lower "x"]
+
+[Event "Club"]
+[Board "3"]
+[Dealer "S"]
+[Vulnerable "EW"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+[Result "many"]
+[Site "Hall"]
+[Date "2024.01.01"]
+[West "W"]
+[North "N"]
+[East "E"]
+[South "S"]
+[Scoring "IMP"]
+[Declarer "N"]
+[Contract "Pass"]
+"#;
+        let issues = lint(pbn);
+        let kinds: Vec<(usize, LintKind)> = issues.iter().map(|i| (i.line, i.kind)).collect();
+        assert_eq!(
+            kinds,
+            [
+                (6, LintKind::InvalidTagName),
+                (1, LintKind::MissingTag),
+                (5, LintKind::DuplicateCard),
+                (3, LintKind::DealerMismatch),
+                (4, LintKind::VulnerabilityMismatch),
+                (13, LintKind::InvalidValue),
+            ]
+        );
+        assert!(issues[2].message.contains("SA"));
+    }
+
+    #[test]
+    fn test_lint_line_length() {
+        let pbn = format!("[Board \"1\"]\n[Event \"{}\"]\n", "x".repeat(250));
+        let issues = lint(&pbn);
+        assert_eq!(issues[0].line, 2);
+        assert_eq!(issues[0].kind, LintKind::LineTooLong);
+    }
+}
@@ -3,8 +3,48 @@
 //! PBN is the standard format for storing bridge hands, results, and analysis.
 //! This module supports reading and writing PBN files with common tags.
 
+mod auction;
+mod composer;
+mod dd_table;
+mod deal;
+mod embedded;
+mod events;
+mod game;
+mod group;
+mod index;
+mod info;
+mod lint;
+mod play;
 mod reader;
+mod stream;
+mod table;
+mod tag;
 mod writer;
 
-pub use reader::{read_pbn, read_pbn_file, TagPair};
-pub use writer::{board_to_pbn, write_pbn, write_pbn_file};
+pub use auction::{AlertMark, Auction, AuctionCall, Call, NoteStyle};
+pub use composer::ComposerTags;
+pub use dd_table::DoubleDummyTable;
+pub use deal::DealTag;
+pub use embedded::{scan_embedded_lin, EmbeddedLin};
+pub use events::{PbnEvent, PbnEvents};
+pub use game::{Commentary, Game, NoteSection};
+pub use group::{group_games, BoardInstance, DealRecord, GameResult};
+pub use index::{IndexEntry, PbnIndex};
+pub use info::{GameInfo, PlayingMode, Scoring, ScoringMethod, TimeOfDay};
+pub use lint::{lint, LintIssue, LintKind};
+pub use play::{Play, PlayNag, PlayNote};
+pub(crate) use reader::is_unterminated_deal;
+pub use reader::{
+    read_pbn, read_pbn_file, read_pbn_file_with_encoding, read_pbn_games, read_pbn_recovering,
+    read_pbn_with_limits, TagPair,
+};
+pub use stream::PbnReader;
+pub use table::{PbnTable, Ranking, TableColumn, TableRow};
+pub use tag::PbnTag;
+pub(crate) use writer::write_header;
+pub use writer::{
+    board_to_pbn, board_to_pbn_with, check_mandatory_tags, check_tag_values, game_to_pbn_with,
+    write_pbn, write_pbn_file, write_pbn_games, write_pbn_games_with_report, write_pbn_to,
+    write_pbn_to_with, write_pbn_with, write_pbn_with_report, ExportProfile, MandatoryTags,
+    NoteSuits, PbnWriteOptions, TagOrder, MAX_LINE_LENGTH, PBN_VERSION,
+};
@@ -0,0 +1,310 @@
+//! PBN play sections.
+
+use super::auction::{is_suffix, parse_nag, suffix_nag};
+use crate::seat::rotate_seat;
+use bridge_types::{Card, Direction, Rank, Strain, Suit};
+
+/// A parsed play section.
+///
+/// Each trick lists its cards by column: column 0 is the player named in
+/// the Play tag (the opening leader), the others follow clockwise. Cards
+/// not played, or not recorded, are `None`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Play {
+    /// The opening leader (the Play tag value)
+    pub leader: Direction,
+    /// Tricks in order, cards by column
+    pub tricks: Vec<[Option<Card>; 4]>,
+    /// Note references (`=n=`) following a card
+    pub notes: Vec<PlayNote>,
+    /// Numeric annotation glyphs (`$n`) following a card; suffixes are
+    /// stored as their glyph (`!` is `$7`, `?` is `$8`, ...)
+    pub nags: Vec<PlayNag>,
+}
+
+/// A note referenced from the play section
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayNote {
+    /// Trick index
+    pub trick: usize,
+    /// Column of the annotated card
+    pub column: usize,
+    /// Note number
+    pub note: u32,
+}
+
+/// A numeric annotation glyph on a played card
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PlayNag {
+    /// Trick index
+    pub trick: usize,
+    /// Column of the annotated card
+    pub column: usize,
+    /// Glyph value
+    pub nag: u8,
+}
+
+impl Play {
+    /// Parse the tokens of a play section led by `leader`.
+    ///
+    /// `-` marks a card not played; suffixes and NAGs (`$n`) annotate the
+    /// card before them, and parsing stops at a `*` terminator.
+    pub fn parse(leader: Direction, section: &str) -> Result<Self, String> {
+        let mut tricks = Vec::new();
+        let mut trick: [Option<Card>; 4] = [None; 4];
+        let mut column = 0;
+        let mut notes = Vec::new();
+        let mut nags = Vec::new();
+
+        for token in section.split_whitespace() {
+            if token == "*" {
+                break;
+            }
+            // Annotations follow the card they annotate
+            let (previous_trick, previous_column) = match column {
+                0 => (tricks.len().saturating_sub(1), 3),
+                c => (tricks.len(), c - 1),
+            };
+            let mut annotate = |nag| {
+                nags.push(PlayNag {
+                    trick: previous_trick,
+                    column: previous_column,
+                    nag,
+                })
+            };
+            if let Some(nag) = parse_nag(token)? {
+                annotate(nag);
+                continue;
+            }
+            if is_suffix(token) {
+                annotate(card_suffix_nag(token)?);
+                continue;
+            }
+            if let Some(number) = token.strip_prefix('=').and_then(|t| t.strip_suffix('=')) {
+                let note = number
+                    .parse()
+                    .map_err(|_| format!("invalid note reference: {}", token))?;
+                notes.push(PlayNote {
+                    trick: previous_trick,
+                    column: previous_column,
+                    note,
+                });
+                continue;
+            }
+            if token != "-" {
+                let text = token.trim_end_matches(['!', '?']);
+                let card = parse_card(text).ok_or_else(|| format!("invalid card: {}", token))?;
+                trick[column] = Some(card);
+                if text.len() < token.len() {
+                    nags.push(PlayNag {
+                        trick: tricks.len(),
+                        column,
+                        nag: card_suffix_nag(&token[text.len()..])?,
+                    });
+                }
+            }
+            column += 1;
+            if column == 4 {
+                tricks.push(std::mem::take(&mut trick));
+                column = 0;
+            }
+        }
+        if column > 0 {
+            tricks.push(trick);
+        }
+
+        Ok(Play {
+            leader,
+            tricks,
+            notes,
+            nags,
+        })
+    }
+
+    /// The play from cards in the order played, starting with the opening
+    /// lead by `leader`; `trump` decides who wins each trick and leads to
+    /// the next. The last trick may be incomplete.
+    pub fn from_played(leader: Direction, trump: Strain, cards: &[Card]) -> Self {
+        let trump = match trump {
+            Strain::Spades => Some(Suit::Spades),
+            Strain::Hearts => Some(Suit::Hearts),
+            Strain::Diamonds => Some(Suit::Diamonds),
+            Strain::Clubs => Some(Suit::Clubs),
+            Strain::NoTrump => None,
+        };
+        let mut play = Play {
+            leader,
+            tricks: Vec::new(),
+            notes: Vec::new(),
+            nags: Vec::new(),
+        };
+        // Column of the player leading the current trick
+        let mut lead_column = 0;
+        for cards in cards.chunks(4) {
+            let mut trick = [None; 4];
+            for (i, &card) in cards.iter().enumerate() {
+                trick[(lead_column + i) % 4] = Some(card);
+            }
+            play.tricks.push(trick);
+            let winner = (0..cards.len()).max_by_key(|&i| {
+                let card = cards[i];
+                if Some(card.suit) == trump {
+                    (2, Some(card.rank))
+                } else if card.suit == cards[0].suit {
+                    (1, Some(card.rank))
+                } else {
+                    (0, None)
+                }
+            });
+            lead_column = (lead_column + winner.unwrap_or(0)) % 4;
+        }
+        play
+    }
+
+    /// The player whose cards are in `column`
+    pub fn seat(&self, column: usize) -> Direction {
+        rotate_seat(self.leader, column)
+    }
+
+    /// The card `seat` played to trick `index`, if recorded
+    pub fn card(&self, index: usize, seat: Direction) -> Option<Card> {
+        let trick = self.tricks.get(index)?;
+        (0..4)
+            .find(|&c| self.seat(c) == seat)
+            .and_then(|c| trick[c])
+    }
+
+    /// The section text: one trick per line, `-` for missing cards, and a
+    /// `*` line ending play that stops before the last trick
+    pub fn to_pbn(&self) -> String {
+        let mut out = String::new();
+        for (t, trick) in self.tricks.iter().enumerate() {
+            let cells: Vec<String> = trick
+                .iter()
+                .enumerate()
+                .map(|(column, card)| {
+                    let mut cell = match card {
+                        Some(card) => format!("{}{}", card.suit.to_char(), card.rank.to_char()),
+                        None => "-".to_string(),
+                    };
+                    for note in self
+                        .notes
+                        .iter()
+                        .filter(|n| n.trick == t && n.column == column)
+                    {
+                        cell.push_str(&format!(" ={}=", note.note));
+                    }
+                    let mut nags: Vec<u8> = self
+                        .nags
+                        .iter()
+                        .filter(|n| n.trick == t && n.column == column)
+                        .map(|n| n.nag)
+                        .collect();
+                    nags.sort_unstable();
+                    for nag in nags {
+                        cell.push_str(&format!(" ${}", nag));
+                    }
+                    cell
+                })
+                .collect();
+            out.push_str(&cells.join(" "));
+            out.push('\n');
+        }
+        if !self.is_complete() {
+            out.push_str("*\n");
+        }
+        out
+    }
+
+    /// Whether all 13 tricks are recorded in full
+    pub fn is_complete(&self) -> bool {
+        self.tricks.len() == 13 && self.tricks.iter().flatten().all(Option::is_some)
+    }
+
+    /// All recorded cards, trick by trick in column order
+    pub fn cards(&self) -> Vec<Card> {
+        self.tricks.iter().flatten().flatten().copied().collect()
+    }
+}
+
+/// The card NAG ($7-$12) of a suffix
+fn card_suffix_nag(suffix: &str) -> Result<u8, String> {
+    suffix_nag(suffix)
+        .map(|nag| nag + 6)
+        .ok_or_else(|| format!("invalid suffix: {}", suffix))
+}
+
+/// Parse a card like `SA` or `HT`
+fn parse_card(s: &str) -> Option<Card> {
+    let mut chars = s.chars();
+    let suit = Suit::from_char(chars.next()?.to_ascii_uppercase())?;
+    let rank = Rank::from_char(chars.next()?.to_ascii_uppercase())?;
+    if chars.next().is_some() {
+        return None;
+    }
+    Some(Card::new(suit, rank))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_play() {
+        let play = Play::parse(
+            Direction::West,
+            "D2 DA D6 D5\nS3 S2 SQ SA\nH4 - HK HA\nC2 *",
+        )
+        .unwrap();
+        assert_eq!(play.tricks.len(), 4);
+        assert_eq!(play.seat(1), Direction::North);
+        assert_eq!(
+            play.card(0, Direction::North),
+            Some(Card::new(Suit::Diamonds, Rank::Ace))
+        );
+        assert_eq!(play.card(2, Direction::North), None);
+        assert_eq!(
+            play.tricks[3],
+            [Some(Card::new(Suit::Clubs, Rank::Two)), None, None, None]
+        );
+        assert_eq!(play.cards().len(), 12);
+    }
+
+    #[test]
+    fn test_play_notes_round_trip() {
+        let play = Play::parse(Direction::South, "HK =1= H2 H3 HA\nS2 =2= - - -").unwrap();
+        assert_eq!(
+            play.notes,
+            vec![
+                PlayNote {
+                    trick: 0,
+                    column: 0,
+                    note: 1
+                },
+                PlayNote {
+                    trick: 1,
+                    column: 0,
+                    note: 2
+                }
+            ]
+        );
+        assert_eq!(Play::parse(Direction::South, &play.to_pbn()).unwrap(), play);
+    }
+
+    #[test]
+    fn test_play_nags() {
+        let play = Play::parse(Direction::South, "HK! $20 H2 H3?? HA\nS2 ?! - - -").unwrap();
+        let nags: Vec<_> = play
+            .nags
+            .iter()
+            .map(|n| (n.trick, n.column, n.nag))
+            .collect();
+        assert_eq!(nags, [(0, 0, 7), (0, 0, 20), (0, 2, 10), (1, 0, 12)]);
+        assert_eq!(Play::parse(Direction::South, &play.to_pbn()).unwrap(), play);
+    }
+
+    #[test]
+    fn test_invalid_card() {
+        assert!(Play::parse(Direction::North, "SA ZZ").is_err());
+    }
+}
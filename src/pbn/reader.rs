@@ -1,17 +1,32 @@
 //! PBN file reader.
 
-use crate::error::Result;
-use bridge_types::{Board, Deal, Direction, Vulnerability};
+use super::dd_table::DoubleDummyTable;
+use super::deal::DealTag;
+use super::game::Game;
+use super::stream::PbnReader;
+use super::tag::PbnTag;
+use crate::encoding::TextEncoding;
+use crate::error::{BoardError, ParseError, Result};
+use crate::limits::Limits;
+use crate::seat::parse_seat;
+use bridge_types::{Board, Vulnerability};
 
 /// A parsed PBN tag pair
 #[derive(Debug, Clone)]
 pub struct TagPair {
-    pub name: String,
+    pub tag: PbnTag,
     pub value: String,
 }
 
+impl TagPair {
+    /// The tag name as written in the file
+    pub fn name(&self) -> &str {
+        self.tag.name()
+    }
+}
+
 /// Parse a tag pair from a line: [TagName "value"]
-fn parse_tag_pair(line: &str) -> Option<TagPair> {
+pub(crate) fn parse_tag_pair(line: &str) -> Option<TagPair> {
     let line = line.trim();
     if !line.starts_with('[') || !line.ends_with(']') {
         return None;
@@ -21,146 +36,209 @@ fn parse_tag_pair(line: &str) -> Option<TagPair> {
 
     // Find the space between tag name and quoted value
     let space_pos = inner.find(' ')?;
-    let name = inner[..space_pos].trim().to_string();
+    let tag = PbnTag::from_name(inner[..space_pos].trim());
     let rest = inner[space_pos..].trim();
 
     // Extract quoted value
-    if !rest.starts_with('"') || !rest.ends_with('"') {
+    if rest.len() < 2 || !rest.starts_with('"') || !rest.ends_with('"') {
         return None;
     }
-    let value = rest[1..rest.len() - 1].to_string();
+    let value = unescape_tag_value(&rest[1..rest.len() - 1]);
 
-    Some(TagPair { name, value })
+    Some(TagPair { tag, value })
 }
 
-/// Read boards from PBN content
-pub fn read_pbn(content: &str) -> Result<Vec<Board>> {
-    let mut boards = Vec::new();
-    let mut current_board = Board::new();
-    let mut has_content = false;
-    let mut in_commentary = false;
-
-    for line in content.lines() {
-        let line = line.trim();
-
-        // Track multi-line commentary blocks { ... }
-        if in_commentary {
-            if line.contains('}') {
-                in_commentary = false;
-            }
-            continue;
-        }
-
-        // Check for start of commentary
-        if line.starts_with('{') {
-            if !line.contains('}') {
-                in_commentary = true;
-            }
-            continue;
-        }
+/// Whether `line` opens a Deal tag without closing it, as when a mail
+/// client or editor wraps a long Deal tag onto the next line
+pub(crate) fn is_unterminated_deal(line: &str) -> bool {
+    line.starts_with("[Deal \"") && !line.ends_with(']')
+}
 
-        // Empty line may signal end of board
-        if line.is_empty() {
-            if has_content {
-                boards.push(current_board);
-                current_board = Board::new();
-                has_content = false;
+/// Undo the `\"` and `\\` escapes of a tag value; other backslashes (as in
+/// table headers like `Rank\2R`) are kept
+fn unescape_tag_value(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match (c, chars.peek()) {
+            ('\\', Some(&next)) if next == '"' || next == '\\' => {
+                out.push(next);
+                chars.next();
             }
-            continue;
+            _ => out.push(c),
         }
+    }
+    out
+}
 
-        // Skip line comments and directives
-        if line.starts_with(';') || line.starts_with('%') {
-            continue;
-        }
+/// Read boards from PBN content
+pub fn read_pbn(content: &str) -> Result<Vec<Board>> {
+    PbnReader::new(content.as_bytes()).collect()
+}
 
-        // Parse tag pair
-        if line.starts_with('[') {
-            if let Some(tag) = parse_tag_pair(line) {
-                has_content = true;
-                apply_tag_to_board(&mut current_board, &tag);
-            }
-        }
+/// Read boards from PBN content, failing with
+/// [`ParseError::LimitExceeded`](crate::ParseError::LimitExceeded) if the
+/// content or the number of boards is over the limits
+pub fn read_pbn_with_limits(content: &str, limits: &Limits) -> Result<Vec<Board>> {
+    limits.check_bytes(content)?;
+    let mut boards = Vec::new();
+    for board in PbnReader::new(content.as_bytes()) {
+        boards.push(board?);
+        limits.check_boards(boards.len())?;
     }
+    Ok(boards)
+}
 
-    // Don't forget the last board
-    if has_content {
-        boards.push(current_board);
+/// Read boards from PBN content, skipping the games that can't be read.
+///
+/// Each game is read as by a strict [`PbnReader`]: a game with a malformed
+/// tag pair or an invalid value for a known tag is left out and reported,
+/// and reading continues with the next game.
+pub fn read_pbn_recovering(content: &str) -> (Vec<Board>, Vec<BoardError>) {
+    let mut reader = PbnReader::new(content.as_bytes()).with_strict(true);
+    let mut boards = Vec::new();
+    let mut errors = Vec::new();
+    while let Some(game) = reader.next_game() {
+        match game {
+            Ok(game) => boards.push(game.board),
+            Err(ParseError::PbnGame {
+                span,
+                message,
+                location,
+            }) => errors.push(BoardError {
+                span,
+                message,
+                location,
+            }),
+            // Reading from a string doesn't fail otherwise
+            Err(_) => break,
+        }
     }
+    (boards, errors)
+}
 
-    Ok(boards)
+/// Read games, with all tags and commentary, from PBN content
+pub fn read_pbn_games(content: &str) -> Result<Vec<Game>> {
+    let mut reader = PbnReader::new(content.as_bytes());
+    std::iter::from_fn(|| reader.next_game()).collect()
 }
 
-/// Apply a parsed tag to a board
-fn apply_tag_to_board(board: &mut Board, tag: &TagPair) {
-    match tag.name.as_str() {
-        "Board" => {
-            if let Ok(num) = tag.value.parse::<u32>() {
-                board.number = Some(num);
-            }
-        }
-        "Dealer" => {
-            if let Some(c) = tag.value.chars().next() {
-                board.dealer = Direction::from_char(c);
-            }
+/// Tags interpreted by `apply_tag_to_board`; all others are ignored
+pub(crate) const APPLIED_TAGS: &[PbnTag] = &[
+    PbnTag::Board,
+    PbnTag::Dealer,
+    PbnTag::Vulnerable,
+    PbnTag::Deal,
+    PbnTag::Event,
+    PbnTag::Site,
+    PbnTag::Date,
+    PbnTag::DoubleDummyTricks,
+    PbnTag::OptimumScore,
+    PbnTag::ParContract,
+];
+
+/// Apply a parsed tag to a board.
+///
+/// Returns a description of the problem when a known tag has an invalid
+/// value; the board is left unchanged for that tag.
+pub(crate) fn apply_tag_to_board(
+    board: &mut Board,
+    tag: &TagPair,
+) -> std::result::Result<(), String> {
+    match tag.tag {
+        PbnTag::Board => {
+            let num = tag
+                .value
+                .parse::<u32>()
+                .map_err(|_| format!("invalid Board number: \"{}\"", tag.value))?;
+            board.number = Some(num);
         }
-        "Vulnerable" => {
-            board.vulnerable = Vulnerability::from_pbn(&tag.value).unwrap_or_default();
+        PbnTag::Dealer => {
+            let dealer = parse_seat(&tag.value)
+                .ok_or_else(|| format!("invalid Dealer: \"{}\"", tag.value))?;
+            board.dealer = Some(dealer);
         }
-        "Deal" => {
-            if let Some(deal) = Deal::from_pbn(&tag.value) {
-                board.deal = deal;
+        PbnTag::Vulnerable => {
+            let vulnerable = Vulnerability::from_pbn(&tag.value);
+            board.vulnerable = vulnerable.unwrap_or_default();
+            if vulnerable.is_none() {
+                return Err(format!("invalid Vulnerable: \"{}\"", tag.value));
             }
         }
-        "Event" => {
+        PbnTag::Deal => {
+            board.deal = DealTag::from_pbn(&tag.value)?.to_deal();
+        }
+        PbnTag::Event => {
             if !tag.value.is_empty() {
                 board.event = Some(tag.value.clone());
             }
         }
-        "Site" => {
+        PbnTag::Site => {
             if !tag.value.is_empty() {
                 board.site = Some(tag.value.clone());
             }
         }
-        "Date" => {
+        PbnTag::Date => {
             if !tag.value.is_empty() {
                 board.date = Some(tag.value.clone());
             }
         }
-        "DoubleDummyTricks" => {
+        PbnTag::DoubleDummyTricks if !tag.value.is_empty() => {
+            DoubleDummyTable::parse_dd_tricks(&tag.value)?;
             board.double_dummy_tricks = Some(tag.value.clone());
         }
-        "OptimumScore" => {
+        PbnTag::OptimumScore => {
             board.optimum_score = Some(tag.value.clone());
         }
-        "ParContract" => {
+        PbnTag::ParContract => {
             board.par_contract = Some(tag.value.clone());
         }
         _ => {
             // Ignore other tags
         }
     }
+    Ok(())
 }
 
-/// Read boards from a PBN file
+/// Read boards from a PBN file.
+///
+/// The whole file is loaded; [`PbnReader::open`](super::PbnReader::open)
+/// reads large files one game at a time. Files that aren't valid UTF-8 are
+/// read as Windows-1252, as written by many older programs.
 pub fn read_pbn_file(path: &std::path::Path) -> Result<Vec<Board>> {
-    let content = std::fs::read_to_string(path)?;
+    read_pbn_file_with_encoding(path, TextEncoding::Auto)
+}
+
+/// Read boards from a PBN file in the given encoding
+pub fn read_pbn_file_with_encoding(
+    path: &std::path::Path,
+    encoding: TextEncoding,
+) -> Result<Vec<Board>> {
+    let content = encoding.decode(&std::fs::read(path)?)?;
     read_pbn(&content)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::error::GameSpan;
+    use bridge_types::Direction;
 
     #[test]
     fn test_parse_tag_pair() {
         let tag = parse_tag_pair("[Board \"1\"]").unwrap();
-        assert_eq!(tag.name, "Board");
+        assert_eq!(tag.tag, PbnTag::Board);
         assert_eq!(tag.value, "1");
 
         let tag = parse_tag_pair("[Vulnerable \"NS\"]").unwrap();
-        assert_eq!(tag.name, "Vulnerable");
+        assert_eq!(tag.name(), "Vulnerable");
         assert_eq!(tag.value, "NS");
+
+        let tag = parse_tag_pair(r#"[North "Bob \"Ace\" Smith \\ Jr"]"#).unwrap();
+        assert_eq!(tag.value, r#"Bob "Ace" Smith \ Jr"#);
+        let tag = parse_tag_pair(r#"[ScoreTable "Rank\2R;Names\20L"]"#).unwrap();
+        assert_eq!(tag.value, r"Rank\2R;Names\20L");
+        assert!(parse_tag_pair("[Board \"]").is_none());
     }
 
     #[test]
@@ -199,6 +277,55 @@ mod tests {
         assert_eq!(boards[1].vulnerable, Vulnerability::NorthSouth);
     }
 
+    #[test]
+    fn test_read_dealer_words() {
+        let boards =
+            read_pbn("[Board \"1\"]\n[Dealer \"North\"]\n\n[Board \"2\"]\n[Dealer \"Oost\"]\n")
+                .unwrap();
+        assert_eq!(boards[0].dealer, Some(Direction::North));
+        assert_eq!(boards[1].dealer, Some(Direction::East));
+    }
+
+    #[test]
+    fn test_read_pbn_recovering() {
+        let pbn = r#"[Board "1"]
+[Dealer "N"]
+[Board "2"]
+[Dealer "Q"]
+
+[Board "3"]
+[Vulnerable "EW"]
+"#;
+        let (boards, errors) = read_pbn_recovering(pbn);
+        let numbers: Vec<_> = boards.iter().map(|b| b.number).collect();
+        assert_eq!(numbers, [Some(1), Some(3)]);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(
+            errors[0].span,
+            GameSpan {
+                start_line: 3,
+                end_line: 4
+            }
+        );
+        assert_eq!(errors[0].location.as_ref().map(|l| l.line), Some(4));
+    }
+
+    #[test]
+    fn test_double_dummy_tricks_validated() {
+        let mut board = Board::new();
+        let tag = |value: &str| TagPair {
+            tag: PbnTag::DoubleDummyTricks,
+            value: value.to_string(),
+        };
+        assert!(apply_tag_to_board(&mut board, &tag("8866")).is_err());
+        assert!(board.double_dummy_tricks.is_none());
+        apply_tag_to_board(&mut board, &tag("88667799555566775555")).unwrap();
+        assert_eq!(
+            board.double_dummy_tricks.as_deref(),
+            Some("88667799555566775555")
+        );
+    }
+
     #[test]
     fn test_read_pbn_with_commentary() {
         let pbn = r#"
@@ -218,4 +345,28 @@ several lines.}
         let boards = read_pbn(pbn).unwrap();
         assert_eq!(boards.len(), 2);
     }
+
+    #[test]
+    fn test_read_pbn_with_limits() {
+        let pbn = "[Board \"1\"]\n\n[Board \"2\"]\n\n[Board \"3\"]\n";
+        assert_eq!(read_pbn_with_limits(pbn, &Limits::new()).unwrap().len(), 3);
+        assert!(read_pbn_with_limits(pbn, &Limits::new().with_max_boards(3)).is_ok());
+
+        let err = read_pbn_with_limits(pbn, &Limits::new().with_max_boards(2)).unwrap_err();
+        assert!(matches!(err, crate::ParseError::LimitExceeded(_)));
+        let err = read_pbn_with_limits(pbn, &Limits::new().with_max_bytes(10)).unwrap_err();
+        assert!(matches!(err, crate::ParseError::LimitExceeded(_)));
+    }
+
+    #[test]
+    fn test_read_latin1_file() {
+        let path = std::env::temp_dir().join(format!("latin1-test-{}.pbn", std::process::id()));
+        std::fs::write(&path, b"[Event \"Z\xFCrich\"]\n[Board \"1\"]\n").unwrap();
+        let boards = read_pbn_file(&path);
+        let strict = read_pbn_file_with_encoding(&path, TextEncoding::Utf8);
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(boards.unwrap()[0].event.as_deref(), Some("Zürich"));
+        assert!(strict.is_err());
+    }
 }
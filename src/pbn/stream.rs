@@ -0,0 +1,1225 @@
+//! Streaming PBN reader.
+
+use super::auction::Auction;
+use super::dd_table::DoubleDummyTable;
+use super::game::{Commentary, Game};
+use super::play::Play;
+use super::reader::{
+    apply_tag_to_board, is_unterminated_deal, parse_tag_pair, TagPair, APPLIED_TAGS,
+};
+use super::table::PbnTable;
+use super::tag::PbnTag;
+use crate::debug::{Trace, TraceAction};
+use crate::diagnostics::{Diagnostics, WarningKind};
+use crate::encoding::TextEncoding;
+use crate::error::{GameSpan, ParseError, Result, SourceLocation};
+use crate::sanity::irregular_hands;
+use crate::scoring::parse_contract;
+use crate::seat::parse_seat;
+use bridge_types::{Board, Deal, Direction};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+/// Reads PBN games one at a time from any `BufRead` source.
+///
+/// Games end at a blank line (outside commentary) or where a new `[Event`
+/// or `[Board` tag starts while the current game already has one.
+///
+/// By default invalid tag values are ignored, as in [`read_pbn`](super::read_pbn).
+/// In strict mode a game with a malformed tag pair or an invalid value for a
+/// known tag is reported as [`ParseError::PbnGame`] carrying the game's line
+/// span and the location of the offending value; the reader then resumes
+/// with the next game instead of giving up on the rest of the file.
+///
+/// Tag values are inherited as in PBN 4.8: `#` takes the value of the tag
+/// in the nearest previous game, and a value `##text` means `text` for
+/// this and all following games that omit the tag.
+///
+/// A `% PBN 1.0` header (or [`with_legacy`](Self::with_legacy)) switches
+/// to legacy mode for the looser values written by PBN 1.0 era programs:
+/// Vulnerable words like `Neither`, `N-S` or `both` in any case, and Deal
+/// values in lowercase or with `10` for the ten.
+///
+/// A Deal with hands of other than 13 cards (a misdeal) is kept as found
+/// and reported as a [`WarningKind::IrregularDeal`] warning. With
+/// [`with_irregular_deals(false)`](Self::with_irregular_deals) it is an
+/// invalid value instead: cleared in lenient mode, an error in strict mode.
+///
+/// Lines are decoded as by [`TextEncoding::Auto`] unless another encoding
+/// is set with [`with_encoding`](Self::with_encoding), so files from older
+/// programs in Windows-1252 can be streamed too.
+///
+/// The `% PBN x.y` and `% EXPORT` header lines are available from
+/// [`version`](Self::version) and [`is_export`](Self::is_export) once the
+/// first game has been read.
+///
+/// Conformance mode ([`with_conformance`](Self::with_conformance)) is
+/// strict mode plus the export format rules of the standard: tag names
+/// must start with an upper case letter and contain only letters, digits
+/// and underscores, and every game must have the 15 mandatory tags
+/// (possibly inherited through `##`).
+pub struct PbnReader<R: BufRead> {
+    reader: R,
+    strict: bool,
+    conformance: bool,
+    legacy: bool,
+    irregular_deals: bool,
+    /// Version from the `% PBN x.y` directive
+    version: Option<String>,
+    /// Whether a `% EXPORT` directive was seen
+    export: bool,
+    encoding: TextEncoding,
+    /// The undecoded bytes of the current line
+    byte_buf: Vec<u8>,
+    line_buf: String,
+    line_number: usize,
+    games_read: usize,
+    /// First line of the next game, read while looking for the end of the current one
+    pending: Option<(usize, String)>,
+    /// Warnings for the current game
+    diagnostics: Diagnostics,
+    /// The latest value of each tag in the games read so far
+    previous_tags: Vec<TagPair>,
+    /// Tags given with `##`, inherited by games that omit them
+    header_tags: Vec<PbnTag>,
+    /// Parsing decisions, recorded when explaining
+    trace: Trace,
+}
+
+/// What a reader carries from one game to the next: the header
+/// directives and the tag values later games can inherit
+#[derive(Debug, Clone, Default)]
+pub(crate) struct CarriedState {
+    legacy: bool,
+    version: Option<String>,
+    export: bool,
+    previous_tags: Vec<TagPair>,
+    header_tags: Vec<PbnTag>,
+}
+
+/// The significant lines of one game, before parsing
+struct GameLines {
+    span: GameSpan,
+    /// Lines with their line numbers
+    lines: Vec<(usize, String)>,
+    /// Text of the commentary blocks, without braces, with the number of
+    /// lines before each
+    commentary: Vec<(usize, String)>,
+}
+
+impl<R: BufRead> PbnReader<R> {
+    /// Create a new lenient reader.
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            strict: false,
+            conformance: false,
+            legacy: false,
+            irregular_deals: true,
+            version: None,
+            export: false,
+            encoding: TextEncoding::Auto,
+            byte_buf: Vec::new(),
+            line_buf: String::new(),
+            line_number: 0,
+            games_read: 0,
+            pending: None,
+            diagnostics: Diagnostics::new(),
+            previous_tags: Vec::new(),
+            header_tags: Vec::new(),
+            trace: Trace::default(),
+        }
+    }
+
+    /// Enable or disable strict mode.
+    pub fn with_strict(mut self, strict: bool) -> Self {
+        self.strict = strict;
+        self
+    }
+
+    /// Enable or disable conformance mode, which rejects games that break
+    /// the PBN export format rules and implies strict mode.
+    pub fn with_conformance(mut self, conformance: bool) -> Self {
+        self.conformance = conformance;
+        self
+    }
+
+    /// Enable or disable legacy mode for PBN 1.0 files.
+    pub fn with_legacy(mut self, legacy: bool) -> Self {
+        self.legacy = legacy;
+        self
+    }
+
+    /// Keep deals whose hands don't have 13 cards each (misdeals), reporting
+    /// them as warnings (the default), or treat them as invalid values.
+    pub fn with_irregular_deals(mut self, irregular_deals: bool) -> Self {
+        self.irregular_deals = irregular_deals;
+        self
+    }
+
+    /// Set the encoding lines are decoded with.
+    pub fn with_encoding(mut self, encoding: TextEncoding) -> Self {
+        self.encoding = encoding;
+        self
+    }
+
+    /// Record each parsing decision in `trace`, for
+    /// [`explain_record`](crate::debug::explain_record)
+    pub(crate) fn with_trace(mut self, trace: Trace) -> Self {
+        self.trace = trace;
+        self
+    }
+
+    /// The parsing decisions recorded so far
+    pub(crate) fn into_trace(self) -> Trace {
+        self.trace
+    }
+
+    /// The state carried to the next game
+    pub(crate) fn carried_state(&self) -> CarriedState {
+        CarriedState {
+            legacy: self.legacy,
+            version: self.version.clone(),
+            export: self.export,
+            previous_tags: self.previous_tags.clone(),
+            header_tags: self.header_tags.clone(),
+        }
+    }
+
+    /// Continue from `state`, as if the input had been read up to and
+    /// including line `line_number` by a reader ending in that state
+    pub(crate) fn with_carried_state(mut self, state: CarriedState, line_number: usize) -> Self {
+        self.legacy = state.legacy;
+        self.version = state.version;
+        self.export = state.export;
+        self.previous_tags = state.previous_tags;
+        self.header_tags = state.header_tags;
+        self.line_number = line_number;
+        self
+    }
+
+    /// Whether legacy mode is on, by request or from a `% PBN 1.0` header.
+    pub fn is_legacy(&self) -> bool {
+        self.legacy
+    }
+
+    /// The version claimed by a `% PBN x.y` directive (e.g. `2.1`).
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+
+    /// Whether a `% EXPORT` directive marks the file as export format.
+    pub fn is_export(&self) -> bool {
+        self.export
+    }
+
+    /// Number of games successfully read so far.
+    pub fn games_read(&self) -> usize {
+        self.games_read
+    }
+
+    /// Current line number in the input.
+    pub fn line_number(&self) -> usize {
+        self.line_number
+    }
+
+    /// Warnings for the game most recently returned (lenient mode only).
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
+    /// Whether invalid games are errors rather than warnings
+    fn is_strict(&self) -> bool {
+        self.strict || self.conformance
+    }
+
+    /// The value of `tag` in the nearest previous game that has it
+    fn previous_value(&self, tag: &PbnTag) -> Option<&str> {
+        self.previous_tags
+            .iter()
+            .find(|pair| pair.tag == *tag)
+            .map(|pair| pair.value.as_str())
+    }
+
+    /// Check the hand sizes of a just-applied Deal. Irregular deals are
+    /// kept with a warning when allowed, otherwise cleared and reported as
+    /// an invalid value.
+    fn check_deal(&mut self, board: &mut Board, line: usize) -> std::result::Result<(), String> {
+        let irregular = irregular_hands(&board.deal);
+        if irregular.is_empty() {
+            return Ok(());
+        }
+        let sizes: Vec<String> = irregular
+            .iter()
+            .map(|(dir, len)| format!("{} has {} cards", dir.to_char(), len))
+            .collect();
+        let message = format!("irregular Deal: {}", sizes.join(", "));
+        if self.irregular_deals {
+            self.diagnostics
+                .warn(Some(line), WarningKind::IrregularDeal, message);
+            Ok(())
+        } else {
+            board.deal = Deal::new();
+            Err(message)
+        }
+    }
+
+    /// Read and decode the next line into `line_buf`. Returns `false` at EOF.
+    fn read_line(&mut self) -> Result<bool> {
+        self.byte_buf.clear();
+        if self.reader.read_until(b'\n', &mut self.byte_buf)? == 0 {
+            return Ok(false);
+        }
+        self.line_buf = self.encoding.decode(&self.byte_buf)?;
+        Ok(true)
+    }
+
+    /// Collect the lines of the next game. Returns `None` at EOF.
+    fn read_game(&mut self) -> Result<Option<GameLines>> {
+        let mut game = GameLines {
+            span: GameSpan {
+                start_line: 0,
+                end_line: 0,
+            },
+            lines: Vec::new(),
+            commentary: Vec::new(),
+        };
+        let mut has_event = false;
+        let mut in_commentary = false;
+        let mut comment = String::new();
+
+        let mut has_board = false;
+        if let Some((line_number, line)) = self.pending.take() {
+            has_event = line.starts_with("[Event ");
+            has_board = line.starts_with("[Board ");
+            game.push(line_number, line);
+        }
+
+        while self.read_line()? {
+            self.line_number += 1;
+            let line = self.line_buf.trim();
+
+            // Track multi-line commentary blocks { ... }
+            if in_commentary {
+                match line.find('}') {
+                    Some(end) => {
+                        comment.push('\n');
+                        comment.push_str(&line[..end]);
+                        game.commentary
+                            .push((game.lines.len(), std::mem::take(&mut comment)));
+                        in_commentary = false;
+                    }
+                    None => {
+                        comment.push('\n');
+                        comment.push_str(line);
+                    }
+                }
+                continue;
+            }
+            if let Some(text) = line.strip_prefix('{') {
+                self.trace
+                    .record(Some(self.line_number), TraceAction::Applied, || {
+                        "commentary".to_string()
+                    });
+                match text.find('}') {
+                    Some(end) => game
+                        .commentary
+                        .push((game.lines.len(), text[..end].to_string())),
+                    None => {
+                        comment.push_str(text);
+                        in_commentary = true;
+                    }
+                }
+                continue;
+            }
+
+            // Empty line ends the game
+            if line.is_empty() {
+                if !game.lines.is_empty() {
+                    return Ok(Some(game));
+                }
+                continue;
+            }
+
+            // Skip line comments and directives
+            if line.starts_with(';') || line.starts_with('%') {
+                let directive = line.trim_start_matches('%').trim();
+                let line_number = Some(self.line_number);
+                if line.starts_with(';') {
+                    self.trace.record(line_number, TraceAction::Skipped, || {
+                        format!("comment: {}", line)
+                    });
+                } else if let Some(version) = directive.strip_prefix("PBN ") {
+                    if version.starts_with("1.") {
+                        self.legacy = true;
+                    }
+                    self.version = Some(version.trim().to_string());
+                    let legacy = self.legacy;
+                    self.trace.record(line_number, TraceAction::Applied, || {
+                        let mode = if legacy { " (legacy mode)" } else { "" };
+                        format!("PBN version {}{}", version.trim(), mode)
+                    });
+                } else if directive == "EXPORT" {
+                    self.export = true;
+                    self.trace.record(line_number, TraceAction::Applied, || {
+                        "export format".to_string()
+                    });
+                } else {
+                    self.trace.record(line_number, TraceAction::Skipped, || {
+                        format!("directive: {}", line)
+                    });
+                }
+                continue;
+            }
+
+            if line.starts_with("[Event ") {
+                if has_event {
+                    self.pending = Some((self.line_number, line.to_string()));
+                    return Ok(Some(game));
+                }
+                has_event = true;
+            }
+            // A second Board tag means the blank line before it is missing
+            if line.starts_with("[Board ") {
+                if has_board {
+                    self.pending = Some((self.line_number, line.to_string()));
+                    return Ok(Some(game));
+                }
+                has_board = true;
+            }
+
+            game.push(self.line_number, line.to_string());
+        }
+
+        if game.lines.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(game))
+        }
+    }
+
+    /// Build a game from its lines. Returns `None` for games without tags.
+    ///
+    /// In lenient mode, skipped tags and invalid values are recorded in
+    /// `self.diagnostics`.
+    fn parse_game(&mut self, game: GameLines) -> Result<Option<Game>> {
+        let mut board = Board::new();
+        let mut tags = Vec::new();
+        // Interpreted sections, with the text following their tags
+        let mut sections: Vec<Section> = Vec::new();
+        let mut in_section = false;
+        let mut contract = None;
+        let mut comments = game.commentary.into_iter().peekable();
+        let mut commentary = Vec::new();
+
+        for (index, (line_number, line)) in game.lines.iter().enumerate() {
+            while let Some((_, text)) = comments.next_if(|(before, _)| *before == index) {
+                let tag = tags.last().map(|pair: &TagPair| pair.tag.clone());
+                let section = sections.last().filter(|_| in_section);
+                commentary.push(anchor_commentary(text, tag, section));
+            }
+
+            // Section data; only SECTION_TAGS are interpreted
+            if !line.starts_with('[') {
+                match (in_section, sections.last_mut()) {
+                    (true, Some(section)) => {
+                        section.text.push_str(line);
+                        section.text.push('\n');
+                        section.text_lines.push(*line_number);
+                    }
+                    _ => self
+                        .trace
+                        .record(Some(*line_number), TraceAction::Ignored, || {
+                            format!("section data: {}", line)
+                        }),
+                }
+                continue;
+            }
+
+            in_section = false;
+            match parse_tag_pair(line) {
+                Some(mut tag) => {
+                    if self.conformance && !is_valid_tag_name(tag.tag.name()) {
+                        return Err(located_error(
+                            game.span,
+                            format!("invalid tag name: {}", tag.tag.name()),
+                            SourceLocation::find(*line_number, line, tag.tag.name()),
+                        ));
+                    }
+                    let mut note = "";
+                    if !NOT_INHERITED_TAGS.contains(&tag.tag) {
+                        if tag.value == "#" {
+                            tag.value = self.previous_value(&tag.tag).unwrap_or("").to_string();
+                            note = " (from the previous game)";
+                        } else if let Some(text) = tag.value.strip_prefix("##") {
+                            tag.value = text.to_string();
+                            note = " (for this and following games)";
+                            if !self.header_tags.contains(&tag.tag) {
+                                self.header_tags.push(tag.tag.clone());
+                            }
+                        }
+                    }
+                    if self.legacy {
+                        if let Some(value) = legacy_value(&tag.tag, &tag.value) {
+                            tag.value = value;
+                            note = " (PBN 1.0 value)";
+                        }
+                    }
+                    if SECTION_TAGS.contains(&tag.tag) {
+                        sections.push(Section {
+                            tag: tag.tag.clone(),
+                            value: tag.value.clone(),
+                            line: *line_number,
+                            text: String::new(),
+                            text_lines: Vec::new(),
+                        });
+                        in_section = true;
+                    }
+                    let applied = apply_tag_to_board(&mut board, &tag);
+                    let applied = match (applied, &tag.tag) {
+                        (Ok(()), PbnTag::Deal) => self.check_deal(&mut board, *line_number),
+                        (Ok(()), PbnTag::Contract) if !tag.value.trim().is_empty() => {
+                            parse_contract(&tag.value).map(|parsed| contract = parsed)
+                        }
+                        (applied, _) => applied,
+                    };
+                    if let Err(message) = applied {
+                        if self.is_strict() {
+                            return Err(located_error(
+                                game.span,
+                                message,
+                                value_location(*line_number, line),
+                            ));
+                        }
+                        let kind = if tag.tag == PbnTag::Vulnerable {
+                            WarningKind::DefaultedValue
+                        } else {
+                            WarningKind::IgnoredValue
+                        };
+                        self.trace
+                            .record(Some(*line_number), TraceAction::Rejected, || {
+                                message.clone()
+                            });
+                        self.diagnostics.warn(Some(*line_number), kind, message);
+                    } else if is_interpreted(&tag.tag) {
+                        self.trace
+                            .record(Some(*line_number), TraceAction::Applied, || {
+                                format!("{} = \"{}\"{}", tag.tag, tag.value, note)
+                            });
+                    } else {
+                        self.trace
+                            .record(Some(*line_number), TraceAction::Ignored, || {
+                                format!("tag {} is kept but not interpreted", tag.tag)
+                            });
+                    }
+                    tags.push(tag);
+                }
+                None if self.is_strict() => {
+                    return Err(located_error(
+                        game.span,
+                        format!("malformed tag pair: {}", line),
+                        SourceLocation::find(*line_number, line, line),
+                    ));
+                }
+                None => {
+                    self.trace
+                        .record(Some(*line_number), TraceAction::Skipped, || {
+                            format!("malformed tag pair: {}", line)
+                        });
+                    self.diagnostics.warn(
+                        Some(*line_number),
+                        WarningKind::SkippedLine,
+                        format!("malformed tag pair: {}", line),
+                    )
+                }
+            }
+        }
+
+        for (_, text) in comments {
+            let tag = tags.last().map(|pair| pair.tag.clone());
+            let section = sections.last().filter(|_| in_section);
+            commentary.push(anchor_commentary(text, tag, section));
+        }
+
+        if tags.is_empty() {
+            if self.is_strict() {
+                return Err(game_error(game.span, "game has no tag pairs".to_string()));
+            }
+            return Ok(None);
+        }
+        for inherited in &self.header_tags {
+            if tags.iter().any(|pair| pair.tag == *inherited) {
+                continue;
+            }
+            let value = self.previous_value(inherited).unwrap_or("");
+            let tag = TagPair {
+                tag: inherited.clone(),
+                value: value.to_string(),
+            };
+            // Already checked in the game that set it
+            let _ = apply_tag_to_board(&mut board, &tag);
+            self.trace.record(None, TraceAction::Applied, || {
+                format!("{} = \"{}\" (inherited)", tag.tag, tag.value)
+            });
+            tags.push(tag);
+        }
+        if self.conformance {
+            let missing: Vec<&str> = PbnTag::MANDATORY
+                .iter()
+                .filter(|tag| !tags.iter().any(|pair| pair.tag == **tag))
+                .map(PbnTag::name)
+                .collect();
+            if !missing.is_empty() {
+                return Err(game_error(
+                    game.span,
+                    format!("missing mandatory tags: {}", missing.join(", ")),
+                ));
+            }
+        }
+        for pair in &tags {
+            match self.previous_tags.iter_mut().find(|p| p.tag == pair.tag) {
+                Some(previous) => previous.value = pair.value.clone(),
+                None => self.previous_tags.push(pair.clone()),
+            }
+        }
+
+        let auction =
+            self.parse_section(game.span, &sections, PbnTag::Auction, |value, text| {
+                Auction::parse(section_player(value)?, text)
+            })?;
+        let play = self.parse_section(game.span, &sections, PbnTag::Play, |value, text| {
+            Play::parse(section_player(value)?, text)
+        })?;
+        let optimum_result_table = self.parse_section(
+            game.span,
+            &sections,
+            PbnTag::OptimumResultTable,
+            DoubleDummyTable::parse_optimum_result_table,
+        )?;
+        let mut table = |tag| self.parse_section(game.span, &sections, tag, PbnTable::parse);
+        let score_table = table(PbnTag::ScoreTable)?;
+        let total_score_table = table(PbnTag::TotalScoreTable)?;
+        let action_table = table(PbnTag::ActionTable)?;
+        let instant_score_table = table(PbnTag::InstantScoreTable)?;
+
+        Ok(Some(Game {
+            board,
+            tags,
+            contract,
+            auction,
+            play,
+            optimum_result_table,
+            score_table,
+            total_score_table,
+            action_table,
+            instant_score_table,
+            commentary,
+            span: game.span,
+        }))
+    }
+}
+
+/// Tags for which `#` and `##` are not inheritance markers
+const NOT_INHERITED_TAGS: &[PbnTag] = &[
+    PbnTag::Dealer,
+    PbnTag::Vulnerable,
+    PbnTag::Deal,
+    PbnTag::Declarer,
+    PbnTag::Contract,
+    PbnTag::Auction,
+    PbnTag::Play,
+    PbnTag::Note,
+];
+
+/// Whether the reader interprets `tag`, rather than only keeping it
+fn is_interpreted(tag: &PbnTag) -> bool {
+    APPLIED_TAGS.contains(tag) || SECTION_TAGS.contains(tag) || *tag == PbnTag::Contract
+}
+
+/// Tags whose section data is interpreted
+const SECTION_TAGS: &[PbnTag] = &[
+    PbnTag::Auction,
+    PbnTag::Play,
+    PbnTag::OptimumResultTable,
+    PbnTag::ScoreTable,
+    PbnTag::TotalScoreTable,
+    PbnTag::ActionTable,
+    PbnTag::InstantScoreTable,
+];
+
+/// A section following its tag
+struct Section {
+    tag: PbnTag,
+    /// The tag value
+    value: String,
+    /// Line of the tag
+    line: usize,
+    text: String,
+    /// Line numbers of the lines of `text`
+    text_lines: Vec<usize>,
+}
+
+impl Section {
+    /// The location of the token a parse error message ends with (as in
+    /// `invalid call: 1Z`), or of the section's tag when it isn't found
+    fn error_location(&self, message: &str) -> SourceLocation {
+        let token = message
+            .rsplit_once(": ")
+            .map_or("", |(_, token)| token.trim_matches('"'));
+        self.text
+            .lines()
+            .zip(&self.text_lines)
+            .find(|(line, _)| !token.is_empty() && line.contains(token))
+            .map(|(line, &number)| SourceLocation::find(number, line, token))
+            .unwrap_or_else(|| SourceLocation {
+                line: self.line,
+                start_column: 1,
+                end_column: 1,
+                text: String::new(),
+            })
+    }
+}
+
+impl GameLines {
+    /// Add a line; the continuation of a wrapped Deal tag is joined to it
+    fn push(&mut self, line_number: usize, line: String) {
+        if let Some((_, last)) = self.lines.last_mut() {
+            if is_unterminated_deal(last) {
+                last.push(' ');
+                last.push_str(&line);
+                self.span.end_line = line_number;
+                return;
+            }
+        }
+        if self.lines.is_empty() {
+            self.span.start_line = line_number;
+        }
+        self.span.end_line = line_number;
+        self.lines.push((line_number, line));
+    }
+}
+
+/// The PBN 2.1 form of a PBN 1.0 era tag value, if it differs
+fn legacy_value(tag: &PbnTag, value: &str) -> Option<String> {
+    let normalized = match tag {
+        PbnTag::Vulnerable => match value.trim().to_ascii_lowercase().as_str() {
+            "none" | "love" | "-" | "neither" | "nil" => "None",
+            "ns" | "n-s" | "n/s" | "northsouth" | "north-south" => "NS",
+            "ew" | "e-w" | "e/w" | "eastwest" | "east-west" => "EW",
+            "all" | "both" => "All",
+            _ => return None,
+        }
+        .to_string(),
+        PbnTag::Deal => value.trim().to_ascii_uppercase().replace("10", "T"),
+        _ => return None,
+    };
+    (normalized != value).then_some(normalized)
+}
+
+/// The player named by an Auction or Play tag value
+fn section_player(value: &str) -> std::result::Result<Direction, String> {
+    parse_seat(value).ok_or_else(|| format!("invalid section player: {}", value))
+}
+
+/// Place a commentary block after `tag`, counting the calls or tricks
+/// before it when it is inside an Auction or Play `section`
+fn anchor_commentary(text: String, tag: Option<PbnTag>, section: Option<&Section>) -> Commentary {
+    let position = section.and_then(|section| {
+        let player = section_player(&section.value).ok()?;
+        match section.tag {
+            PbnTag::Auction => Some(Auction::parse(player, &section.text).ok()?.calls.len()),
+            PbnTag::Play => Some(Play::parse(player, &section.text).ok()?.tricks.len()),
+            _ => None,
+        }
+    });
+    Commentary {
+        text,
+        tag,
+        position,
+    }
+}
+
+/// Tag names start with an upper case letter and contain only letters,
+/// digits and underscores
+pub(super) fn is_valid_tag_name(name: &str) -> bool {
+    name.starts_with(|c: char| c.is_ascii_uppercase())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn game_error(span: GameSpan, message: String) -> ParseError {
+    ParseError::PbnGame {
+        span,
+        message,
+        location: None,
+    }
+}
+
+/// A game error located at `text` on a line of the game
+fn located_error(span: GameSpan, message: String, location: SourceLocation) -> ParseError {
+    ParseError::PbnGame {
+        span,
+        message,
+        location: Some(location),
+    }
+}
+
+/// The location of the quoted value of a tag pair line
+fn value_location(line_number: usize, line: &str) -> SourceLocation {
+    match (line.find('"'), line.rfind('"')) {
+        (Some(start), Some(end)) if end > start => {
+            SourceLocation::new(line_number, line, start + 1, &line[start + 1..end])
+        }
+        _ => SourceLocation::find(line_number, line, line),
+    }
+}
+
+impl<R: BufRead> PbnReader<R> {
+    /// Interpret the first section for `tag`. In lenient mode a section
+    /// that fails to parse is dropped with a warning.
+    fn parse_section<T>(
+        &mut self,
+        span: GameSpan,
+        sections: &[Section],
+        tag: PbnTag,
+        parse: impl Fn(&str, &str) -> std::result::Result<T, String>,
+    ) -> Result<Option<T>> {
+        let Some(section) = sections.iter().find(|s| s.tag == tag) else {
+            return Ok(None);
+        };
+        match parse(&section.value, &section.text) {
+            Ok(parsed) => Ok(Some(parsed)),
+            Err(message) if self.is_strict() => {
+                let location = section.error_location(&message);
+                Err(located_error(span, message, location))
+            }
+            Err(message) => {
+                self.trace
+                    .record(Some(section.line), TraceAction::Rejected, || {
+                        format!("{} section: {}", tag, message)
+                    });
+                self.diagnostics
+                    .warn(Some(section.line), WarningKind::IgnoredValue, message);
+                Ok(None)
+            }
+        }
+    }
+
+    /// Iterate the remaining games with all of their tags and commentary.
+    pub fn games(&mut self) -> impl Iterator<Item = Result<Game>> + '_ {
+        std::iter::from_fn(move || self.next_game())
+    }
+
+    /// Read the next game with all of its tags and commentary.
+    pub fn next_game(&mut self) -> Option<Result<Game>> {
+        loop {
+            let traced = self.trace.count();
+            let game = match self.read_game() {
+                Ok(Some(game)) => game,
+                Ok(None) => return None,
+                Err(e) => return Some(Err(e)),
+            };
+
+            self.diagnostics.clear();
+            let parsed = self.parse_game(game);
+            // Lines are read before they are parsed
+            self.trace.sort_from(traced);
+            match parsed {
+                Ok(Some(game)) => {
+                    self.games_read += 1;
+                    return Some(Ok(game));
+                }
+                Ok(None) => continue,
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl PbnReader<BufReader<File>> {
+    /// Open a file for reading one game at a time, without loading it
+    /// into memory.
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(Self::new(BufReader::new(File::open(path)?)))
+    }
+}
+
+impl<R: BufRead> Iterator for PbnReader<R> {
+    type Item = Result<Board>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_game().map(|result| result.map(|game| game.board))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::game::NoteSection;
+    use super::*;
+    use bridge_types::Vulnerability;
+    use std::io::Cursor;
+
+    const PBN: &str = r#"[Event "Club"]
+[Board "1"]
+[Dealer "N"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+
+[Event "Club"]
+[Board "2"]
+[Dealer "Q"]
+[Deal "E:Q7.AKT9.JT3.JT96 J653.QJ8.A.AQ732 K92.654.K954.K84 AT84.732.Q8762.5"]
+
+[Event "Club"]
+[Board "3"]
+[Dealer "S"]
+"#;
+
+    #[test]
+    fn test_lenient_reads_all_games() {
+        let boards: Vec<_> = PbnReader::new(Cursor::new(PBN)).collect();
+        assert_eq!(boards.len(), 3);
+        assert!(boards.iter().all(|b| b.is_ok()));
+    }
+
+    #[test]
+    fn test_strict_recovers_after_bad_game() {
+        let mut reader = PbnReader::new(Cursor::new(PBN)).with_strict(true);
+
+        let first = reader.next().unwrap().unwrap();
+        assert_eq!(first.number, Some(1));
+
+        match reader.next().unwrap() {
+            Err(ParseError::PbnGame {
+                span,
+                message,
+                location,
+            }) => {
+                assert_eq!(span.start_line, 6);
+                assert_eq!(span.end_line, 9);
+                assert!(message.contains("Dealer"));
+                let location = location.unwrap();
+                assert_eq!(location.line, 8);
+                assert_eq!((location.start_column, location.end_column), (10, 10));
+                assert_eq!(location.text, "Q");
+            }
+            other => panic!("expected game error, got {:?}", other),
+        }
+
+        let third = reader.next().unwrap().unwrap();
+        assert_eq!(third.number, Some(3));
+        assert!(reader.next().is_none());
+        assert_eq!(reader.games_read(), 2);
+    }
+
+    #[test]
+    fn test_event_tag_starts_new_game() {
+        let pbn = "[Event \"A\"]\n[Board \"1\"]\n[Event \"B\"]\n[Board \"2\"]\n";
+        let boards: Vec<_> = PbnReader::new(Cursor::new(pbn))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert_eq!(boards.len(), 2);
+        assert_eq!(boards[1].event.as_deref(), Some("B"));
+    }
+
+    #[test]
+    fn test_inherited_tag_values() {
+        let pbn = "[Event \"##Club\"]\n[Site \"Hall\"]\n[Board \"1\"]\n\n\
+                   [Site \"#\"]\n[Board \"2\"]\n\n\
+                   [Site \"Annex\"]\n[Board \"3\"]\n\n\
+                   [Event \"Teams\"]\n[Board \"4\"]\n\n\
+                   [Site \"#\"]\n[Board \"5\"]\n";
+        let boards: Vec<_> = PbnReader::new(Cursor::new(pbn))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        let events: Vec<_> = boards.iter().map(|b| b.event.as_deref()).collect();
+        let sites: Vec<_> = boards.iter().map(|b| b.site.as_deref()).collect();
+        assert_eq!(
+            events,
+            [
+                Some("Club"),
+                Some("Club"),
+                Some("Club"),
+                Some("Teams"),
+                Some("Teams")
+            ]
+        );
+        assert_eq!(
+            sites,
+            [
+                Some("Hall"),
+                Some("Hall"),
+                Some("Annex"),
+                None,
+                Some("Annex")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_legacy_values() {
+        let pbn = "% PBN 1.0\n[Board \"1\"]\n[Vulnerable \"n-s\"]\n\
+                   [Deal \"n:k843.10542.j6.863 aqj7.k.q75.a10942 962.aj7.k1082.j75 105.q9863.a943.kq\"]\n";
+        let mut reader = PbnReader::new(Cursor::new(pbn));
+        let board = reader.next().unwrap().unwrap();
+        assert!(reader.is_legacy());
+        assert_eq!(board.vulnerable, Vulnerability::NorthSouth);
+        assert_eq!(board.deal.hand(Direction::North).len(), 13);
+
+        let pbn = pbn.replace("% PBN 1.0", "% PBN 2.1");
+        let mut reader = PbnReader::new(Cursor::new(pbn.as_str())).with_strict(true);
+        assert!(reader.next().unwrap().is_err());
+        assert!(!reader.is_legacy());
+    }
+
+    #[test]
+    fn test_legacy_note_only_for_changed_values() {
+        let pbn = "% PBN 1.0\n[Board \"1\"]\n[Vulnerable \"NS\"]\n\
+                   [Deal \"N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ\"]\n\
+                   \n[Board \"2\"]\n[Vulnerable \"both\"]\n";
+        let mut reader = PbnReader::new(Cursor::new(pbn)).with_trace(Trace::enabled());
+        while reader.next_game().is_some() {}
+        let details: Vec<String> = reader
+            .into_trace()
+            .into_entries()
+            .into_iter()
+            .map(|entry| entry.detail)
+            .collect();
+        let noted: Vec<&str> = details
+            .iter()
+            .filter(|detail| detail.ends_with(" (PBN 1.0 value)"))
+            .map(String::as_str)
+            .collect();
+        assert_eq!(noted, ["Vulnerable = \"All\" (PBN 1.0 value)"]);
+    }
+
+    #[test]
+    fn test_header_directives() {
+        let pbn = "% PBN 2.1\n% EXPORT\n%Content-type: text/x-pbn\n\n[Board \"1\"]\n";
+        let mut reader = PbnReader::new(Cursor::new(pbn));
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(reader.version(), Some("2.1"));
+        assert!(reader.is_export());
+
+        let mut reader = PbnReader::new(Cursor::new("[Board \"1\"]\n"));
+        assert!(reader.next().unwrap().is_ok());
+        assert_eq!(reader.version(), None);
+        assert!(!reader.is_export());
+    }
+
+    #[test]
+    fn test_windows_1252_lines() {
+        let pbn: &[u8] = b"[Board \"1\"]\n[North \"J\xF6rg \x93Ace\x94\"]\n";
+        let game = PbnReader::new(pbn).next_game().unwrap().unwrap();
+        assert_eq!(
+            game.tag(&PbnTag::North),
+            Some("J\u{F6}rg \u{201C}Ace\u{201D}")
+        );
+
+        let mut reader = PbnReader::new(pbn).with_encoding(TextEncoding::Utf8);
+        assert!(matches!(reader.next_game(), Some(Err(ParseError::Io(_)))));
+    }
+
+    #[test]
+    fn test_wrapped_deal_tag() {
+        let pbn = "[Board \"1\"]\n\
+                   [Deal \"N:K843.T542.J6.863 AQJ7.K.Q75.AT942\n\
+                   962.AJ7.KT82.J75 T5.Q9863.A943.KQ\"]\n\
+                   [Dealer \"E\"]\n";
+        let game = PbnReader::new(Cursor::new(pbn))
+            .next_game()
+            .unwrap()
+            .unwrap();
+        assert_eq!(game.board.deal.hand(Direction::West).len(), 13);
+        assert_eq!(game.board.dealer, Some(Direction::East));
+        assert_eq!(game.span.end_line, 4);
+    }
+
+    #[test]
+    fn test_section_error_location() {
+        let pbn = "[Board \"1\"]\n[Auction \"N\"]\n1S Pass\n2S 1Z Pass\n";
+        let err = PbnReader::new(Cursor::new(pbn))
+            .with_strict(true)
+            .next_game()
+            .unwrap()
+            .unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!(location.line, 4);
+        assert_eq!((location.start_column, location.end_column), (4, 5));
+        assert_eq!(location.text, "1Z");
+    }
+
+    #[test]
+    fn test_irregular_deals() {
+        let pbn = "[Board \"1\"]\n\
+                   [Deal \"N:AK843.T542.J6.863 QJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ\"]\n";
+        // Kept by default, in lenient and strict mode alike
+        for strict in [false, true] {
+            let mut reader = PbnReader::new(Cursor::new(pbn)).with_strict(strict);
+            let board = reader.next().unwrap().unwrap();
+            assert_eq!(board.deal.hand(Direction::North).len(), 14);
+            let warning = &reader.diagnostics().warnings()[0];
+            assert_eq!(warning.kind, WarningKind::IrregularDeal);
+            assert_eq!(
+                warning.message,
+                "irregular Deal: N has 14 cards, E has 12 cards"
+            );
+        }
+
+        let mut reader = PbnReader::new(Cursor::new(pbn)).with_irregular_deals(false);
+        let board = reader.next().unwrap().unwrap();
+        assert!(board.deal.hand(Direction::North).is_empty());
+        assert_eq!(
+            reader.diagnostics().warnings()[0].kind,
+            WarningKind::IgnoredValue
+        );
+
+        let mut reader = PbnReader::new(Cursor::new(pbn))
+            .with_strict(true)
+            .with_irregular_deals(false);
+        assert!(reader.next().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_invalid_contract() {
+        let pbn = "[Board \"1\"]\n[Contract \"8NT\"]\n";
+        let mut reader = PbnReader::new(Cursor::new(pbn));
+        let game = reader.next_game().unwrap().unwrap();
+        assert_eq!(game.contract, None);
+        assert_eq!(reader.diagnostics().len(), 1);
+
+        let mut reader = PbnReader::new(Cursor::new(pbn)).with_strict(true);
+        assert!(reader.next_game().unwrap().is_err());
+    }
+
+    #[test]
+    fn test_strict_rejects_malformed_tag() {
+        let pbn = "[Board \"1\"]\n[Dealer N]\n";
+        let mut reader = PbnReader::new(Cursor::new(pbn)).with_strict(true);
+        assert!(matches!(
+            reader.next(),
+            Some(Err(ParseError::PbnGame { .. }))
+        ));
+    }
+
+    #[test]
+    fn test_conformance() {
+        let game = r#"[Event "Club"]
+[Site ""]
+[Date "2024.05.01"]
+[Board "1"]
+[West ""]
+[North ""]
+[East ""]
+[South ""]
+[Dealer "N"]
+[Vulnerable "None"]
+[Deal "N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ"]
+[Scoring ""]
+[Declarer ""]
+[Contract ""]
+[Result ""]
+"#;
+        let pbn = format!("{}\n[Board \"2\"]\n\n{}[board \"3\"]\n", game, game);
+
+        let mut reader = PbnReader::new(Cursor::new(pbn.as_str())).with_conformance(true);
+        assert!(reader.next().unwrap().is_ok());
+        match reader.next().unwrap() {
+            Err(ParseError::PbnGame { message, .. }) => {
+                assert!(message.starts_with("missing mandatory tags: Event, Site"))
+            }
+            other => panic!("expected game error, got {:?}", other),
+        }
+        match reader.next().unwrap() {
+            Err(ParseError::PbnGame { message, .. }) => {
+                assert_eq!(message, "invalid tag name: board")
+            }
+            other => panic!("expected game error, got {:?}", other),
+        }
+
+        let games = PbnReader::new(Cursor::new(pbn.as_str())).with_strict(true);
+        assert!(games.into_iter().all(|game| game.is_ok()));
+    }
+
+    #[test]
+    fn test_open_file() {
+        let path = std::env::temp_dir().join(format!("stream-test-{}.pbn", std::process::id()));
+        std::fs::write(&path, PBN).unwrap();
+        let mut reader = PbnReader::open(&path).unwrap();
+        let games: Vec<_> = reader.games().collect();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(games.len(), 3);
+        let first = games[0].as_ref().unwrap();
+        assert_eq!(first.tag(&PbnTag::Event), Some("Club"));
+        assert!(PbnReader::open("/nonexistent/file.pbn").is_err());
+    }
+
+    #[test]
+    fn test_lenient_diagnostics() {
+        let mut reader = PbnReader::new(Cursor::new(PBN));
+        reader.next().unwrap().unwrap();
+        assert!(reader.diagnostics().is_empty());
+
+        reader.next().unwrap().unwrap();
+        let warnings = reader.diagnostics().warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line, Some(8));
+        assert_eq!(warnings[0].kind, WarningKind::IgnoredValue);
+    }
+
+    #[test]
+    fn test_auction_section() {
+        let pbn = "[Board \"1\"]\n[Dealer \"S\"]\n[Auction \"S\"]\n1NT Pass 3NT\nAP\n[Contract \"3NT\"]\n[Play \"W\"]\nS2 SA S3 S4\n*\n";
+        let mut reader = PbnReader::new(Cursor::new(pbn));
+        let game = reader.next_game().unwrap().unwrap();
+        let auction = game.auction.unwrap();
+        assert_eq!(auction.first, Direction::South);
+        assert_eq!(auction.calls.len(), 6);
+        assert_eq!(auction.contract().unwrap().1, Direction::South);
+        assert_eq!(game.play.unwrap().tricks.len(), 1);
+
+        let bad = "[Board \"1\"]\n[Auction \"N\"]\n1NT 9Q\n";
+        let mut reader = PbnReader::new(Cursor::new(bad));
+        assert!(reader.next_game().unwrap().unwrap().auction.is_none());
+        assert_eq!(reader.diagnostics().warnings()[0].line, Some(2));
+    }
+
+    #[test]
+    fn test_notes_by_section() {
+        let pbn = "[Board \"1\"]\n[Auction \"S\"]\n1NT =1= Pass 3NT AP\n[Note \"1:15-17\"]\n\
+                   [Play \"W\"]\nS2 =1= SA S3 S4\n*\n[Note \"1:fourth best\"]\n";
+        let mut reader = PbnReader::new(Cursor::new(pbn));
+        let game = reader.next_game().unwrap().unwrap();
+        assert_eq!(game.auction_note(1), Some("15-17"));
+        assert_eq!(game.play_note(1), Some("fourth best"));
+        assert_eq!(game.call_notes()[0], Some("15-17"));
+        assert_eq!(
+            game.section_notes(),
+            [
+                (NoteSection::Auction, 1, "15-17"),
+                (NoteSection::Play, 1, "fourth best")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_commentary_anchors() {
+        let pbn = "{Intro}\n[Board \"1\"]\n{About the deal}\n[Auction \"S\"]\n1NT Pass\n{Why 3NT?}\n3NT AP\n[Play \"W\"]\nS2 SA S3 S4\n{A\nlong note}\n";
+        let mut reader = PbnReader::new(Cursor::new(pbn));
+        let game = reader.next_game().unwrap().unwrap();
+        let anchors: Vec<_> = game
+            .commentary
+            .iter()
+            .map(|c| (c.text.as_str(), c.tag.clone(), c.position))
+            .collect();
+        assert_eq!(
+            anchors,
+            [
+                ("Intro", None, None),
+                ("About the deal", Some(PbnTag::Board), None),
+                ("Why 3NT?", Some(PbnTag::Auction), Some(2)),
+                ("A\nlong note", Some(PbnTag::Play), Some(1)),
+            ]
+        );
+    }
+}
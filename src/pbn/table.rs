@@ -0,0 +1,292 @@
+//! PBN table sections.
+//!
+//! ScoreTable, TotalScoreTable and the other table sections share one
+//! layout: the tag value names the columns (with optional output formats
+//! like `\4R`) and the section lists the cells row by row.
+
+use crate::scoring::PlayedResult;
+
+/// A column of a table section, from the tag value
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TableColumn {
+    /// Column name, e.g. `PairId_NS` or `Contract`
+    pub name: String,
+    /// Column width for output, from a format like `\4R`
+    pub width: Option<usize>,
+    /// Right-align (`R`) rather than left-align (`L`) the column
+    pub right_aligned: bool,
+}
+
+/// A table section, e.g. a ScoreTable with one row per table that played
+/// the board
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PbnTable {
+    pub columns: Vec<TableColumn>,
+    /// Cell values without quotes; `-` marks an empty cell
+    pub rows: Vec<Vec<String>>,
+}
+
+/// A row of a table section
+#[derive(Debug, Clone, Copy)]
+pub struct TableRow<'a> {
+    table: &'a PbnTable,
+    index: usize,
+}
+
+impl<'a> TableRow<'a> {
+    /// Position of the row in the table
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// The value of column `name`, or `None` if the column is missing or
+    /// the cell is empty (`-`)
+    pub fn get(&self, name: &str) -> Option<&'a str> {
+        self.table.get(self.index, name)
+    }
+
+    /// The cells in column order
+    pub fn cells(&self) -> &'a [String] {
+        &self.table.rows[self.index]
+    }
+}
+
+/// One row of a TotalScoreTable
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ranking {
+    pub rank: Option<u32>,
+    pub pair_id: Option<String>,
+    /// Player names, as written (e.g. `Smith - Jones`)
+    pub names: Option<String>,
+    /// Total score or percentage
+    pub total: Option<f64>,
+}
+
+impl PbnTable {
+    /// Parse a table from its tag value (`header`) and section text
+    pub fn parse(header: &str, section: &str) -> Result<Self, String> {
+        let columns: Vec<TableColumn> = header
+            .split(';')
+            .filter(|column| !column.trim().is_empty())
+            .map(parse_column)
+            .collect();
+        if columns.is_empty() {
+            return Err("table has no columns".to_string());
+        }
+
+        let cells = tokenize(section)?;
+        if cells.len() % columns.len() != 0 {
+            return Err(format!(
+                "table has {} cells, not a multiple of {} columns",
+                cells.len(),
+                columns.len()
+            ));
+        }
+        let rows = cells
+            .chunks(columns.len())
+            .map(|row| row.to_vec())
+            .collect();
+        Ok(PbnTable { columns, rows })
+    }
+
+    /// Index of the column named `name`
+    pub fn column(&self, name: &str) -> Option<usize> {
+        self.columns
+            .iter()
+            .position(|c| c.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Row `index`, with cells looked up by column name
+    pub fn row(&self, index: usize) -> Option<TableRow<'_>> {
+        (index < self.rows.len()).then_some(TableRow { table: self, index })
+    }
+
+    /// All rows, with cells looked up by column name
+    pub fn iter_rows(&self) -> impl Iterator<Item = TableRow<'_>> {
+        (0..self.rows.len()).map(move |index| TableRow { table: self, index })
+    }
+
+    /// The value of column `name` in row `row`, or `None` if the column is
+    /// missing or the cell is empty (`-`)
+    pub fn get(&self, row: usize, name: &str) -> Option<&str> {
+        let value = self.rows.get(row)?.get(self.column(name)?)?;
+        (value != "-").then_some(value.as_str())
+    }
+
+    /// The result played at row `row`, from its Contract, Declarer and
+    /// Result columns
+    pub fn played_result(&self, row: usize) -> Option<PlayedResult> {
+        PlayedResult::from_pbn(
+            self.get(row, "Contract")?,
+            self.get(row, "Declarer")?,
+            self.get(row, "Result")?,
+        )
+        .ok()
+        .flatten()
+    }
+
+    /// The rows of a TotalScoreTable, from its Rank, PairId, Names and
+    /// first `TotalScore...` or `TotalPercentage` columns
+    pub fn rankings(&self) -> Vec<Ranking> {
+        let total = self.columns.iter().find(|c| {
+            c.name.starts_with("TotalScore") || c.name.eq_ignore_ascii_case("TotalPercentage")
+        });
+        (0..self.rows.len())
+            .map(|row| Ranking {
+                rank: self.get(row, "Rank").and_then(|v| v.parse().ok()),
+                pair_id: self.get(row, "PairId").map(str::to_string),
+                names: self.get(row, "Names").map(str::to_string),
+                total: total
+                    .and_then(|c| self.get(row, &c.name))
+                    .and_then(|v| v.parse().ok()),
+            })
+            .collect()
+    }
+
+    /// The tag value describing the columns
+    pub fn header(&self) -> String {
+        self.columns
+            .iter()
+            .map(|c| match c.width {
+                Some(width) => format!(
+                    "{}\\{}{}",
+                    c.name,
+                    width,
+                    if c.right_aligned { 'R' } else { 'L' }
+                ),
+                None => c.name.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(";")
+    }
+
+    /// The section text, one row per line, padded to the column widths
+    pub fn to_pbn(&self) -> String {
+        let mut out = String::new();
+        for row in &self.rows {
+            let cells: Vec<String> = row
+                .iter()
+                .zip(&self.columns)
+                .map(|(value, column)| {
+                    let value = if value.is_empty() || value.contains(char::is_whitespace) {
+                        format!("\"{}\"", value)
+                    } else {
+                        value.clone()
+                    };
+                    let width = column.width.unwrap_or(0);
+                    if column.right_aligned {
+                        format!("{:>width$}", value)
+                    } else {
+                        format!("{:<width$}", value)
+                    }
+                })
+                .collect();
+            out.push_str(cells.join(" ").trim_end());
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Parse a column like `Score_NS\6R`
+fn parse_column(column: &str) -> TableColumn {
+    let (name, format) = match column.trim().split_once('\\') {
+        Some((name, format)) => (name, Some(format)),
+        None => (column.trim(), None),
+    };
+    let format = format.unwrap_or("");
+    let digits: String = format.chars().take_while(char::is_ascii_digit).collect();
+    TableColumn {
+        name: name.to_string(),
+        width: digits.parse().ok(),
+        right_aligned: format.ends_with('R'),
+    }
+}
+
+/// Split section text into cells; quoted strings may contain spaces
+fn tokenize(section: &str) -> Result<Vec<String>, String> {
+    let mut cells = Vec::new();
+    let mut chars = section.chars().peekable();
+    while let Some(&c) = chars.peek() {
+        if c.is_whitespace() {
+            chars.next();
+        } else if c == '"' {
+            chars.next();
+            let mut cell = String::new();
+            loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some(c) => cell.push(c),
+                    None => return Err("unterminated string in table".to_string()),
+                }
+            }
+            cells.push(cell);
+        } else {
+            let mut cell = String::new();
+            while let Some(&c) = chars.peek() {
+                if c.is_whitespace() {
+                    break;
+                }
+                cell.push(c);
+                chars.next();
+            }
+            cells.push(cell);
+        }
+    }
+    Ok(cells)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str =
+        "PairId_NS\\2R;PairId_EW\\2R;Contract\\5L;Declarer\\1R;Result\\2R;Score_NS\\6R;Names\\12L";
+
+    #[test]
+    fn test_parse_score_table() {
+        let table = PbnTable::parse(
+            HEADER,
+            " 1  2 \"4S\"  N 10    420 \"Ann and Bob\"\n 3  4 \"3NT\" S  8      - -\n",
+        )
+        .unwrap();
+        assert_eq!(table.rows.len(), 2);
+        assert_eq!(table.columns[2].width, Some(5));
+        assert!(!table.columns[2].right_aligned);
+        assert_eq!(table.get(0, "Names"), Some("Ann and Bob"));
+        assert_eq!(table.get(1, "Score_NS"), None);
+        assert_eq!(table.played_result(1).unwrap().to_string(), "3NT S -1");
+        assert_eq!(table.header(), HEADER);
+        let names: Vec<_> = table.iter_rows().map(|row| row.get("Names")).collect();
+        assert_eq!(names, vec![Some("Ann and Bob"), None]);
+        assert!(table.row(2).is_none());
+
+        let reparsed = PbnTable::parse(&table.header(), &table.to_pbn()).unwrap();
+        assert_eq!(reparsed, table);
+        assert!(table
+            .to_pbn()
+            .starts_with(" 1  2 4S    N 10    420 \"Ann and Bob\"\n"));
+    }
+
+    #[test]
+    fn test_rankings() {
+        let table = PbnTable::parse(
+            "Rank\\2R;PairId\\2R;TotalScoreMP\\5R;Names\\20L",
+            " 1  7  61.5 \"Ann - Bob\"\n 2  3    -  \"Cy - Di\"\n",
+        )
+        .unwrap();
+        let rankings = table.rankings();
+        assert_eq!(rankings.len(), 2);
+        assert_eq!(rankings[0].rank, Some(1));
+        assert_eq!(rankings[0].pair_id.as_deref(), Some("7"));
+        assert_eq!(rankings[0].total, Some(61.5));
+        assert_eq!(rankings[1].names.as_deref(), Some("Cy - Di"));
+        assert_eq!(rankings[1].total, None);
+    }
+
+    #[test]
+    fn test_ragged_table() {
+        assert!(PbnTable::parse("A;B", "1 2 3").is_err());
+        assert!(PbnTable::parse("A;B", "1 \"2").is_err());
+    }
+}
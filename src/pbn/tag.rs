@@ -0,0 +1,301 @@
+//! PBN tag names.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+/// Declares the known tags; the tag name is the variant name.
+macro_rules! pbn_tags {
+    ($($(#[$doc:meta])* $tag:ident,)*) => {
+        /// A PBN tag name.
+        ///
+        /// Covers the tags defined by the PBN 2.1 standard plus the common
+        /// `DoubleDummyTricks` and `ParContract` extensions. Any other name
+        /// is kept as [`PbnTag::Unknown`].
+        #[derive(Debug, Clone, PartialEq, Eq, Hash)]
+        pub enum PbnTag {
+            $($(#[$doc])* $tag,)*
+            /// A tag not defined by the standard
+            Unknown(String),
+        }
+
+        impl PbnTag {
+            /// All known tags, in declaration order
+            pub const KNOWN: &'static [PbnTag] = &[$(PbnTag::$tag,)*];
+
+            /// The tag name as written in PBN files
+            pub fn name(&self) -> &str {
+                match self {
+                    $(PbnTag::$tag => stringify!($tag),)*
+                    PbnTag::Unknown(name) => name,
+                }
+            }
+
+            /// Look up a tag by its (case-sensitive) name
+            pub fn from_name(name: &str) -> PbnTag {
+                match name {
+                    $(stringify!($tag) => PbnTag::$tag,)*
+                    _ => PbnTag::Unknown(name.to_string()),
+                }
+            }
+        }
+    };
+}
+
+pbn_tags! {
+    // Mandatory tag set (in export order)
+    /// Name of the tournament or match
+    Event,
+    /// Location of the event
+    Site,
+    /// Starting date of the game
+    Date,
+    /// Board number
+    Board,
+    /// West player
+    West,
+    /// North player
+    North,
+    /// East player
+    East,
+    /// South player
+    South,
+    /// Dealer
+    Dealer,
+    /// Vulnerability
+    Vulnerable,
+    /// The dealt cards
+    Deal,
+    /// Scoring method
+    Scoring,
+    /// Declarer of the contract
+    Declarer,
+    /// The contract
+    Contract,
+    /// Result of the game
+    Result,
+
+    // Sections
+    /// Auction section
+    Auction,
+    /// Play section
+    Play,
+    /// Note referenced from the auction or play
+    Note,
+
+    // Supplemental tags
+    /// Competition type
+    Competition,
+    /// Unique deal identifier
+    DealId,
+    /// Description of the game
+    Description,
+    /// French matchpoint score
+    FrenchMP,
+    /// Program that generated the deal
+    Generator,
+    /// Hands hidden from the reader
+    Hidden,
+    /// Open or closed room
+    Room,
+    /// How the game ended
+    Termination,
+    /// Score of the declaring side
+    Score,
+    /// IMP score
+    ScoreIMP,
+    /// Matchpoint score
+    ScoreMP,
+    /// Matchpoint percentage
+    ScorePercentage,
+    /// Rubber bridge score
+    ScoreRubber,
+    /// Rubber bridge score history
+    ScoreRubberHistory,
+    /// Optimum (par) score
+    OptimumScore,
+    /// EW bidding system
+    BidSystemEW,
+    /// NS bidding system
+    BidSystemNS,
+    /// EW pair name
+    PairEW,
+    /// NS pair name
+    PairNS,
+    /// West player's network address
+    WestNA,
+    /// North player's network address
+    NorthNA,
+    /// East player's network address
+    EastNA,
+    /// South player's network address
+    SouthNA,
+    /// West player type
+    WestType,
+    /// North player type
+    NorthType,
+    /// East player type
+    EastType,
+    /// South player type
+    SouthType,
+    /// Starting date of the event
+    EventDate,
+    /// Sponsor of the event
+    EventSponsor,
+    /// Home team
+    HomeTeam,
+    /// Round of the event
+    Round,
+    /// Section of the event
+    Section,
+    /// Stage of the event
+    Stage,
+    /// Table number
+    Table,
+    /// Visiting team
+    VisitTeam,
+    /// Local starting time
+    Time,
+    /// UTC starting date
+    UTCDate,
+    /// UTC starting time
+    UTCTime,
+    /// Time control
+    TimeControl,
+    /// Time used per call
+    TimeCall,
+    /// Time used per card
+    TimeCard,
+    /// Annotator
+    Annotator,
+    /// Annotator's network address
+    AnnotatorNA,
+    /// Application used to create the file
+    Application,
+    /// Playing mode
+    Mode,
+
+    // Supplemental sections
+    /// Action table
+    ActionTable,
+    /// Auction time table
+    AuctionTimeTable,
+    /// Instant score table
+    InstantScoreTable,
+    /// Optimum play table
+    OptimumPlayTable,
+    /// Optimum result table
+    OptimumResultTable,
+    /// Play time table
+    PlayTimeTable,
+    /// Score table
+    ScoreTable,
+    /// Total score table
+    TotalScoreTable,
+
+    // Common extensions
+    /// Double dummy tricks per declarer and strain
+    DoubleDummyTricks,
+    /// Par contract
+    ParContract,
+}
+
+impl PbnTag {
+    /// The mandatory tag set, in the order required for export format
+    pub const MANDATORY: [PbnTag; 15] = [
+        PbnTag::Event,
+        PbnTag::Site,
+        PbnTag::Date,
+        PbnTag::Board,
+        PbnTag::West,
+        PbnTag::North,
+        PbnTag::East,
+        PbnTag::South,
+        PbnTag::Dealer,
+        PbnTag::Vulnerable,
+        PbnTag::Deal,
+        PbnTag::Scoring,
+        PbnTag::Declarer,
+        PbnTag::Contract,
+        PbnTag::Result,
+    ];
+
+    /// Whether this tag belongs to the mandatory tag set
+    pub fn is_mandatory(&self) -> bool {
+        self.mandatory_index().is_some()
+    }
+
+    /// Position in the mandatory tag set, if mandatory
+    fn mandatory_index(&self) -> Option<usize> {
+        PbnTag::MANDATORY.iter().position(|t| t == self)
+    }
+
+    /// Compare tags by export order: mandatory tags in their fixed order,
+    /// then all other tags alphabetically by name
+    pub fn export_order(&self, other: &PbnTag) -> Ordering {
+        match (self.mandatory_index(), other.mandatory_index()) {
+            (Some(a), Some(b)) => a.cmp(&b),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => self.name().cmp(other.name()),
+        }
+    }
+}
+
+impl fmt::Display for PbnTag {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for PbnTag {
+    type Err = std::convert::Infallible;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        Ok(PbnTag::from_name(s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_name_round_trip() {
+        for tag in PbnTag::KNOWN {
+            assert_eq!(&PbnTag::from_name(tag.name()), tag);
+            assert_eq!(tag.to_string().parse::<PbnTag>().unwrap(), *tag);
+        }
+        assert_eq!(
+            PbnTag::from_name("BCFlags"),
+            PbnTag::Unknown("BCFlags".to_string())
+        );
+        // Tag names are case sensitive
+        assert_eq!(
+            PbnTag::from_name("board"),
+            PbnTag::Unknown("board".to_string())
+        );
+    }
+
+    #[test]
+    fn test_export_order() {
+        let mut tags = vec![
+            PbnTag::ParContract,
+            PbnTag::Deal,
+            PbnTag::Unknown("Apple".to_string()),
+            PbnTag::Event,
+            PbnTag::DoubleDummyTricks,
+        ];
+        tags.sort_by(|a, b| a.export_order(b));
+        assert_eq!(
+            tags,
+            vec![
+                PbnTag::Event,
+                PbnTag::Deal,
+                PbnTag::Unknown("Apple".to_string()),
+                PbnTag::DoubleDummyTricks,
+                PbnTag::ParContract,
+            ]
+        );
+    }
+}
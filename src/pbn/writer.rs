@@ -1,104 +1,967 @@
 //! PBN file writer.
 
+use super::auction::{AlertMark, Auction, Call, NoteStyle};
+use super::dd_table::DoubleDummyTable;
+use super::game::{note_number, Commentary, Game, NoteSection};
+use super::reader::APPLIED_TAGS;
+use super::tag::PbnTag;
+use crate::encoding::{to_ascii, AsciiReport};
+use crate::error::ParseError;
+use crate::layout::{LayoutWriter, WriteOptions};
+use crate::scoring::format_contract;
+use crate::seat::SeatOrder;
 use bridge_types::{Board, Direction};
+use std::borrow::Cow;
+use std::io::Write;
+
+/// The PBN version written in the header by default
+pub const PBN_VERSION: &str = "2.1";
+
+/// The longest line, in characters, the PBN standard allows
+pub const MAX_LINE_LENGTH: usize = 255;
+
+/// Options controlling PBN output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PbnWriteOptions {
+    /// Write the `% PBN x.y` header, and `% EXPORT` if `export` is set
+    pub header: bool,
+    /// The PBN version in the header, e.g. `2.1`
+    pub version: String,
+    /// Write the `% EXPORT` line, declaring the file in export format
+    pub export: bool,
+    /// Write mandatory tags that have no value as empty tags (e.g. `[West ""]`)
+    pub empty_tags: bool,
+    /// Write the DoubleDummyTricks, OptimumScore and ParContract tags when present
+    pub analysis_tags: bool,
+    /// Tags never written, even when they have a value
+    pub excluded_tags: Vec<PbnTag>,
+    /// Values written for tags that have none, e.g. a Scoring method or an
+    /// Event name for a whole file; tags not otherwise written are added
+    pub default_values: Vec<(PbnTag, String)>,
+    /// With the analysis tags, also write the DoubleDummyTricks tag as an
+    /// OptimumResultTable section, for games without one
+    pub optimum_result_table: bool,
+    /// Seats whose cards are written as `-` in the Deal tag, e.g. to produce
+    /// teaching files where only two hands are shown
+    pub hidden_hands: Vec<Direction>,
+    /// How alerted calls are marked in the auction
+    pub alert_mark: AlertMark,
+    /// Whether calls reference their notes, or are only marked as alerted
+    /// with the notes left out
+    pub note_style: NoteStyle,
+    /// How suit escapes in note text are written
+    pub note_suits: NoteSuits,
+    /// The order tags are written in
+    pub tag_order: TagOrder,
+    /// Write control characters (line breaks, tabs, ...) in tag values as
+    /// spaces, so a stray line break can't split a tag across lines; on by
+    /// default
+    pub replace_control_chars: bool,
+    /// Settings shared with the other writers
+    pub common: WriteOptions,
+}
+
+/// The order in which [`game_to_pbn_with`] and [`board_to_pbn_with`]
+/// write tags
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TagOrder {
+    /// The mandatory tag set in the sequence given by the standard,
+    /// followed by the other tags alphabetically
+    #[default]
+    Export,
+    /// The order of the game's tags in its source file, for output that
+    /// stays byte-compatible with the input. Tags the source didn't have,
+    /// and all tags of boards, follow in export order.
+    Input,
+}
+
+impl TagOrder {
+    /// Sort `tags`, given the tags of the source in their order
+    fn sort(self, tags: &mut [(PbnTag, Option<String>)], source: &[PbnTag]) {
+        match self {
+            TagOrder::Export => tags.sort_by(|(a, _), (b, _)| a.export_order(b)),
+            TagOrder::Input => {
+                let position = |tag: &PbnTag| source.iter().position(|t| t == tag);
+                tags.sort_by(|(a, _), (b, _)| match (position(a), position(b)) {
+                    (Some(i), Some(j)) => i.cmp(&j),
+                    (Some(_), None) => std::cmp::Ordering::Less,
+                    (None, Some(_)) => std::cmp::Ordering::Greater,
+                    (None, None) => a.export_order(b),
+                });
+            }
+        }
+    }
+}
+
+/// How the suit escapes (`\S`, `\H`, `\D`, `\C`) in note text are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum NoteSuits {
+    /// Kept as escapes, as the PBN standard specifies
+    #[default]
+    Escapes,
+    /// As suit symbols (`♠`)
+    Unicode,
+    /// As suit letters (`S`)
+    Letters,
+}
+
+impl NoteSuits {
+    /// `text` with its suit escapes written in this style
+    fn apply(self, text: &str) -> String {
+        if self == NoteSuits::Escapes {
+            return text.to_string();
+        }
+        let mut out = String::with_capacity(text.len());
+        let mut chars = text.chars().peekable();
+        while let Some(c) = chars.next() {
+            let symbol = match chars.peek() {
+                Some('S') if c == '\\' => '♠',
+                Some('H') if c == '\\' => '♥',
+                Some('D') if c == '\\' => '♦',
+                Some('C') if c == '\\' => '♣',
+                _ => {
+                    out.push(c);
+                    continue;
+                }
+            };
+            let letter = chars.next().unwrap_or_default();
+            out.push(if self == NoteSuits::Unicode {
+                symbol
+            } else {
+                letter
+            });
+        }
+        out
+    }
+}
+
+impl Default for PbnWriteOptions {
+    fn default() -> Self {
+        Self {
+            header: true,
+            version: PBN_VERSION.to_string(),
+            export: true,
+            empty_tags: true,
+            analysis_tags: true,
+            optimum_result_table: false,
+            excluded_tags: Vec::new(),
+            default_values: Vec::new(),
+            hidden_hands: Vec::new(),
+            alert_mark: AlertMark::default(),
+            note_style: NoteStyle::default(),
+            note_suits: NoteSuits::default(),
+            tag_order: TagOrder::default(),
+            replace_control_chars: true,
+            common: WriteOptions::default(),
+        }
+    }
+}
+
+/// Named option sets producing output known to work with specific consumers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ExportProfile {
+    /// Hand records for upload to Bridge Base Online: only the tags that
+    /// carry data, no analysis
+    BboUpload,
+    /// Files for editing in BridgeComposer: the full mandatory tag set plus
+    /// any analysis tags, with game tags in their input order, in import
+    /// format (no `% EXPORT` line) since the file will be edited
+    BridgeComposer,
+    /// ACBL-style hand records: the full mandatory tag set with double
+    /// dummy analysis, also written as an OptimumResultTable section, and
+    /// par results when known
+    AcblHandRecords,
+}
+
+impl ExportProfile {
+    /// The writer options for this profile: the defaults with the
+    /// profile's overrides
+    pub fn options(self) -> PbnWriteOptions {
+        match self {
+            ExportProfile::BboUpload => PbnWriteOptions {
+                empty_tags: false,
+                analysis_tags: false,
+                ..PbnWriteOptions::default()
+            },
+            ExportProfile::BridgeComposer => PbnWriteOptions {
+                export: false,
+                tag_order: TagOrder::Input,
+                ..PbnWriteOptions::default()
+            },
+            ExportProfile::AcblHandRecords => PbnWriteOptions {
+                optimum_result_table: true,
+                ..PbnWriteOptions::default()
+            },
+        }
+    }
+}
+
+impl PbnWriteOptions {
+    /// Set the settings shared with the other writers
+    pub fn with_common(mut self, common: WriteOptions) -> Self {
+        self.common = common;
+        self
+    }
+
+    /// Set whether to write the `% PBN x.y` / `% EXPORT` header
+    pub fn with_header(mut self, header: bool) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Set the PBN version written in the header
+    pub fn with_version(mut self, version: &str) -> Self {
+        self.version = version.to_string();
+        self
+    }
+
+    /// Set whether the header includes the `% EXPORT` line
+    pub fn with_export(mut self, export: bool) -> Self {
+        self.export = export;
+        self
+    }
+
+    /// Set whether mandatory tags without a value are written empty
+    pub fn with_empty_tags(mut self, empty_tags: bool) -> Self {
+        self.empty_tags = empty_tags;
+        self
+    }
+
+    /// Set whether the analysis tags are written
+    pub fn with_analysis_tags(mut self, analysis_tags: bool) -> Self {
+        self.analysis_tags = analysis_tags;
+        self
+    }
+
+    /// Set whether the DoubleDummyTricks tag is also written as an
+    /// OptimumResultTable section
+    pub fn with_optimum_result_table(mut self, optimum_result_table: bool) -> Self {
+        self.optimum_result_table = optimum_result_table;
+        self
+    }
+
+    /// Set the tags never written
+    pub fn with_excluded_tags(mut self, excluded_tags: Vec<PbnTag>) -> Self {
+        self.excluded_tags = excluded_tags;
+        self
+    }
+
+    /// Add a value written for `tag` when it has none
+    pub fn with_default_value(mut self, tag: PbnTag, value: &str) -> Self {
+        self.default_values.retain(|(t, _)| *t != tag);
+        self.default_values.push((tag, value.to_string()));
+        self
+    }
+
+    /// Set the seats whose cards are written as `-`
+    pub fn with_hidden_hands(mut self, hidden_hands: Vec<Direction>) -> Self {
+        self.hidden_hands = hidden_hands;
+        self
+    }
+
+    /// Set how alerted calls are marked
+    pub fn with_alert_mark(mut self, alert_mark: AlertMark) -> Self {
+        self.alert_mark = alert_mark;
+        self
+    }
+
+    /// Set whether calls reference their notes
+    pub fn with_note_style(mut self, note_style: NoteStyle) -> Self {
+        self.note_style = note_style;
+        self
+    }
+
+    /// Set how suit escapes in note text are written
+    pub fn with_note_suits(mut self, note_suits: NoteSuits) -> Self {
+        self.note_suits = note_suits;
+        self
+    }
+
+    /// Set the order tags are written in
+    pub fn with_tag_order(mut self, tag_order: TagOrder) -> Self {
+        self.tag_order = tag_order;
+        self
+    }
+
+    /// Set whether control characters in tag values are written as spaces
+    pub fn with_replace_control_chars(mut self, replace_control_chars: bool) -> Self {
+        self.replace_control_chars = replace_control_chars;
+        self
+    }
+}
+
+impl From<ExportProfile> for PbnWriteOptions {
+    fn from(profile: ExportProfile) -> Self {
+        profile.options()
+    }
+}
 
 /// Write boards to PBN format
 pub fn write_pbn(boards: &[Board]) -> String {
-    let mut output = String::new();
+    write_pbn_with(boards, &PbnWriteOptions::default())
+}
+
+/// Write boards to PBN format, one board at a time, without holding the
+/// whole file in memory
+pub fn write_pbn_to<'a, W: Write>(
+    writer: &mut W,
+    boards: impl IntoIterator<Item = &'a Board>,
+) -> std::io::Result<()> {
+    write_pbn_to_with(writer, boards, &PbnWriteOptions::default())
+}
+
+/// Write boards to PBN format one at a time with the given options; the
+/// output is the same as from [`write_pbn_with`]
+pub fn write_pbn_to_with<'a, W: Write>(
+    writer: &mut W,
+    boards: impl IntoIterator<Item = &'a Board>,
+    options: &PbnWriteOptions,
+) -> std::io::Result<()> {
+    let mut out = LayoutWriter::new(writer, options.common);
+    let mut header = String::new();
+    write_header(&mut header, options);
+    out.write(&header)?;
+    for (i, board) in boards.into_iter().enumerate() {
+        let mut text = board_to_pbn_with(board, options);
+        if i > 0 {
+            text.insert(0, '\n');
+        }
+        out.write(&text)?;
+    }
+    out.finish()
+}
 
-    // PBN header
-    output.push_str("% PBN 2.1\n");
-    output.push_str("% EXPORT\n");
-    output.push('\n');
+/// Write boards to PBN format with the given options
+pub fn write_pbn_with(boards: &[Board], options: &PbnWriteOptions) -> String {
+    write_pbn_with_report(boards, options).0
+}
+
+/// Write boards to PBN format as by [`write_pbn_with`], also returning
+/// what ASCII-only mode transliterated; the report is empty otherwise
+pub fn write_pbn_with_report(boards: &[Board], options: &PbnWriteOptions) -> (String, AsciiReport) {
+    let mut report = AsciiReport::default();
+    let mut output = String::new();
+    write_header(&mut output, options);
 
     for (i, board) in boards.iter().enumerate() {
         if i > 0 {
             output.push('\n');
         }
-        output.push_str(&board_to_pbn(board));
+        output.push_str(&board_to_pbn_reported(board, options, &mut report));
     }
 
-    output
+    (options.common.finish(output), report)
+}
+
+/// Write the file header, if `options` asks for one
+pub(crate) fn write_header(output: &mut String, options: &PbnWriteOptions) {
+    if options.header {
+        output.push_str(&format!("% PBN {}\n", options.version));
+        if options.export {
+            output.push_str("% EXPORT\n");
+        }
+        output.push('\n');
+    }
 }
 
 /// Convert a single board to PBN format
 pub fn board_to_pbn(board: &Board) -> String {
+    board_to_pbn_with(board, &PbnWriteOptions::default())
+}
+
+/// Convert a single board to PBN format with the given options.
+///
+/// Tags are written in export order: the mandatory tag set in the sequence
+/// given by the standard, followed by the other tags alphabetically. A
+/// board has no source order, so [`TagOrder::Input`] writes the same.
+pub fn board_to_pbn_with(board: &Board, options: &PbnWriteOptions) -> String {
+    board_to_pbn_reported(board, options, &mut AsciiReport::default())
+}
+
+/// [`board_to_pbn_with`], adding what ASCII-only mode transliterated to
+/// `report`
+fn board_to_pbn_reported(
+    board: &Board,
+    options: &PbnWriteOptions,
+    report: &mut AsciiReport,
+) -> String {
+    let table = board_dd_table(board, options);
+    let mut tags = board_tags(board, None, options);
+    if let Some(table) = &table {
+        optimum_result_table_tag(&mut tags, table);
+    }
+    apply_tag_options(&mut tags, options);
+    ascii_tags(&mut tags, options, report);
+    TagOrder::Export.sort(&mut tags, &[]);
+
     let mut lines = Vec::new();
+    for (tag, value) in &tags {
+        push_optional_tag(&mut lines, tag, value.as_deref(), options);
+        if *tag == PbnTag::OptimumResultTable {
+            if let Some(table) = &table {
+                lines.extend(table.to_optimum_result_table().lines().map(str::to_string));
+            }
+        }
+    }
 
-    // Event tag
-    if let Some(ref event) = board.event {
-        lines.push(format!("[Event \"{}\"]", event));
-    } else {
-        lines.push("[Event \"\"]".to_string());
+    lines.join("\n") + "\n"
+}
+
+/// The board's DoubleDummyTricks as a table, when `options` asks for the
+/// OptimumResultTable section
+fn board_dd_table(board: &Board, options: &PbnWriteOptions) -> Option<DoubleDummyTable> {
+    if !(options.analysis_tags && options.optimum_result_table) {
+        return None;
     }
+    DoubleDummyTable::from_dd_tricks(board.double_dummy_tricks.as_deref()?)
+}
 
-    // Site tag
-    if let Some(ref site) = board.site {
-        lines.push(format!("[Site \"{}\"]", site));
-    } else {
-        lines.push("[Site \"\"]".to_string());
+/// Add the OptimumResultTable tag for a table set without one
+fn optimum_result_table_tag(tags: &mut Vec<(PbnTag, Option<String>)>, table: &DoubleDummyTable) {
+    if table != &DoubleDummyTable::new()
+        && !tags
+            .iter()
+            .any(|(tag, _)| *tag == PbnTag::OptimumResultTable)
+    {
+        tags.push((
+            PbnTag::OptimumResultTable,
+            Some(DoubleDummyTable::OPTIMUM_RESULT_TABLE_HEADER.to_string()),
+        ));
     }
+}
 
-    // Date tag
-    if let Some(ref date) = board.date {
-        lines.push(format!("[Date \"{}\"]", date));
-    } else {
-        lines.push("[Date \"\"]".to_string());
+/// Write games to PBN format, keeping all of their tags, the auction and
+/// play sections and their notes
+pub fn write_pbn_games(games: &[Game], options: &PbnWriteOptions) -> String {
+    write_pbn_games_with_report(games, options).0
+}
+
+/// Write games to PBN format as by [`write_pbn_games`], also returning
+/// what ASCII-only mode transliterated; the report is empty otherwise
+pub fn write_pbn_games_with_report(
+    games: &[Game],
+    options: &PbnWriteOptions,
+) -> (String, AsciiReport) {
+    let mut report = AsciiReport::default();
+    let mut output = String::new();
+    write_header(&mut output, options);
+
+    for (i, game) in games.iter().enumerate() {
+        if i > 0 {
+            output.push('\n');
+        }
+        output.push_str(&game_to_pbn_reported(game, options, &mut report));
     }
 
-    // Board number
-    if let Some(num) = board.number {
-        lines.push(format!("[Board \"{}\"]", num));
+    (options.common.finish(output), report)
+}
+
+/// Convert a game to PBN format.
+///
+/// Board data is written as by [`board_to_pbn_with`], with the game's
+/// other tags filling in the empty mandatory tags. The Deal tag starts
+/// with the same seat as the game's original Deal tag, so converted files
+/// stay close to their source. Auction, play and table
+/// sections follow their tags; the auction and play are each followed by
+/// the notes they reference. Notes of the same section with identical
+/// text are written once and referenced from each call or card that used
+/// them, keeping their numbers; auction notes are left out with
+/// [`NoteStyle::Inline`]. Tags are written in the order set by
+/// [`PbnWriteOptions::tag_order`]. A game with an auction but no Auction tag gets
+/// one, and empty Contract and Declarer tags are filled from the auction;
+/// likewise for a double dummy table and its OptimumResultTable tag.
+pub fn game_to_pbn_with(game: &Game, options: &PbnWriteOptions) -> String {
+    game_to_pbn_reported(game, options, &mut AsciiReport::default())
+}
+
+/// [`game_to_pbn_with`], adding what ASCII-only mode transliterated to
+/// `report`
+fn game_to_pbn_reported(
+    game: &Game,
+    options: &PbnWriteOptions,
+    report: &mut AsciiReport,
+) -> String {
+    let mut game = game.clone();
+    game.merge_duplicate_notes();
+    if options.common.ascii_only {
+        for pair in &mut game.tags {
+            ascii_value(&mut pair.value, report);
+        }
+        for block in &mut game.commentary {
+            ascii_value(&mut block.text, report);
+        }
+    }
+    if game.optimum_result_table.is_none() {
+        game.optimum_result_table = board_dd_table(&game.board, options);
+    }
+    let game = &game;
+    let deal_first = game.deal_tag().map(|deal| deal.first);
+    let mut tags = board_tags(&game.board, deal_first, options);
+    let mut notes: Vec<(NoteSection, Option<u32>, &str)> = Vec::new();
+    let mut note_section = NoteSection::Auction;
+    for pair in &game.tags {
+        match pair.tag {
+            PbnTag::Auction => note_section = NoteSection::Auction,
+            PbnTag::Play => note_section = NoteSection::Play,
+            PbnTag::Note => {
+                let number = note_number(&pair.value).map(|(n, _)| n);
+                notes.push((note_section, number, &pair.value));
+                continue;
+            }
+            _ => {}
+        }
+        if APPLIED_TAGS.contains(&pair.tag) {
+            continue;
+        }
+        match tags.iter_mut().find(|(tag, _)| *tag == pair.tag) {
+            Some((_, value)) => {
+                if value.is_none() {
+                    *value = Some(pair.value.clone());
+                }
+            }
+            None => tags.push((pair.tag.clone(), Some(pair.value.clone()))),
+        }
+    }
+    if let Some(contract) = &game.contract {
+        if let Some((_, value)) = tags.iter_mut().find(|(tag, _)| *tag == PbnTag::Contract) {
+            *value = Some(format_contract(contract));
+        }
     }
+    if let Some(auction) = &game.auction {
+        auction_tags(&mut tags, auction, game.contract.is_none());
+    }
+    if let Some(table) = &game.optimum_result_table {
+        optimum_result_table_tag(&mut tags, table);
+    }
+    apply_tag_options(&mut tags, options);
+    ascii_tags(&mut tags, options, report);
+    let source: Vec<PbnTag> = game.tags.iter().map(|pair| pair.tag.clone()).collect();
+    options.tag_order.sort(&mut tags, &source);
 
-    // Player names (empty for hand records)
-    lines.push("[West \"\"]".to_string());
-    lines.push("[North \"\"]".to_string());
-    lines.push("[East \"\"]".to_string());
-    lines.push("[South \"\"]".to_string());
+    let mut commentary: Vec<&Commentary> = game.commentary.iter().collect();
+    let mut lines = Vec::new();
+    push_commentary(&mut lines, &mut commentary, |tag| tag.is_none());
+    for (tag, value) in &tags {
+        push_optional_tag(&mut lines, tag, value.as_deref(), options);
+        let section: Option<(String, Option<NoteSection>, Vec<u32>)> = match tag {
+            PbnTag::Auction => game.auction.as_ref().map(|auction| {
+                (
+                    auction.to_pbn_with(options.alert_mark, options.note_style),
+                    Some(NoteSection::Auction),
+                    auction.calls.iter().filter_map(|c| c.note).collect(),
+                )
+            }),
+            PbnTag::Play => game.play.as_ref().map(|play| {
+                (
+                    play.to_pbn(),
+                    Some(NoteSection::Play),
+                    play.notes.iter().map(|n| n.note).collect(),
+                )
+            }),
+            PbnTag::OptimumResultTable => game
+                .optimum_result_table
+                .as_ref()
+                .map(|table| (table.to_optimum_result_table(), None, Vec::new())),
+            _ => game
+                .table(tag)
+                .map(|table| (table.to_pbn(), None, Vec::new())),
+        };
+        if let Some((section, note_section, referenced)) = section {
+            lines.extend(section.lines().map(str::to_string));
+            // Inline notes are dropped along with their references
+            let inline = *tag == PbnTag::Auction && options.note_style == NoteStyle::Inline;
+            notes.retain(|&(section, number, value)| {
+                let keep = Some(section) != note_section
+                    || number.is_none_or(|n| !referenced.contains(&n));
+                if !keep && !inline {
+                    push_note(&mut lines, value, options);
+                }
+                keep
+            });
+        }
+        push_commentary(&mut lines, &mut commentary, |t| t == Some(tag));
+    }
+    for (_, _, value) in notes {
+        push_note(&mut lines, value, options);
+    }
+    push_commentary(&mut lines, &mut commentary, |_| true);
 
-    // Dealer
-    if let Some(dealer) = board.dealer {
-        lines.push(format!("[Dealer \"{}\"]", dealer.to_char()));
+    lines.join("\n") + "\n"
+}
+
+/// Add the Auction tag for a game whose auction was set without one, and
+/// with `fill_contract` fill empty Contract and Declarer tags from the
+/// auction
+fn auction_tags(tags: &mut Vec<(PbnTag, Option<String>)>, auction: &Auction, fill_contract: bool) {
+    if !tags.iter().any(|(tag, _)| *tag == PbnTag::Auction) {
+        tags.push((PbnTag::Auction, Some(auction.first.to_char().to_string())));
     }
+    if !fill_contract {
+        return;
+    }
+    let passed_out = auction.calls.len() >= 4 && auction.calls.iter().all(|c| c.call == Call::Pass);
+    let (contract, declarer) = match auction.contract() {
+        Some((contract, declarer)) => (format_contract(&contract), Some(declarer)),
+        None if passed_out => ("Pass".to_string(), None),
+        None => return,
+    };
+    for (tag, value) in tags.iter_mut() {
+        let fill = match tag {
+            PbnTag::Contract => Some(contract.clone()),
+            PbnTag::Declarer => declarer.map(|seat| seat.to_char().to_string()),
+            _ => None,
+        };
+        if value.as_deref().is_none_or(str::is_empty) && fill.is_some() {
+            *value = fill;
+        }
+    }
+}
+
+/// Which tags of the mandatory tag set a board has values for
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MandatoryTags {
+    /// Mandatory tags the board has no value for, in export order
+    pub missing: Vec<PbnTag>,
+    /// The missing tags that export writes with an empty value; the
+    /// others are left out
+    pub back_filled: Vec<PbnTag>,
+}
 
-    // Vulnerability
-    lines.push(format!("[Vulnerable \"{}\"]", board.vulnerable.to_pbn()));
+impl MandatoryTags {
+    /// Whether the board has a value for every mandatory tag
+    pub fn is_complete(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
 
-    // Deal
-    let first_dir = board.dealer.unwrap_or(Direction::North);
-    lines.push(format!("[Deal \"{}\"]", board.deal.to_pbn(first_dir)));
+/// Check a board against the mandatory tag set, reporting which tags
+/// [`board_to_pbn_with`] would back-fill with `options`.
+///
+/// Board data has no players, scoring or result, so those tags are always
+/// missing, while Vulnerable never is; a Deal without cards counts as
+/// missing.
+pub fn check_mandatory_tags(board: &Board, options: &PbnWriteOptions) -> MandatoryTags {
+    let mut tags = board_tags(board, None, options);
+    apply_tag_options(&mut tags, options);
+    let mut report = MandatoryTags::default();
+    for tag in PbnTag::MANDATORY {
+        let written = tags.iter().find(|(t, _)| *t == tag);
+        let has_value = match tag {
+            PbnTag::Deal => SeatOrder::NESW
+                .into_iter()
+                .any(|seat| !board.deal.hand(seat).is_empty()),
+            _ => written.is_some_and(|(_, value)| value.is_some()),
+        };
+        if has_value {
+            continue;
+        }
+        if options.empty_tags && written.is_some_and(|(_, value)| value.is_none()) {
+            report.back_filled.push(tag.clone());
+        }
+        report.missing.push(tag);
+    }
+    report
+}
 
-    // Scoring (empty for hand records)
-    lines.push("[Scoring \"\"]".to_string());
-    lines.push("[Declarer \"\"]".to_string());
-    lines.push("[Contract \"\"]".to_string());
-    lines.push("[Result \"\"]".to_string());
+/// Write and remove the commentary blocks whose tag matches `after`.
+/// Blocks inside a section are written after the whole section; long
+/// lines are folded to [`MAX_LINE_LENGTH`].
+fn push_commentary(
+    lines: &mut Vec<String>,
+    commentary: &mut Vec<&Commentary>,
+    after: impl Fn(Option<&PbnTag>) -> bool,
+) {
+    commentary.retain(|block| {
+        let keep = !after(block.tag.as_ref());
+        if !keep {
+            lines.push(fold_lines(&format!("{{{}}}", block.text)).into_owned());
+        }
+        keep
+    });
+}
+
+/// `text` with its lines longer than [`MAX_LINE_LENGTH`] folded.
+///
+/// Only those lines change: each is broken at its last space within the
+/// limit, the line break taking the place of that one space, and a word
+/// longer than the limit goes on a line of its own. The text's own line
+/// breaks and all other whitespace are kept.
+fn fold_lines(text: &str) -> Cow<'_, str> {
+    if text
+        .split('\n')
+        .all(|line| line.chars().count() <= MAX_LINE_LENGTH)
+    {
+        return Cow::Borrowed(text);
+    }
+    let mut folded = Vec::new();
+    for line in text.split('\n') {
+        let mut rest = line;
+        while rest.chars().count() > MAX_LINE_LENGTH {
+            let Some((limit, c)) = rest.char_indices().nth(MAX_LINE_LENGTH) else {
+                break;
+            };
+            // A space right at the limit still leaves a full first line
+            let space = rest[..limit + c.len_utf8()]
+                .rfind(' ')
+                .filter(|&i| i > 0)
+                .or_else(|| rest[limit..].find(' ').map(|i| limit + i));
+            let Some(space) = space else {
+                break;
+            };
+            folded.push(&rest[..space]);
+            rest = &rest[space + 1..];
+        }
+        folded.push(rest);
+    }
+    Cow::Owned(folded.join("\n"))
+}
+
+/// The tags written for a board, in no particular order. The Deal tag
+/// starts with `deal_first`, or else with the dealer.
+fn board_tags(
+    board: &Board,
+    deal_first: Option<Direction>,
+    options: &PbnWriteOptions,
+) -> Vec<(PbnTag, Option<String>)> {
+    let first_dir = deal_first.or(board.dealer).unwrap_or(Direction::North);
+
+    // Player names, scoring and result are empty for hand records
+    let mut tags: Vec<(PbnTag, Option<String>)> = vec![
+        (PbnTag::Event, board.event.clone()),
+        (PbnTag::Site, board.site.clone()),
+        (PbnTag::Date, board.date.clone()),
+        (PbnTag::West, None),
+        (PbnTag::North, None),
+        (PbnTag::East, None),
+        (PbnTag::South, None),
+        (
+            PbnTag::Vulnerable,
+            Some(board.vulnerable.to_pbn().to_string()),
+        ),
+        (
+            PbnTag::Deal,
+            Some(deal_value(
+                board,
+                first_dir,
+                &options
+                    .common
+                    .absent_seats(&board.deal, &options.hidden_hands),
+            )),
+        ),
+        (PbnTag::Scoring, None),
+        (PbnTag::Declarer, None),
+        (PbnTag::Contract, None),
+        (PbnTag::Result, None),
+    ];
+    if let Some(num) = board.number {
+        tags.push((PbnTag::Board, Some(num.to_string())));
+    }
+    if let Some(dealer) = board.dealer {
+        tags.push((PbnTag::Dealer, Some(dealer.to_char().to_string())));
+    }
 
     // Analysis tags if present
-    if let Some(ref dd) = board.double_dummy_tricks {
-        lines.push(format!("[DoubleDummyTricks \"{}\"]", dd));
+    if options.analysis_tags {
+        for (tag, value) in [
+            (PbnTag::DoubleDummyTricks, &board.double_dummy_tricks),
+            (PbnTag::OptimumScore, &board.optimum_score),
+            (PbnTag::ParContract, &board.par_contract),
+        ] {
+            if value.is_some() {
+                tags.push((tag, value.clone()));
+            }
+        }
+    }
+
+    tags
+}
+
+/// The Deal tag value, with hidden hands replaced by `-`
+fn deal_value(board: &Board, first_dir: Direction, hidden: &[Direction]) -> String {
+    let full = board.deal.to_pbn(first_dir);
+    if hidden.is_empty() {
+        return full;
+    }
+
+    let Some((first, hands)) = full.split_once(':') else {
+        return full;
+    };
+    let hands: Vec<&str> = hands
+        .split_whitespace()
+        .zip(SeatOrder::clockwise_from(first_dir))
+        .map(|(hand, seat)| if hidden.contains(&seat) { "-" } else { hand })
+        .collect();
+
+    format!("{}:{}", first, hands.join(" "))
+}
+
+/// Remove the excluded tags and fill in default values
+fn apply_tag_options(tags: &mut Vec<(PbnTag, Option<String>)>, options: &PbnWriteOptions) {
+    for (tag, default) in &options.default_values {
+        match tags.iter_mut().find(|(t, _)| t == tag) {
+            Some((_, value)) => {
+                if value.as_deref().is_none_or(str::is_empty) {
+                    *value = Some(default.clone());
+                }
+            }
+            None => tags.push((tag.clone(), Some(default.clone()))),
+        }
+    }
+    tags.retain(|(tag, _)| !options.excluded_tags.contains(tag));
+}
+
+/// Push a tag that has a value, or an empty tag if `options.empty_tags` is set
+fn push_optional_tag(
+    lines: &mut Vec<String>,
+    tag: &PbnTag,
+    value: Option<&str>,
+    options: &PbnWriteOptions,
+) {
+    match value {
+        Some(value) => lines.push(tag_line(tag, &clean_value(value, options))),
+        None if options.empty_tags => lines.push(format!("[{} \"\"]", tag)),
+        None => {}
+    }
+}
+
+/// Push a Note tag, with its suit escapes written as `options` asks
+fn push_note(lines: &mut Vec<String>, value: &str, options: &PbnWriteOptions) {
+    let value = options.note_suits.apply(value);
+    lines.push(tag_line(&PbnTag::Note, &clean_value(&value, options)));
+}
+
+/// Transliterate the tag values to ASCII if `options` asks for it. This
+/// is done before the values are escaped, so that a curly quote becomes
+/// an escaped `\"`
+fn ascii_tags(
+    tags: &mut [(PbnTag, Option<String>)],
+    options: &PbnWriteOptions,
+    report: &mut AsciiReport,
+) {
+    if options.common.ascii_only {
+        for value in tags.iter_mut().filter_map(|(_, value)| value.as_mut()) {
+            ascii_value(value, report);
+        }
     }
-    if let Some(ref opt) = board.optimum_score {
-        lines.push(format!("[OptimumScore \"{}\"]", opt));
+}
+
+/// Transliterate `value` to ASCII, adding what changed to `report`
+fn ascii_value(value: &mut String, report: &mut AsciiReport) {
+    if !value.is_ascii() {
+        let (ascii, changes) = to_ascii(value);
+        *value = ascii;
+        report.merge(changes);
     }
-    if let Some(ref par) = board.par_contract {
-        lines.push(format!("[ParContract \"{}\"]", par));
+}
+
+/// `value` with its control characters replaced by spaces, if `options`
+/// asks for it
+fn clean_value<'v>(value: &'v str, options: &PbnWriteOptions) -> Cow<'v, str> {
+    if options.replace_control_chars && value.contains(char::is_control) {
+        Cow::Owned(value.replace(char::is_control, " "))
+    } else {
+        Cow::Borrowed(value)
     }
+}
 
-    lines.join("\n") + "\n"
+/// Check that no tag value of a game contains a control character, and
+/// that no tag line is longer than [`MAX_LINE_LENGTH`], as the PBN
+/// standard requires; the [`ParseError::TagValue`] error names the first
+/// offending tag.
+///
+/// Tag lines, unlike commentary, can't be folded, so the writers write an
+/// over-long one whole; check a game with this first to refuse it.
+pub fn check_tag_values(game: &Game) -> crate::Result<()> {
+    let board_tags = board_tags(&game.board, None, &PbnWriteOptions::default());
+    let values = board_tags
+        .iter()
+        .filter_map(|(tag, value)| Some((tag, value.as_deref()?)))
+        .chain(
+            game.tags
+                .iter()
+                .map(|pair| (&pair.tag, pair.value.as_str())),
+        );
+    for (tag, value) in values {
+        if value.contains(char::is_control) {
+            return Err(ParseError::TagValue {
+                tag: tag.to_string(),
+                message: "control character".to_string(),
+            });
+        }
+        let length = tag_line(tag, value).chars().count();
+        if length > MAX_LINE_LENGTH {
+            return Err(ParseError::TagValue {
+                tag: tag.to_string(),
+                message: format!(
+                    "tag line is {} characters long, over {}",
+                    length, MAX_LINE_LENGTH
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// A tag pair line, escaping quotes and the backslashes that would
+/// otherwise be read as escapes
+fn tag_line(tag: &PbnTag, value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' if matches!(chars.peek(), None | Some('"') | Some('\\')) => {
+                escaped.push_str("\\\\")
+            }
+            _ => escaped.push(c),
+        }
+    }
+    format!("[{} \"{}\"]", tag, escaped)
 }
 
 /// Write boards to a PBN file
 pub fn write_pbn_file(boards: &[Board], path: &std::path::Path) -> std::io::Result<()> {
-    let content = write_pbn(boards);
-    std::fs::write(path, content)
+    let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+    write_pbn_to(&mut writer, boards)?;
+    writer.flush()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use bridge_types::{Deal, Vulnerability};
+    use bridge_types::{Deal, Strain, Vulnerability};
+
+    #[test]
+    fn test_check_mandatory_tags() {
+        let mut board = Board::new().with_number(3);
+        board.event = Some("Club".to_string());
+        let report = check_mandatory_tags(&board, &PbnWriteOptions::default());
+        assert_eq!(
+            report.missing,
+            [
+                PbnTag::Site,
+                PbnTag::Date,
+                PbnTag::West,
+                PbnTag::North,
+                PbnTag::East,
+                PbnTag::South,
+                PbnTag::Dealer,
+                PbnTag::Deal,
+                PbnTag::Scoring,
+                PbnTag::Declarer,
+                PbnTag::Contract,
+                PbnTag::Result,
+            ]
+        );
+        assert!(!report.back_filled.contains(&PbnTag::Dealer));
+        assert!(report.back_filled.contains(&PbnTag::Site));
+        assert!(!report.is_complete());
+
+        let options = ExportProfile::BboUpload.options();
+        assert!(check_mandatory_tags(&board, &options)
+            .back_filled
+            .is_empty());
+    }
 
     #[test]
     fn test_write_simple_board() {
@@ -121,6 +984,46 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_tag_values_escaped() {
+        use crate::pbn::read_pbn;
+
+        for event in [r#"The "Big" Cup"#, r"back\slash", r"trailing\", r#"\""#] {
+            let mut board = Board::new();
+            board.event = Some(event.to_string());
+            let pbn = board_to_pbn(&board);
+            assert_eq!(read_pbn(&pbn).unwrap()[0].event.as_deref(), Some(event));
+        }
+        assert_eq!(
+            tag_line(&PbnTag::Event, r#"The "Big" Cup"#),
+            r#"[Event "The \"Big\" Cup"]"#
+        );
+        assert_eq!(tag_line(&PbnTag::Event, r"A\2R"), r#"[Event "A\2R"]"#);
+    }
+
+    #[test]
+    fn test_control_chars_in_values() {
+        let mut game = Game::new(Board::new().with_number(1));
+        game.set_player(Direction::North, "Smith\n[Board \"9\"]");
+        let err = check_tag_values(&game).unwrap_err();
+        assert!(matches!(&err, ParseError::TagValue { tag, .. } if tag == "North"));
+        assert_eq!(
+            err.to_string(),
+            "Invalid North tag value: control character"
+        );
+
+        let pbn = game_to_pbn_with(&game, &PbnWriteOptions::default());
+        assert!(pbn.contains(r#"[North "Smith [Board \"9\"]"]"#));
+        assert_eq!(crate::pbn::read_pbn_games(&pbn).unwrap().len(), 1);
+
+        let options = PbnWriteOptions::default().with_replace_control_chars(false);
+        let pbn = game_to_pbn_with(&game, &options);
+        assert!(pbn.contains("[North \"Smith\n[Board"));
+
+        game.set_player(Direction::North, "Smith");
+        assert!(check_tag_values(&game).is_ok());
+    }
+
     #[test]
     fn test_write_pbn_header() {
         let boards = vec![];
@@ -130,6 +1033,144 @@ mod tests {
         assert!(pbn.contains("% EXPORT"));
     }
 
+    #[test]
+    fn test_write_pbn_to_matches_write_pbn() {
+        let boards: Vec<Board> = (1..=3).map(|n| Board::new().with_number(n)).collect();
+        for layout in [
+            WriteOptions::default(),
+            WriteOptions::default()
+                .with_line_ending(crate::LineEnding::CrLf)
+                .with_trailing_newline(false),
+        ] {
+            let options = PbnWriteOptions::default().with_common(layout);
+            let mut out = Vec::new();
+            write_pbn_to_with(&mut out, &boards, &options).unwrap();
+            assert_eq!(
+                String::from_utf8(out).unwrap(),
+                write_pbn_with(&boards, &options)
+            );
+        }
+        let mut out = Vec::new();
+        write_pbn_to_with(
+            &mut out,
+            &[],
+            &PbnWriteOptions::default().with_header(false),
+        )
+        .unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_ascii_only() {
+        let mut board = Board::new().with_number(1);
+        board.event = Some("Coupe d’Été".to_string());
+        let options =
+            PbnWriteOptions::default().with_common(WriteOptions::default().with_ascii_only(true));
+        let pbn = write_pbn_with(std::slice::from_ref(&board), &options);
+        assert!(pbn.is_ascii());
+        assert!(pbn.contains("[Event \"Coupe d'Ete\"]"));
+
+        let mut out = Vec::new();
+        write_pbn_to_with(&mut out, [&board], &options).unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), pbn);
+    }
+
+    #[test]
+    fn test_long_commentary_folded() {
+        let word = "abcdefghi ";
+        let text = word.repeat(60);
+        let pbn = format!("[Board \"1\"]\n{{{}}}\n", text.trim_end());
+        let games = crate::pbn::read_pbn_games(&pbn).unwrap();
+        let written = game_to_pbn_with(&games[0], &PbnWriteOptions::default());
+        assert!(written
+            .lines()
+            .all(|l| l.chars().count() <= MAX_LINE_LENGTH));
+        assert!(crate::pbn::lint(&written)
+            .iter()
+            .all(|issue| issue.kind != crate::pbn::LintKind::LineTooLong));
+        let reread = crate::pbn::read_pbn_games(&written).unwrap();
+        assert_eq!(reread[0].commentary[0].text.split_whitespace().count(), 60);
+        assert_eq!(
+            reread[0].commentary[0].text.replace('\n', " "),
+            text.trim_end()
+        );
+    }
+
+    #[test]
+    fn test_fold_lines() {
+        let short = "a  b\n\n  c\td ";
+        assert_eq!(fold_lines(short), short);
+
+        // Only the long line is broken, at a single space
+        let long = format!("{}  {}", "x ".repeat(130), "y".repeat(10));
+        let text = format!("first  line\n{}\nlast", long);
+        let folded = fold_lines(&text);
+        assert!(folded.lines().all(|l| l.chars().count() <= MAX_LINE_LENGTH));
+        assert!(folded.starts_with("first  line\n"));
+        assert!(folded.ends_with("\nlast"));
+        let lines: Vec<&str> = folded.lines().collect();
+        assert_eq!(lines.len(), 4);
+        assert_eq!(lines[1].chars().count(), MAX_LINE_LENGTH);
+        assert_eq!(lines[2], "x x  yyyyyyyyyy");
+
+        // A word over the limit stands on its own line
+        let url = "u".repeat(300);
+        let folded = fold_lines(&format!("see {} for more", url));
+        assert_eq!(folded, format!("see\n{}\nfor more", url));
+    }
+
+    #[test]
+    fn test_long_tag_line_rejected() {
+        let mut game = Game::new(Board::new().with_number(1));
+        game.board.event = Some("x".repeat(250));
+        let err = check_tag_values(&game).unwrap_err();
+        assert!(matches!(&err, ParseError::TagValue { tag, .. } if tag == "Event"));
+        assert!(err.to_string().contains("over 255"));
+
+        game.board.event = Some("x".repeat(200));
+        assert!(check_tag_values(&game).is_ok());
+    }
+
+    #[test]
+    fn test_ascii_only_quotes() {
+        use crate::pbn::read_pbn_games;
+
+        let mut game = Game::new(Board::new().with_number(1));
+        game.board.event = Some("The \u{201C}Open\u{201D} Pairs".to_string());
+        game.commentary.push(Commentary {
+            text: "A \u{201C}safe\u{201D} lead".to_string(),
+            tag: Some(PbnTag::Board),
+            position: None,
+        });
+        let options =
+            PbnWriteOptions::default().with_common(WriteOptions::default().with_ascii_only(true));
+        let (pbn, report) = write_pbn_games_with_report(std::slice::from_ref(&game), &options);
+        assert!(pbn.is_ascii());
+        assert!(pbn.contains(r#"[Event "The \"Open\" Pairs"]"#));
+        assert!(pbn.contains("{A \"safe\" lead}"));
+        assert_eq!(report.changes.len(), 2);
+        assert_eq!(report.changes[0].original, '\u{201C}');
+        assert_eq!(report.changes[0].count, 2);
+
+        let reread = read_pbn_games(&pbn).unwrap();
+        assert_eq!(reread[0].board.event.as_deref(), Some("The \"Open\" Pairs"));
+
+        let (_, report) = write_pbn_games_with_report(&[game], &PbnWriteOptions::default());
+        assert!(report.is_empty());
+    }
+
+    #[test]
+    fn test_header_options() {
+        let options = PbnWriteOptions::default()
+            .with_version("1.0")
+            .with_export(false)
+            .with_default_value(PbnTag::Event, "Club Pairs")
+            .with_common(WriteOptions::default().with_line_ending(crate::LineEnding::CrLf));
+        let pbn = write_pbn_with(&[Board::new().with_number(1)], &options);
+        assert!(pbn.starts_with("% PBN 1.0\r\n\r\n[Event \"Club Pairs\"]\r\n"));
+        assert!(!pbn.contains("% EXPORT"));
+    }
+
     #[test]
     fn test_round_trip() {
         use crate::pbn::read_pbn;
@@ -150,4 +1191,390 @@ mod tests {
         assert_eq!(boards[0].number, Some(1));
         assert_eq!(boards[0].dealer, Some(Direction::North));
     }
+
+    #[test]
+    fn test_export_profiles() {
+        let mut board = Board::new()
+            .with_number(1)
+            .with_dealer(Direction::North)
+            .with_vulnerability(Vulnerability::None);
+        board.double_dummy_tricks = Some("88667799555566775555".to_string());
+
+        let bbo = board_to_pbn_with(&board, &ExportProfile::BboUpload.into());
+        assert!(!bbo.contains("[West \"\"]"));
+        assert!(!bbo.contains("[Event \"\"]"));
+        assert!(!bbo.contains("DoubleDummyTricks"));
+        assert!(bbo.contains("[Board \"1\"]"));
+
+        let acbl = board_to_pbn_with(&board, &ExportProfile::AcblHandRecords.options());
+        assert!(acbl.contains("[West \"\"]"));
+        assert!(acbl.contains("DoubleDummyTricks"));
+        assert!(acbl.contains("[OptimumResultTable "));
+
+        let boards = std::slice::from_ref(&board);
+        let composer = write_pbn_with(boards, &ExportProfile::BridgeComposer.into());
+        assert!(composer.starts_with("% PBN 2.1\n\n"));
+        assert!(!composer.contains("OptimumResultTable"));
+        assert_eq!(
+            ExportProfile::BridgeComposer.options().tag_order,
+            TagOrder::Input
+        );
+
+        let outputs: Vec<String> = [
+            ExportProfile::BboUpload,
+            ExportProfile::BridgeComposer,
+            ExportProfile::AcblHandRecords,
+        ]
+        .into_iter()
+        .map(|profile| write_pbn_with(boards, &profile.into()))
+        .collect();
+        assert_ne!(outputs[0], outputs[1]);
+        assert_ne!(outputs[1], outputs[2]);
+        assert_ne!(outputs[0], outputs[2]);
+    }
+
+    #[test]
+    fn test_tag_selection_and_defaults() {
+        let mut board = Board::new().with_number(4);
+        board.site = Some("Club".to_string());
+        let options = PbnWriteOptions::default()
+            .with_empty_tags(false)
+            .with_excluded_tags(vec![PbnTag::Site])
+            .with_default_value(PbnTag::Scoring, "MP")
+            .with_default_value(PbnTag::Annotator, "Director");
+
+        let pbn = board_to_pbn_with(&board, &options);
+        assert!(!pbn.contains("[Site"));
+        assert!(!pbn.contains("[West"));
+        assert!(pbn.contains("[Scoring \"MP\"]\n"));
+        assert!(pbn.contains("[Annotator \"Director\"]\n"));
+
+        let report = check_mandatory_tags(&board, &options);
+        assert!(report.missing.contains(&PbnTag::Site));
+        assert!(!report.missing.contains(&PbnTag::Scoring));
+    }
+
+    #[test]
+    fn test_optimum_result_table_written() {
+        let mut table = DoubleDummyTable::new();
+        for seat in Direction::ALL {
+            for strain in [
+                Strain::NoTrump,
+                Strain::Spades,
+                Strain::Hearts,
+                Strain::Diamonds,
+                Strain::Clubs,
+            ] {
+                let tricks = if matches!(seat, Direction::North | Direction::South) {
+                    9
+                } else {
+                    4
+                };
+                table.set(seat, strain, tricks);
+            }
+        }
+        let mut game = Game::new(Board::new().with_number(1));
+        game.set_double_dummy_table(table);
+        let pbn = game_to_pbn_with(&game, &PbnWriteOptions::default());
+        assert!(pbn.contains("[DoubleDummyTricks \"99999999994444444444\"]\n"));
+        assert!(pbn.contains(
+            "[OptimumResultTable \"Declarer;Denomination\\2R;Result\\2R\"]\nN NT 9\nN S 9\n"
+        ));
+        assert_eq!(
+            crate::pbn::read_pbn_games(&pbn).unwrap()[0].optimum_result_table,
+            Some(table)
+        );
+
+        let options = PbnWriteOptions::default().with_optimum_result_table(true);
+        let board_pbn = board_to_pbn_with(&game.board, &options);
+        assert!(board_pbn.ends_with("W C 4\n"));
+        assert!(!board_to_pbn(&game.board).contains("OptimumResultTable"));
+    }
+
+    #[test]
+    fn test_tags_in_export_order() {
+        let mut board = Board::new().with_number(3).with_dealer(Direction::South);
+        board.event = Some("Club".to_string());
+        board.par_contract = Some("4S N".to_string());
+
+        let pbn = board_to_pbn(&board);
+        let names: Vec<PbnTag> = pbn
+            .lines()
+            .filter_map(|line| super::super::reader::parse_tag_pair(line).map(|t| t.tag))
+            .collect();
+        let mut sorted = names.clone();
+        sorted.sort_by(|a, b| a.export_order(b));
+        assert_eq!(names, sorted);
+        assert_eq!(names[0], PbnTag::Event);
+        assert_eq!(names.last(), Some(&PbnTag::ParContract));
+    }
+
+    #[test]
+    fn test_game_tag_orders() {
+        use crate::pbn::read_pbn_games;
+
+        let pbn = "[Board \"2\"]\n[Dealer \"E\"]\n[Event \"Club\"]\n[Vulnerable \"NS\"]\n[Annotator \"Jo\"]\n";
+        let games = read_pbn_games(pbn).unwrap();
+        let names = |options: &PbnWriteOptions| -> Vec<PbnTag> {
+            game_to_pbn_with(&games[0], options)
+                .lines()
+                .filter_map(|line| super::super::reader::parse_tag_pair(line).map(|t| t.tag))
+                .collect()
+        };
+
+        let export = names(&PbnWriteOptions::default());
+        let mut sorted = export.clone();
+        sorted.sort_by(|a, b| a.export_order(b));
+        assert_eq!(export, sorted);
+        assert_eq!(export[0], PbnTag::Event);
+
+        let input = names(&PbnWriteOptions::default().with_tag_order(TagOrder::Input));
+        assert_eq!(
+            input[..5],
+            [
+                PbnTag::Board,
+                PbnTag::Dealer,
+                PbnTag::Event,
+                PbnTag::Vulnerable,
+                PbnTag::Annotator
+            ]
+        );
+        // Tags the source didn't have follow in export order
+        let added: Vec<PbnTag> = export
+            .iter()
+            .filter(|tag| !input[..5].contains(tag))
+            .cloned()
+            .collect();
+        assert_eq!(input[5..], added[..]);
+    }
+
+    #[test]
+    fn test_mandatory_tag_sequence() {
+        let board = Board::new().with_number(1).with_dealer(Direction::North);
+        let tags: Vec<PbnTag> = board_to_pbn(&board)
+            .lines()
+            .filter_map(|line| super::super::reader::parse_tag_pair(line).map(|t| t.tag))
+            .collect();
+        assert_eq!(tags, PbnTag::MANDATORY);
+    }
+
+    #[test]
+    fn test_hidden_hands() {
+        let deal =
+            Deal::from_pbn("N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ")
+                .unwrap();
+        let board = Board::new()
+            .with_number(1)
+            .with_dealer(Direction::East)
+            .with_deal(deal);
+        let options = PbnWriteOptions {
+            hidden_hands: vec![Direction::East, Direction::West],
+            ..PbnWriteOptions::default()
+        };
+
+        let pbn = board_to_pbn_with(&board, &options);
+        assert!(pbn.contains("[Deal \"E:- 962.AJ7.KT82.J75 - K843.T542.J6.863\"]"));
+    }
+
+    #[test]
+    fn test_partial_deal() {
+        let full =
+            Deal::from_pbn("N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ")
+                .unwrap();
+        let mut deal = Deal::new();
+        deal.set_hand(Direction::North, full.hand(Direction::North).clone());
+        deal.set_hand(Direction::South, full.hand(Direction::South).clone());
+        let board = Board::new().with_dealer(Direction::North).with_deal(deal);
+
+        let options =
+            PbnWriteOptions::default().with_common(WriteOptions::new().with_partial_deals(true));
+        let pbn = board_to_pbn_with(&board, &options);
+        assert!(pbn.contains("[Deal \"N:K843.T542.J6.863 - 962.AJ7.KT82.J75 -\"]"));
+    }
+
+    #[test]
+    fn test_crlf_layout() {
+        let options = PbnWriteOptions::default().with_common(
+            WriteOptions::new()
+                .with_line_ending(crate::LineEnding::CrLf)
+                .with_trailing_newline(false),
+        );
+        let pbn = write_pbn_with(&[Board::new().with_number(1)], &options);
+        assert!(pbn.starts_with("% PBN 2.1\r\n% EXPORT\r\n\r\n"));
+        assert!(!pbn.replace("\r\n", "").contains('\n'));
+        assert!(pbn.ends_with("\"]"));
+    }
+
+    #[test]
+    fn test_game_round_trip_keeps_notes() {
+        use crate::pbn::read_pbn_games;
+
+        let pbn = r#"[Board "1"]
+[West "Wes"]
+[North "Nora"]
+[East "Ed"]
+[South "Sam"]
+[Dealer "N"]
+[Declarer "S"]
+[Contract "3NT"]
+[Result "9"]
+[BCFlags "1f"]
+[Auction "N"]
+1C! =1= Pass 1NT Pass
+3NT AP
+[Note "1:could be short"]
+[Play "W"]
+S2 =2= SA S3 S4
+*
+[Note "2:fourth best"]
+[OptimumResultTable "Declarer;Denomination\2R;Result\2R"]
+N NT 9
+S NT 9
+[ScoreTable "PairId_NS\2R;PairId_EW\2R;Contract\4L;Declarer\1R;Result\2R"]
+ 1  2 3NT  S  9
+ 3  4 4S   N 10
+[TotalScoreTable "Rank\2R;PairId\2R;TotalPercentage\6R;Names\20L"]
+ 1  3  62.50 "Ann - Bob"
+ 2  1  37.50 "Cy - Di"
+[InstantScoreTable "PairId_NS\2R;PairId_EW\2R;Score_NS\6R"]
+ 1  2    400
+"#;
+        let games = read_pbn_games(pbn).unwrap();
+        let written = write_pbn_games(&games, &PbnWriteOptions::default());
+        let reread = read_pbn_games(&written).unwrap();
+
+        assert!(written.contains("[North \"Nora\"]"));
+        assert_eq!(reread[0].extra_tags().len(), 1);
+        assert_eq!(reread[0].extra_tags()[0].value, "1f");
+        assert_eq!(reread[0].auction, games[0].auction);
+        assert_eq!(reread[0].play, games[0].play);
+        assert_eq!(
+            reread[0].optimum_result_table,
+            games[0].optimum_result_table
+        );
+        assert_eq!(reread[0].score_table, games[0].score_table);
+        assert_eq!(reread[0].score_table.as_ref().unwrap().rows.len(), 2);
+        assert_eq!(reread[0].total_score_table, games[0].total_score_table);
+        assert!(reread[0].instant_score_table.is_some());
+        assert_eq!(reread[0].instant_score_table, games[0].instant_score_table);
+        assert_eq!(reread[0].auction_note(1), Some("could be short"));
+        assert_eq!(reread[0].play_note(2), Some("fourth best"));
+        assert_eq!(reread[0].tag(&PbnTag::Contract), Some("3NT"));
+        assert!(written.contains("Pass\n[Note \"1:could be short\"]\n"));
+    }
+
+    #[test]
+    fn test_game_contract() {
+        use crate::pbn::read_pbn_games;
+        use bridge_types::{Contract, Doubled, Strain};
+
+        let mut games = read_pbn_games("[Board \"1\"]\n[Contract \"4hx\"]\n").unwrap();
+        let contract = Contract::new(4, Strain::Hearts, Doubled::Doubled);
+        assert_eq!(games[0].contract, Some(contract));
+
+        games[0].contract = Some(Contract::new(3, Strain::NoTrump, Doubled::None));
+        let written = write_pbn_games(&games, &PbnWriteOptions::default());
+        assert!(written.contains("[Contract \"3NT\"]"));
+
+        let passed = read_pbn_games("[Board \"2\"]\n[Contract \"Pass\"]\n").unwrap();
+        assert_eq!(passed[0].contract, None);
+        assert!(passed[0].is_passed_out());
+        assert!(!games[0].is_passed_out());
+    }
+
+    #[test]
+    fn test_deal_keeps_first_seat() {
+        use crate::pbn::read_pbn_games;
+
+        let deal = "W:T5.Q9863.A943.KQ K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75";
+        let pbn = format!("[Board \"1\"]\n[Dealer \"N\"]\n[Deal \"{}\"]\n", deal);
+        let games = read_pbn_games(&pbn).unwrap();
+        let written = game_to_pbn_with(&games[0], &PbnWriteOptions::default());
+        assert!(written.contains(&format!("[Deal \"{}\"]", deal)));
+        assert!(board_to_pbn(&games[0].board).contains("[Deal \"N:K843"));
+    }
+
+    #[test]
+    fn test_commentary_written() {
+        use crate::pbn::read_pbn_games;
+
+        let pbn = "{Intro}\n[Board \"1\"]\n{On the deal\nin two lines}\n[Auction \"N\"]\n1NT AP\n{Quick}\n";
+        let games = read_pbn_games(pbn).unwrap();
+        let written = game_to_pbn_with(&games[0], &PbnWriteOptions::default());
+        let board = written.find("[Board \"1\"]").unwrap();
+        let deal_note = written.find("{On the deal\nin two lines}").unwrap();
+        assert!(written.starts_with("{Intro}\n"));
+        assert_eq!(written[board..deal_note].lines().count(), 1);
+        assert!(written.ends_with("Pass\n{Quick}\n"));
+        assert_eq!(
+            read_pbn_games(&written).unwrap()[0].commentary,
+            games[0].commentary
+        );
+    }
+
+    #[test]
+    fn test_auction_set_on_board() {
+        let board = Board::new().with_number(3).with_dealer(Direction::South);
+        let mut game = Game::new(board);
+        let mut auction =
+            Auction::parse(Direction::South, "1NT Pass 2C Pass 2H Pass 4H AP").unwrap();
+        auction.calls[2].note = Some(game.add_note("Stayman"));
+        auction.calls[4].alert = true;
+        game.auction = Some(auction);
+
+        let pbn = game_to_pbn_with(&game, &PbnWriteOptions::default());
+        assert!(pbn.contains("[Declarer \"S\"]\n[Contract \"4H\"]\n"));
+        assert!(pbn.contains(
+            "[Auction \"S\"]\n1NT Pass 2C =1= Pass\n2H! Pass 4H Pass\nPass Pass\n[Note \"1:Stayman\"]\n"
+        ));
+    }
+
+    #[test]
+    fn test_auction_and_play_notes_numbered_alike() {
+        use crate::pbn::read_pbn_games;
+
+        let pbn = r#"[Board "1"]
+[Dealer "N"]
+[Auction "N"]
+1C =1= Pass 1NT Pass
+3NT AP
+[Note "1:could be short"]
+[Play "W"]
+S2 =1= SA S3 S4
+*
+[Note "1:fourth best"]
+"#;
+        let games = read_pbn_games(pbn).unwrap();
+        let written = write_pbn_games(&games, &PbnWriteOptions::default());
+        assert_eq!(written.matches("[Note \"1:").count(), 2);
+        assert!(written.contains("Pass\n[Note \"1:could be short\"]\n"));
+        assert!(written.contains("*\n[Note \"1:fourth best\"]\n"));
+
+        let reread = read_pbn_games(&written).unwrap();
+        assert_eq!(reread[0].auction_note(1), Some("could be short"));
+        assert_eq!(reread[0].play_note(1), Some("fourth best"));
+    }
+
+    #[test]
+    fn test_duplicate_notes_are_merged() {
+        use crate::pbn::read_pbn_games;
+
+        let pbn = r#"[Board "1"]
+[Dealer "N"]
+[Auction "N"]
+1C =1= Pass 1D =2= Pass
+1NT =3= Pass Pass Pass
+[Note "1:artificial"]
+[Note "2:natural"]
+[Note "3:artificial"]
+"#;
+        let games = read_pbn_games(pbn).unwrap();
+        let written = write_pbn_games(&games, &PbnWriteOptions::default());
+        assert!(written.contains("1NT =1="));
+        assert_eq!(written.matches("[Note").count(), 2);
+
+        let reread = read_pbn_games(&written).unwrap();
+        assert_eq!(reread[0].call_notes(), games[0].call_notes());
+        assert_eq!(reread[0].call_notes()[4], Some("artificial"));
+    }
 }
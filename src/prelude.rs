@@ -0,0 +1,27 @@
+//! The commonly used types, options and traits.
+//!
+//! ```
+//! use bridge_encodings::prelude::*;
+//!
+//! let mut reader = PbnReader::new("[Board \"1\"]\n[Dealer \"E\"]\n".as_bytes());
+//! let game: Game = reader.next_game().unwrap().unwrap();
+//! assert_eq!(game.board.dealer, Some(Direction::East));
+//! ```
+//!
+//! Only types and traits are exported; the reader and writer functions
+//! stay in their format modules (`pbn::read_pbn`, `lin::parse_lin`, ...).
+//!
+//! [`Transform`] and [`DealSink`] are the crate's extension points and
+//! are meant to be implemented outside the crate.
+
+pub use crate::lin::LinData;
+pub use crate::pbn::{Game, PbnReader, PbnWriteOptions};
+pub use crate::sink::DealSink;
+pub use crate::transform::{Chain, Transform};
+pub use crate::{
+    CancelToken, DealReader, Diagnostics, Format, Limits, Outcome, ParseError, Result, SeatOrder,
+    SeatParser, TextEncoding, TextLayout, WriteOptions,
+};
+pub use bridge_types::{
+    Board, Card, Contract, Deal, Direction, Doubled, Hand, Rank, Strain, Suit, Vulnerability,
+};
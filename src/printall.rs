@@ -12,7 +12,9 @@
 //! Columns are: North, East, South, West (20 chars each).
 //! Rows are: Spades, Hearts, Diamonds, Clubs.
 
-use crate::error::{ParseError, Result};
+use crate::error::{ParseError, Result, SourceLocation};
+use crate::layout::WriteOptions;
+use crate::seat::SeatOrder;
 use bridge_types::{Card, Deal, Direction, Hand, Rank, Suit};
 
 /// Column width in the printall format (each position gets 20 chars).
@@ -22,22 +24,23 @@ const COLUMN_WIDTH: usize = 20;
 ///
 /// The board number line (e.g. "   1.") is included.
 pub fn format_printall(deal: &Deal, board_number: usize) -> String {
+    format_printall_with(deal, board_number, &[])
+}
+
+/// Format a deal in printall format, leaving the columns of `absent` seats blank
+fn format_printall_with(deal: &Deal, board_number: usize, absent: &[Direction]) -> String {
     let mut result = String::new();
 
     result.push_str(&format!("{:4}.\n", board_number));
 
     let suits = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
-    let positions = [
-        Direction::North,
-        Direction::East,
-        Direction::South,
-        Direction::West,
-    ];
+    let positions = SeatOrder::NESW.seats();
 
     for &suit in &suits {
         // cards_count tracks card slots used (each slot = 2 chars: "X ").
         // Start at 10 so the first column doesn't get padded.
         let mut cards_count: usize = 10;
+        let row_start = result.len();
 
         for &dir in &positions {
             // Pad to column boundary (10 card slots = 20 chars)
@@ -46,6 +49,9 @@ pub fn format_printall(deal: &Deal, board_number: usize) -> String {
                 cards_count += 1;
             }
             cards_count = 0;
+            if absent.contains(&dir) {
+                continue;
+            }
 
             let mut cards = deal.hand(dir).cards_in_suit(suit);
             cards.sort_by(|a, b| b.rank.cmp(&a.rank));
@@ -61,6 +67,11 @@ pub fn format_printall(deal: &Deal, board_number: usize) -> String {
                 }
             }
         }
+        if absent.contains(&Direction::West) {
+            // No padding for a blank last column
+            let row_end = row_start + result[row_start..].trim_end().len();
+            result.truncate(row_end);
+        }
         result.push('\n');
     }
     result.push('\n');
@@ -68,6 +79,19 @@ pub fn format_printall(deal: &Deal, board_number: usize) -> String {
     result
 }
 
+/// Write deals in printall format, numbered from 1, with the given options.
+///
+/// With [`WriteOptions::partial_deals`], the columns of hands without
+/// cards are left blank.
+pub fn write_printall(deals: &[Deal], options: &WriteOptions) -> String {
+    let text = deals
+        .iter()
+        .enumerate()
+        .map(|(i, deal)| format_printall_with(deal, i + 1, &options.absent_seats(deal, &[])))
+        .collect();
+    options.finish(text)
+}
+
 /// Parse a single printall block (one deal) from dealer output.
 ///
 /// Expects the board number line followed by 4 suit lines, then a blank line.
@@ -92,20 +116,19 @@ pub fn parse_printall(lines: &[&str]) -> Result<(Deal, usize)> {
             .parse::<usize>()
             .is_err()
     {
-        return Err(ParseError::Pbn(format!(
-            "Expected board number line (e.g. '   1.'), got: '{}'",
-            header
-        )));
+        return Err(ParseError::Syntax {
+            format: "Printall",
+            location: SourceLocation::find(idx + 1, lines[idx], header),
+            message: format!(
+                "Expected board number line (e.g. '   1.'), got: '{}'",
+                header
+            ),
+        });
     }
     idx += 1;
 
     let suits = [Suit::Spades, Suit::Hearts, Suit::Diamonds, Suit::Clubs];
-    let positions = [
-        Direction::North,
-        Direction::East,
-        Direction::South,
-        Direction::West,
-    ];
+    let positions = SeatOrder::NESW.seats();
 
     let mut hands: [Vec<Card>; 4] = [vec![], vec![], vec![], vec![]];
 
@@ -121,7 +144,7 @@ pub fn parse_printall(lines: &[&str]) -> Result<(Deal, usize)> {
         idx += 1;
 
         // Parse 4 columns of 20 chars each
-        for (col_idx, &dir) in positions.iter().enumerate() {
+        for (col_idx, hand) in hands.iter_mut().enumerate() {
             let start = col_idx * COLUMN_WIDTH;
             let end = (start + COLUMN_WIDTH).min(line.len());
 
@@ -137,20 +160,16 @@ pub fn parse_printall(lines: &[&str]) -> Result<(Deal, usize)> {
             }
 
             // Parse space-separated rank characters
-            let hand_idx = match dir {
-                Direction::North => 0,
-                Direction::East => 1,
-                Direction::South => 2,
-                Direction::West => 3,
-            };
-
-            for token in column.split_whitespace() {
-                for c in token.chars() {
-                    let rank = Rank::from_char(c).ok_or_else(|| {
-                        ParseError::Pbn(format!("Invalid rank character '{}' in printall", c))
-                    })?;
-                    hands[hand_idx].push(Card::new(suit, rank));
+            for (i, c) in line[start..end].char_indices() {
+                if c.is_whitespace() {
+                    continue;
                 }
+                let rank = Rank::from_char(c).ok_or_else(|| ParseError::Syntax {
+                    format: "Printall",
+                    location: SourceLocation::new(idx, line, start + i, &c.to_string()),
+                    message: format!("Invalid rank character '{}' in printall", c),
+                })?;
+                hand.push(Card::new(suit, rank));
             }
         }
     }
@@ -228,6 +247,17 @@ mod tests {
         assert_eq!(output.lines().count(), 6);
     }
 
+    #[test]
+    fn test_error_location() {
+        let output = format_printall(&sample_deal(), 1).replacen('K', "X", 1);
+        let lines: Vec<&str> = output.lines().collect();
+        let err = parse_printall(&lines).unwrap_err();
+        let location = err.location().unwrap();
+        assert_eq!(location.text, "X");
+        assert_eq!(location.line, 2);
+        assert_eq!(lines[1].chars().nth(location.start_column - 1), Some('X'));
+    }
+
     #[test]
     fn test_round_trip() {
         let deal = sample_deal();
@@ -285,6 +315,21 @@ mod tests {
         assert_eq!(deals.len(), 1);
     }
 
+    #[test]
+    fn test_partial_deal() {
+        let full =
+            Deal::from_pbn("N:AKQ976.KJ84.T32. J84.Q97.AK4.QJ87 T53.AT65..AT9654 2.32.QJ98765.K32")
+                .unwrap();
+        let mut deal = Deal::new();
+        deal.set_hand(Direction::North, full.hand(Direction::North).clone());
+        deal.set_hand(Direction::South, full.hand(Direction::South).clone());
+
+        let output = write_printall(&[deal], &WriteOptions::new().with_partial_deals(true));
+        let lines: Vec<&str> = output.lines().collect();
+        assert_eq!(lines[1], format!("{:40}T 5 3", "A K Q 9 7 6"));
+        assert_eq!(lines[3], format!("{:40}-", "T 3 2"));
+    }
+
     #[test]
     fn test_format_with_void() {
         // Realistic deal with void suits (6-4-3-0 and 5-4-4-0 shapes)
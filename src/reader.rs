@@ -16,7 +16,10 @@
 //! assert_eq!(deals.len(), 1);
 //! ```
 
-use crate::error::{ParseError, Result};
+use crate::checksum::split_checksum;
+use crate::diagnostics::{Diagnostics, WarningKind};
+use crate::error::{ParseError, Result, SourceLocation};
+use crate::pbn::{is_unterminated_deal, DealTag};
 use bridge_types::Deal;
 use std::io::BufRead;
 
@@ -30,6 +33,8 @@ pub struct DealReader<R: BufRead> {
     line_buf: String,
     line_number: usize,
     deals_read: usize,
+    diagnostics: Diagnostics,
+    require_checksums: bool,
 }
 
 impl<R: BufRead> DealReader<R> {
@@ -40,9 +45,19 @@ impl<R: BufRead> DealReader<R> {
             line_buf: String::new(),
             line_number: 0,
             deals_read: 0,
+            diagnostics: Diagnostics::new(),
+            require_checksums: false,
         }
     }
 
+    /// Set whether oneline records must end with a checksum; a record
+    /// without one is an error, so truncated lines are caught. Checksums
+    /// that are present are always verified.
+    pub fn with_required_checksums(mut self, require: bool) -> Self {
+        self.require_checksums = require;
+        self
+    }
+
     /// Number of deals successfully read so far.
     pub fn deals_read(&self) -> usize {
         self.deals_read
@@ -53,6 +68,11 @@ impl<R: BufRead> DealReader<R> {
         self.line_number
     }
 
+    /// Warnings for lines skipped while reading the most recent record.
+    pub fn diagnostics(&self) -> &Diagnostics {
+        &self.diagnostics
+    }
+
     /// Read one line from the underlying reader. Returns false at EOF.
     fn read_line(&mut self) -> std::result::Result<bool, std::io::Error> {
         self.line_buf.clear();
@@ -90,13 +110,14 @@ impl<R: BufRead> DealReader<R> {
                 self.deals_read += 1;
                 Some(Ok(deal))
             }
-            Err(e) => Some(Err(e)),
+            // Line 1 of the block is the header read before the suit lines
+            Err(e) => Some(Err(e.offset_lines(self.line_number.saturating_sub(5)))),
         }
     }
 }
 
 /// Check if a line looks like a printall board number header (e.g. "   1.", "  42.")
-fn is_board_number_line(line: &str) -> bool {
+pub(crate) fn is_board_number_line(line: &str) -> bool {
     let trimmed = line.trim();
     trimmed.ends_with('.')
         && !trimmed.is_empty()
@@ -107,6 +128,7 @@ impl<R: BufRead> Iterator for DealReader<R> {
     type Item = Result<Deal>;
 
     fn next(&mut self) -> Option<Self::Item> {
+        self.diagnostics.clear();
         loop {
             match self.read_line() {
                 Ok(true) => {}
@@ -114,24 +136,55 @@ impl<R: BufRead> Iterator for DealReader<R> {
                 Err(e) => return Some(Err(ParseError::Io(e))),
             }
 
-            let line = self.line_buf.trim().to_string();
+            let mut line = self.line_buf.trim().to_string();
 
             if line.is_empty() {
                 continue;
             }
 
             // Try oneline format first (cheap check: 8 whitespace-separated parts)
-            if let Ok(deal) = crate::oneline::parse_oneline(&line) {
-                self.deals_read += 1;
-                return Some(Ok(deal));
+            let (_, checksum) = split_checksum(&line);
+            match crate::oneline::parse_oneline(&line) {
+                Ok(_) if checksum.is_none() && self.require_checksums => {
+                    return Some(Err(ParseError::Syntax {
+                        format: "Oneline",
+                        location: SourceLocation::new(self.line_number, &line, 0, &line),
+                        message: "record has no checksum".to_string(),
+                    }));
+                }
+                Ok(deal) => {
+                    self.deals_read += 1;
+                    return Some(Ok(deal));
+                }
+                // A line with a checksum is a record; one that fails is corrupt
+                Err(e) if checksum.is_some() => {
+                    return Some(Err(e.offset_lines(self.line_number - 1)));
+                }
+                Err(_) => {}
             }
 
             // Try PBN Deal tag: [Deal "N:..."]
             if line.starts_with("[Deal ") {
+                // A Deal tag wrapped onto the next line
+                if is_unterminated_deal(&line) {
+                    match self.read_line() {
+                        Ok(true) => {
+                            line.push(' ');
+                            line.push_str(self.line_buf.trim());
+                        }
+                        Ok(false) => {}
+                        Err(e) => return Some(Err(ParseError::Io(e))),
+                    }
+                }
                 if let Some(deal) = try_parse_pbn_deal_tag(&line) {
                     self.deals_read += 1;
                     return Some(Ok(deal));
                 }
+                self.diagnostics.warn(
+                    Some(self.line_number),
+                    WarningKind::SkippedLine,
+                    format!("invalid Deal tag: {}", line),
+                );
             }
 
             // Try printall: board number header followed by 4 suit lines
@@ -151,7 +204,7 @@ fn try_parse_pbn_deal_tag(line: &str) -> Option<Deal> {
     let inner = line.strip_prefix('[')?.strip_suffix(']')?;
     let rest = inner.strip_prefix("Deal ")?;
     let value = rest.strip_prefix('"')?.strip_suffix('"')?;
-    Deal::from_pbn(value)
+    DealTag::from_pbn(value).ok().map(|tag| tag.to_deal())
 }
 
 #[cfg(test)]
@@ -172,6 +225,33 @@ n A754.7642.KJ2.A9 e QT.AK95.87.K8652 s K93.J83.QT6543.T w J862.QT.A9.QJ743
         assert!(deals.iter().all(|r| r.is_ok()));
     }
 
+    #[test]
+    fn test_checksummed_records() {
+        let deal = crate::oneline::parse_oneline_partial("n AKxx.Kx.Qxxx.xxx e - s - w -")
+            .unwrap()
+            .complete()
+            .unwrap();
+        let options = crate::WriteOptions::new().with_checksums(true);
+        let good = crate::oneline::write_oneline(&[deal.clone(), deal], &options);
+        let mut reader = DealReader::new(Cursor::new(good.clone())).with_required_checksums(true);
+        assert!(reader.all(|r| r.is_ok()));
+
+        let damaged = good.replacen("AK", "AQ", 1);
+        let results: Vec<_> = DealReader::new(Cursor::new(damaged)).collect();
+        assert_eq!(results.len(), 2);
+        assert_eq!(
+            results[0].as_ref().unwrap_err().location().map(|l| l.line),
+            Some(1)
+        );
+
+        let truncated = format!(
+            "{}n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72\n",
+            good
+        );
+        let reader = DealReader::new(Cursor::new(truncated)).with_required_checksums(true);
+        assert!(reader.last().unwrap().is_err());
+    }
+
     #[test]
     fn test_read_pbn_deals() {
         let input = r#"[Event "test"]
@@ -191,6 +271,16 @@ n A754.7642.KJ2.A9 e QT.AK95.87.K8652 s K93.J83.QT6543.T w J862.QT.A9.QJ743
         assert!(deals.iter().all(|r| r.is_ok()));
     }
 
+    #[test]
+    fn test_wrapped_deal_tag() {
+        let input = "[Board \"1\"]\n\
+                     [Deal \"N:KQ4.QJ982..AKQ43 J653.A73.985.J97\n\
+                     9.K54.KQT732.652 AT872.T6.AJ64.T8\"]\n";
+        let deals: Vec<_> = DealReader::new(Cursor::new(input)).collect();
+        assert_eq!(deals.len(), 1);
+        assert_eq!(deals[0].as_ref().unwrap().hand(Direction::West).len(), 13);
+    }
+
     #[test]
     fn test_auto_detect_mixed_formats() {
         let input = "\
@@ -323,4 +413,18 @@ Time needed    0.001 sec
         let deals: Vec<_> = reader.collect();
         assert_eq!(deals.len(), 1);
     }
+
+    #[test]
+    fn test_diagnostics_for_skipped_deal_tag() {
+        let input = "\
+[Deal \"N:AKQ\"]
+n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72
+";
+        let mut reader = DealReader::new(Cursor::new(input));
+        reader.next().unwrap().unwrap();
+        let warnings = reader.diagnostics().warnings();
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].line, Some(1));
+        assert_eq!(warnings[0].kind, WarningKind::SkippedLine);
+    }
 }
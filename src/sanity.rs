@@ -0,0 +1,142 @@
+//! Plausibility checks of deals and of recorded results.
+//!
+//! [`irregular_hands`] finds misdealt hands (14 or 12 cards), which the PBN
+//! reader keeps with a warning unless told to reject them.
+//!
+//! A declarer rarely takes many more or fewer tricks than double dummy
+//! analysis predicts. Large deviations usually mean a data-entry error
+//! (wrong contract, declarer or result), so [`review_results`] lists them
+//! for a human to check.
+
+use crate::pbn::Game;
+use crate::scoring::{format_contract, PlayedResult};
+use bridge_types::{Deal, Direction};
+use std::fmt;
+
+/// The seats holding other than 13 cards, with their card counts.
+///
+/// Hands without cards are taken as absent (partial deals), not irregular.
+pub fn irregular_hands(deal: &Deal) -> Vec<(Direction, usize)> {
+    Direction::ALL
+        .into_iter()
+        .map(|dir| (dir, deal.hand(dir).len()))
+        .filter(|&(_, len)| len != 0 && len != 13)
+        .collect()
+}
+
+/// A recorded result that deviates strongly from double dummy
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResultAnomaly {
+    /// Index of the game in the reviewed slice
+    pub game_index: usize,
+    /// The board number, if any
+    pub board_number: Option<u32>,
+    /// The recorded contract, declarer and tricks
+    pub played: PlayedResult,
+    /// Tricks available to declarer double dummy
+    pub dd_tricks: u8,
+}
+
+impl ResultAnomaly {
+    /// Tricks taken minus tricks available double dummy
+    pub fn deviation(&self) -> i8 {
+        self.played.tricks as i8 - self.dd_tricks as i8
+    }
+}
+
+impl fmt::Display for ResultAnomaly {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.board_number {
+            Some(num) => write!(f, "board {}", num)?,
+            None => write!(f, "game #{}", self.game_index + 1)?,
+        }
+        write!(
+            f,
+            ": {} by {} took {} tricks, double dummy {}",
+            format_contract(&self.played.contract),
+            self.played.declarer.to_char(),
+            self.played.tricks,
+            self.dd_tricks
+        )
+    }
+}
+
+/// List games whose Result differs from the double dummy tricks for the
+/// contract's strain and declarer by at least `threshold` tricks.
+///
+/// Games without a result or double dummy table (OptimumResultTable or
+/// DoubleDummyTricks), or with values that can't be interpreted, are not
+/// reviewed.
+pub fn review_results(games: &[Game], threshold: u8) -> Vec<ResultAnomaly> {
+    games
+        .iter()
+        .enumerate()
+        .filter_map(|(index, game)| check_game(index, game))
+        .filter(|anomaly| anomaly.deviation().unsigned_abs() >= threshold)
+        .collect()
+}
+
+fn check_game(game_index: usize, game: &Game) -> Option<ResultAnomaly> {
+    let played = PlayedResult::from_game(game)?;
+    let dd_tricks = game
+        .double_dummy_table()?
+        .get(played.declarer, played.contract.strain)?;
+
+    Some(ResultAnomaly {
+        game_index,
+        board_number: game.board.number,
+        played,
+        dd_tricks,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pbn::read_pbn_games;
+
+    const PBN: &str = r#"[Board "1"]
+[Declarer "S"]
+[Contract "3NT"]
+[Result "9"]
+[DoubleDummyTricks "a9a9a9a9a94545444545"]
+
+[Board "2"]
+[Declarer "E"]
+[Contract "2H"]
+[Result "13"]
+[DoubleDummyTricks "a9a9a9a9a94545444545"]
+
+[Board "3"]
+[Declarer "N"]
+[Contract "Pass"]
+[DoubleDummyTricks "a9a9a9a9a94545444545"]
+"#;
+
+    #[test]
+    fn test_irregular_hands() {
+        let deal =
+            Deal::from_pbn("N:AK843.T542.J6.863 QJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ")
+                .unwrap();
+        assert_eq!(
+            irregular_hands(&deal),
+            [(Direction::North, 14), (Direction::East, 12)]
+        );
+        assert!(irregular_hands(&Deal::new()).is_empty());
+    }
+
+    #[test]
+    fn test_review_results() {
+        let games = read_pbn_games(PBN).unwrap();
+        let anomalies = review_results(&games, 3);
+
+        assert_eq!(anomalies.len(), 1);
+        assert_eq!(anomalies[0].board_number, Some(2));
+        assert_eq!(anomalies[0].dd_tricks, 4);
+        assert_eq!(anomalies[0].deviation(), 9);
+        assert_eq!(
+            anomalies[0].to_string(),
+            "board 2: 2H by E took 13 tricks, double dummy 4"
+        );
+    }
+}
@@ -0,0 +1,600 @@
+//! Played results and duplicate scoring.
+//!
+//! [`PlayedResult`] is the single representation of "contract, declarer
+//! and tricks" for every source: PBN Contract/Declarer/Result tags, LIN
+//! auctions with a claim, and the plausibility checks in
+//! [`sanity`](crate::sanity).
+
+use crate::pbn::{Auction, Call, Game, PbnTag};
+use crate::seat::parse_seat;
+use bridge_types::{Contract, Direction, Doubled, Strain, Vulnerability};
+use std::fmt;
+
+/// A contract played by a declarer, with the tricks declarer took
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct PlayedResult {
+    pub contract: Contract,
+    pub declarer: Direction,
+    /// Tricks taken by declarer (0-13)
+    pub tricks: u8,
+}
+
+impl PlayedResult {
+    /// Create a result, or `None` if `tricks` is over 13
+    pub fn new(contract: Contract, declarer: Direction, tricks: u8) -> Option<Self> {
+        (tricks <= 13).then_some(Self {
+            contract,
+            declarer,
+            tricks,
+        })
+    }
+
+    /// Build from PBN Contract, Declarer and Result values.
+    ///
+    /// Returns `Ok(None)` for a passed-out board.
+    pub fn from_pbn(
+        contract: &str,
+        declarer: &str,
+        result: &str,
+    ) -> std::result::Result<Option<Self>, String> {
+        let Some(contract) = parse_contract(contract)? else {
+            return Ok(None);
+        };
+        let declarer = DeclarerTag::from_pbn(declarer)?
+            .ok_or_else(|| "missing declarer".to_string())?
+            .seat;
+        let tricks = ResultTag::from_pbn(result)?
+            .and_then(|result| result.declarer_tricks(declarer))
+            .ok_or_else(|| format!("invalid result: {}", result))?;
+        Ok(Self::new(contract, declarer, tricks))
+    }
+
+    /// The result recorded in a game's Contract, Declarer and Result tags,
+    /// or `None` if a tag is missing or invalid or the board was passed out
+    pub fn from_game(game: &Game) -> Option<Self> {
+        Self::from_pbn(
+            game.tag(&PbnTag::Contract)?,
+            game.tag(&PbnTag::Declarer)?,
+            game.tag(&PbnTag::Result)?,
+        )
+        .ok()
+        .flatten()
+    }
+
+    /// Tricks over (positive) or under (negative) the contract
+    pub fn over_under(&self) -> i8 {
+        self.tricks as i8 - (self.contract.level as i8 + 6)
+    }
+
+    /// Whether declarer's side is vulnerable
+    pub fn is_vulnerable(&self, vulnerability: Vulnerability) -> bool {
+        let north_south = matches!(self.declarer, Direction::North | Direction::South);
+        match vulnerability {
+            Vulnerability::None => false,
+            Vulnerability::Both => true,
+            Vulnerability::NorthSouth => north_south,
+            Vulnerability::EastWest => !north_south,
+        }
+    }
+
+    /// Duplicate score for declarer's side
+    pub fn score(&self, vulnerability: Vulnerability) -> i32 {
+        let vul = self.is_vulnerable(vulnerability);
+        let over = self.over_under() as i32;
+        let multiplier = match self.contract.doubled {
+            Doubled::None => 1,
+            Doubled::Doubled => 2,
+            Doubled::Redoubled => 4,
+        };
+
+        if over < 0 {
+            let down = -over;
+            return -match self.contract.doubled {
+                Doubled::None => down * if vul { 100 } else { 50 },
+                _ => {
+                    let doubled = if vul {
+                        200 + (down - 1) * 300
+                    } else {
+                        match down {
+                            1 => 100,
+                            2 => 300,
+                            _ => 500 + (down - 3) * 300,
+                        }
+                    };
+                    doubled * multiplier / 2
+                }
+            };
+        }
+
+        let level = self.contract.level as i32;
+        let (first, rest) = match self.contract.strain {
+            Strain::Clubs | Strain::Diamonds => (20, 20),
+            Strain::Hearts | Strain::Spades => (30, 30),
+            Strain::NoTrump => (40, 30),
+        };
+        let trick_points = (first + rest * (level - 1)) * multiplier;
+
+        let mut score = trick_points;
+        score += if trick_points >= 100 {
+            if vul {
+                500
+            } else {
+                300
+            }
+        } else {
+            50
+        };
+        score += match level {
+            6 => {
+                if vul {
+                    750
+                } else {
+                    500
+                }
+            }
+            7 => {
+                if vul {
+                    1500
+                } else {
+                    1000
+                }
+            }
+            _ => 0,
+        };
+        score += match self.contract.doubled {
+            Doubled::None => over * rest,
+            Doubled::Doubled => 50 + over * if vul { 200 } else { 100 },
+            Doubled::Redoubled => 100 + over * if vul { 400 } else { 200 },
+        };
+        score
+    }
+
+    /// Duplicate score from North-South's point of view
+    pub fn score_ns(&self, vulnerability: Vulnerability) -> i32 {
+        match self.declarer {
+            Direction::North | Direction::South => self.score(vulnerability),
+            Direction::East | Direction::West => -self.score(vulnerability),
+        }
+    }
+}
+
+impl fmt::Display for PlayedResult {
+    /// Contract, declarer and over/under, e.g. `4SX E -1` or `3NT S =`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} {} ",
+            format_contract(&self.contract),
+            self.declarer.to_char()
+        )?;
+        match self.over_under() {
+            0 => f.write_str("="),
+            n => write!(f, "{:+}", n),
+        }
+    }
+}
+
+/// A Declarer tag value
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct DeclarerTag {
+    /// The declarer
+    pub seat: Direction,
+    /// Declarer and dummy were swapped by accident (`^S`)
+    pub swapped: bool,
+}
+
+impl DeclarerTag {
+    /// Parse a Declarer tag value; `Ok(None)` for the empty value of a
+    /// passed-out board
+    pub fn from_pbn(value: &str) -> std::result::Result<Option<Self>, String> {
+        let value = value.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+        let (seat, swapped) = match value.strip_prefix('^') {
+            Some(seat) => (seat, true),
+            None => (value, false),
+        };
+        let seat = parse_seat(seat).ok_or_else(|| format!("invalid declarer: {}", value))?;
+        Ok(Some(Self { seat, swapped }))
+    }
+}
+
+/// Values given for declarer, for NS and/or for EW, the forms shared by
+/// the Result and Score tags (`9`, `NS 9`, `EW 4 NS 9`)
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct SideValues<T> {
+    /// Value for declarer's side
+    pub declarer: Option<T>,
+    /// Value for North-South
+    pub north_south: Option<T>,
+    /// Value for East-West
+    pub east_west: Option<T>,
+}
+
+impl<T: std::str::FromStr> SideValues<T> {
+    fn parse(value: &str) -> Option<Self> {
+        let tokens: Vec<&str> = value.split_whitespace().collect();
+        let mut values = Self {
+            declarer: None,
+            north_south: None,
+            east_west: None,
+        };
+        match tokens.as_slice() {
+            [number] => values.declarer = Some(number.parse().ok()?),
+            [_, _] | [_, _, _, _] => {
+                for pair in tokens.chunks(2) {
+                    let number = Some(pair[1].parse().ok()?);
+                    let slot = match pair[0].to_ascii_uppercase().as_str() {
+                        "NS" => &mut values.north_south,
+                        "EW" => &mut values.east_west,
+                        _ => return None,
+                    };
+                    if slot.is_some() {
+                        return None;
+                    }
+                    *slot = number;
+                }
+            }
+            _ => return None,
+        }
+        Some(values)
+    }
+}
+
+/// A Result tag value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ResultTag {
+    /// Tricks won
+    pub tricks: SideValues<u8>,
+    /// The players agreed on a number of tricks other than those actually
+    /// won (`^9`)
+    pub disputed: bool,
+}
+
+impl ResultTag {
+    /// Parse a Result tag value; `Ok(None)` for the empty value of a
+    /// passed-out board
+    pub fn from_pbn(value: &str) -> std::result::Result<Option<Self>, String> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() {
+            return Ok(None);
+        }
+        let (rest, disputed) = match trimmed.strip_prefix('^') {
+            Some(rest) => (rest, true),
+            None => (trimmed, false),
+        };
+        let tricks = SideValues::<u8>::parse(rest)
+            .filter(|t| {
+                [t.declarer, t.north_south, t.east_west]
+                    .iter()
+                    .flatten()
+                    .all(|&n| n <= 13)
+            })
+            .ok_or_else(|| format!("invalid result: {}", value))?;
+        Ok(Some(Self { tricks, disputed }))
+    }
+
+    /// Tricks won by `declarer`'s side
+    pub fn declarer_tricks(&self, declarer: Direction) -> Option<u8> {
+        let (own, other) = match Side::of(declarer) {
+            Side::NorthSouth => (self.tricks.north_south, self.tricks.east_west),
+            Side::EastWest => (self.tricks.east_west, self.tricks.north_south),
+        };
+        self.tricks
+            .declarer
+            .or(own)
+            .or_else(|| other.map(|tricks| 13 - tricks))
+    }
+}
+
+/// A Score tag value
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct ScoreTag {
+    /// Points scored
+    pub points: SideValues<i32>,
+}
+
+impl ScoreTag {
+    /// Parse a Score tag value; `Ok(None)` for an empty value
+    pub fn from_pbn(value: &str) -> std::result::Result<Option<Self>, String> {
+        if value.trim().is_empty() {
+            return Ok(None);
+        }
+        let points = SideValues::parse(value).ok_or_else(|| format!("invalid score: {}", value))?;
+        Ok(Some(Self { points }))
+    }
+
+    /// Points scored by North-South; a declarer's score needs `declarer`
+    pub fn score_ns(&self, declarer: Option<Direction>) -> Option<i32> {
+        let from_declarer = || {
+            let points = self.points.declarer?;
+            match Side::of(declarer?) {
+                Side::NorthSouth => Some(points),
+                Side::EastWest => Some(-points),
+            }
+        };
+        self.points
+            .north_south
+            .or_else(|| self.points.east_west.map(|points| -points))
+            .or_else(from_declarer)
+    }
+}
+
+/// An OptimumScore tag value (`NS 420`, `EW -50`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct OptimumScoreTag {
+    /// The side the score is given for
+    pub side: Side,
+    /// Points for that side
+    pub score: i32,
+}
+
+impl OptimumScoreTag {
+    /// Parse an OptimumScore tag value; `Ok(None)` for an empty value.
+    ///
+    /// A value giving both sides (`NS 420 EW -420`) keeps the NS score.
+    pub fn from_pbn(value: &str) -> std::result::Result<Option<Self>, String> {
+        if value.trim().is_empty() {
+            return Ok(None);
+        }
+        let invalid = || format!("invalid optimum score: {}", value);
+        let points = SideValues::<i32>::parse(value).ok_or_else(invalid)?;
+        match (points.north_south, points.east_west) {
+            (Some(ns), Some(ew)) if ns != -ew => Err(invalid()),
+            (Some(score), _) => Ok(Some(Self {
+                side: Side::NorthSouth,
+                score,
+            })),
+            (None, Some(score)) => Ok(Some(Self {
+                side: Side::EastWest,
+                score,
+            })),
+            (None, None) => Err(invalid()),
+        }
+    }
+
+    /// Points for North-South
+    pub fn score_ns(&self) -> i32 {
+        match self.side {
+            Side::NorthSouth => self.score,
+            Side::EastWest => -self.score,
+        }
+    }
+
+    /// The tag value
+    pub fn to_pbn(&self) -> String {
+        format!("{} {}", self.side, self.score)
+    }
+}
+
+/// A ParContract tag value: a contract and its declarer (`4SN`, `3NTXE`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParContractTag {
+    pub contract: Contract,
+    pub declarer: Direction,
+}
+
+impl ParContractTag {
+    /// Parse a ParContract tag value, with or without a space before the
+    /// declarer (`4SN`, `4S N`); `Ok(None)` for an empty value or `Pass`
+    pub fn from_pbn(value: &str) -> std::result::Result<Option<Self>, String> {
+        let trimmed = value.trim();
+        if trimmed.is_empty() || trimmed.eq_ignore_ascii_case("pass") {
+            return Ok(None);
+        }
+        let invalid = || format!("invalid par contract: {}", value);
+        let split = trimmed.len() - trimmed.chars().last().map_or(0, char::len_utf8);
+        let (contract, declarer) = trimmed.split_at(split);
+        let declarer = parse_seat(declarer).ok_or_else(invalid)?;
+        let contract = parse_contract(contract.trim_end())
+            .ok()
+            .flatten()
+            .ok_or_else(invalid)?;
+        Ok(Some(Self { contract, declarer }))
+    }
+
+    /// The tag value
+    pub fn to_pbn(&self) -> String {
+        format!(
+            "{}{}",
+            format_contract(&self.contract),
+            self.declarer.to_char()
+        )
+    }
+}
+
+/// A partnership
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Side {
+    /// North and South
+    NorthSouth,
+    /// East and West
+    EastWest,
+}
+
+impl Side {
+    /// The side `seat` belongs to
+    pub fn of(seat: Direction) -> Self {
+        match seat {
+            Direction::North | Direction::South => Side::NorthSouth,
+            Direction::East | Direction::West => Side::EastWest,
+        }
+    }
+}
+
+impl fmt::Display for Side {
+    /// `NS` or `EW`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Side::NorthSouth => "NS",
+            Side::EastWest => "EW",
+        })
+    }
+}
+
+/// Parse a contract in PBN notation (`4SX`, `3NT`, `7CXX`).
+///
+/// Returns `Ok(None)` for `Pass`.
+pub fn parse_contract(s: &str) -> std::result::Result<Option<Contract>, String> {
+    let upper = s.trim().to_ascii_uppercase();
+    if upper == "PASS" || upper == "AP" {
+        return Ok(None);
+    }
+    let (bid, doubled) = if let Some(bid) = upper.strip_suffix("XX") {
+        (bid, Doubled::Redoubled)
+    } else if let Some(bid) = upper.strip_suffix('X') {
+        (bid, Doubled::Doubled)
+    } else {
+        (upper.as_str(), Doubled::None)
+    };
+    match Call::parse(bid) {
+        Some(Call::Bid(level, strain)) => Ok(Some(Contract::new(level, strain, doubled))),
+        _ => Err(format!("invalid contract: {}", s)),
+    }
+}
+
+/// Whether two contracts in PBN notation are the same contract (`3nt`
+/// and `3N` are); text that isn't a contract only matches itself,
+/// ignoring case
+pub(crate) fn same_contract(a: &str, b: &str) -> bool {
+    match (parse_contract(a), parse_contract(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => a.trim().eq_ignore_ascii_case(b.trim()),
+    }
+}
+
+/// Format a contract in PBN notation
+pub fn format_contract(contract: &Contract) -> String {
+    let doubled = match contract.doubled {
+        Doubled::None => "",
+        Doubled::Doubled => "X",
+        Doubled::Redoubled => "XX",
+    };
+    format!("{}{}", Call::Bid(contract.level, contract.strain), doubled)
+}
+
+impl crate::lin::LinData {
+    /// The contract and declarer from the auction with the claimed tricks,
+    /// or `None` if the auction is incomplete or passed out or there is no claim
+    pub fn played_result(&self) -> Option<PlayedResult> {
+        let bids: Vec<&str> = self.auction.iter().map(|b| b.bid.as_str()).collect();
+        let auction = Auction::parse(self.dealer, &bids.join(" ")).ok()?;
+        let (contract, declarer) = auction.contract()?;
+        PlayedResult::new(contract, declarer, self.claim?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pbn::TagPair;
+    use bridge_types::Board;
+
+    fn result(contract: &str, declarer: &str, tricks: &str) -> PlayedResult {
+        PlayedResult::from_pbn(contract, declarer, tricks)
+            .unwrap()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_scores() {
+        let none = Vulnerability::None;
+        let both = Vulnerability::Both;
+        assert_eq!(result("3NT", "S", "9").score(none), 400);
+        assert_eq!(result("3NT", "S", "10").score(both), 630);
+        assert_eq!(result("2H", "E", "8").score(none), 110);
+        assert_eq!(result("4SX", "N", "10").score(both), 790);
+        assert_eq!(result("1NTXX", "W", "8").score(none), 760);
+        assert_eq!(result("6C", "S", "12").score(both), 1370);
+        assert_eq!(result("7NT", "N", "13").score(none), 1520);
+        assert_eq!(result("4H", "S", "8").score(both), -200);
+        assert_eq!(result("3CX", "E", "5").score(none), -800);
+        assert_eq!(result("3CX", "E", "5").score(both), -1100);
+        assert_eq!(result("2SXX", "N", "7").score(both), -400);
+        assert_eq!(result("2SXX", "N", "7").score_ns(both), -400);
+        assert_eq!(result("2H", "E", "8").score_ns(none), -110);
+    }
+
+    #[test]
+    fn test_from_pbn() {
+        assert_eq!(PlayedResult::from_pbn("Pass", "", ""), Ok(None));
+        assert!(PlayedResult::from_pbn("4S", "N", "14").is_err());
+        assert!(PlayedResult::from_pbn("4Q", "N", "10").is_err());
+        assert_eq!(result("4SX", "E", "9").to_string(), "4SX E -1");
+        assert_eq!(result("3NT", "S", "9").to_string(), "3NT S =");
+    }
+
+    #[test]
+    fn test_tag_values() {
+        let declarer = DeclarerTag::from_pbn("^s").unwrap().unwrap();
+        assert_eq!(declarer.seat, Direction::South);
+        assert!(declarer.swapped);
+        assert_eq!(DeclarerTag::from_pbn(""), Ok(None));
+
+        let tricks = |value: &str, declarer| {
+            ResultTag::from_pbn(value)
+                .unwrap()
+                .unwrap()
+                .declarer_tricks(declarer)
+        };
+        assert_eq!(tricks("9", Direction::North), Some(9));
+        assert_eq!(tricks("EW 4", Direction::North), Some(9));
+        assert_eq!(tricks("NS 9 EW 4", Direction::West), Some(4));
+        assert!(ResultTag::from_pbn("^10").unwrap().unwrap().disputed);
+        assert!(ResultTag::from_pbn("NS 14").is_err());
+        assert!(ResultTag::from_pbn("NS 9 NS 4").is_err());
+        assert_eq!(result("4S", "N", "EW 3").to_string(), "4S N =");
+
+        let score = |value: &str| ScoreTag::from_pbn(value).unwrap().unwrap();
+        assert_eq!(score("NS 620").score_ns(None), Some(620));
+        assert_eq!(score("EW 100").score_ns(None), Some(-100));
+        assert_eq!(score("-50").score_ns(Some(Direction::East)), Some(50));
+        assert_eq!(score("-50").score_ns(None), None);
+        assert!(ScoreTag::from_pbn("lots").is_err());
+
+        let mut game = Game::new(Board::new());
+        for (tag, value) in [(PbnTag::Declarer, "^E"), (PbnTag::Score, "EW 100")] {
+            game.tags.push(TagPair {
+                tag,
+                value: value.to_string(),
+            });
+        }
+        assert_eq!(game.declarer().map(|d| d.seat), Some(Direction::East));
+        assert_eq!(game.result(), None);
+        assert_eq!(game.score().and_then(|s| s.score_ns(None)), Some(-100));
+    }
+
+    #[test]
+    fn test_optimum_score_and_par_contract() {
+        let score = OptimumScoreTag::from_pbn("EW 50").unwrap().unwrap();
+        assert_eq!(score.side, Side::EastWest);
+        assert_eq!(score.score_ns(), -50);
+        assert_eq!(score.to_pbn(), "EW 50");
+        assert!(OptimumScoreTag::from_pbn("NS 420 EW 420").is_err());
+        assert!(OptimumScoreTag::from_pbn("420").is_err());
+
+        let par = ParContractTag::from_pbn("4S N").unwrap().unwrap();
+        assert_eq!(
+            par.contract,
+            Contract::new(4, Strain::Spades, Doubled::None)
+        );
+        assert_eq!(par.declarer, Direction::North);
+        assert_eq!(par.to_pbn(), "4SN");
+        let par = ParContractTag::from_pbn("3NTXE").unwrap().unwrap();
+        assert_eq!(par.to_pbn(), "3NTXE");
+        assert_eq!(ParContractTag::from_pbn("Pass"), Ok(None));
+        assert!(ParContractTag::from_pbn("4S").is_err());
+    }
+
+    #[test]
+    fn test_lin_played_result() {
+        let lin = crate::lin::parse_lin(
+            "md|3S87HAQT2D95CAQT43,SAQT95H874DAJ2CJ5,S62HKJ53DKQT43CK6,|sv|n|mb|1N|mb|p|mb|3N|mb|p|mb|p|mb|p|mc|10|",
+        )
+        .unwrap();
+        let played = lin.played_result().unwrap();
+        assert_eq!(played.declarer, Direction::North);
+        assert_eq!(played.to_string(), "3NT N +1");
+    }
+}
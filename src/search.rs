@@ -0,0 +1,201 @@
+//! Searching PBN archives.
+//!
+//! A [`Query`] filters games by player name, event, contract, result and
+//! date. Text criteria match case-insensitive substrings, so partial or
+//! differently capitalised names still find their games.
+
+use crate::error::GameSpan;
+use crate::pbn::{Game, PbnTag};
+use crate::scoring::same_contract;
+use std::ops::RangeInclusive;
+
+/// Filter criteria for games; all given criteria must match
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    player: Option<String>,
+    event: Option<String>,
+    contract: Option<String>,
+    result: Option<RangeInclusive<u8>>,
+    date: Option<(String, String)>,
+}
+
+/// A game matched by a query
+#[derive(Debug, Clone, Copy)]
+pub struct SearchHit<'a> {
+    /// Position of the game in the searched slice
+    pub index: usize,
+    /// Lines of the source file occupied by the game
+    pub span: GameSpan,
+    /// The matching game
+    pub game: &'a Game,
+}
+
+/// Player tags searched by [`Query::player`]
+const PLAYER_TAGS: [PbnTag; 4] = [PbnTag::West, PbnTag::North, PbnTag::East, PbnTag::South];
+
+impl Query {
+    /// Create a query matching every game
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Match games where any player name contains `name`
+    pub fn player(mut self, name: &str) -> Self {
+        self.player = Some(name.to_lowercase());
+        self
+    }
+
+    /// Match games whose Event tag contains `event`
+    pub fn event(mut self, event: &str) -> Self {
+        self.event = Some(event.to_lowercase());
+        self
+    }
+
+    /// Match games played in `contract` (e.g. "3NT", "4SX"), ignoring declarer
+    pub fn contract(mut self, contract: &str) -> Self {
+        self.contract = Some(contract.to_string());
+        self
+    }
+
+    /// Match games whose Result (tricks taken by declarer) is in `range`
+    pub fn result(mut self, range: RangeInclusive<u8>) -> Self {
+        self.result = Some(range);
+        self
+    }
+
+    /// Match games dated between `from` and `to` inclusive, in PBN date
+    /// format ("YYYY.MM.DD"). Games with an unknown date never match.
+    pub fn date_range(mut self, from: &str, to: &str) -> Self {
+        self.date = Some((from.to_string(), to.to_string()));
+        self
+    }
+
+    /// Whether a game satisfies the query
+    pub fn matches(&self, game: &Game) -> bool {
+        if let Some(ref name) = self.player {
+            let found = PLAYER_TAGS.iter().any(|tag| {
+                game.tag(tag)
+                    .is_some_and(|player| player.to_lowercase().contains(name.as_str()))
+            });
+            if !found {
+                return false;
+            }
+        }
+
+        if let Some(ref event) = self.event {
+            let found = game
+                .tag(&PbnTag::Event)
+                .is_some_and(|value| value.to_lowercase().contains(event.as_str()));
+            if !found {
+                return false;
+            }
+        }
+
+        if let Some(ref contract) = self.contract {
+            let found = game
+                .tag(&PbnTag::Contract)
+                .is_some_and(|value| same_contract(value, contract));
+            if !found {
+                return false;
+            }
+        }
+
+        if let Some(ref range) = self.result {
+            let found = game
+                .tag(&PbnTag::Result)
+                .and_then(|value| value.trim().parse::<u8>().ok())
+                .is_some_and(|tricks| range.contains(&tricks));
+            if !found {
+                return false;
+            }
+        }
+
+        if let Some((ref from, ref to)) = self.date {
+            let found = game
+                .tag(&PbnTag::Date)
+                .filter(|date| !date.contains('?'))
+                .is_some_and(|date| date >= from.as_str() && date <= to.as_str());
+            if !found {
+                return false;
+            }
+        }
+
+        true
+    }
+
+    /// All games matching the query, in order
+    pub fn search<'a>(&self, games: &'a [Game]) -> Vec<SearchHit<'a>> {
+        games
+            .iter()
+            .enumerate()
+            .filter(|(_, game)| self.matches(game))
+            .map(|(index, game)| SearchHit {
+                index,
+                span: game.span,
+                game,
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pbn::read_pbn_games;
+
+    const PBN: &str = r#"[Event "Club Pairs"]
+[Date "2024.03.05"]
+[Board "1"]
+[North "Alice Smith"]
+[South "Bob Jones"]
+[Contract "3NT"]
+[Result "9"]
+
+[Event "Club Pairs"]
+[Date "2024.03.05"]
+[Board "2"]
+[North "Carol White"]
+[South "Dan Brown"]
+[Contract "4HX"]
+[Result "8"]
+
+[Event "Spring Teams"]
+[Date "2024.??.??"]
+[Board "1"]
+[West "alice smith"]
+[Contract "3N"]
+[Result "10"]
+"#;
+
+    #[test]
+    fn test_search_by_player_and_contract() {
+        let games = read_pbn_games(PBN).unwrap();
+
+        let hits = Query::new().player("SMITH").search(&games);
+        assert_eq!(hits.len(), 2);
+        assert_eq!(hits[1].index, 2);
+        assert_eq!(hits[1].span.start_line, 17);
+
+        let hits = Query::new().contract("3nt").search(&games);
+        assert_eq!(hits.len(), 2);
+
+        let hits = Query::new().contract("3NT").result(10..=13).search(&games);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].game.tag(&PbnTag::Event), Some("Spring Teams"));
+    }
+
+    #[test]
+    fn test_search_by_event_and_date() {
+        let games = read_pbn_games(PBN).unwrap();
+
+        assert_eq!(Query::new().event("club").search(&games).len(), 2);
+        let hits = Query::new()
+            .date_range("2024.01.01", "2024.12.31")
+            .search(&games);
+        assert_eq!(hits.len(), 2);
+        assert!(Query::new()
+            .date_range("2025.01.01", "2025.12.31")
+            .search(&games)
+            .is_empty());
+    }
+}
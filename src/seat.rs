@@ -0,0 +1,328 @@
+//! Seat names and seat orders.
+//!
+//! Files name seats in many ways: PBN uses `N`, but tags written by hand
+//! or by localized software say `North`, `Noord` or `Süd`, and BBO numbers
+//! the seats `1`-`4`. [`SeatParser`] accepts a configurable set of these.
+//!
+//! Formats also list the four hands in different orders; [`SeatOrder`]
+//! names each of them. [`rotate_seat`] and the helpers built on it do the
+//! clockwise seat arithmetic the readers and writers share, including the
+//! standard dealer and vulnerability of a board number.
+
+use bridge_types::{Direction, Vulnerability};
+
+/// Localized seat words, in N, E, S, W order
+const LOCALIZED: &[[&str; 4]] = &[
+    // Dutch
+    ["noord", "oost", "zuid", "west"],
+    // German
+    ["nord", "ost", "süd", "west"],
+    ["nord", "ost", "sued", "west"],
+    // French and Italian
+    ["nord", "est", "sud", "ouest"],
+    ["nord", "est", "sud", "ovest"],
+    // Spanish
+    ["norte", "este", "sur", "oeste"],
+];
+
+/// An order of the four seats, each seat appearing once
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SeatOrder([Direction; 4]);
+
+impl SeatOrder {
+    /// North, East, South, West: clockwise from North (printall columns,
+    /// oneline hands, fingerprints)
+    pub const NESW: SeatOrder = SeatOrder([
+        Direction::North,
+        Direction::East,
+        Direction::South,
+        Direction::West,
+    ]);
+
+    /// South, West, North, East: the hands of a LIN `md` command
+    pub const SWNE: SeatOrder = SeatOrder([
+        Direction::South,
+        Direction::West,
+        Direction::North,
+        Direction::East,
+    ]);
+
+    /// West, North, East, South: the columns of auction tables
+    pub const WNES: SeatOrder = SeatOrder([
+        Direction::West,
+        Direction::North,
+        Direction::East,
+        Direction::South,
+    ]);
+
+    /// Clockwise from `first`, as the hands of a PBN Deal tag
+    pub fn clockwise_from(first: Direction) -> Self {
+        SeatOrder(std::array::from_fn(|i| rotate_seat(first, i)))
+    }
+
+    /// Any order; `None` unless every seat appears exactly once
+    pub fn custom(seats: [Direction; 4]) -> Option<Self> {
+        Direction::ALL
+            .iter()
+            .all(|seat| seats.contains(seat))
+            .then_some(SeatOrder(seats))
+    }
+
+    /// The seats in this order
+    pub const fn seats(self) -> [Direction; 4] {
+        self.0
+    }
+
+    /// Index of `seat` in this order
+    pub fn position(self, seat: Direction) -> usize {
+        self.0.iter().position(|&s| s == seat).unwrap_or(0)
+    }
+}
+
+impl IntoIterator for SeatOrder {
+    type Item = Direction;
+    type IntoIter = std::array::IntoIter<Direction, 4>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.0.into_iter()
+    }
+}
+
+/// The seat `steps` places clockwise (to the left) of `seat`
+pub fn rotate_seat(seat: Direction, steps: usize) -> Direction {
+    SeatOrder::NESW.seats()[(SeatOrder::NESW.position(seat) + steps) % 4]
+}
+
+/// The seat clockwise of `seat`: the next to call or play
+pub fn next_seat(seat: Direction) -> Direction {
+    rotate_seat(seat, 1)
+}
+
+/// The partner of `seat`
+pub fn partner(seat: Direction) -> Direction {
+    rotate_seat(seat, 2)
+}
+
+/// The opponents of `seat`: left-hand then right-hand opponent
+pub fn opponents(seat: Direction) -> [Direction; 2] {
+    [rotate_seat(seat, 1), rotate_seat(seat, 3)]
+}
+
+/// The number of places clockwise from `from` to `to`, from 0 to 3
+pub fn seat_offset(from: Direction, to: Direction) -> usize {
+    (SeatOrder::NESW.position(to) + 4 - SeatOrder::NESW.position(from)) % 4
+}
+
+/// Dealer of a board by the standard rotation (board 1 = North)
+pub(crate) fn standard_dealer(number: u32) -> Direction {
+    rotate_seat(Direction::North, (number.saturating_sub(1) % 4) as usize)
+}
+
+/// Vulnerability of a board by the standard 16-board cycle
+pub(crate) fn standard_vulnerability(number: u32) -> Vulnerability {
+    const CYCLE: [Vulnerability; 16] = [
+        Vulnerability::None,
+        Vulnerability::NorthSouth,
+        Vulnerability::EastWest,
+        Vulnerability::Both,
+        Vulnerability::NorthSouth,
+        Vulnerability::EastWest,
+        Vulnerability::Both,
+        Vulnerability::None,
+        Vulnerability::EastWest,
+        Vulnerability::Both,
+        Vulnerability::None,
+        Vulnerability::NorthSouth,
+        Vulnerability::Both,
+        Vulnerability::None,
+        Vulnerability::NorthSouth,
+        Vulnerability::EastWest,
+    ];
+    CYCLE[(number.saturating_sub(1) % 16) as usize]
+}
+
+/// Parses seat names in the spellings it is configured to accept.
+///
+/// Matching ignores case and surrounding whitespace.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SeatParser {
+    /// `N`, `E`, `S`, `W`
+    pub letters: bool,
+    /// `North`, `East`, `South`, `West`
+    pub english: bool,
+    /// Dutch, German, French, Italian and Spanish words (`Noord`, `Süd`, ...)
+    pub localized: bool,
+    /// BBO seat digits: `1` = South, `2` = West, `3` = North, `4` = East
+    pub bbo_digits: bool,
+}
+
+impl Default for SeatParser {
+    /// Letters, English and localized words; digits are off since their
+    /// meaning depends on the source
+    fn default() -> Self {
+        Self {
+            letters: true,
+            english: true,
+            localized: true,
+            bbo_digits: false,
+        }
+    }
+}
+
+impl SeatParser {
+    /// A parser accepting letters, English and localized words
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Accept single letters
+    pub fn with_letters(mut self, letters: bool) -> Self {
+        self.letters = letters;
+        self
+    }
+
+    /// Accept English words
+    pub fn with_english(mut self, english: bool) -> Self {
+        self.english = english;
+        self
+    }
+
+    /// Accept localized words
+    pub fn with_localized(mut self, localized: bool) -> Self {
+        self.localized = localized;
+        self
+    }
+
+    /// Accept BBO seat digits
+    pub fn with_bbo_digits(mut self, bbo_digits: bool) -> Self {
+        self.bbo_digits = bbo_digits;
+        self
+    }
+
+    /// Parse a seat name
+    pub fn parse(&self, s: &str) -> Option<Direction> {
+        let s = s.trim().to_lowercase();
+        if self.letters {
+            let seat = match s.as_str() {
+                "n" => Some(Direction::North),
+                "e" => Some(Direction::East),
+                "s" => Some(Direction::South),
+                "w" => Some(Direction::West),
+                _ => None,
+            };
+            if seat.is_some() {
+                return seat;
+            }
+        }
+        if self.english {
+            if let Some(i) = ["north", "east", "south", "west"]
+                .iter()
+                .position(|w| *w == s)
+            {
+                return Some(SeatOrder::NESW.seats()[i]);
+            }
+        }
+        if self.localized {
+            for words in LOCALIZED {
+                if let Some(i) = words.iter().position(|w| *w == s) {
+                    return Some(SeatOrder::NESW.seats()[i]);
+                }
+            }
+        }
+        if self.bbo_digits {
+            let seat = match s.as_str() {
+                "1" => Some(Direction::South),
+                "2" => Some(Direction::West),
+                "3" => Some(Direction::North),
+                "4" => Some(Direction::East),
+                _ => None,
+            };
+            if seat.is_some() {
+                return seat;
+            }
+        }
+        None
+    }
+}
+
+/// Parse a seat letter or an English or localized seat word
+pub fn parse_seat(s: &str) -> Option<Direction> {
+    SeatParser::default().parse(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_seat_order() {
+        let order = SeatOrder::clockwise_from(Direction::West);
+        assert_eq!(
+            order.seats(),
+            [
+                Direction::West,
+                Direction::North,
+                Direction::East,
+                Direction::South
+            ]
+        );
+        assert_eq!(order, SeatOrder::WNES);
+        assert_eq!(SeatOrder::SWNE.position(Direction::East), 3);
+        assert!(SeatOrder::custom([Direction::North; 4]).is_none());
+        assert!(SeatOrder::custom(SeatOrder::SWNE.seats()).is_some());
+    }
+
+    #[test]
+    fn test_seat_arithmetic() {
+        use Direction::*;
+        assert_eq!(next_seat(West), North);
+        assert_eq!(partner(East), West);
+        assert_eq!(opponents(South), [West, East]);
+        assert_eq!(seat_offset(West, South), 3);
+        assert_eq!(rotate_seat(South, seat_offset(South, North)), North);
+        // Format orders are rotations of the clockwise order
+        for order in [SeatOrder::NESW, SeatOrder::SWNE, SeatOrder::WNES] {
+            let first = order.seats()[0];
+            assert_eq!(order, SeatOrder::clockwise_from(first));
+            assert!(Direction::ALL
+                .iter()
+                .all(|&seat| order.position(seat) == seat_offset(first, seat)));
+        }
+    }
+
+    #[test]
+    fn test_standard_board_conventions() {
+        use Direction::*;
+        let dealers: Vec<_> = (1..=5).map(standard_dealer).collect();
+        assert_eq!(dealers, [North, East, South, West, North]);
+        assert_eq!(standard_vulnerability(1), Vulnerability::None);
+        assert_eq!(standard_vulnerability(7), Vulnerability::Both);
+        assert_eq!(standard_vulnerability(16), Vulnerability::EastWest);
+        assert_eq!(standard_vulnerability(17), Vulnerability::None);
+        assert_eq!(standard_dealer(0), North);
+    }
+
+    #[test]
+    fn test_parse_seat() {
+        assert_eq!(parse_seat("N"), Some(Direction::North));
+        assert_eq!(parse_seat(" north "), Some(Direction::North));
+        assert_eq!(parse_seat("Noord"), Some(Direction::North));
+        assert_eq!(parse_seat("SÜD"), Some(Direction::South));
+        assert_eq!(parse_seat("Oost"), Some(Direction::East));
+        assert_eq!(parse_seat("ouest"), Some(Direction::West));
+        assert_eq!(parse_seat("Q"), None);
+        assert_eq!(parse_seat("3"), None);
+    }
+
+    #[test]
+    fn test_configured_spellings() {
+        let digits = SeatParser::new().with_bbo_digits(true);
+        assert_eq!(digits.parse("1"), Some(Direction::South));
+        assert_eq!(digits.parse("4"), Some(Direction::East));
+
+        let words = SeatParser::new().with_letters(false).with_localized(false);
+        assert_eq!(words.parse("s"), None);
+        assert_eq!(words.parse("South"), Some(Direction::South));
+        assert_eq!(words.parse("zuid"), None);
+    }
+}
@@ -0,0 +1,233 @@
+//! Destinations for boards, written all or nothing.
+//!
+//! A [`DealSink`] receives the boards of a session between
+//! [`begin`](DealSink::begin) and [`commit`](DealSink::commit); nothing is
+//! stored unless the whole session commits. [`write_session`] drives a
+//! sink through one session, rolling back on the first error.
+//!
+//! [`FileSink`] writes a file atomically, by writing a temporary file next
+//! to it and renaming it into place; [`MemorySink`] keeps committed boards
+//! in memory. [`convert_to_sink`](crate::convert::convert_to_sink) feeds a
+//! converted file to a sink as one session.
+
+use crate::error::Result;
+use crate::files::format_boards;
+use crate::format::Format;
+use bridge_types::Board;
+use std::path::{Path, PathBuf};
+
+/// A destination that stores boards a session at a time.
+///
+/// Implement this for other stores (databases, network services); it is
+/// the supported extension point for output.
+pub trait DealSink {
+    /// Start a session, discarding boards written but not committed
+    fn begin(&mut self) -> Result<()>;
+
+    /// Add a board to the current session
+    fn write(&mut self, board: &Board) -> Result<()>;
+
+    /// Store the boards of the current session
+    fn commit(&mut self) -> Result<()>;
+
+    /// Discard the boards of the current session
+    fn rollback(&mut self) -> Result<()>;
+}
+
+/// Write `boards` to `sink` as one session, returning the number written.
+///
+/// If a write or the commit fails the session is rolled back, and the
+/// error returned.
+pub fn write_session<S, I>(sink: &mut S, boards: I) -> Result<usize>
+where
+    S: DealSink + ?Sized,
+    I: IntoIterator<Item = Board>,
+{
+    sink.begin()?;
+    let mut written = 0;
+    let result = boards
+        .into_iter()
+        .try_for_each(|board| sink.write(&board).map(|()| written += 1))
+        .and_then(|()| sink.commit());
+    match result {
+        Ok(()) => Ok(written),
+        Err(e) => {
+            // The original error matters more than a failed rollback
+            let _ = sink.rollback();
+            Err(e)
+        }
+    }
+}
+
+/// Keeps committed boards in memory
+#[derive(Debug, Clone, Default)]
+pub struct MemorySink {
+    boards: Vec<Board>,
+    pending: Vec<Board>,
+}
+
+impl MemorySink {
+    /// An empty sink
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The committed boards, in order
+    pub fn boards(&self) -> &[Board] {
+        &self.boards
+    }
+}
+
+impl DealSink for MemorySink {
+    fn begin(&mut self) -> Result<()> {
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn write(&mut self, board: &Board) -> Result<()> {
+        self.pending.push(board.clone());
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        self.boards.append(&mut self.pending);
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+/// Writes the sessions committed to it to a file, in order.
+///
+/// The file is written as by [`write_deals`](crate::write_deals), but
+/// through a temporary file renamed into place, so readers see either the
+/// sessions committed before or those plus the whole new session. The
+/// file's content from before the sink was created is replaced.
+#[derive(Debug, Clone)]
+pub struct FileSink {
+    path: PathBuf,
+    format: Format,
+    committed: Vec<Board>,
+    pending: Vec<Board>,
+}
+
+impl FileSink {
+    /// A sink writing `path` in `format`
+    pub fn new(path: impl AsRef<Path>, format: Format) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            format,
+            committed: Vec::new(),
+            pending: Vec::new(),
+        }
+    }
+
+    /// The temporary file written before the rename
+    fn temp_path(&self) -> PathBuf {
+        let mut name = self.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".tmp");
+        self.path.with_file_name(name)
+    }
+}
+
+impl DealSink for FileSink {
+    fn begin(&mut self) -> Result<()> {
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn write(&mut self, board: &Board) -> Result<()> {
+        self.pending.push(board.clone());
+        Ok(())
+    }
+
+    fn commit(&mut self) -> Result<()> {
+        let temp = self.temp_path();
+        let mut boards = self.committed.clone();
+        boards.extend(self.pending.iter().cloned());
+        std::fs::write(&temp, format_boards(&boards, self.format))?;
+        if let Err(e) = std::fs::rename(&temp, &self.path) {
+            let _ = std::fs::remove_file(&temp);
+            return Err(e.into());
+        }
+        self.committed = boards;
+        self.pending.clear();
+        Ok(())
+    }
+
+    fn rollback(&mut self) -> Result<()> {
+        self.pending.clear();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::ParseError;
+
+    /// Fails on the board with the given number
+    struct Failing(MemorySink, u32);
+
+    impl DealSink for Failing {
+        fn begin(&mut self) -> Result<()> {
+            self.0.begin()
+        }
+
+        fn write(&mut self, board: &Board) -> Result<()> {
+            if board.number == Some(self.1) {
+                return Err(ParseError::Pbn("rejected".to_string()));
+            }
+            self.0.write(board)
+        }
+
+        fn commit(&mut self) -> Result<()> {
+            self.0.commit()
+        }
+
+        fn rollback(&mut self) -> Result<()> {
+            self.0.rollback()
+        }
+    }
+
+    fn boards(count: u32) -> Vec<Board> {
+        (1..=count).map(|n| Board::new().with_number(n)).collect()
+    }
+
+    #[test]
+    fn test_session_all_or_nothing() {
+        let mut sink = Failing(MemorySink::new(), 3);
+        assert!(write_session(&mut sink, boards(4)).is_err());
+        assert!(sink.0.boards().is_empty());
+        assert_eq!(write_session(&mut sink, boards(2)).unwrap(), 2);
+        assert_eq!(sink.0.boards().len(), 2);
+    }
+
+    #[test]
+    fn test_file_sink() {
+        let dir = std::env::temp_dir().join(format!("sink-test-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("out.pbn");
+        let mut sink = FileSink::new(&path, Format::Pbn);
+
+        write_session(&mut sink, boards(3)).unwrap();
+        let read = crate::pbn::read_pbn_file(&path).unwrap();
+        assert_eq!(read.len(), 3);
+        assert!(!sink.temp_path().exists());
+
+        sink.begin().unwrap();
+        sink.write(&Board::new().with_number(9)).unwrap();
+        sink.rollback().unwrap();
+        assert_eq!(crate::pbn::read_pbn_file(&path).unwrap().len(), 3);
+
+        // Later sessions are added to the earlier ones
+        write_session(&mut sink, boards(2)).unwrap();
+        let read = crate::pbn::read_pbn_file(&path).unwrap();
+        let numbers: Vec<_> = read.iter().filter_map(|board| board.number).collect();
+        assert_eq!(numbers, [1, 2, 3, 1, 2]);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,141 @@
+//! Output size estimation.
+//!
+//! [`estimate_size`] predicts how many bytes [`write_deals`](crate::write_deals)
+//! would write, from card counts and tag value lengths alone, so callers
+//! enforcing size limits don't have to format the output first.
+
+use crate::cards::SUITS;
+use crate::format::Format;
+use crate::seat::SeatOrder;
+use bridge_types::Board;
+
+/// Length of the `% PBN 2.1` / `% EXPORT` header and the blank line after it
+const PBN_HEADER: usize = "% PBN 2.1\n% EXPORT\n\n".len();
+
+/// Predict the size in bytes of `boards` written in `format` with the
+/// default options.
+///
+/// The prediction is exact except for PBN tag values containing quotes or
+/// backslashes, which gain an escape character each.
+pub fn estimate_size(boards: &[Board], format: Format) -> usize {
+    let total: usize = boards
+        .iter()
+        .enumerate()
+        .map(|(i, board)| board_size(board, i + 1, format))
+        .sum();
+    match format {
+        // Header, and a blank line between games
+        Format::Pbn => PBN_HEADER + total + boards.len().saturating_sub(1),
+        _ => total,
+    }
+}
+
+/// Size of the `position`th board (from 1), with its line endings
+fn board_size(board: &Board, position: usize, format: Format) -> usize {
+    let cards: usize = SeatOrder::NESW
+        .into_iter()
+        .map(|seat| board.deal.hand(seat).len())
+        .sum();
+    let digits = board.number.map(|n| n.to_string().len());
+    match format {
+        Format::Pbn => {
+            // Four hands of dotted suits, separated by spaces, after "N:"
+            let deal = 2 + cards + 4 * 3 + 3;
+            let value_len = |value: &Option<String>| value.as_ref().map_or(0, String::len);
+            let mut tags = vec![
+                ("Event", value_len(&board.event)),
+                ("Site", value_len(&board.site)),
+                ("Date", value_len(&board.date)),
+                ("West", 0),
+                ("North", 0),
+                ("East", 0),
+                ("South", 0),
+                ("Vulnerable", board.vulnerable.to_pbn().len()),
+                ("Deal", deal),
+                ("Scoring", 0),
+                ("Declarer", 0),
+                ("Contract", 0),
+                ("Result", 0),
+            ];
+            if let Some(digits) = digits {
+                tags.push(("Board", digits));
+            }
+            if board.dealer.is_some() {
+                tags.push(("Dealer", 1));
+            }
+            for (name, value) in [
+                ("DoubleDummyTricks", &board.double_dummy_tricks),
+                ("OptimumScore", &board.optimum_score),
+                ("ParContract", &board.par_contract),
+            ] {
+                if value.is_some() {
+                    tags.push((name, value_len(value)));
+                }
+            }
+            // [Name "value"] and a newline
+            tags.iter().map(|(name, len)| name.len() + len + 6).sum()
+        }
+        Format::Lin => {
+            // md|<digit><four hands of suit letters and ranks>|, then sv|x|
+            let md = 3 + 1 + cards + 4 * 4 + 3 + 1;
+            let ah = digits.map_or(0, |digits| "ah|Board |".len() + digits);
+            md + 5 + ah + 1
+        }
+        // Seat letter and space before each hand of dotted suits
+        Format::Oneline => 4 * 2 + cards + 4 * 3 + 3 + 1,
+        Format::Printall => {
+            // The position, right-aligned in four columns, a dot and a newline
+            let header = position.to_string().len().max(4) + 2;
+            let rows: usize = SUITS
+                .into_iter()
+                .map(|suit| {
+                    let widths: Vec<usize> = SeatOrder::NESW
+                        .into_iter()
+                        .map(|seat| 2 * board.deal.hand(seat).suit_length(suit).max(1))
+                        .collect();
+                    // North, East and South are padded to 20 columns
+                    widths[..3].iter().map(|&w| w.max(20)).sum::<usize>() + widths[3] + 1
+                })
+                .sum();
+            header + rows + 1
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::WriteOptions;
+    use bridge_types::{Deal, Direction, Vulnerability};
+
+    #[test]
+    fn test_estimate_size() {
+        let deal =
+            Deal::from_pbn("N:AKQ976.KJ84.T32. J84.Q97.AK4.QJ87 T53.AT65..AT9654 2.32.QJ98765.K32")
+                .unwrap();
+        let mut board = Board::new()
+            .with_number(12)
+            .with_dealer(Direction::West)
+            .with_vulnerability(Vulnerability::NorthSouth)
+            .with_deal(deal.clone());
+        board.event = Some("Club".to_string());
+        let boards = [board, Board::new().with_deal(deal)];
+        let deals: Vec<Deal> = boards.iter().map(|b| b.deal.clone()).collect();
+
+        for (format, text) in [
+            (Format::Pbn, crate::pbn::write_pbn(&boards)),
+            (Format::Lin, crate::lin::write_lin(&boards)),
+            (
+                Format::Oneline,
+                crate::oneline::write_oneline(&deals, &WriteOptions::new()),
+            ),
+            (
+                Format::Printall,
+                crate::printall::write_printall(&deals, &WriteOptions::new()),
+            ),
+        ] {
+            assert_eq!(estimate_size(&boards, format), text.len(), "{:?}", format);
+        }
+        assert_eq!(estimate_size(&[], Format::Pbn), PBN_HEADER);
+    }
+}
@@ -0,0 +1,191 @@
+//! Card-play statistics over many results.
+//!
+//! [`PlayStats`] counts how often contracts make, broken down by contract,
+//! by the suit of the opening lead, and for doubled and redoubled
+//! contracts, and writes the counts as CSV.
+
+use crate::cards::SUITS;
+use crate::lin::LinData;
+use crate::pbn::{Call, Game};
+use crate::scoring::PlayedResult;
+use bridge_types::{Card, Doubled, Strain};
+
+/// Strains in bidding order, for sorting contracts
+const STRAINS: [Strain; 5] = [
+    Strain::Clubs,
+    Strain::Diamonds,
+    Strain::Hearts,
+    Strain::Spades,
+    Strain::NoTrump,
+];
+
+/// How many contracts were played and how many of them made
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MakeCount {
+    pub played: usize,
+    pub made: usize,
+}
+
+impl MakeCount {
+    /// Fraction of the contracts that made; `None` if none were played
+    pub fn make_rate(&self) -> Option<f64> {
+        (self.played > 0).then(|| self.made as f64 / self.played as f64)
+    }
+
+    fn add(&mut self, made: bool) {
+        self.played += 1;
+        if made {
+            self.made += 1;
+        }
+    }
+}
+
+/// Make counts aggregated over played results
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PlayStats {
+    /// Counts per contract level and strain (doubling ignored), in bidding
+    /// order
+    pub contracts: Vec<(u8, Strain, MakeCount)>,
+    /// Counts per opening lead suit, spades first; results without a
+    /// recorded lead aren't counted
+    pub leads: [MakeCount; 4],
+    /// Counts for doubled contracts; the double succeeded when the
+    /// contract didn't make
+    pub doubled: MakeCount,
+    /// Counts for redoubled contracts
+    pub redoubled: MakeCount,
+}
+
+impl PlayStats {
+    /// Empty statistics
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Count a result, with the opening lead if known
+    pub fn add(&mut self, result: &PlayedResult, lead: Option<Card>) {
+        let made = result.over_under() >= 0;
+        let contract = result.contract;
+        let key = |level: u8, strain: Strain| {
+            (
+                level,
+                STRAINS.iter().position(|&s| s == strain).unwrap_or(0),
+            )
+        };
+        let new_key = key(contract.level, contract.strain);
+        let index = match self
+            .contracts
+            .binary_search_by_key(&new_key, |&(level, strain, _)| key(level, strain))
+        {
+            Ok(index) => index,
+            Err(index) => {
+                self.contracts.insert(
+                    index,
+                    (contract.level, contract.strain, MakeCount::default()),
+                );
+                index
+            }
+        };
+        self.contracts[index].2.add(made);
+
+        if let Some(lead) = lead {
+            if let Some(suit) = SUITS.iter().position(|&s| s == lead.suit) {
+                self.leads[suit].add(made);
+            }
+        }
+        match contract.doubled {
+            Doubled::None => {}
+            Doubled::Doubled => self.doubled.add(made),
+            Doubled::Redoubled => self.redoubled.add(made),
+        }
+    }
+
+    /// Count the result of a PBN game, if it has one
+    pub fn add_game(&mut self, game: &Game) {
+        if let Some(result) = PlayedResult::from_game(game) {
+            let lead = game
+                .play
+                .as_ref()
+                .and_then(|play| play.tricks.first())
+                .and_then(|trick| trick[0]);
+            self.add(&result, lead);
+        }
+    }
+
+    /// Count the result of a LIN record, if it has one
+    pub fn add_lin(&mut self, lin: &LinData) {
+        if let Some(result) = lin.played_result() {
+            self.add(&result, lin.play.first().copied());
+        }
+    }
+
+    /// The counts as CSV: a `category,key,played,made,make_rate` header,
+    /// then one row per contract (`contract,4S`), lead suit (`lead,S`)
+    /// and doubling (`doubled,X` and `doubled,XX`)
+    pub fn to_csv(&self) -> String {
+        let mut out = String::from("category,key,played,made,make_rate\n");
+        let mut row = |category: &str, key: &str, count: &MakeCount| {
+            let rate = count
+                .make_rate()
+                .map_or_else(String::new, |rate| format!("{:.3}", rate));
+            out.push_str(&format!(
+                "{},{},{},{},{}\n",
+                category, key, count.played, count.made, rate
+            ));
+        };
+        for (level, strain, count) in &self.contracts {
+            row("contract", &Call::Bid(*level, *strain).to_string(), count);
+        }
+        for (suit, count) in SUITS.iter().zip(&self.leads) {
+            row("lead", &suit.to_char().to_string(), count);
+        }
+        row("doubled", "X", &self.doubled);
+        row("doubled", "XX", &self.redoubled);
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bridge_types::{Contract, Direction, Rank, Suit};
+
+    #[test]
+    fn test_play_stats() {
+        let result = |level, strain, doubled, tricks| {
+            PlayedResult::new(
+                Contract::new(level, strain, doubled),
+                Direction::South,
+                tricks,
+            )
+            .unwrap()
+        };
+        let mut stats = PlayStats::new();
+        let lead = Some(Card::new(Suit::Hearts, Rank::King));
+        stats.add(&result(4, Strain::Spades, Doubled::None, 10), lead);
+        stats.add(&result(4, Strain::Spades, Doubled::Doubled, 9), lead);
+        stats.add(&result(3, Strain::NoTrump, Doubled::None, 9), None);
+        stats.add(&result(3, Strain::Clubs, Doubled::None, 8), None);
+
+        let contracts: Vec<_> = stats
+            .contracts
+            .iter()
+            .map(|&(level, strain, count)| (level, strain, count.played, count.made))
+            .collect();
+        assert_eq!(
+            contracts,
+            [
+                (3, Strain::Clubs, 1, 0),
+                (3, Strain::NoTrump, 1, 1),
+                (4, Strain::Spades, 2, 1),
+            ]
+        );
+        assert_eq!(stats.leads[1], MakeCount { played: 2, made: 1 });
+        assert_eq!(stats.doubled, MakeCount { played: 1, made: 0 });
+
+        let csv = stats.to_csv();
+        assert!(csv.contains("\ncontract,4S,2,1,0.500\n"));
+        assert!(csv.contains("\nlead,H,2,1,0.500\n"));
+        assert!(csv.ends_with("doubled,X,1,0,0.000\ndoubled,XX,0,0,\n"));
+    }
+}
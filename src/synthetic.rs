@@ -0,0 +1,342 @@
+//! Synthetic test data.
+//!
+//! [`Generator`] produces random but syntactically valid files in each
+//! supported format, for fuzzing and integration tests of tools built on
+//! this crate. Output is fully determined by the seed. Every fourth deal
+//! is skewed to contain a 7-card suit and a void, and PBN games and LIN
+//! records include alerted bids, a lead and a claim.
+
+use crate::cards::all_cards;
+use crate::lin::board_to_lin;
+use crate::oneline::format_oneline;
+use crate::pbn::{
+    write_pbn_games, Auction, AuctionCall, Call, Game, PbnTag, PbnWriteOptions, Play, TagPair,
+};
+use crate::printall::format_printall;
+use crate::seat::{next_seat, standard_dealer, standard_vulnerability, SeatOrder};
+use bridge_types::{Board, Card, Contract, Deal, Direction, Doubled, Hand, Strain, Suit};
+
+/// Seeded generator of deals, boards and files
+#[derive(Debug, Clone)]
+pub struct Generator {
+    state: u64,
+    deals: u64,
+}
+
+impl Generator {
+    /// Create a generator; equal seeds give equal output
+    pub fn new(seed: u64) -> Self {
+        Self {
+            state: seed,
+            deals: 0,
+        }
+    }
+
+    /// splitmix64
+    fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// A random index below `n`
+    fn below(&mut self, n: usize) -> usize {
+        (self.next_u64() % n as u64) as usize
+    }
+
+    fn shuffle<T>(&mut self, items: &mut [T]) {
+        for i in (1..items.len()).rev() {
+            let j = self.below(i + 1);
+            items.swap(i, j);
+        }
+    }
+
+    /// A random complete deal
+    pub fn deal(&mut self) -> Deal {
+        self.deals += 1;
+        if self.deals.is_multiple_of(4) {
+            self.skewed_deal()
+        } else {
+            self.random_deal()
+        }
+    }
+
+    fn random_deal(&mut self) -> Deal {
+        let mut cards: Vec<Card> = all_cards().collect();
+        self.shuffle(&mut cards);
+        deal_from(&[], &cards)
+    }
+
+    /// A deal where one hand has a 7-card suit and a void
+    fn skewed_deal(&mut self) -> Deal {
+        let seat = SeatOrder::NESW.seats()[self.below(4)];
+        let mut suits = Suit::ALL;
+        self.shuffle(&mut suits);
+        let (long, void) = (suits[0], suits[3]);
+
+        let mut cards: Vec<Card> = all_cards().collect();
+        self.shuffle(&mut cards);
+        let mut hand = Vec::with_capacity(13);
+        let mut rest = Vec::with_capacity(39);
+        let mut long_cards = 0;
+        for card in cards {
+            if card.suit == long && long_cards < 7 {
+                long_cards += 1;
+                hand.push(card);
+            } else {
+                rest.push(card);
+            }
+        }
+        // Fill with six cards from the two side suits
+        let mut side = 0;
+        let mut remaining = Vec::with_capacity(39);
+        for card in rest {
+            if side < 6 && card.suit != long && card.suit != void {
+                side += 1;
+                hand.push(card);
+            } else {
+                remaining.push(card);
+            }
+        }
+
+        deal_from(&[(seat, hand)], &remaining)
+    }
+
+    /// A random board with standard dealer and vulnerability for its number
+    pub fn board(&mut self, number: u32) -> Board {
+        Board::new()
+            .with_number(number)
+            .with_dealer(standard_dealer(number))
+            .with_vulnerability(standard_vulnerability(number))
+            .with_deal(self.deal())
+    }
+
+    /// A PBN file of `count` games with auctions, alerts, a lead and a claim
+    pub fn pbn(&mut self, count: u32) -> String {
+        let games: Vec<Game> = (1..=count)
+            .map(|number| {
+                let board = self.board(number);
+                let played = self.played(&board);
+                pbn_game(board, &played)
+            })
+            .collect();
+        write_pbn_games(&games, &PbnWriteOptions::default())
+    }
+
+    /// A LIN file of `count` records with auctions, alerts, a lead and a claim
+    pub fn lin(&mut self, count: u32) -> String {
+        let mut out = String::new();
+        for number in 1..=count {
+            let board = self.board(number);
+            let played = self.played(&board);
+            out.push_str(&board_to_lin(&board));
+            out.push_str(&lin_auction_and_play(&played));
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Dealer opens, the others pass; the opening is sometimes alerted
+    fn played(&mut self, board: &Board) -> Played {
+        const STRAINS: [Strain; 5] = [
+            Strain::Clubs,
+            Strain::Diamonds,
+            Strain::Hearts,
+            Strain::Spades,
+            Strain::NoTrump,
+        ];
+        let level = 1 + self.below(3) as u8;
+        let strain = STRAINS[self.below(5)];
+        let alerted = self.below(2) == 0;
+
+        // Opening lead by the player left of declarer (the dealer)
+        let dealer = board.dealer.unwrap_or(Direction::North);
+        let hand = board.deal.hand(next_seat(dealer));
+        let suits: Vec<Suit> = Suit::ALL
+            .into_iter()
+            .filter(|&s| hand.suit_length(s) > 0)
+            .collect();
+        let lead = (!suits.is_empty()).then(|| {
+            let cards = hand.cards_in_suit(suits[self.below(suits.len())]);
+            cards[self.below(cards.len())]
+        });
+        Played {
+            declarer: dealer,
+            level,
+            strain,
+            alerted,
+            lead,
+            claim: self.below(14) as u8,
+        }
+    }
+
+    /// A oneline file of `count` deals
+    pub fn oneline(&mut self, count: usize) -> String {
+        (0..count).map(|_| format_oneline(&self.deal())).collect()
+    }
+
+    /// A printall file of `count` deals
+    pub fn printall(&mut self, count: usize) -> String {
+        (1..=count)
+            .map(|n| format_printall(&self.deal(), n))
+            .collect()
+    }
+}
+
+/// The auction and play of a generated record
+struct Played {
+    /// The dealer, who opens and declares
+    declarer: Direction,
+    level: u8,
+    strain: Strain,
+    /// Whether the opening is alerted and explained
+    alerted: bool,
+    lead: Option<Card>,
+    /// Tricks claimed by declarer
+    claim: u8,
+}
+
+/// The LIN auction, lead and claim
+fn lin_auction_and_play(played: &Played) -> String {
+    let strain = match played.strain {
+        Strain::Clubs => 'C',
+        Strain::Diamonds => 'D',
+        Strain::Hearts => 'H',
+        Strain::Spades => 'S',
+        Strain::NoTrump => 'N',
+    };
+    let mut out = format!("mb|{}{}", played.level, strain);
+    if played.alerted {
+        out.push_str("!|an|artificial");
+    }
+    out.push_str("|mb|p|mb|p|mb|p|");
+    if let Some(card) = played.lead {
+        out.push_str(&format!(
+            "pc|{}{}|",
+            card.suit.to_char(),
+            card.rank.to_char()
+        ));
+    }
+    out.push_str(&format!("mc|{}|", played.claim));
+    out
+}
+
+/// A PBN game with the auction, the lead and the claimed result
+fn pbn_game(board: Board, played: &Played) -> Game {
+    let declarer = played.declarer;
+    let mut game = Game::new(board);
+    let call = |call| AuctionCall {
+        call,
+        alert: false,
+        note: None,
+        nags: Vec::new(),
+    };
+    let mut opening = call(Call::Bid(played.level, played.strain));
+    if played.alerted {
+        opening.alert = true;
+        opening.note = Some(game.add_note("artificial"));
+    }
+    game.auction = Some(Auction {
+        first: declarer,
+        calls: vec![
+            opening,
+            call(Call::Pass),
+            call(Call::Pass),
+            call(Call::Pass),
+        ],
+    });
+    game.contract = Some(Contract::new(played.level, played.strain, Doubled::None));
+
+    let leader = next_seat(declarer);
+    let mut push = |tag, value: String| game.tags.push(TagPair { tag, value });
+    push(PbnTag::Declarer, declarer.to_char().to_string());
+    push(PbnTag::Result, played.claim.to_string());
+    if let Some(lead) = played.lead {
+        push(PbnTag::Play, leader.to_char().to_string());
+        game.play = Some(Play::from_played(leader, played.strain, &[lead]));
+    }
+    game
+}
+
+/// Build a deal from preassigned hands, dealing `rest` to the other seats
+fn deal_from(fixed: &[(Direction, Vec<Card>)], rest: &[Card]) -> Deal {
+    let mut deal = Deal::new();
+    let mut rest = rest.iter().copied();
+    for seat in SeatOrder::NESW {
+        let cards = match fixed.iter().find(|(s, _)| *s == seat) {
+            Some((_, cards)) => cards.clone(),
+            None => rest.by_ref().take(13).collect(),
+        };
+        deal.set_hand(seat, Hand::from_cards(cards));
+    }
+    deal
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fingerprint::Fingerprint;
+
+    #[test]
+    fn test_deals_are_complete_and_seeded() {
+        let mut a = Generator::new(7);
+        let mut b = Generator::new(7);
+        for _ in 0..20 {
+            let deal = a.deal();
+            assert!(Fingerprint::of(&deal).is_some());
+            for seat in SeatOrder::NESW {
+                assert_eq!(deal.hand(seat).len(), 13);
+            }
+            assert_eq!(Fingerprint::of(&deal), Fingerprint::of(&b.deal()));
+        }
+    }
+
+    #[test]
+    fn test_skewed_deal_has_long_suit_and_void() {
+        let mut generator = Generator::new(1);
+        for _ in 0..3 {
+            generator.deal();
+        }
+        let deal = generator.deal();
+        let skewed = SeatOrder::NESW.into_iter().any(|seat| {
+            let lengths: Vec<usize> = Suit::ALL
+                .iter()
+                .map(|&s| deal.hand(seat).suit_length(s))
+                .collect();
+            lengths.contains(&7) && lengths.contains(&0)
+        });
+        assert!(skewed);
+    }
+
+    #[test]
+    fn test_generated_files_parse() {
+        let mut generator = Generator::new(42);
+
+        let games = crate::pbn::read_pbn_games(&generator.pbn(8)).unwrap();
+        assert_eq!(games.len(), 8);
+        assert!(games
+            .iter()
+            .all(|game| game.auction.is_some() && game.play.is_some() && game.result().is_some()));
+        assert!(games.iter().any(|game| !game.notes().is_empty()));
+
+        let records = crate::lin::parse_lin_file(&generator.lin(8)).unwrap();
+        assert_eq!(records.len(), 8);
+        assert!(records
+            .iter()
+            .all(|r| r.claim.is_some() && r.play.len() == 1));
+        assert_eq!(
+            records[0].final_contract().map(|c| c.is_empty()),
+            Some(false)
+        );
+
+        let deals = crate::printall::parse_printall_string(&generator.printall(5)).unwrap();
+        assert_eq!(deals.len(), 5);
+
+        let text = generator.oneline(5);
+        assert!(text
+            .lines()
+            .all(|line| crate::oneline::parse_oneline(line).is_ok()));
+    }
+}
@@ -0,0 +1,139 @@
+//! Following deal logs that grow while being read.
+//!
+//! Live dealing setups append deals to a shared PBN or oneline file. A
+//! [`TailReader`] returns the deals added since the last poll and records
+//! the byte offset it has consumed, so reading can resume after a restart.
+
+use crate::error::{ParseError, Result};
+use crate::reader::DealReader;
+use bridge_types::Deal;
+use std::collections::VecDeque;
+use std::fs::File;
+use std::io::{Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// Reads deals appended to a file, `tail -f` style.
+///
+/// Only complete deals are consumed: a record still being written when
+/// the file is polled is picked up by a later poll. If the file shrinks
+/// (truncated or replaced), reading restarts from the beginning.
+#[derive(Debug, Clone)]
+pub struct TailReader {
+    path: PathBuf,
+    offset: u64,
+}
+
+impl TailReader {
+    /// Read `path` from the beginning
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        Self::resume(path, 0)
+    }
+
+    /// Continue reading `path` from a byte offset saved from [`offset`](Self::offset)
+    pub fn resume(path: impl AsRef<Path>, offset: u64) -> Self {
+        Self {
+            path: path.as_ref().to_path_buf(),
+            offset,
+        }
+    }
+
+    /// Byte offset just past the last consumed deal
+    pub fn offset(&self) -> u64 {
+        self.offset
+    }
+
+    /// Return the complete deals appended since the last poll.
+    ///
+    /// Blocks that fail to parse are skipped and not retried.
+    pub fn poll(&mut self) -> Result<Vec<Deal>> {
+        let mut file = File::open(&self.path)?;
+        if file.metadata()?.len() < self.offset {
+            self.offset = 0;
+        }
+        file.seek(SeekFrom::Start(self.offset))?;
+        let mut buf = Vec::new();
+        file.read_to_end(&mut buf)?;
+
+        // Only look at complete lines
+        let line_ends: Vec<usize> = buf
+            .iter()
+            .enumerate()
+            .filter(|&(_, &b)| b == b'\n')
+            .map(|(i, _)| i + 1)
+            .collect();
+        let Some(&complete) = line_ends.last() else {
+            return Ok(Vec::new());
+        };
+
+        let mut reader = DealReader::new(&buf[..complete]);
+        let mut deals = Vec::new();
+        let mut consumed_lines = 0;
+        while let Some(result) = reader.next() {
+            match result {
+                Ok(deal) => deals.push(deal),
+                Err(ParseError::Io(e)) => return Err(ParseError::Io(e)),
+                Err(_) => {}
+            }
+            consumed_lines = reader.line_number();
+        }
+
+        if consumed_lines > 0 {
+            self.offset += line_ends[consumed_lines - 1] as u64;
+        }
+        Ok(deals)
+    }
+
+    /// Iterate over deals indefinitely, polling every `interval` when no
+    /// new deals are available. Errors are yielded and polling continues.
+    pub fn follow(&mut self, interval: Duration) -> impl Iterator<Item = Result<Deal>> + '_ {
+        let mut ready = VecDeque::new();
+        std::iter::from_fn(move || loop {
+            if let Some(deal) = ready.pop_front() {
+                return Some(Ok(deal));
+            }
+            match self.poll() {
+                Ok(deals) if deals.is_empty() => std::thread::sleep(interval),
+                Ok(deals) => ready.extend(deals),
+                Err(e) => return Some(Err(e)),
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    const LINE: &str =
+        "n AKQT3.J6.KJ42.95 e 652.AK42.AQ87.T4 s J74.QT95.T.AK863 w 98.873.9653.QJ72\n";
+
+    #[test]
+    fn test_poll_appended_deals() {
+        let path = std::env::temp_dir().join(format!("tail-test-{}.txt", std::process::id()));
+        std::fs::write(&path, LINE).unwrap();
+
+        let mut tail = TailReader::new(&path);
+        assert_eq!(tail.poll().unwrap().len(), 1);
+        assert_eq!(tail.offset(), LINE.len() as u64);
+        assert!(tail.poll().unwrap().is_empty());
+
+        // A partially written line is left for the next poll
+        let mut file = std::fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap();
+        file.write_all(LINE.as_bytes()).unwrap();
+        file.write_all(&LINE.as_bytes()[..20]).unwrap();
+        assert_eq!(tail.poll().unwrap().len(), 1);
+        file.write_all(&LINE.as_bytes()[20..]).unwrap();
+
+        // Resuming from a saved offset sees only the remaining deal
+        let mut resumed = TailReader::resume(&path, tail.offset());
+        assert_eq!(resumed.poll().unwrap().len(), 1);
+        assert_eq!(resumed.offset(), 3 * LINE.len() as u64);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,449 @@
+//! Board transforms for reader-to-writer pipelines.
+//!
+//! A [`Transform`] rewrites or drops one board at a time. Transforms are
+//! composed with a [`Chain`], which can be applied to any stream of boards.
+//!
+//! # Example
+//!
+//! ```
+//! use bridge_encodings::pbn::read_pbn;
+//! use bridge_encodings::transform::{Anonymize, Chain, Renumber};
+//!
+//! let input = "[Event \"Club\"]\n[Board \"7\"]\n";
+//! let chain = Chain::new().with(Anonymize).with(Renumber::from(1));
+//! let boards: Vec<_> = chain.run(read_pbn(input).unwrap()).collect();
+//! assert_eq!(boards[0].number, Some(1));
+//! assert_eq!(boards[0].event, None);
+//! ```
+
+use crate::analysis::{apply_analysis, DdAnalysis};
+use crate::fingerprint::Fingerprint;
+use crate::pbn::DoubleDummyTable;
+use crate::scoring::{OptimumScoreTag, ParContractTag, Side};
+use crate::seat::{rotate_seat, SeatOrder};
+use bridge_types::{Board, Card, Deal, Direction, Hand, Rank, Suit, Vulnerability};
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+/// A step in a board pipeline.
+///
+/// Implement this for custom steps; it is the supported extension point
+/// for pipelines.
+pub trait Transform: Send + Sync {
+    /// Transform a board, or return `None` to drop it
+    fn apply(&self, board: Board) -> Option<Board>;
+}
+
+/// A sequence of transforms applied in order
+#[derive(Default)]
+pub struct Chain {
+    steps: Vec<Box<dyn Transform>>,
+}
+
+impl Chain {
+    /// Create an empty chain (passes boards through unchanged)
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Append a transform
+    pub fn with<T: Transform + 'static>(mut self, transform: T) -> Self {
+        self.steps.push(Box::new(transform));
+        self
+    }
+
+    /// Number of transforms in the chain
+    pub fn len(&self) -> usize {
+        self.steps.len()
+    }
+
+    /// Whether the chain has no transforms
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// Apply the chain to a stream of boards, dropping filtered boards
+    pub fn run<'a, I>(&'a self, boards: I) -> impl Iterator<Item = Board> + 'a
+    where
+        I: IntoIterator<Item = Board>,
+        I::IntoIter: 'a,
+    {
+        boards.into_iter().filter_map(|board| self.apply(board))
+    }
+}
+
+impl fmt::Debug for Chain {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Chain")
+            .field("steps", &self.steps.len())
+            .finish()
+    }
+}
+
+impl Transform for Chain {
+    fn apply(&self, board: Board) -> Option<Board> {
+        self.steps
+            .iter()
+            .try_fold(board, |board, step| step.apply(board))
+    }
+}
+
+/// Number boards consecutively
+#[derive(Debug)]
+pub struct Renumber {
+    next: AtomicU32,
+}
+
+impl From<u32> for Renumber {
+    /// Start numbering at `start`
+    fn from(start: u32) -> Self {
+        Self {
+            next: AtomicU32::new(start),
+        }
+    }
+}
+
+impl Transform for Renumber {
+    fn apply(&self, mut board: Board) -> Option<Board> {
+        board.number = Some(self.next.fetch_add(1, Ordering::Relaxed));
+        Some(board)
+    }
+}
+
+/// Rotate the table clockwise by a number of seats.
+///
+/// Hands, dealer and vulnerability all move, so the board is the same
+/// problem seen from another seat. The double dummy analysis moves with
+/// them; analysis values that can't be read are removed, as they would
+/// describe the wrong seats.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotate(pub u8);
+
+impl Rotate {
+    fn seat(&self, dir: Direction) -> Direction {
+        rotate_seat(dir, usize::from(self.0))
+    }
+}
+
+impl Transform for Rotate {
+    fn apply(&self, mut board: Board) -> Option<Board> {
+        let mut deal = Deal::new();
+        for dir in SeatOrder::NESW {
+            deal.set_hand(self.seat(dir), board.deal.hand(dir).clone());
+        }
+        board.deal = deal;
+        board.dealer = board.dealer.map(|dealer| self.seat(dealer));
+        if self.0 % 2 == 1 {
+            board.vulnerable = match board.vulnerable {
+                Vulnerability::NorthSouth => Vulnerability::EastWest,
+                Vulnerability::EastWest => Vulnerability::NorthSouth,
+                other => other,
+            };
+        }
+        self.rotate_analysis(&mut board);
+        Some(board)
+    }
+}
+
+impl Rotate {
+    /// Move the DoubleDummyTricks, OptimumScore and ParContract values
+    /// with the seats
+    fn rotate_analysis(&self, board: &mut Board) {
+        board.double_dummy_tricks = board
+            .double_dummy_tricks
+            .as_deref()
+            .and_then(DoubleDummyTable::from_dd_tricks)
+            .and_then(|table| table.rotated(usize::from(self.0)).to_dd_tricks());
+        board.optimum_score = board.optimum_score.as_deref().and_then(|value| {
+            let mut optimum = OptimumScoreTag::from_pbn(value).ok()??;
+            if self.0 % 2 == 1 {
+                optimum.side = match optimum.side {
+                    Side::NorthSouth => Side::EastWest,
+                    Side::EastWest => Side::NorthSouth,
+                };
+            }
+            Some(optimum.to_pbn())
+        });
+        board.par_contract = board.par_contract.as_deref().and_then(|value| {
+            let mut par = ParContractTag::from_pbn(value).ok()??;
+            par.declarer = self.seat(par.declarer);
+            Some(par.to_pbn())
+        });
+    }
+}
+
+/// Remove identifying event data (event, site and date)
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Anonymize;
+
+impl Transform for Anonymize {
+    fn apply(&self, mut board: Board) -> Option<Board> {
+        board.event = None;
+        board.site = None;
+        board.date = None;
+        Some(board)
+    }
+}
+
+/// Keep only boards matching a predicate
+pub struct Filter<F>(pub F);
+
+impl<F> Transform for Filter<F>
+where
+    F: Fn(&Board) -> bool + Send + Sync,
+{
+    fn apply(&self, board: Board) -> Option<Board> {
+        (self.0)(&board).then_some(board)
+    }
+}
+
+/// Attach double dummy analyses to boards by fingerprint or board number
+#[derive(Debug, Clone, Default)]
+pub struct AttachDd {
+    by_fingerprint: HashMap<Fingerprint, DdAnalysis>,
+    by_number: HashMap<u32, DdAnalysis>,
+}
+
+impl AttachDd {
+    /// Index analyses for lookup. Analyses without a fingerprint are matched
+    /// by board number.
+    pub fn new(analyses: Vec<DdAnalysis>) -> Self {
+        let mut attach = Self::default();
+        for analysis in analyses {
+            match (analysis.fingerprint, analysis.board_number) {
+                (Some(fp), _) => {
+                    attach.by_fingerprint.insert(fp, analysis);
+                }
+                (None, Some(number)) => {
+                    attach.by_number.insert(number, analysis);
+                }
+                (None, None) => {}
+            }
+        }
+        attach
+    }
+}
+
+impl Transform for AttachDd {
+    fn apply(&self, mut board: Board) -> Option<Board> {
+        let analysis = Fingerprint::of(&board.deal)
+            .and_then(|fp| self.by_fingerprint.get(&fp))
+            .or_else(|| board.number.and_then(|n| self.by_number.get(&n)));
+        if let Some(analysis) = analysis {
+            apply_analysis(&mut board, analysis);
+        }
+        Some(board)
+    }
+}
+
+/// A relabelling of the suits
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Permutation {
+    /// Image of each suit, indexed in `Suit::ALL` order
+    images: [Suit; 4],
+}
+
+impl Permutation {
+    /// The permutation leaving every suit in place
+    pub fn identity() -> Self {
+        Self { images: Suit::ALL }
+    }
+
+    /// The suit that `suit` maps to
+    pub fn apply(&self, suit: Suit) -> Suit {
+        self.images[suit_index(suit)]
+    }
+
+    /// The permutation undoing this one
+    pub fn inverse(&self) -> Self {
+        let mut images = Suit::ALL;
+        for suit in Suit::ALL {
+            images[suit_index(self.apply(suit))] = suit;
+        }
+        Self { images }
+    }
+
+    /// Relabel the suits of every card in a deal
+    pub fn apply_to_deal(&self, deal: &Deal) -> Deal {
+        let mut out = Deal::new();
+        for dir in SeatOrder::NESW {
+            let cards = Suit::ALL
+                .into_iter()
+                .flat_map(|suit| deal.hand(dir).cards_in_suit(suit))
+                .map(|card| Card::new(self.apply(card.suit), card.rank))
+                .collect();
+            out.set_hand(dir, Hand::from_cards(cards));
+        }
+        out
+    }
+}
+
+impl Default for Permutation {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// Relabel the suits of a deal into a canonical order, so deals that differ
+/// only by a suit permutation canonicalize to the same deal.
+///
+/// Suits are ordered by the holdings of North, then East, South and West
+/// (longer and higher holdings first), and the first becomes spades.
+/// Returns the canonical deal and the permutation mapping it back to
+/// `deal`.
+pub fn canonicalize_suits(deal: &Deal) -> (Deal, Permutation) {
+    let holdings = |suit: Suit| -> Vec<Vec<Rank>> {
+        SeatOrder::NESW
+            .into_iter()
+            .map(|dir| {
+                let mut ranks: Vec<Rank> = deal
+                    .hand(dir)
+                    .cards_in_suit(suit)
+                    .iter()
+                    .map(|card| card.rank)
+                    .collect();
+                ranks.sort_by(|a, b| b.cmp(a));
+                ranks
+            })
+            .collect()
+    };
+    let by_length_then_rank = |a: &Vec<Vec<Rank>>, b: &Vec<Vec<Rank>>| {
+        a.iter()
+            .zip(b)
+            .map(|(a, b)| b.len().cmp(&a.len()).then_with(|| b.cmp(a)))
+            .find(|order| order.is_ne())
+            .unwrap_or(std::cmp::Ordering::Equal)
+    };
+
+    let mut order = Suit::ALL;
+    order.sort_by(|&a, &b| by_length_then_rank(&holdings(a), &holdings(b)));
+
+    // order[i] is the original suit that becomes Suit::ALL[i]
+    let mut images = Suit::ALL;
+    for (canonical, original) in Suit::ALL.into_iter().zip(order) {
+        images[suit_index(original)] = canonical;
+    }
+    let to_canonical = Permutation { images };
+    (to_canonical.apply_to_deal(deal), to_canonical.inverse())
+}
+
+fn suit_index(suit: Suit) -> usize {
+    match suit {
+        Suit::Spades => 0,
+        Suit::Hearts => 1,
+        Suit::Diamonds => 2,
+        Suit::Clubs => 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn boards() -> Vec<Board> {
+        let deal =
+            Deal::from_pbn("N:K843.T542.J6.863 AQJ7.K.Q75.AT942 962.AJ7.KT82.J75 T5.Q9863.A943.KQ")
+                .unwrap();
+        (1..=4)
+            .map(|n| {
+                let mut board = Board::new()
+                    .with_number(n)
+                    .with_dealer(Direction::North)
+                    .with_vulnerability(Vulnerability::NorthSouth)
+                    .with_deal(deal.clone());
+                board.event = Some("Club".to_string());
+                board
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_chain() {
+        let chain = Chain::new()
+            .with(Filter(|b: &Board| b.number.is_some_and(|n| n % 2 == 0)))
+            .with(Anonymize)
+            .with(Renumber::from(10));
+        let out: Vec<Board> = chain.run(boards()).collect();
+
+        assert_eq!(out.len(), 2);
+        assert_eq!(out[0].number, Some(10));
+        assert_eq!(out[1].number, Some(11));
+        assert_eq!(out[0].event, None);
+    }
+
+    #[test]
+    fn test_rotate() {
+        let board = boards().remove(0);
+        let north = board.deal.hand(Direction::North).hcp();
+
+        let rotated = Rotate(1).apply(board).unwrap();
+        assert_eq!(rotated.deal.hand(Direction::East).hcp(), north);
+        assert_eq!(rotated.dealer, Some(Direction::East));
+        assert_eq!(rotated.vulnerable, Vulnerability::EastWest);
+        assert_eq!(
+            Fingerprint::of(&Rotate(3).apply(rotated.clone()).unwrap().deal),
+            Fingerprint::of(&boards()[0].deal)
+        );
+    }
+
+    #[test]
+    fn test_rotate_analysis() {
+        let mut board = boards().remove(0);
+        board.double_dummy_tricks = Some("88667799555566775555".to_string());
+        board.optimum_score = Some("NS 620".to_string());
+        board.par_contract = Some("4SN".to_string());
+
+        let rotated = Rotate(1).apply(board.clone()).unwrap();
+        // North's row moves to East and West's to North
+        assert_eq!(
+            rotated.double_dummy_tricks.as_deref(),
+            Some("75555556678866779955")
+        );
+        assert_eq!(rotated.optimum_score.as_deref(), Some("EW 620"));
+        assert_eq!(rotated.par_contract.as_deref(), Some("4SE"));
+
+        let back = Rotate(3).apply(rotated).unwrap();
+        assert_eq!(back.double_dummy_tricks, board.double_dummy_tricks);
+        assert_eq!(back.optimum_score, board.optimum_score);
+        assert_eq!(back.par_contract, board.par_contract);
+
+        board.par_contract = Some("NS 4S".to_string());
+        assert_eq!(Rotate(2).apply(board).unwrap().par_contract, None);
+    }
+
+    #[test]
+    fn test_attach_dd() {
+        let analyses = vec![DdAnalysis {
+            board_number: Some(3),
+            fingerprint: None,
+            double_dummy_tricks: None,
+            optimum_score: Some("NS 400".to_string()),
+            par_contract: None,
+        }];
+        let out: Vec<Board> = Chain::new()
+            .with(AttachDd::new(analyses))
+            .run(boards())
+            .collect();
+        assert_eq!(out[2].optimum_score.as_deref(), Some("NS 400"));
+        assert_eq!(out[0].optimum_score, None);
+    }
+
+    #[test]
+    fn test_canonicalize_suits() {
+        let deal = boards()[0].deal.clone();
+        let (canonical, back) = canonicalize_suits(&deal);
+        let pbn = |deal: &Deal| deal.to_pbn(Direction::North);
+        assert_eq!(pbn(&back.apply_to_deal(&canonical)), pbn(&deal));
+        // North's longest suit (4 cards, K high beats T high) comes first
+        assert_eq!(back.apply(Suit::Spades), Suit::Spades);
+        assert_eq!(back.apply(Suit::Hearts), Suit::Hearts);
+
+        let swapped = Permutation {
+            images: [Suit::Clubs, Suit::Diamonds, Suit::Hearts, Suit::Spades],
+        };
+        let relabelled = swapped.apply_to_deal(&deal);
+        assert_eq!(pbn(&canonicalize_suits(&relabelled).0), pbn(&canonical));
+        assert_eq!(swapped.inverse().inverse(), swapped);
+    }
+}